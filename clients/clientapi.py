@@ -2,7 +2,7 @@
 from fastapi import FastAPI, Depends, HTTPException, status, Header, Body, Path, Form, Query, \
     security, BackgroundTasks
 from fastapi.security import APIKeyHeader, HTTPBasic, HTTPBasicCredentials
-from fastapi.responses import PlainTextResponse, JSONResponse, Response, FileResponse
+from fastapi.responses import PlainTextResponse, JSONResponse, Response, FileResponse, StreamingResponse
 from fastapi.middleware.cors import CORSMiddleware
 from starlette.concurrency import run_in_threadpool
 import smtplib
@@ -23,6 +23,9 @@ from fastapi.middleware.gzip import GZipMiddleware
 from starlette.middleware.sessions import SessionMiddleware
 from starlette.requests import Request
 import secrets
+import hmac
+import hashlib
+import ipaddress
 from pydantic import BaseModel, Field, HttpUrl
 from typing import Dict
 from typing import List
@@ -30,6 +33,7 @@ from typing import Optional
 from typing import Generator
 import json
 import logging
+from collections import defaultdict, deque
 import argparse
 import sys
 from pyotp import TOTP, random_base32
@@ -291,9 +295,22 @@ async def has_elevated_access(api_key: str, cnx):
     return is_admin or is_web_key
 
 
+# Reported by /api/pinepods_check so clients can tell what they are talking to.
+# Add a capability here whenever an optional endpoint or parameter is added.
+PINEPODS_VERSION = "0.6.0"
+API_CAPABILITIES = ["gpodder", "nextcloud", "queue_bump", "stream", "pagination", "playback_settings", "podcast_playback",
+                    "audio_processing", "continuous_playback",
+                    "position_sync", "bookmarks", "completion"]
+
+
 @app.get('/api/pinepods_check')
 async def pinepods_check():
-    return {"status_code": 200, "pinepods_instance": True}
+    return {
+        "status_code": 200,
+        "pinepods_instance": True,
+        "version": PINEPODS_VERSION,
+        "capabilities": API_CAPABILITIES,
+    }
 
 
 @app.get('/api/data/verify_key')
@@ -476,7 +493,8 @@ async def api_verify_password(data: VerifyPasswordInput, cnx=Depends(get_databas
 
 @app.get("/api/data/return_episodes/{user_id}")
 async def api_return_episodes(user_id: int, cnx=Depends(get_database_connection),
-                              api_key: str = Depends(get_api_key_from_header)):
+                              api_key: str = Depends(get_api_key_from_header),
+                              offset: int = Query(0, ge=0), limit: Optional[int] = Query(None, ge=1)):
     is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
     if not is_valid_key:
         raise HTTPException(status_code=403,
@@ -489,7 +507,7 @@ async def api_return_episodes(user_id: int, cnx=Depends(get_database_connection)
 
     # Allow the action if the API key belongs to the user, or it's the web API key
     if key_id == user_id or is_web_key:
-        episodes = database_functions.functions.return_episodes(database_type, cnx, user_id)
+        episodes = database_functions.functions.return_episodes(database_type, cnx, user_id, offset, limit)
         if episodes is None:
             episodes = []  # Return an empty list instead of raising an exception
         return {"episodes": episodes}
@@ -499,7 +517,8 @@ async def api_return_episodes(user_id: int, cnx=Depends(get_database_connection)
 
 
 @app.get("/api/data/podcast_episodes")
-async def api_podcast_episodes(cnx=Depends(get_database_connection), api_key: str = Depends(get_api_key_from_header), user_id: int = Query(...), podcast_id: int = Query(...)):
+async def api_podcast_episodes(cnx=Depends(get_database_connection), api_key: str = Depends(get_api_key_from_header), user_id: int = Query(...), podcast_id: int = Query(...),
+                               offset: int = Query(0, ge=0), limit: Optional[int] = Query(None, ge=1)):
     is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
     if not is_valid_key:
         raise HTTPException(status_code=403,
@@ -512,7 +531,7 @@ async def api_podcast_episodes(cnx=Depends(get_database_connection), api_key: st
 
     # Allow the action if the API key belongs to the user, or it's the web API key
     if key_id == user_id or is_web_key:
-        episodes = database_functions.functions.return_podcast_episodes(database_type, cnx, user_id, podcast_id)
+        episodes = database_functions.functions.return_podcast_episodes(database_type, cnx, user_id, podcast_id, offset, limit)
         if episodes is None:
             episodes = []  # Return an empty list instead of raising an exception
         return {"episodes": episodes}
@@ -545,6 +564,74 @@ async def api_podcast_id(cnx=Depends(get_database_connection),
         raise HTTPException(status_code=403,
                             detail="You can only return pocast ids of your own podcasts!")
 
+@app.get("/api/data/podcast_playback_settings")
+async def api_get_podcast_playback_settings(cnx=Depends(get_database_connection),
+                                            api_key: str = Depends(get_api_key_from_header),
+                                            user_id: int = Query(...), podcast_id: Optional[int] = Query(None),
+                                            episode_id: Optional[int] = Query(None)):
+    is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
+    if not is_valid_key:
+        raise HTTPException(status_code=403,
+                            detail="Your API key is either invalid or does not have correct permission")
+
+    if (podcast_id is None) == (episode_id is None):
+        raise HTTPException(status_code=400, detail="Pass either a podcast_id or an episode_id")
+
+    # Check if the provided API key is the web key
+    is_web_key = api_key == base_webkey.web_key
+
+    key_id = database_functions.functions.id_from_api_key(cnx, api_key)
+
+    # Allow the action if the API key belongs to the user, or it's the web API key
+    if key_id == user_id or is_web_key:
+        if podcast_id is not None:
+            return database_functions.functions.get_podcast_playback_settings(cnx, user_id, podcast_id)
+        return database_functions.functions.get_episode_playback_settings(cnx, user_id, episode_id)
+    else:
+        raise HTTPException(status_code=403,
+                            detail="You can only get playback settings for your own podcasts!")
+
+
+class PodcastPlaybackSettingsModel(BaseModel):
+    user_id: int
+    podcast_id: int
+    playback_speed: float
+    skip_intro: int
+    skip_outro: int
+    # None follows the user's own audio processing setting
+    voice_boost: Optional[bool] = None
+    normalize_loudness: Optional[bool] = None
+    skip_silence: Optional[bool] = None
+
+
+@app.put("/api/data/podcast_playback_settings")
+async def api_set_podcast_playback_settings(data: PodcastPlaybackSettingsModel, cnx=Depends(get_database_connection),
+                                            api_key: str = Depends(get_api_key_from_header)):
+    is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
+    if not is_valid_key:
+        raise HTTPException(status_code=403,
+                            detail="Your API key is either invalid or does not have correct permission")
+
+    if not 0.5 <= data.playback_speed <= 2.0 or data.skip_intro < 0 or data.skip_outro < 0:
+        raise HTTPException(status_code=400,
+                            detail="Speed must be between 0.5 and 2.0 and skips can't be negative")
+
+    # Check if the provided API key is the web key
+    is_web_key = api_key == base_webkey.web_key
+
+    key_id = database_functions.functions.id_from_api_key(cnx, api_key)
+
+    # Allow the action if the API key belongs to the user, or it's the web API key
+    if key_id == data.user_id or is_web_key:
+        database_functions.functions.set_podcast_playback_settings(
+            cnx, data.user_id, data.podcast_id, data.playback_speed, data.skip_intro, data.skip_outro,
+            data.voice_boost, data.normalize_loudness, data.skip_silence)
+        return {"message": "Podcast playback settings updated successfully"}
+    else:
+        raise HTTPException(status_code=403,
+                            detail="You can only set playback settings for your own podcasts!")
+
+
 @app.get("/api/data/get_podcast_details")
 async def api_podcast_id(podcast_id: str = Query(...), cnx=Depends(get_database_connection),
                               api_key: str = Depends(get_api_key_from_header),
@@ -761,6 +848,36 @@ async def api_increment_listen_time(user_id: int, cnx=Depends(get_database_conne
                             detail="You can only increment your own listen time.")
 
 
+class SilenceTimeSavedModel(BaseModel):
+    user_id: int
+    seconds: int
+
+
+@app.put("/api/data/add_silence_time_saved")
+async def api_add_silence_time_saved(data: SilenceTimeSavedModel, cnx=Depends(get_database_connection),
+                                     api_key: str = Depends(get_api_key_from_header)):
+    is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
+    if not is_valid_key:
+        raise HTTPException(status_code=403,
+                            detail="Your API key is either invalid or does not have correct permission")
+
+    if data.seconds < 0:
+        raise HTTPException(status_code=400, detail="Time saved can't be negative")
+
+    # Check if the provided API key is the web key
+    is_web_key = api_key == base_webkey.web_key
+
+    key_id = database_functions.functions.id_from_api_key(cnx, api_key)
+
+    # Allow the action if the API key belongs to the user, or it's the web API key
+    if key_id == data.user_id or is_web_key:
+        database_functions.functions.add_silence_time_saved(cnx, data.user_id, data.seconds)
+        return {"detail": "Time saved recorded."}
+    else:
+        raise HTTPException(status_code=403,
+                            detail="You can only record your own time saved.")
+
+
 @app.put("/api/data/increment_played/{user_id}")
 async def api_increment_played(user_id: int, cnx=Depends(get_database_connection),
                                api_key: str = Depends(get_api_key_from_header)):
@@ -947,11 +1064,11 @@ class RecordListenDurationData(BaseModel):
     episode_id: int
     user_id: int
     listen_duration: float
+    # Server time, in milliseconds, when the player was at this position
+    observed_at: Optional[int] = None
 
 
-@app.post("/api/data/record_listen_duration")
-async def api_record_listen_duration(data: RecordListenDurationData, cnx=Depends(get_database_connection),
-                                     api_key: str = Depends(get_api_key_from_header)):
+def record_listen_duration_for_key(cnx, api_key, data: RecordListenDurationData):
     is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
     if not is_valid_key:
         raise HTTPException(status_code=403,
@@ -966,12 +1083,186 @@ async def api_record_listen_duration(data: RecordListenDurationData, cnx=Depends
     key_id = database_functions.functions.id_from_api_key(cnx, api_key)
 
     if key_id == data.user_id or is_web_key:
-        database_functions.functions.record_listen_duration(cnx, data.episode_id, data.user_id, data.listen_duration)
-        return {"detail": "Listen duration recorded."}
+        recorded = database_functions.functions.record_listen_duration(cnx, data.episode_id, data.user_id,
+                                                                       data.listen_duration, data.observed_at)
+        # The server's clock lets clients stamp their positions in the same time as other devices
+        return {
+            "detail": "Listen duration recorded." if recorded else "A newer position is already saved.",
+            "recorded": recorded,
+            "server_time": int(time.time() * 1000),
+        }
     else:
         raise HTTPException(status_code=403, detail="You can only record your own listen duration")
 
 
+@app.post("/api/data/record_listen_duration")
+async def api_record_listen_duration(data: RecordListenDurationData, cnx=Depends(get_database_connection),
+                                     api_key: str = Depends(get_api_key_from_header)):
+    return record_listen_duration_for_key(cnx, api_key, data)
+
+
+# How far ahead a beacon's signature may expire. Clients sign them for five minutes.
+BEACON_LINK_MAX_SECONDS = 300
+
+
+def signed_api_key(cnx, message: str, expires: int, key: str, token: str, max_seconds: int) -> str:
+    # Requests that can't send headers name their key by a fingerprint and carry an HMAC,
+    # made with the key, of what they ask for and when that stops working
+    now = int(time.time())
+    if not now < expires <= now + max_seconds:
+        raise HTTPException(status_code=403, detail="This link has expired")
+    api_key = database_functions.functions.api_key_from_fingerprint(cnx, key)
+    expected = hmac.new(api_key.encode(), f"{message}\n{expires}".encode(), hashlib.sha256).hexdigest() if api_key else ""
+    if not api_key or not hmac.compare_digest(expected, token):
+        raise HTTPException(status_code=403, detail="This link is not valid")
+    return api_key
+
+
+@app.post("/api/data/record_listen_duration_beacon")
+async def api_record_listen_duration_beacon(request: Request, cnx=Depends(get_database_connection),
+                                            expires: int = Query(...), key: str = Query(...),
+                                            token: str = Query(...)):
+    # navigator.sendBeacon can't set headers, so the query string carries a signature of the
+    # body instead of the key, and the JSON arrives as text/plain to avoid a preflight the
+    # closing page wouldn't wait for
+    body = (await request.body()).decode("utf-8", errors="replace")
+    api_key = signed_api_key(cnx, body, expires, key, token, BEACON_LINK_MAX_SECONDS)
+    try:
+        data = RecordListenDurationData(**json.loads(body))
+    except (ValueError, TypeError):
+        raise HTTPException(status_code=400, detail="Expected a JSON listen duration")
+    return record_listen_duration_for_key(cnx, api_key, data)
+
+
+class MarkCompletedData(BaseModel):
+    episode_id: int
+    user_id: int
+
+
+@app.post("/api/data/mark_episode_completed")
+async def api_mark_episode_completed(data: MarkCompletedData, cnx=Depends(get_database_connection),
+                                     api_key: str = Depends(get_api_key_from_header)):
+    is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
+    if not is_valid_key:
+        raise HTTPException(status_code=403,
+                            detail="Your API key is either invalid or does not have correct permission")
+
+    is_web_key = api_key == base_webkey.web_key
+    key_id = database_functions.functions.id_from_api_key(cnx, api_key)
+
+    if key_id == data.user_id or is_web_key:
+        database_functions.functions.mark_episode_completed(cnx, data.episode_id, data.user_id)
+        return {"detail": "Episode marked completed."}
+    else:
+        raise HTTPException(status_code=403, detail="You can only mark your own episodes completed")
+
+
+@app.post("/api/data/mark_episode_uncompleted")
+async def api_mark_episode_uncompleted(data: MarkCompletedData, cnx=Depends(get_database_connection),
+                                       api_key: str = Depends(get_api_key_from_header)):
+    is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
+    if not is_valid_key:
+        raise HTTPException(status_code=403,
+                            detail="Your API key is either invalid or does not have correct permission")
+
+    is_web_key = api_key == base_webkey.web_key
+    key_id = database_functions.functions.id_from_api_key(cnx, api_key)
+
+    if key_id == data.user_id or is_web_key:
+        database_functions.functions.mark_episode_uncompleted(cnx, data.episode_id, data.user_id)
+        return {"detail": "Episode marked uncompleted."}
+    else:
+        raise HTTPException(status_code=403, detail="You can only mark your own episodes uncompleted")
+
+
+@app.post("/api/data/mark_older_episodes_completed")
+async def api_mark_older_episodes_completed(data: MarkCompletedData, cnx=Depends(get_database_connection),
+                                            api_key: str = Depends(get_api_key_from_header)):
+    is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
+    if not is_valid_key:
+        raise HTTPException(status_code=403,
+                            detail="Your API key is either invalid or does not have correct permission")
+
+    is_web_key = api_key == base_webkey.web_key
+    key_id = database_functions.functions.id_from_api_key(cnx, api_key)
+
+    if key_id == data.user_id or is_web_key:
+        marked = database_functions.functions.mark_older_episodes_completed(cnx, data.episode_id, data.user_id)
+        return {"detail": "Older episodes marked completed.", "marked": marked}
+    else:
+        raise HTTPException(status_code=403, detail="You can only mark your own episodes completed")
+
+
+class AddBookmarkData(BaseModel):
+    user_id: int
+    episode_id: int
+    time: int
+    note: Optional[str] = None
+
+
+@app.post("/api/data/add_bookmark")
+async def api_add_bookmark(data: AddBookmarkData, cnx=Depends(get_database_connection),
+                           api_key: str = Depends(get_api_key_from_header)):
+    is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
+    if not is_valid_key:
+        raise HTTPException(status_code=403,
+                            detail="Your API key is either invalid or does not have correct permission")
+
+    if data.time < 0:
+        raise HTTPException(status_code=400, detail="A bookmark can't be before the start of the episode")
+
+    is_web_key = api_key == base_webkey.web_key
+    key_id = database_functions.functions.id_from_api_key(cnx, api_key)
+
+    if key_id == data.user_id or is_web_key:
+        note = data.note.strip() if data.note and data.note.strip() else None
+        bookmark_id = database_functions.functions.add_bookmark(cnx, data.user_id, data.episode_id, data.time, note)
+        return {"detail": "Bookmark added.", "bookmark_id": bookmark_id}
+    else:
+        raise HTTPException(status_code=403, detail="You can only add your own bookmarks")
+
+
+class DeleteBookmarkData(BaseModel):
+    user_id: int
+    bookmark_id: int
+
+
+@app.post("/api/data/delete_bookmark")
+async def api_delete_bookmark(data: DeleteBookmarkData, cnx=Depends(get_database_connection),
+                              api_key: str = Depends(get_api_key_from_header)):
+    is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
+    if not is_valid_key:
+        raise HTTPException(status_code=403,
+                            detail="Your API key is either invalid or does not have correct permission")
+
+    is_web_key = api_key == base_webkey.web_key
+    key_id = database_functions.functions.id_from_api_key(cnx, api_key)
+
+    if key_id == data.user_id or is_web_key:
+        if not database_functions.functions.delete_bookmark(cnx, data.user_id, data.bookmark_id):
+            raise HTTPException(status_code=404, detail="Bookmark not found")
+        return {"detail": "Bookmark deleted."}
+    else:
+        raise HTTPException(status_code=403, detail="You can only delete your own bookmarks")
+
+
+@app.get("/api/data/bookmarks")
+async def api_get_bookmarks(cnx=Depends(get_database_connection), api_key: str = Depends(get_api_key_from_header),
+                            user_id: int = Query(...), episode_id: Optional[int] = Query(None)):
+    is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
+    if not is_valid_key:
+        raise HTTPException(status_code=403,
+                            detail="Your API key is either invalid or does not have correct permission")
+
+    is_web_key = api_key == base_webkey.web_key
+    key_id = database_functions.functions.id_from_api_key(cnx, api_key)
+
+    # Without an episode_id, every bookmark the user has made
+    if key_id == user_id or is_web_key:
+        return {"bookmarks": database_functions.functions.get_bookmarks(cnx, user_id, episode_id)}
+    else:
+        raise HTTPException(status_code=403, detail="You can only see your own bookmarks")
+
 
 @app.get("/api/data/refresh_pods")
 async def api_refresh_pods(background_tasks: BackgroundTasks, is_admin: bool = Depends(check_if_admin)):
@@ -1165,7 +1456,8 @@ async def api_return_pods(user_id: int, cnx=Depends(get_database_connection),
 
 @app.get("/api/data/user_history/{user_id}")
 async def api_user_history(user_id: int, cnx=Depends(get_database_connection),
-                           api_key: str = Depends(get_api_key_from_header)):
+                           api_key: str = Depends(get_api_key_from_header),
+                           offset: int = Query(0, ge=0), limit: Optional[int] = Query(None, ge=1)):
     is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
     if not is_valid_key:
         raise HTTPException(status_code=403,
@@ -1178,7 +1470,7 @@ async def api_user_history(user_id: int, cnx=Depends(get_database_connection),
 
     # Allow the action if the API key belongs to the user or it's the web API key
     if key_id == user_id or is_web_key:
-        history = database_functions.functions.user_history(cnx, user_id)
+        history = database_functions.functions.user_history(cnx, user_id, offset, limit)
         return {"data": history}
     else:
         raise HTTPException(status_code=403,
@@ -1187,7 +1479,8 @@ async def api_user_history(user_id: int, cnx=Depends(get_database_connection),
 
 @app.get("/api/data/saved_episode_list/{user_id}")
 async def api_saved_episode_list(user_id: int, cnx=Depends(get_database_connection),
-                                 api_key: str = Depends(get_api_key_from_header)):
+                                 api_key: str = Depends(get_api_key_from_header),
+                                 offset: int = Query(0, ge=0), limit: Optional[int] = Query(None, ge=1)):
     is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
     if not is_valid_key:
         raise HTTPException(status_code=403,
@@ -1200,7 +1493,7 @@ async def api_saved_episode_list(user_id: int, cnx=Depends(get_database_connecti
 
     # Allow the action if the API key belongs to the user or it's the web API key
     if key_id == user_id or is_web_key:
-        saved_episodes = database_functions.functions.saved_episode_list(database_type, cnx, user_id)
+        saved_episodes = database_functions.functions.saved_episode_list(database_type, cnx, user_id, offset, limit)
         return {"saved_episodes": saved_episodes}
     else:
         raise HTTPException(status_code=403,
@@ -1437,6 +1730,79 @@ async def api_set_theme(user_id: int = Body(...), new_theme: str = Body(...), cn
                             detail="You can only set your own theme!")
 
 
+@app.get("/api/data/get_playback_settings/{user_id}")
+async def api_get_playback_settings(user_id: int, cnx=Depends(get_database_connection),
+                                    api_key: str = Depends(get_api_key_from_header)):
+    is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
+    if not is_valid_key:
+        raise HTTPException(status_code=403,
+                            detail="Your API key is either invalid or does not have correct permission")
+
+    # Check if the provided API key is the web key
+    is_web_key = api_key == base_webkey.web_key
+
+    key_id = database_functions.functions.id_from_api_key(cnx, api_key)
+
+    # Allow the action if the API key belongs to the user or it's the web API key
+    if key_id == user_id or is_web_key:
+        return database_functions.functions.get_playback_settings(cnx, user_id)
+    else:
+        raise HTTPException(status_code=403,
+                            detail="You can only get your own playback settings!")
+
+
+class KeyBindingModel(BaseModel):
+    action: str
+    key: str
+
+
+class PlaybackSettingsModel(BaseModel):
+    user_id: int
+    skip_back: int
+    skip_forward: int
+    key_bindings: Optional[List[KeyBindingModel]] = None
+    voice_boost: bool = False
+    normalize_loudness: bool = False
+    skip_silence: bool = False
+    auto_play_next: bool = True
+    remove_finished: bool = True
+    completion_percent: int = 95
+    completion_remaining: int = 60
+
+
+@app.put("/api/data/user/set_playback_settings")
+async def api_set_playback_settings(data: PlaybackSettingsModel, cnx=Depends(get_database_connection),
+                                    api_key: str = Depends(get_api_key_from_header)):
+    is_valid_key = database_functions.functions.verify_api_key(cnx, api_key)
+    if not is_valid_key:
+        raise HTTPException(status_code=403,
+                            detail="Your API key is either invalid or does not have correct permission")
+
+    if not (1 <= data.skip_back <= 300 and 1 <= data.skip_forward <= 300):
+        raise HTTPException(status_code=400, detail="Skip intervals must be between 1 and 300 seconds")
+    if not (50 <= data.completion_percent <= 100 and 0 <= data.completion_remaining <= 600):
+        raise HTTPException(status_code=400,
+                            detail="Episodes must count as played between 50% and 100% and within 10 minutes of the end")
+
+    # Check if the provided API key is the web key
+    is_web_key = api_key == base_webkey.web_key
+
+    key_id = database_functions.functions.id_from_api_key(cnx, api_key)
+
+    # Allow the action if the API key belongs to the user or it's the web API key
+    if key_id == data.user_id or is_web_key:
+        key_bindings = [binding.dict() for binding in data.key_bindings] if data.key_bindings is not None else None
+        database_functions.functions.set_playback_settings(cnx, data.user_id, data.skip_back, data.skip_forward,
+                                                           key_bindings, data.voice_boost, data.normalize_loudness,
+                                                           data.skip_silence, data.auto_play_next,
+                                                           data.remove_finished, data.completion_percent,
+                                                           data.completion_remaining)
+        return {"message": "Playback settings updated successfully"}
+    else:
+        raise HTTPException(status_code=403,
+                            detail="You can only set your own playback settings!")
+
+
 @app.get("/api/data/user/check_downloaded")
 async def api_check_downloaded(user_id: int, title: str, url: str, cnx=Depends(get_database_connection),
                                api_key: str = Depends(get_api_key_from_header)):
@@ -2700,6 +3066,148 @@ async def stream_episode(
         raise HTTPException(status_code=403, detail="You do not have permission to access this episode")
 
 
+# Headers worth passing along when relaying remote audio, so the player can seek
+RELAY_HEADERS = ["content-type", "content-length", "content-range", "accept-ranges"]
+
+
+# How far ahead a relay link may expire. Clients sign links for 11 to 12 hours.
+RELAY_LINK_MAX_SECONDS = 12 * 3600
+RELAY_MAX_REDIRECTS = 5
+# A signed link can name any public URL, so the relay only passes on media, and only so much of it
+RELAY_MAX_BYTES = 1024 * 1024 * 1024
+RELAY_REQUESTS_PER_MINUTE = 240
+RELAY_MEDIA_TYPES = ("application/octet-stream", "binary/octet-stream", "application/ogg")
+
+# Recent relay requests per key fingerprint
+relay_requests = defaultdict(deque)
+
+
+def relay_allowed(fingerprint: str) -> bool:
+    # Players send a request per seek, so this is generous, but it stops the relay being used in bulk
+    now = time.monotonic()
+    recent = relay_requests[fingerprint]
+    while recent and recent[0] <= now - 60:
+        recent.popleft()
+    if len(recent) >= RELAY_REQUESTS_PER_MINUTE:
+        return False
+    recent.append(now)
+    return True
+
+
+def is_relayable_media(content_type: Optional[str]) -> bool:
+    if not content_type:
+        return True
+    media_type = content_type.split(";")[0].strip().lower()
+    return media_type.startswith(("audio/", "video/")) or media_type in RELAY_MEDIA_TYPES
+
+
+async def public_address(host: str) -> Optional[str]:
+    # Relaying to the server's own network would let any user read internal services.
+    # The request is sent to the address checked here, so a host that answers a second
+    # lookup differently can't point it somewhere else.
+    try:
+        addresses = await asyncio.get_running_loop().getaddrinfo(host, None)
+    except (OSError, UnicodeError):
+        return None
+    for address in addresses:
+        ip = ipaddress.ip_address(address[4][0].split("%")[0])
+        if getattr(ip, "ipv4_mapped", None):
+            ip = ip.ipv4_mapped
+        if not ip.is_global:
+            return None
+    return addresses[0][4][0] if addresses else None
+
+
+@app.get("/api/data/relay_audio")
+async def relay_audio(
+    request: Request,
+    url: str = Query(...),
+    expires: int = Query(...),
+    key: str = Query(...),
+    token: str = Query(...)
+):
+    # The player can only process audio it's allowed to read, and most podcast hosts
+    # don't allow that from another origin. Relaying it through here puts it on ours.
+    # Media elements can't send headers, so instead of the API key the link carries the
+    # start of the key's hash and an HMAC of the URL and expiry made with the key. That
+    # lets any user relay any public URL, not just their episodes, so responses have to
+    # be media, at most RELAY_MAX_BYTES, and each key gets RELAY_REQUESTS_PER_MINUTE.
+    # The connection is only needed for the key lookup, so don't hold it for the whole stream.
+    cnx = create_database_connection()
+    try:
+        signed_api_key(cnx, url, expires, key, token, RELAY_LINK_MAX_SECONDS)
+    finally:
+        if database_type == "postgresql":
+            connection_pool.putconn(cnx)
+        else:
+            cnx.close()
+    if not relay_allowed(key):
+        raise HTTPException(status_code=429, detail="Too many relay requests, try again in a minute")
+
+    headers = {"Accept-Encoding": "identity"}
+    if "range" in request.headers:
+        headers["Range"] = request.headers["range"]
+
+    # Redirects are followed by hand so every hop gets the same checks
+    client = httpx.AsyncClient(follow_redirects=False, timeout=httpx.Timeout(30.0, read=None))
+    try:
+        for _ in range(RELAY_MAX_REDIRECTS + 1):
+            target = httpx.URL(url)
+            if target.scheme not in ("http", "https"):
+                raise HTTPException(status_code=400, detail="Only http and https audio can be relayed")
+            address = await public_address(target.host)
+            if address is None:
+                raise HTTPException(status_code=400, detail="Only audio on public hosts can be relayed")
+            # Connect to the checked address, while the host still names the site and its certificate
+            pinned = target.copy_with(host=f"[{address}]" if ":" in address else address)
+            request_headers = {**headers, "Host": target.netloc.decode("ascii")}
+            upstream = await client.send(
+                client.build_request("GET", pinned, headers=request_headers, extensions={"sni_hostname": target.host}),
+                stream=True,
+            )
+            if not upstream.is_redirect:
+                break
+            url = str(target.join(upstream.headers["location"]))
+            await upstream.aclose()
+        else:
+            raise HTTPException(status_code=502, detail="The episode's host redirected too many times")
+    except httpx.HTTPError:
+        await client.aclose()
+        raise HTTPException(status_code=502, detail="Couldn't reach the episode's host")
+    except HTTPException:
+        await client.aclose()
+        raise
+
+    if upstream.status_code >= 400:
+        await upstream.aclose()
+        await client.aclose()
+        raise HTTPException(status_code=502, detail=f"The episode's host answered {upstream.status_code}")
+    if not is_relayable_media(upstream.headers.get("content-type")):
+        await upstream.aclose()
+        await client.aclose()
+        raise HTTPException(status_code=415, detail="Only audio can be relayed")
+    length = upstream.headers.get("content-length")
+    if length and length.isdigit() and int(length) > RELAY_MAX_BYTES:
+        await upstream.aclose()
+        await client.aclose()
+        raise HTTPException(status_code=413, detail="This audio is too large to relay")
+
+    async def body():
+        sent = 0
+        try:
+            async for chunk in upstream.aiter_raw():
+                sent += len(chunk)
+                if sent > RELAY_MAX_BYTES:
+                    break
+                yield chunk
+        finally:
+            await upstream.aclose()
+            await client.aclose()
+
+    relayed = {name: upstream.headers[name] for name in RELAY_HEADERS if name in upstream.headers}
+    return StreamingResponse(body(), status_code=upstream.status_code, headers=relayed)
+
+
 class BackupUser(BaseModel):
     user_id: int
 
@@ -1,5 +1,6 @@
 import random
 import string
+import hashlib
 import mysql.connector
 from mysql.connector import errorcode
 import mysql.connector.pooling
@@ -12,6 +13,7 @@ import appdirs
 import base64
 import subprocess
 import psycopg2
+import json
 from psycopg2.extras import RealDictCursor
 from requests.exceptions import RequestException
 from fastapi import HTTPException
@@ -293,6 +295,10 @@ def remove_podcast(cnx, podcast_name, podcast_url, user_id):
         delete_saved = "DELETE FROM SavedEpisodes WHERE EpisodeID IN (SELECT EpisodeID FROM Episodes WHERE PodcastID = %s)"
         cursor.execute(delete_saved, (podcast_id,))
 
+        # Delete bookmarks in the podcast's episodes
+        delete_bookmarks = "DELETE FROM Bookmarks WHERE EpisodeID IN (SELECT EpisodeID FROM Episodes WHERE PodcastID = %s)"
+        cursor.execute(delete_bookmarks, (podcast_id,))
+
         # Delete episode queue items associated with the podcast
         delete_queue = "DELETE FROM EpisodeQueue WHERE EpisodeID IN (SELECT EpisodeID FROM Episodes WHERE PodcastID = %s)"
         cursor.execute(delete_queue, (podcast_id,))
@@ -343,6 +349,10 @@ def remove_podcast_id(cnx, podcast_id, user_id):
         delete_saved = "DELETE FROM SavedEpisodes WHERE EpisodeID IN (SELECT EpisodeID FROM Episodes WHERE PodcastID = %s)"
         cursor.execute(delete_saved, (podcast_id,))
 
+        # Delete bookmarks in the podcast's episodes
+        delete_bookmarks = "DELETE FROM Bookmarks WHERE EpisodeID IN (SELECT EpisodeID FROM Episodes WHERE PodcastID = %s)"
+        cursor.execute(delete_bookmarks, (podcast_id,))
+
         # Delete episode queue items associated with the podcast
         delete_queue = "DELETE FROM EpisodeQueue WHERE EpisodeID IN (SELECT EpisodeID FROM Episodes WHERE PodcastID = %s)"
         cursor.execute(delete_queue, (podcast_id,))
@@ -373,7 +383,16 @@ def remove_user(cnx, user_name):
     pass
 
 
-def return_episodes(database_type, cnx, user_id):
+def page_clause(offset, limit):
+    # Both MySQL and PostgreSQL accept LIMIT ... OFFSET. Without a limit the whole list is returned.
+    # The query's ORDER BY has to end on a unique column, or rows sharing a sort value can
+    # repeat or go missing between pages.
+    if limit is None:
+        return "", ()
+    return " LIMIT %s OFFSET %s", (limit, offset)
+
+
+def return_episodes(database_type, cnx, user_id, offset=0, limit=None):
     if database_type == "postgresql":
         cursor = cnx.cursor(cursor_factory=RealDictCursor)
     else:  # Assuming MariaDB/MySQL if not PostgreSQL
@@ -381,15 +400,16 @@ def return_episodes(database_type, cnx, user_id):
 
     query = (f"SELECT Podcasts.PodcastName, Episodes.EpisodeTitle, Episodes.EpisodePubDate, "
              f"Episodes.EpisodeDescription, Episodes.EpisodeArtwork, Episodes.EpisodeURL, Episodes.EpisodeDuration, "
-             f"UserEpisodeHistory.ListenDuration, Episodes.EpisodeID "
+             f"UserEpisodeHistory.ListenDuration, UserEpisodeHistory.Completed, Episodes.EpisodeID "
              f"FROM Episodes "
              f"INNER JOIN Podcasts ON Episodes.PodcastID = Podcasts.PodcastID "
              f"LEFT JOIN UserEpisodeHistory ON Episodes.EpisodeID = UserEpisodeHistory.EpisodeID AND UserEpisodeHistory.UserID = %s "
              f"WHERE Episodes.EpisodePubDate >= DATE_SUB(NOW(), INTERVAL 30 DAY) "
              f"AND Podcasts.UserID = %s "
-             f"ORDER BY Episodes.EpisodePubDate DESC")
+             f"ORDER BY Episodes.EpisodePubDate DESC, Episodes.EpisodeID DESC")
+    page, page_params = page_clause(offset, limit)
 
-    cursor.execute(query, (user_id, user_id))
+    cursor.execute(query + page, (user_id, user_id) + page_params)
     rows = cursor.fetchall()
 
     cursor.close()
@@ -400,7 +420,7 @@ def return_episodes(database_type, cnx, user_id):
     return rows
 
 
-def return_podcast_episodes(database_type, cnx, user_id, podcast_id):
+def return_podcast_episodes(database_type, cnx, user_id, podcast_id, offset=0, limit=None):
     if database_type == "postgresql":
         cursor = cnx.cursor(cursor_factory=RealDictCursor)
     else:  # Assuming MariaDB/MySQL if not PostgreSQL
@@ -410,15 +430,16 @@ def return_podcast_episodes(database_type, cnx, user_id, podcast_id):
         "SELECT Podcasts.PodcastID, Podcasts.PodcastName, Episodes.EpisodeID, "
         "Episodes.EpisodeTitle, Episodes.EpisodePubDate, Episodes.EpisodeDescription, "
         "Episodes.EpisodeArtwork, Episodes.EpisodeURL, Episodes.EpisodeDuration, "
-        "UserEpisodeHistory.ListenDuration, CAST(Episodes.EpisodeID AS CHAR) AS guid "
+        "UserEpisodeHistory.ListenDuration, UserEpisodeHistory.Completed, CAST(Episodes.EpisodeID AS CHAR) AS guid "
         "FROM Episodes "
         "INNER JOIN Podcasts ON Episodes.PodcastID = Podcasts.PodcastID "
         "LEFT JOIN UserEpisodeHistory ON Episodes.EpisodeID = UserEpisodeHistory.EpisodeID AND UserEpisodeHistory.UserID = %s "
         "WHERE Podcasts.PodcastID = %s AND Podcasts.UserID = %s "
-        "ORDER BY Episodes.EpisodePubDate DESC"
+        "ORDER BY Episodes.EpisodePubDate DESC, Episodes.EpisodeID DESC"
     )
+    page, page_params = page_clause(offset, limit)
 
-    cursor.execute(query, (user_id, podcast_id, user_id))
+    cursor.execute(query + page, (user_id, podcast_id, user_id) + page_params)
     rows = cursor.fetchall()
     cursor.close()
 
@@ -460,6 +481,62 @@ def get_podcast_id(database_type, cnx, user_id, podcast_feed, podcast_name):
     return row['PodcastID']  # Assuming the column name is 'PodcastID'
 
 
+def podcast_playback_settings_from_row(row):
+    if not row:
+        return {"playback_speed": 1.0, "skip_intro": 0, "skip_outro": 0,
+                "voice_boost": None, "normalize_loudness": None, "skip_silence": None}
+    playback_speed, skip_intro, skip_outro, voice_boost, normalize_loudness, skip_silence = row
+    # The audio processing overrides stay None when the podcast follows the user's setting
+    return {
+        "playback_speed": float(playback_speed) if playback_speed is not None else 1.0,
+        "skip_intro": skip_intro or 0,
+        "skip_outro": skip_outro or 0,
+        "voice_boost": bool(voice_boost) if voice_boost is not None else None,
+        "normalize_loudness": bool(normalize_loudness) if normalize_loudness is not None else None,
+        "skip_silence": bool(skip_silence) if skip_silence is not None else None,
+    }
+
+
+def get_podcast_playback_settings(cnx, user_id, podcast_id):
+    cursor = cnx.cursor()
+    try:
+        query = ("SELECT PlaybackSpeed, SkipIntroSeconds, SkipOutroSeconds, "
+                 "VoiceBoost, NormalizeLoudness, SkipSilence "
+                 "FROM Podcasts WHERE PodcastID = %s AND UserID = %s")
+        cursor.execute(query, (podcast_id, user_id))
+        return podcast_playback_settings_from_row(cursor.fetchone())
+    finally:
+        cursor.close()
+
+
+def get_episode_playback_settings(cnx, user_id, episode_id):
+    cursor = cnx.cursor()
+    try:
+        # Settings belong to the podcast, so look up the one this episode came from
+        query = ("SELECT Podcasts.PlaybackSpeed, Podcasts.SkipIntroSeconds, Podcasts.SkipOutroSeconds, "
+                 "Podcasts.VoiceBoost, Podcasts.NormalizeLoudness, Podcasts.SkipSilence "
+                 "FROM Episodes INNER JOIN Podcasts ON Episodes.PodcastID = Podcasts.PodcastID "
+                 "WHERE Episodes.EpisodeID = %s AND Podcasts.UserID = %s")
+        cursor.execute(query, (episode_id, user_id))
+        return podcast_playback_settings_from_row(cursor.fetchone())
+    finally:
+        cursor.close()
+
+
+def set_podcast_playback_settings(cnx, user_id, podcast_id, playback_speed, skip_intro, skip_outro,
+                                  voice_boost=None, normalize_loudness=None, skip_silence=None):
+    cursor = cnx.cursor()
+    try:
+        query = ("UPDATE Podcasts SET PlaybackSpeed = %s, SkipIntroSeconds = %s, SkipOutroSeconds = %s, "
+                 "VoiceBoost = %s, NormalizeLoudness = %s, SkipSilence = %s "
+                 "WHERE PodcastID = %s AND UserID = %s")
+        cursor.execute(query, (playback_speed, skip_intro, skip_outro, voice_boost, normalize_loudness,
+                               skip_silence, podcast_id, user_id))
+        cnx.commit()
+    finally:
+        cursor.close()
+
+
 def delete_episode(cnx, episode_id, user_id):
     cursor = cnx.cursor()
 
@@ -772,18 +849,19 @@ def get_user_details_id(cnx, user_id):
         return None
 
 
-def user_history(cnx, user_id):
+def user_history(cnx, user_id, offset=0, limit=None):
     cursor = cnx.cursor()
-    query = ("SELECT Episodes.EpisodeID, UserEpisodeHistory.ListenDate, UserEpisodeHistory.ListenDuration, "
+    query = ("SELECT Episodes.EpisodeID, UserEpisodeHistory.ListenDate, UserEpisodeHistory.ListenDuration, UserEpisodeHistory.Completed, "
              "Episodes.EpisodeTitle, Episodes.EpisodeDescription, Episodes.EpisodeArtwork, "
              "Episodes.EpisodeURL, Episodes.EpisodeDuration, Podcasts.PodcastName, Episodes.EpisodePubDate "
              "FROM UserEpisodeHistory "
              "JOIN Episodes ON UserEpisodeHistory.EpisodeID = Episodes.EpisodeID "
              "JOIN Podcasts ON Episodes.PodcastID = Podcasts.PodcastID "
              "WHERE UserEpisodeHistory.UserID = %s "
-             "ORDER BY UserEpisodeHistory.ListenDate DESC")
+             "ORDER BY UserEpisodeHistory.ListenDate DESC, UserEpisodeHistory.UserEpisodeHistoryID DESC")
+    page, page_params = page_clause(offset, limit)
 
-    cursor.execute(query, (user_id,))
+    cursor.execute(query + page, (user_id,) + page_params)
     # results = cursor.fetchall()
     results = [dict(zip([column[0] for column in cursor.description], row)) for row in cursor.fetchall()]
 
@@ -926,7 +1004,8 @@ def download_episode_list(database_type, cnx, user_id):
         Episodes.EpisodeDuration, 
         Podcasts.WebsiteURL, 
         DownloadedEpisodes.DownloadedLocation,
-        UserEpisodeHistory.ListenDuration
+        UserEpisodeHistory.ListenDuration,
+        UserEpisodeHistory.Completed
     FROM DownloadedEpisodes 
     INNER JOIN Episodes ON DownloadedEpisodes.EpisodeID = Episodes.EpisodeID 
     INNER JOIN Podcasts ON Episodes.PodcastID = Podcasts.PodcastID 
@@ -1118,38 +1197,192 @@ def check_usernames(cnx, username):
     return count > 0
 
 
-def record_listen_duration(cnx, episode_id, user_id, listen_duration):
+def record_listen_duration(cnx, episode_id, user_id, listen_duration, observed_at=None):
     if listen_duration < 0:
         logging.info(f"Skipped updating listen duration for user {user_id} and episode {episode_id} due to invalid duration: {listen_duration}")
-        return
-    
+        return False
+
     listen_date = datetime.datetime.now()
+    # Positions are ordered by when they were observed, in server milliseconds. Clients
+    # ahead of the server are treated as now; clients that don't say are now too.
+    now_ms = int(time.time() * 1000)
+    observed_at = now_ms if observed_at is None else min(observed_at, now_ms)
     cursor = cnx.cursor()
+    recorded = False
 
     try:
         # Check if UserEpisodeHistory row already exists for the given user and episode
-        cursor.execute("SELECT ListenDuration FROM UserEpisodeHistory WHERE UserID=%s AND EpisodeID=%s", (user_id, episode_id))
-        existing_duration = cursor.fetchone()
-
-        if existing_duration:
-            # Update only if the new duration is greater than the existing duration
-            if listen_duration > existing_duration[0]:
-                update_listen_duration = "UPDATE UserEpisodeHistory SET ListenDuration=%s, ListenDate=%s WHERE UserID=%s AND EpisodeID=%s"
-                cursor.execute(update_listen_duration, (listen_duration, listen_date, user_id, episode_id))
+        cursor.execute("SELECT ListenDuration, PositionUpdatedAt FROM UserEpisodeHistory WHERE UserID=%s AND EpisodeID=%s", (user_id, episode_id))
+        existing = cursor.fetchone()
+
+        if existing:
+            # A device that was offline, or a tab left behind, can't overwrite a newer position
+            if existing[1] is not None and observed_at < existing[1]:
+                logging.info(f"Ignored stale listen duration for user {user_id} and episode {episode_id}")
+            else:
+                update_listen_duration = "UPDATE UserEpisodeHistory SET ListenDuration=%s, ListenDate=%s, PositionUpdatedAt=%s WHERE UserID=%s AND EpisodeID=%s"
+                cursor.execute(update_listen_duration, (listen_duration, listen_date, observed_at, user_id, episode_id))
                 logging.info(f"Updated listen duration for user {user_id} and episode {episode_id} to {listen_duration}")
+                recorded = True
         else:
             # Insert new row
-            add_listen_duration = "INSERT INTO UserEpisodeHistory (UserID, EpisodeID, ListenDate, ListenDuration) VALUES (%s, %s, %s, %s)"
-            cursor.execute(add_listen_duration, (user_id, episode_id, listen_date, listen_duration))
+            add_listen_duration = "INSERT INTO UserEpisodeHistory (UserID, EpisodeID, ListenDate, ListenDuration, PositionUpdatedAt) VALUES (%s, %s, %s, %s, %s)"
+            cursor.execute(add_listen_duration, (user_id, episode_id, listen_date, listen_duration, observed_at))
             logging.info(f"Inserted new listen duration for user {user_id} and episode {episode_id}: {listen_duration}")
+            recorded = True
 
         cnx.commit()
     except Exception as e:
         logging.error(f"Failed to record listen duration due to: {e}")
         cnx.rollback()
+        recorded = False
     finally:
         cursor.close()
     # cnx.close()
+    return recorded
+
+def mark_episode_completed(cnx, episode_id, user_id):
+    listen_date = datetime.datetime.now()
+    # The mark is the newest position, so positions queued before it can't undo it
+    now_ms = int(time.time() * 1000)
+    cursor = cnx.cursor()
+
+    try:
+        # A finished episode was listened to all the way through
+        cursor.execute("SELECT EpisodeDuration FROM Episodes WHERE EpisodeID = %s", (episode_id,))
+        result = cursor.fetchone()
+        duration = result[0] if result and result[0] is not None else 0
+
+        cursor.execute("SELECT UserEpisodeHistoryID FROM UserEpisodeHistory WHERE UserID=%s AND EpisodeID=%s",
+                       (user_id, episode_id))
+        if cursor.fetchone():
+            cursor.execute("UPDATE UserEpisodeHistory SET Completed = TRUE, ListenDuration = %s, ListenDate = %s, "
+                           "PositionUpdatedAt = %s WHERE UserID = %s AND EpisodeID = %s",
+                           (duration, listen_date, now_ms, user_id, episode_id))
+        else:
+            cursor.execute("INSERT INTO UserEpisodeHistory (UserID, EpisodeID, ListenDate, ListenDuration, Completed, "
+                           "PositionUpdatedAt) VALUES (%s, %s, %s, %s, TRUE, %s)",
+                           (user_id, episode_id, listen_date, duration, now_ms))
+
+        cnx.commit()
+    except Exception as e:
+        logging.error(f"Failed to mark episode completed due to: {e}")
+        cnx.rollback()
+    finally:
+        cursor.close()
+
+
+def mark_episode_uncompleted(cnx, episode_id, user_id):
+    now_ms = int(time.time() * 1000)
+    cursor = cnx.cursor()
+
+    try:
+        # An unplayed episode starts over, rather than resuming at the end it was marked at
+        cursor.execute("UPDATE UserEpisodeHistory SET Completed = FALSE, ListenDuration = 0, PositionUpdatedAt = %s "
+                       "WHERE UserID = %s AND EpisodeID = %s", (now_ms, user_id, episode_id))
+        cnx.commit()
+    except Exception as e:
+        logging.error(f"Failed to mark episode uncompleted due to: {e}")
+        cnx.rollback()
+    finally:
+        cursor.close()
+
+
+def mark_older_episodes_completed(cnx, episode_id, user_id):
+    """Marks every episode of the podcast published before this one completed, and
+    returns the IDs of those that weren't already."""
+    listen_date = datetime.datetime.now()
+    now_ms = int(time.time() * 1000)
+    cursor = cnx.cursor()
+
+    try:
+        cursor.execute("SELECT older.EpisodeID, older.EpisodeDuration, UserEpisodeHistory.UserEpisodeHistoryID "
+                       "FROM Episodes AS episode "
+                       "INNER JOIN Podcasts ON episode.PodcastID = Podcasts.PodcastID "
+                       "INNER JOIN Episodes AS older ON older.PodcastID = episode.PodcastID "
+                       "AND older.EpisodePubDate < episode.EpisodePubDate "
+                       "LEFT JOIN UserEpisodeHistory ON older.EpisodeID = UserEpisodeHistory.EpisodeID "
+                       "AND UserEpisodeHistory.UserID = %s "
+                       "WHERE episode.EpisodeID = %s AND Podcasts.UserID = %s "
+                       "AND (UserEpisodeHistory.Completed IS NULL OR UserEpisodeHistory.Completed = FALSE)",
+                       (user_id, episode_id, user_id))
+        older = cursor.fetchall()
+
+        for older_id, duration, history_id in older:
+            duration = duration if duration is not None else 0
+            if history_id is not None:
+                cursor.execute("UPDATE UserEpisodeHistory SET Completed = TRUE, ListenDuration = %s, PositionUpdatedAt = %s "
+                               "WHERE UserEpisodeHistoryID = %s", (duration, now_ms, history_id))
+            else:
+                cursor.execute("INSERT INTO UserEpisodeHistory (UserID, EpisodeID, ListenDate, ListenDuration, Completed, "
+                               "PositionUpdatedAt) VALUES (%s, %s, %s, %s, TRUE, %s)",
+                               (user_id, older_id, listen_date, duration, now_ms))
+
+        cnx.commit()
+        return [older_id for older_id, _, _ in older]
+    except Exception as e:
+        logging.error(f"Failed to mark older episodes completed due to: {e}")
+        cnx.rollback()
+        return []
+    finally:
+        cursor.close()
+
+
+def add_bookmark(cnx, user_id, episode_id, bookmark_time, note):
+    cursor = cnx.cursor()
+    try:
+        cursor.execute("INSERT INTO Bookmarks (UserID, EpisodeID, BookmarkTime, Note) VALUES (%s, %s, %s, %s)",
+                       (user_id, episode_id, bookmark_time, note))
+        cnx.commit()
+        return cursor.lastrowid
+    finally:
+        cursor.close()
+
+
+def delete_bookmark(cnx, user_id, bookmark_id):
+    cursor = cnx.cursor()
+    try:
+        cursor.execute("DELETE FROM Bookmarks WHERE BookmarkID = %s AND UserID = %s", (bookmark_id, user_id))
+        cnx.commit()
+        return cursor.rowcount > 0
+    finally:
+        cursor.close()
+
+
+def get_bookmarks(cnx, user_id, episode_id=None):
+    cursor = cnx.cursor()
+    try:
+        # With an episode, its bookmarks in playback order; without, everything the user
+        # marked, grouped by episode with the most recently bookmarked first
+        query = ("SELECT Bookmarks.BookmarkID, Bookmarks.EpisodeID, Bookmarks.BookmarkTime, Bookmarks.Note, "
+                 "Bookmarks.CreatedAt, Episodes.EpisodeTitle, Podcasts.PodcastName "
+                 "FROM Bookmarks "
+                 "INNER JOIN Episodes ON Bookmarks.EpisodeID = Episodes.EpisodeID "
+                 "INNER JOIN Podcasts ON Episodes.PodcastID = Podcasts.PodcastID "
+                 "WHERE Bookmarks.UserID = %s")
+        params = [user_id]
+        if episode_id is not None:
+            query += " AND Bookmarks.EpisodeID = %s ORDER BY Bookmarks.BookmarkTime"
+            params.append(episode_id)
+        else:
+            query += " ORDER BY Podcasts.PodcastName, Episodes.EpisodeTitle, Bookmarks.BookmarkTime"
+        cursor.execute(query, tuple(params))
+        return [
+            {
+                "bookmark_id": bookmark_id,
+                "episode_id": bookmarked_episode,
+                "time": bookmark_time or 0,
+                "note": note,
+                "created_at": created_at.isoformat() if created_at else None,
+                "episode_title": episode_title,
+                "podcast_name": podcast_name,
+            }
+            for (bookmark_id, bookmarked_episode, bookmark_time, note, created_at, episode_title, podcast_name)
+            in cursor.fetchall()
+        ]
+    finally:
+        cursor.close()
+
 
 def get_local_episode_times(cnx, user_id):
     cursor = cnx.cursor()
@@ -1284,6 +1517,66 @@ def set_theme(cnx, user_id, theme):
             # cnx.close()
 
 
+def get_playback_settings(cnx, user_id):
+    cursor = None
+    try:
+        cursor = cnx.cursor()
+
+        query = ("SELECT SkipBackSeconds, SkipForwardSeconds, KeyBindings, VoiceBoost, NormalizeLoudness, SkipSilence, "
+                 "AutoPlayNext, RemoveFinishedFromQueue, CompletionPercent, CompletionRemainingSeconds "
+                 "FROM UserSettings WHERE UserID = %s")
+        cursor.execute(query, (user_id,))
+        result = cursor.fetchone()
+        if result is None:
+            return {"skip_back": 15, "skip_forward": 15, "key_bindings": None,
+                    "voice_boost": False, "normalize_loudness": False, "skip_silence": False,
+                    "auto_play_next": True, "remove_finished": True,
+                    "completion_percent": 95, "completion_remaining": 60}
+
+        (skip_back, skip_forward, key_bindings, voice_boost, normalize_loudness, skip_silence,
+         auto_play_next, remove_finished, completion_percent, completion_remaining) = result
+        # Key bindings are kept as JSON; None means the user never changed the defaults
+        return {
+            "skip_back": skip_back if skip_back is not None else 15,
+            "skip_forward": skip_forward if skip_forward is not None else 15,
+            "key_bindings": json.loads(key_bindings) if key_bindings else None,
+            "voice_boost": bool(voice_boost),
+            "normalize_loudness": bool(normalize_loudness),
+            "skip_silence": bool(skip_silence),
+            "auto_play_next": auto_play_next is None or bool(auto_play_next),
+            "remove_finished": remove_finished is None or bool(remove_finished),
+            "completion_percent": completion_percent if completion_percent is not None else 95,
+            "completion_remaining": completion_remaining if completion_remaining is not None else 60,
+        }
+
+    finally:
+        if cursor:
+            cursor.close()
+
+
+def set_playback_settings(cnx, user_id, skip_back, skip_forward, key_bindings,
+                          voice_boost=False, normalize_loudness=False, skip_silence=False,
+                          auto_play_next=True, remove_finished=True,
+                          completion_percent=95, completion_remaining=60):
+    cursor = None
+    try:
+        cursor = cnx.cursor()
+
+        query = ("UPDATE UserSettings SET SkipBackSeconds = %s, SkipForwardSeconds = %s, KeyBindings = %s, "
+                 "VoiceBoost = %s, NormalizeLoudness = %s, SkipSilence = %s, "
+                 "AutoPlayNext = %s, RemoveFinishedFromQueue = %s, "
+                 "CompletionPercent = %s, CompletionRemainingSeconds = %s "
+                 "WHERE UserID = %s")
+        bindings = json.dumps(key_bindings) if key_bindings is not None else None
+        cursor.execute(query, (skip_back, skip_forward, bindings, voice_boost, normalize_loudness, skip_silence,
+                               auto_play_next, remove_finished, completion_percent, completion_remaining, user_id))
+        cnx.commit()
+
+    finally:
+        if cursor:
+            cursor.close()
+
+
 def get_user_info(database_type, cnx):
     if database_type == "postgresql":
         cursor = cnx.cursor(cursor_factory=RealDictCursor)
@@ -1359,8 +1652,8 @@ def create_api_key(cnx, user_id):
     api_key = ''.join(secrets.choice(alphabet) for _ in range(64))
 
     cursor = cnx.cursor()
-    query = "INSERT INTO APIKeys (UserID, APIKey) VALUES (%s, %s)"
-    cursor.execute(query, (user_id, api_key))
+    query = "INSERT INTO APIKeys (UserID, APIKey, KeyFingerprint) VALUES (%s, %s, %s)"
+    cursor.execute(query, (user_id, api_key, api_key_fingerprint(api_key)))
     cnx.commit()
     cursor.close()
     # cnx.close()
@@ -1472,6 +1765,13 @@ def delete_user(cnx, user_id):
     except:
         pass
 
+    # Delete user from Bookmarks table
+    try:
+        query = "DELETE FROM Bookmarks WHERE UserID = %s"
+        cursor.execute(query, (user_id,))
+    except:
+        pass
+
     # Delete user from Podcasts table
     try:
         query = "DELETE FROM Podcasts WHERE UserID = %s"
@@ -1680,6 +1980,19 @@ def id_from_api_key(cnx, passed_key):
     return result[0] if result else None
 
 
+def api_key_fingerprint(api_key):
+    # Relay links name their key by the start of its SHA-256 rather than carrying it
+    return hashlib.sha256(api_key.encode()).hexdigest()[:16]
+
+
+def api_key_from_fingerprint(cnx, fingerprint):
+    cursor = cnx.cursor()
+    cursor.execute("SELECT APIKey FROM APIKeys WHERE KeyFingerprint = %s LIMIT 1", (fingerprint,))
+    result = cursor.fetchone()
+    cursor.close()
+    return result[0] if result else None
+
+
 def check_api_permission(cnx, passed_key):
     import tempfile
     # Create a temporary file to store the content. This is because the mysql command reads from a file.
@@ -1709,7 +2022,8 @@ def check_api_permission(cnx, passed_key):
 def get_stats(cnx, user_id):
     cursor = cnx.cursor()
 
-    query = ("SELECT UserCreated, PodcastsPlayed, TimeListened, PodcastsAdded, EpisodesSaved, EpisodesDownloaded "
+    query = ("SELECT UserCreated, PodcastsPlayed, TimeListened, PodcastsAdded, EpisodesSaved, EpisodesDownloaded, "
+             "SilenceTimeSaved "
              "FROM UserStats "
              "WHERE UserID = %s")
 
@@ -1725,7 +2039,8 @@ def get_stats(cnx, user_id):
             "TimeListened": result[2],
             "PodcastsAdded": result[3],
             "EpisodesSaved": result[4],
-            "EpisodesDownloaded": result[5]
+            "EpisodesDownloaded": result[5],
+            "SilenceTimeSaved": result[6] or 0
         }
     else:
         stats = None
@@ -1736,7 +2051,7 @@ def get_stats(cnx, user_id):
     return stats
 
 
-def saved_episode_list(database_type, cnx, user_id):
+def saved_episode_list(database_type, cnx, user_id, offset=0, limit=None):
     if database_type == "postgresql":
         cursor = cnx.cursor(cursor_factory=RealDictCursor)
     else:  # Assuming MariaDB/MySQL if not PostgreSQL
@@ -1744,15 +2059,16 @@ def saved_episode_list(database_type, cnx, user_id):
 
     query = (f"SELECT Podcasts.PodcastName, Episodes.EpisodeTitle, Episodes.EpisodePubDate, "
              f"Episodes.EpisodeDescription, Episodes.EpisodeID, Episodes.EpisodeArtwork, Episodes.EpisodeURL, "
-             f"Episodes.EpisodeDuration, Podcasts.WebsiteURL, UserEpisodeHistory.ListenDuration "
+             f"Episodes.EpisodeDuration, Podcasts.WebsiteURL, UserEpisodeHistory.ListenDuration, UserEpisodeHistory.Completed "
              f"FROM SavedEpisodes "
              f"INNER JOIN Episodes ON SavedEpisodes.EpisodeID = Episodes.EpisodeID "
              f"INNER JOIN Podcasts ON Episodes.PodcastID = Podcasts.PodcastID "
              f"LEFT JOIN UserEpisodeHistory ON SavedEpisodes.EpisodeID = UserEpisodeHistory.EpisodeID AND UserEpisodeHistory.UserID = %s "
              f"WHERE SavedEpisodes.UserID = %s "
-             f"ORDER BY SavedEpisodes.SaveDate DESC")
+             f"ORDER BY SavedEpisodes.SaveDate DESC, SavedEpisodes.SaveID DESC")
+    page, page_params = page_clause(offset, limit)
 
-    cursor.execute(query, (user_id, user_id))
+    cursor.execute(query + page, (user_id, user_id) + page_params)
     rows = cursor.fetchall()
 
     cursor.close()
@@ -1865,6 +2181,18 @@ def increment_listen_time(cnx, user_id):
     # cnx.close()
 
 
+def add_silence_time_saved(cnx, user_id, seconds):
+    cursor = cnx.cursor()
+
+    # Seconds of silence skipped over while playing, reported by the player
+    query = ("UPDATE UserStats SET SilenceTimeSaved = SilenceTimeSaved + %s "
+             "WHERE UserID = %s")
+    cursor.execute(query, (seconds, user_id))
+    cnx.commit()
+
+    cursor.close()
+
+
 def get_user_episode_count(cnx, user_id):
     cursor = cnx.cursor()
 
@@ -2192,7 +2520,7 @@ def get_episode_metadata(database_type, cnx, episode_id, user_id):
     query = (
         f"SELECT Podcasts.PodcastID, Podcasts.PodcastName, Podcasts.ArtworkURL, Episodes.EpisodeTitle, Episodes.EpisodePubDate, "
         f"Episodes.EpisodeDescription, Episodes.EpisodeArtwork, Episodes.EpisodeURL, Episodes.EpisodeDuration, Episodes.EpisodeID, "
-        f"Podcasts.WebsiteURL, UserEpisodeHistory.ListenDuration "
+        f"Podcasts.WebsiteURL, Podcasts.FeedURL, UserEpisodeHistory.ListenDuration, UserEpisodeHistory.Completed "
         f"FROM Episodes "
         f"INNER JOIN Podcasts ON Episodes.PodcastID = Podcasts.PodcastID "
         f"LEFT JOIN UserEpisodeHistory ON Episodes.EpisodeID = UserEpisodeHistory.EpisodeID AND Podcasts.UserID = UserEpisodeHistory.UserID "
@@ -2480,8 +2808,9 @@ def search_data(database_type, cnx, search_term, user_id):
         cursor = cnx.cursor(dictionary=True)
 
     query = """
-    SELECT * FROM Podcasts 
+    SELECT Podcasts.*, Episodes.*, UserEpisodeHistory.ListenDuration, UserEpisodeHistory.Completed FROM Podcasts 
     INNER JOIN Episodes ON Podcasts.PodcastID = Episodes.PodcastID 
+    LEFT JOIN UserEpisodeHistory ON Episodes.EpisodeID = UserEpisodeHistory.EpisodeID AND UserEpisodeHistory.UserID = Podcasts.UserID 
     WHERE Podcasts.UserID = %s AND 
     Episodes.EpisodeTitle LIKE %s
     """
@@ -2617,6 +2946,7 @@ def get_queued_episodes(database_type, cnx, user_id):
         Episodes.EpisodeDuration, 
         EpisodeQueue.QueueDate,
         UserEpisodeHistory.ListenDuration,
+        UserEpisodeHistory.Completed,
         Episodes.EpisodeID
     FROM EpisodeQueue 
     INNER JOIN Episodes ON EpisodeQueue.EpisodeID = Episodes.EpisodeID 
@@ -1,5 +1,6 @@
 import mysql.connector
 import os
+import hashlib
 import sys
 from cryptography.fernet import Fernet
 import string
@@ -77,8 +78,16 @@ try:
                         UserID INT,
                         APIKey TEXT,
                         Created TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                        KeyFingerprint CHAR(16),
                         FOREIGN KEY (UserID) REFERENCES Users(UserID) ON DELETE CASCADE
                     )""")
+    # Start of the key's SHA-256, which relay links carry instead of the key
+    cursor.execute("""ALTER TABLE APIKeys ADD COLUMN IF NOT EXISTS KeyFingerprint CHAR(16)""")
+    cursor.execute("SELECT APIKeyID, APIKey FROM APIKeys WHERE KeyFingerprint IS NULL")
+    for key_id, key in cursor.fetchall():
+        cursor.execute("UPDATE APIKeys SET KeyFingerprint = %s WHERE APIKeyID = %s",
+                       (hashlib.sha256(key.encode()).hexdigest()[:16], key_id))
+    cnx.commit()
 
     cursor.execute("""CREATE TABLE IF NOT EXISTS UserStats (
                         UserStatsID INT AUTO_INCREMENT PRIMARY KEY,
@@ -89,8 +98,11 @@ try:
                         PodcastsAdded INT DEFAULT 0,
                         EpisodesSaved INT DEFAULT 0,
                         EpisodesDownloaded INT DEFAULT 0,
+                        SilenceTimeSaved INT DEFAULT 0,
                         FOREIGN KEY (UserID) REFERENCES Users(UserID)
                     )""")
+    # Installs created before silence skipping existed
+    cursor.execute("""ALTER TABLE UserStats ADD COLUMN IF NOT EXISTS SilenceTimeSaved INT DEFAULT 0""")
 
     # Generate a key
     key = Fernet.generate_key()
@@ -164,7 +176,8 @@ try:
             api_key = ''.join(secrets.choice(alphabet) for _ in range(64))
 
             # Note the quotes around {api_key}
-            query = f"INSERT INTO APIKeys (UserID, APIKey) VALUES ({user_id}, '{api_key}')"
+            fingerprint = hashlib.sha256(api_key.encode()).hexdigest()[:16]
+            query = f"INSERT INTO APIKeys (UserID, APIKey, KeyFingerprint) VALUES ({user_id}, '{api_key}', '{fingerprint}')"
             cursor_key.execute(query)
 
             cnx.commit()
@@ -214,9 +227,23 @@ try:
                         FeedURL TEXT,
                         WebsiteURL TEXT,
                         Explicit TINYINT(1),
+                        PlaybackSpeed DECIMAL(3,2) DEFAULT 1.00,
+                        SkipIntroSeconds INT DEFAULT 0,
+                        SkipOutroSeconds INT DEFAULT 0,
+                        VoiceBoost BOOLEAN,
+                        NormalizeLoudness BOOLEAN,
+                        SkipSilence BOOLEAN,
                         UserID INT,
                         FOREIGN KEY (UserID) REFERENCES Users(UserID)
                     )""")
+    # Installs created before per-podcast playback settings existed
+    cursor.execute("""ALTER TABLE Podcasts ADD COLUMN IF NOT EXISTS PlaybackSpeed DECIMAL(3,2) DEFAULT 1.00""")
+    cursor.execute("""ALTER TABLE Podcasts ADD COLUMN IF NOT EXISTS SkipIntroSeconds INT DEFAULT 0""")
+    cursor.execute("""ALTER TABLE Podcasts ADD COLUMN IF NOT EXISTS SkipOutroSeconds INT DEFAULT 0""")
+    # Audio processing overrides; NULL follows the user's own setting
+    cursor.execute("""ALTER TABLE Podcasts ADD COLUMN IF NOT EXISTS VoiceBoost BOOLEAN""")
+    cursor.execute("""ALTER TABLE Podcasts ADD COLUMN IF NOT EXISTS NormalizeLoudness BOOLEAN""")
+    cursor.execute("""ALTER TABLE Podcasts ADD COLUMN IF NOT EXISTS SkipSilence BOOLEAN""")
 
     cursor.execute("""CREATE TABLE IF NOT EXISTS Episodes (
                         EpisodeID INT AUTO_INCREMENT PRIMARY KEY,
@@ -238,6 +265,7 @@ try:
     create_index_if_not_exists(cursor, "idx_podcasts_userid", "Podcasts", "UserID")
     create_index_if_not_exists(cursor, "idx_episodes_podcastid", "Episodes", "PodcastID")
     create_index_if_not_exists(cursor, "idx_episodes_episodepubdate", "Episodes", "EpisodePubDate")
+    create_index_if_not_exists(cursor, "idx_apikeys_keyfingerprint", "APIKeys", "KeyFingerprint")
 
 
 
@@ -245,8 +273,29 @@ try:
                         UserSettingID INT AUTO_INCREMENT PRIMARY KEY,
                         UserID INT UNIQUE,
                         Theme VARCHAR(255) DEFAULT 'nordic',
+                        SkipBackSeconds INT DEFAULT 15,
+                        SkipForwardSeconds INT DEFAULT 15,
+                        KeyBindings TEXT,
+                        VoiceBoost BOOLEAN DEFAULT FALSE,
+                        NormalizeLoudness BOOLEAN DEFAULT FALSE,
+                        SkipSilence BOOLEAN DEFAULT FALSE,
+                        AutoPlayNext BOOLEAN DEFAULT TRUE,
+                        RemoveFinishedFromQueue BOOLEAN DEFAULT TRUE,
+                        CompletionPercent INT DEFAULT 95,
+                        CompletionRemainingSeconds INT DEFAULT 60,
                         FOREIGN KEY (UserID) REFERENCES Users(UserID)
                     )""")
+    # Installs created before the playback settings columns existed
+    cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS SkipBackSeconds INT DEFAULT 15""")
+    cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS SkipForwardSeconds INT DEFAULT 15""")
+    cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS KeyBindings TEXT""")
+    cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS VoiceBoost BOOLEAN DEFAULT FALSE""")
+    cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS NormalizeLoudness BOOLEAN DEFAULT FALSE""")
+    cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS SkipSilence BOOLEAN DEFAULT FALSE""")
+    cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS AutoPlayNext BOOLEAN DEFAULT TRUE""")
+    cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS RemoveFinishedFromQueue BOOLEAN DEFAULT TRUE""")
+    cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS CompletionPercent INT DEFAULT 95""")
+    cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS CompletionRemainingSeconds INT DEFAULT 60""")
 
     cursor.execute("""INSERT IGNORE INTO UserSettings (UserID, Theme) VALUES ('1', 'nordic')""")
     cursor.execute("""INSERT IGNORE INTO UserSettings (UserID, Theme) VALUES ('2', 'nordic')""")
@@ -257,9 +306,15 @@ try:
                         EpisodeID INT,
                         ListenDate DATETIME,
                         ListenDuration INT,
+                        Completed BOOLEAN DEFAULT FALSE,
+                        PositionUpdatedAt BIGINT,
                         FOREIGN KEY (UserID) REFERENCES Users(UserID),
                         FOREIGN KEY (EpisodeID) REFERENCES Episodes(EpisodeID)
                     )""")
+    # Installs created before episodes could be marked completed
+    cursor.execute("""ALTER TABLE UserEpisodeHistory ADD COLUMN IF NOT EXISTS Completed BOOLEAN DEFAULT FALSE""")
+    # Server time, in milliseconds, of the position last saved from any device
+    cursor.execute("""ALTER TABLE UserEpisodeHistory ADD COLUMN IF NOT EXISTS PositionUpdatedAt BIGINT""")
 
     cursor.execute("""CREATE TABLE IF NOT EXISTS SavedEpisodes (
                         SaveID INT AUTO_INCREMENT PRIMARY KEY,
@@ -270,6 +325,18 @@ try:
                         FOREIGN KEY (EpisodeID) REFERENCES Episodes(EpisodeID)
                     )""")
 
+    # Moments a user marked in an episode, each with an optional note
+    cursor.execute("""CREATE TABLE IF NOT EXISTS Bookmarks (
+                        BookmarkID INT AUTO_INCREMENT PRIMARY KEY,
+                        UserID INT,
+                        EpisodeID INT,
+                        BookmarkTime INT,
+                        Note TEXT,
+                        CreatedAt TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (UserID) REFERENCES Users(UserID),
+                        FOREIGN KEY (EpisodeID) REFERENCES Episodes(EpisodeID)
+                    )""")
+
 
     # Create the DownloadedEpisodes table
     cursor.execute("""CREATE TABLE IF NOT EXISTS DownloadedEpisodes (
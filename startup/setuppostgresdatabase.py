@@ -1,5 +1,6 @@
 import os
 import sys
+import hashlib
 from cryptography.fernet import Fernet
 import string
 import secrets
@@ -62,8 +63,16 @@ cursor.execute("""CREATE TABLE IF NOT EXISTS APIKeys (
                     UserID INT,
                     APIKey TEXT,
                     Created TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    KeyFingerprint CHAR(16),
                     FOREIGN KEY (UserID) REFERENCES Users(UserID) ON DELETE CASCADE
                 )""")
+# Start of the key's SHA-256, which relay links carry instead of the key
+cursor.execute("""ALTER TABLE APIKeys ADD COLUMN IF NOT EXISTS KeyFingerprint CHAR(16)""")
+cursor.execute("SELECT APIKeyID, APIKey FROM APIKeys WHERE KeyFingerprint IS NULL")
+for key_id, key in cursor.fetchall():
+    cursor.execute("UPDATE APIKeys SET KeyFingerprint = %s WHERE APIKeyID = %s",
+                   (hashlib.sha256(key.encode()).hexdigest()[:16], key_id))
+cnx.commit()
 
 cursor.execute("""CREATE TABLE IF NOT EXISTS UserStats (
                     UserStatsID SERIAL PRIMARY KEY,
@@ -74,8 +83,11 @@ cursor.execute("""CREATE TABLE IF NOT EXISTS UserStats (
                     PodcastsAdded INT DEFAULT 0,
                     EpisodesSaved INT DEFAULT 0,
                     EpisodesDownloaded INT DEFAULT 0,
+                    SilenceTimeSaved INT DEFAULT 0,
                     FOREIGN KEY (UserID) REFERENCES Users(UserID)
                 )""")
+# Installs created before silence skipping existed
+cursor.execute("""ALTER TABLE UserStats ADD COLUMN IF NOT EXISTS SilenceTimeSaved INT DEFAULT 0""")
 
 # Generate a key
 key = Fernet.generate_key()
@@ -149,8 +161,8 @@ def create_api_key(cnx, user_id=1):
         alphabet = string.ascii_letters + string.digits
         api_key = ''.join(secrets.choice(alphabet) for _ in range(64))
 
-        query = "INSERT INTO APIKeys (UserID, APIKey) VALUES (%s, %s)"
-        cursor.execute(query, (user_id, api_key))
+        query = "INSERT INTO APIKeys (UserID, APIKey, KeyFingerprint) VALUES (%s, %s, %s)"
+        cursor.execute(query, (user_id, api_key, hashlib.sha256(api_key.encode()).hexdigest()[:16]))
         cnx.commit()
 
     cursor.close()
@@ -198,9 +210,23 @@ cursor.execute("""CREATE TABLE IF NOT EXISTS Podcasts (
                     EpisodeCount INT,
                     FeedURL TEXT,
                     WebsiteURL TEXT,
+                    PlaybackSpeed DECIMAL(3,2) DEFAULT 1.00,
+                    SkipIntroSeconds INT DEFAULT 0,
+                    SkipOutroSeconds INT DEFAULT 0,
+                    VoiceBoost BOOLEAN,
+                    NormalizeLoudness BOOLEAN,
+                    SkipSilence BOOLEAN,
                     UserID INT,
                     FOREIGN KEY (UserID) REFERENCES Users(UserID)
                 )""")
+# Installs created before per-podcast playback settings existed
+cursor.execute("""ALTER TABLE Podcasts ADD COLUMN IF NOT EXISTS PlaybackSpeed DECIMAL(3,2) DEFAULT 1.00""")
+cursor.execute("""ALTER TABLE Podcasts ADD COLUMN IF NOT EXISTS SkipIntroSeconds INT DEFAULT 0""")
+cursor.execute("""ALTER TABLE Podcasts ADD COLUMN IF NOT EXISTS SkipOutroSeconds INT DEFAULT 0""")
+# Audio processing overrides; NULL follows the user's own setting
+cursor.execute("""ALTER TABLE Podcasts ADD COLUMN IF NOT EXISTS VoiceBoost BOOLEAN""")
+cursor.execute("""ALTER TABLE Podcasts ADD COLUMN IF NOT EXISTS NormalizeLoudness BOOLEAN""")
+cursor.execute("""ALTER TABLE Podcasts ADD COLUMN IF NOT EXISTS SkipSilence BOOLEAN""")
 
 cursor.execute("""CREATE TABLE IF NOT EXISTS Episodes (
                     EpisodeID SERIAL PRIMARY KEY,
@@ -227,6 +253,7 @@ def create_index_if_not_exists(cursor, index_name, table_name, column_name):
 create_index_if_not_exists(cursor, "idx_podcasts_userid", "Podcasts", "UserID")
 create_index_if_not_exists(cursor, "idx_episodes_podcastid", "Episodes", "PodcastID")
 create_index_if_not_exists(cursor, "idx_episodes_episodepubdate", "Episodes", "EpisodePubDate")
+create_index_if_not_exists(cursor, "idx_apikeys_keyfingerprint", "APIKeys", "KeyFingerprint")
 
 
 
@@ -234,8 +261,29 @@ cursor.execute("""CREATE TABLE IF NOT EXISTS UserSettings (
                     UserSettingID SERIAL PRIMARY KEY,
                     UserID INT UNIQUE,
                     Theme VARCHAR(255) DEFAULT 'nordic',
+                    SkipBackSeconds INT DEFAULT 15,
+                    SkipForwardSeconds INT DEFAULT 15,
+                    KeyBindings TEXT,
+                    VoiceBoost BOOLEAN DEFAULT FALSE,
+                    NormalizeLoudness BOOLEAN DEFAULT FALSE,
+                    SkipSilence BOOLEAN DEFAULT FALSE,
+                    AutoPlayNext BOOLEAN DEFAULT TRUE,
+                    RemoveFinishedFromQueue BOOLEAN DEFAULT TRUE,
+                    CompletionPercent INT DEFAULT 95,
+                    CompletionRemainingSeconds INT DEFAULT 60,
                     FOREIGN KEY (UserID) REFERENCES Users(UserID)
                 )""")
+# Installs created before the playback settings columns existed
+cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS SkipBackSeconds INT DEFAULT 15""")
+cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS SkipForwardSeconds INT DEFAULT 15""")
+cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS KeyBindings TEXT""")
+cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS VoiceBoost BOOLEAN DEFAULT FALSE""")
+cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS NormalizeLoudness BOOLEAN DEFAULT FALSE""")
+cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS SkipSilence BOOLEAN DEFAULT FALSE""")
+cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS AutoPlayNext BOOLEAN DEFAULT TRUE""")
+cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS RemoveFinishedFromQueue BOOLEAN DEFAULT TRUE""")
+cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS CompletionPercent INT DEFAULT 95""")
+cursor.execute("""ALTER TABLE UserSettings ADD COLUMN IF NOT EXISTS CompletionRemainingSeconds INT DEFAULT 60""")
 
 cursor.execute("""INSERT INTO UserSettings (UserID, Theme) VALUES ('1', 'nordic') ON CONFLICT (UserID) DO NOTHING""")
 cursor.execute("""INSERT INTO UserSettings (UserID, Theme) VALUES ('2', 'nordic') ON CONFLICT (UserID) DO NOTHING""")
@@ -246,9 +294,15 @@ cursor.execute("""CREATE TABLE IF NOT EXISTS UserEpisodeHistory (
                     EpisodeID INT,
                     ListenDate TIMESTAMP,
                     ListenDuration INT,
+                    Completed BOOLEAN DEFAULT FALSE,
+                    PositionUpdatedAt BIGINT,
                     FOREIGN KEY (UserID) REFERENCES Users(UserID),
                     FOREIGN KEY (EpisodeID) REFERENCES Episodes(EpisodeID)
                 )""")
+# Installs created before episodes could be marked completed
+cursor.execute("""ALTER TABLE UserEpisodeHistory ADD COLUMN IF NOT EXISTS Completed BOOLEAN DEFAULT FALSE""")
+# Server time, in milliseconds, of the position last saved from any device
+cursor.execute("""ALTER TABLE UserEpisodeHistory ADD COLUMN IF NOT EXISTS PositionUpdatedAt BIGINT""")
 
 cursor.execute("""CREATE TABLE IF NOT EXISTS SavedEpisodes (
                     SaveID SERIAL PRIMARY KEY,
@@ -259,6 +313,18 @@ cursor.execute("""CREATE TABLE IF NOT EXISTS SavedEpisodes (
                     FOREIGN KEY (EpisodeID) REFERENCES Episodes(EpisodeID)
                 )""")
 
+# Moments a user marked in an episode, each with an optional note
+cursor.execute("""CREATE TABLE IF NOT EXISTS Bookmarks (
+                    BookmarkID SERIAL PRIMARY KEY,
+                    UserID INT,
+                    EpisodeID INT,
+                    BookmarkTime INT,
+                    Note TEXT,
+                    CreatedAt TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (UserID) REFERENCES Users(UserID),
+                    FOREIGN KEY (EpisodeID) REFERENCES Episodes(EpisodeID)
+                )""")
+
 
 # Create the DownloadedEpisodes table
 cursor.execute("""CREATE TABLE IF NOT EXISTS DownloadedEpisodes (
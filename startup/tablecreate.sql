@@ -13,6 +13,7 @@ CREATE TABLE APIKeys (
   UserID INT,
   APIKey TEXT,
   Created TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  KeyFingerprint CHAR(16),
   FOREIGN KEY (UserID) REFERENCES Users(UserID) ON DELETE CASCADE
 );
 
@@ -26,6 +27,7 @@ CREATE TABLE UserStats (
   PodcastsAdded INT DEFAULT 0,
   EpisodesSaved INT DEFAULT 0,
   EpisodesDownloaded INT DEFAULT 0,
+  SilenceTimeSaved INT DEFAULT 0,
   FOREIGN KEY (UserID) REFERENCES Users(UserID)
 );
 
@@ -51,6 +53,12 @@ CREATE TABLE Podcasts (
   EpisodeCount INT,
   FeedURL TEXT,
   WebsiteURL TEXT,
+  PlaybackSpeed DECIMAL(3,2) DEFAULT 1.00,
+  SkipIntroSeconds INT DEFAULT 0,
+  SkipOutroSeconds INT DEFAULT 0,
+  VoiceBoost BOOLEAN,
+  NormalizeLoudness BOOLEAN,
+  SkipSilence BOOLEAN,
   UserID INT,
   FOREIGN KEY (UserID) REFERENCES Users(UserID)
 );
@@ -71,6 +79,16 @@ CREATE TABLE UserSettings (
   UserSettingID INT AUTO_INCREMENT PRIMARY KEY,
   UserID INT UNIQUE,
   Theme VARCHAR(255) DEFAULT 'nordic',
+  SkipBackSeconds INT DEFAULT 15,
+  SkipForwardSeconds INT DEFAULT 15,
+  KeyBindings TEXT,
+  VoiceBoost BOOLEAN DEFAULT FALSE,
+  NormalizeLoudness BOOLEAN DEFAULT FALSE,
+  SkipSilence BOOLEAN DEFAULT FALSE,
+  AutoPlayNext BOOLEAN DEFAULT TRUE,
+  RemoveFinishedFromQueue BOOLEAN DEFAULT TRUE,
+  CompletionPercent INT DEFAULT 95,
+  CompletionRemainingSeconds INT DEFAULT 60,
   FOREIGN KEY (UserID) REFERENCES Users(UserID)
 );
 
@@ -85,6 +103,8 @@ CREATE TABLE UserEpisodeHistory (
   EpisodeID INT,
   ListenDate DATETIME,
   ListenDuration INT,
+  Completed BOOLEAN DEFAULT FALSE,
+  PositionUpdatedAt BIGINT,
   FOREIGN KEY (UserID) REFERENCES Users(UserID),
   FOREIGN KEY (EpisodeID) REFERENCES Episodes(EpisodeID)
 );
@@ -98,6 +118,17 @@ CREATE TABLE SavedEpisodes (
   FOREIGN KEY (EpisodeID) REFERENCES Episodes(EpisodeID)
 );
 
+CREATE TABLE Bookmarks (
+  BookmarkID INT AUTO_INCREMENT PRIMARY KEY,
+  UserID INT,
+  EpisodeID INT,
+  BookmarkTime INT,
+  Note TEXT,
+  CreatedAt TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  FOREIGN KEY (UserID) REFERENCES Users(UserID),
+  FOREIGN KEY (EpisodeID) REFERENCES Episodes(EpisodeID)
+);
+
 CREATE TABLE DownloadedEpisodes (
   DownloadID INT AUTO_INCREMENT PRIMARY KEY,
   UserID INT,
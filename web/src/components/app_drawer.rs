@@ -1,7 +1,8 @@
 use yew::prelude::*;
 use yew_router::prelude::Link;
 use yewdux::{use_store};
-use crate::components::context::AppState;
+use crate::components::context::{AppState, OutboxState, UIState};
+use crate::components::server_profiles::{self, ServerProfile};
 use super::routes::Route;
 
 #[allow(non_camel_case_types)]
@@ -11,7 +12,9 @@ pub fn app_drawer() -> Html {
     // let (state, _dispatch) = use_store::<AppState>();
 
     let is_drawer_open = use_state(|| false);
-    let (state, _dispatch) = use_store::<AppState>();
+    let (state, dispatch) = use_store::<AppState>();
+    let (_ui_state, ui_dispatch) = use_store::<UIState>();
+    let (outbox_state, _outbox_dispatch) = use_store::<OutboxState>();
     let username = state.user_details.as_ref().map_or("Guest".to_string(), |ud| ud.Username.clone().unwrap());
     let toggle_drawer = {
         let is_drawer_open = is_drawer_open.clone();
@@ -28,6 +31,25 @@ pub fn app_drawer() -> Html {
         }
     };
 
+    // Other saved accounts, read when the drawer opens so new logins show up
+    let other_profiles: Vec<ServerProfile> = if *is_drawer_open {
+        server_profiles::load_profiles()
+            .into_iter()
+            .filter(|profile| state.auth_details.as_ref().map_or(true, |auth| !profile.is_for(auth)))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let on_add_account = {
+        let state = state.clone();
+        let toggle_drawer = toggle_drawer.clone();
+        Callback::from(move |event: MouseEvent| {
+            toggle_drawer(event);
+            server_profiles::add_account(&state);
+        })
+    };
+
     html! {
         <div class="relative min-h-screen">
             // Drawer
@@ -54,6 +76,37 @@ pub fn app_drawer() -> Html {
                             </Link<Route>>
                         </div>
 
+                            // Account switcher
+                            {
+                                other_profiles.into_iter().map(|profile| {
+                                    let label = profile.label();
+                                    let on_switch = {
+                                        let dispatch = dispatch.clone();
+                                        let ui_dispatch = ui_dispatch.clone();
+                                        let toggle_drawer = toggle_drawer.clone();
+                                        Callback::from(move |event: MouseEvent| {
+                                            toggle_drawer(event);
+                                            server_profiles::switch_profile(&dispatch, &ui_dispatch, profile.clone());
+                                        })
+                                    };
+                                    html! {
+                                        <div class="flex items-center space-x-3">
+                                            <div onclick={on_switch} class="drawer-text flex items-center space-x-3 cursor-pointer" title="Switch to this account">
+                                                <span class="material-icons icon-space">{"swap_horiz"}</span>
+                                                <span class="text-sm">{label}</span>
+                                            </div>
+                                        </div>
+                                    }
+                                }).collect::<Html>()
+                            }
+                            <div class="flex items-center space-x-3">
+                                <div onclick={on_add_account} class="drawer-text flex items-center space-x-3 cursor-pointer">
+                                    <span class="material-icons icon-space">{"person_add"}</span>
+                                    <span class="text-sm">{"Add Account"}</span>
+                                </div>
+                            </div>
+                            <hr class="my-4 drawer-hr" />
+
                             // Other Links
                             <div class="m-0 p-0 flex items-center space-x-3">
                                 <div onclick={toggle_drawer.clone()} class="drawer-text flex items-center space-x-3 cursor-pointer">
@@ -95,6 +148,14 @@ pub fn app_drawer() -> Html {
                                     </Link<Route>>
                                 </div>
                             </div>
+                            <div class="flex items-center space-x-3">
+                                <div onclick={toggle_drawer.clone()} class="drawer-text flex items-center space-x-3 cursor-pointer">
+                                    <Link<Route> to={Route::Bookmarks}>
+                                        <span class="material-icons icon-space">{"bookmarks"}</span>
+                                        <span class="text-lg">{"Bookmarks"}</span>
+                                    </Link<Route>>
+                                </div>
+                            </div>
                             <div class="flex items-center space-x-3">
                             <div onclick={toggle_drawer.clone()} class="drawer-text flex items-center space-x-3 cursor-pointer">
                                 <Link<Route> to={Route::Downloads}>
@@ -165,6 +226,18 @@ pub fn app_drawer() -> Html {
                 }
             }
         </div>
+        {
+            if outbox_state.pending > 0 {
+                html! {
+                    <div class="drawer-text flex items-center ml-3" title="Changes made while offline, sent once the connection is back">
+                        <span class="material-icons">{"cloud_upload"}</span>
+                        <span class="text-sm ml-1">{format!("{} pending", outbox_state.pending)}</span>
+                    </div>
+                }
+            } else {
+                html! {}
+            }
+        }
         </div>
 
 
@@ -3,27 +3,50 @@ use yew::{Callback, function_component, Html, html};
 use yew::prelude::*;
 use yew_router::history::{BrowserHistory, History};
 use yewdux::prelude::*;
+use crate::components::audio_chain::{load_source, use_audio_processing};
 use crate::components::context::{AppState, UIState};
-use web_sys::{window, HtmlAudioElement, HtmlInputElement};
+use crate::components::media_session::{self, Action, ActionHandlers};
+use crate::components::shortcuts::{use_keyboard_shortcuts, ShortcutHelp};
+use crate::components::position_sync::{save_outgoing_position, use_position_sync};
+use crate::components::bookmarks::{use_episode_bookmarks, BookmarkButton};
+use crate::components::completion::use_mark_played_at_threshold;
+use crate::components::prefetch::use_prefetch_next;
+use crate::components::scrub_bar::ScrubBar;
+use crate::components::video::VideoSurface;
+use crate::components::up_next::{finish_episode, play_queued, use_up_next, UpNextCard};
+use crate::components::sleep_timer::{expire_sleep_timer, sleep_countdown, use_sleep_timer, SleepMode, SleepTimerControl};
+use web_sys::{HtmlAudioElement, HtmlInputElement};
 use std::string::String;
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen_futures::spawn_local;
 use wasm_bindgen::JsCast;
 use web_sys::HtmlElement;
 use std::rc::Rc;
-use crate::requests::pod_req::{call_add_history, HistoryAddRequest, call_record_listen_duration, RecordListenDurationRequest, call_increment_listen_time, call_increment_played, call_get_queued_episodes, call_remove_queued_episode, QueuePodcastRequest, call_queue_episode, call_check_episode_in_db};
-use futures_util::stream::StreamExt;
-
-
-#[derive(Properties, PartialEq, Debug, Clone)]
+use serde::{Deserialize, Serialize};
+use crate::requests::client::PinepodsClient;
+use crate::requests::outbox::{defer_if_offline, Mutation};
+use crate::requests::login_requests::Capability;
+use crate::requests::pod_req::{next_in_queue, EpisodeInfo, EpisodeRequest, HistoryAddRequest, QueuePodcastRequest};
+use crate::requests::chapters::EpisodeChapters;
+use crate::requests::search_pods::MediaKind;
+use crate::components::gen_funcs::format_time;
+use crate::components::routes::Route;
+use yew_router::Routable;
+
+
+#[derive(Properties, PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct AudioPlayerProps {
     pub src: String,
     pub title: String,
+    pub podcast_name: String,
     pub artwork_url: String,
     pub duration: String,
     pub episode_id: i32,
     pub duration_sec: f64,
     pub start_pos_sec: f64,
+    #[prop_or_default]
+    #[serde(default)]
+    pub media_kind: MediaKind,
 }
 
 #[function_component(AudioPlayer)]
@@ -32,13 +55,18 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
     let (state, _dispatch) = use_store::<AppState>();
     let (audio_state, _audio_dispatch) = use_store::<UIState>();
     let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
+    let client = state.client();
     let episode_id = audio_state.currently_playing.as_ref().map(|props| props.episode_id);
     let history = BrowserHistory::new();
     let history_clone = history.clone();
     let episode_in_db = audio_state.episode_in_db.unwrap_or_default();
-    let progress: UseStateHandle<f64> = use_state(|| 0.0);
+    use_sleep_timer();
+    use_audio_processing();
+    use_up_next();
+    use_position_sync();
+    use_mark_played_at_threshold();
+    use_prefetch_next();
+    let bookmarks = use_episode_bookmarks(episode_id.filter(|_| episode_in_db));
     let artwork_class = if audio_state.audio_playing.unwrap_or(false) {
         classes!("artwork", "playing")
     } else {
@@ -74,42 +102,11 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
             || ()
         }
     });
-    // Update playing state when Spacebar is pressed
-    let audio_dispatch_effect = _audio_dispatch.clone();
-    use_effect_with(
-        (),
-        move |_| {
-            let keydown_handler = {
-                let audio_info = audio_dispatch_effect.clone();
-                Closure::wrap(Box::new(move |event: KeyboardEvent| {
-                    // Check if the event target is not an input or textarea
-                    let target = event.target().unwrap().dyn_into::<web_sys::HtmlElement>().unwrap();
-                    if !(target.tag_name().eq_ignore_ascii_case("input") || target.tag_name().eq_ignore_ascii_case("textarea")) {
-                        if event.key() == " " {
-                            // Prevent the default behavior of the spacebar key
-                            event.prevent_default();
-                            // Toggle `audio_playing` here
-                            audio_info.reduce_mut(|state| {
-                                state.toggle_playback()
-                            });
-                        }
-                    }
-                }) as Box<dyn FnMut(_)>)
-            };
-            window().unwrap().add_event_listener_with_callback("keydown", keydown_handler.as_ref().unchecked_ref()).unwrap();
-            keydown_handler.forget(); // Note: this will make the listener permanent
-            || ()
-        }
-    );
-    
-
-
     // Effect for setting up an interval to update the current playback time
     // Clone `audio_ref` for `use_effect_with`
     let state_clone = audio_state.clone();
     use_effect_with((), {
         let audio_dispatch = _audio_dispatch.clone();
-        let progress = progress.clone(); // Clone for the interval closure
         move |_| {
             let interval_handle = Interval::new(1000, move || {
                 if let Some(audio_element) = state_clone.audio_element.as_ref() {
@@ -121,20 +118,24 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
                     let seconds = (time_in_seconds % 60.0).floor() as i32;
                     let formatted_time = format!("{:02}:{:02}:{:02}", hours, minutes, seconds);
 
-                    // Calculate progress as a percentage
-                    let progress_percentage = if duration > 0.0 {
-                        (time_in_seconds / duration * 100.0)
-                    } else {
-                        0.0
-                    };
-
                     audio_dispatch.reduce_mut(move |state_clone| {
                         // Update the global state with the current time
                         state_clone.current_time_seconds = time_in_seconds;
                         state_clone.current_time_formatted = formatted_time;
                     });
 
-                    progress.set(progress_percentage);
+                    media_session::set_position(duration, audio_element.playback_rate(), time_in_seconds);
+
+                    // Cut the podcast's outro by ending the episode early, the same way it
+                    // would end on its own
+                    let episode_end = audio_dispatch.get().episode_end();
+                    if episode_end < duration && time_in_seconds >= episode_end && !audio_element.paused() {
+                        let _ = audio_element.pause();
+                        audio_dispatch.reduce_mut(|state| state.audio_playing = Some(false));
+                        if let Ok(ended) = web_sys::Event::new("ended") {
+                            let _ = audio_element.dispatch_event(&ended);
+                        }
+                    }
 
                 }
             });
@@ -144,75 +145,23 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
         }
     });
 
-    // Effect for recording the listen duration
-
-    let state_clone_the_squeakuel = audio_state.clone();
-    use_effect_with((), {
-        let server_name = server_name.clone(); // Assuming this is defined elsewhere in your component
-        let api_key = api_key.clone(); // Assuming this is defined elsewhere in your component
-        let episode_id = episode_id.clone(); // Assuming this is defined elsewhere in your component
-        let user_id = user_id.clone(); // Assuming this is defined elsewhere in your component
-        // let episode_in_db_effect = audio_state.episode_in_db.unwrap_or_default();
-    
-        move |_| {
-        // Spawn a new async task
-            let future = async move {
-                let mut interval = gloo_timers::future::IntervalStream::new(30_000);
-                loop {
-                    interval.next().await; // Wait for the next interval tick
-                    // Check if audio is playing before proceeding
-                    if state_clone_the_squeakuel.audio_playing.unwrap_or_default() {
-                        if let Some(audio_element) = state_clone_the_squeakuel.audio_element.as_ref() {
-                            // Use the local_current_seconds here
-                            // let listen_duration = (*local_current_seconds); // Dereference and get the value
-                            let listen_duration = audio_element.current_time();
-
-                            let request_data = RecordListenDurationRequest {
-                                episode_id: episode_id.unwrap().clone(),
-                                user_id: user_id.unwrap().clone(),
-                                listen_duration,
-                            };
-    
-                            // Perform the API call to record the listen duration
-                            match call_record_listen_duration(&server_name.clone().unwrap(), &api_key.clone().unwrap().unwrap(), request_data).await {
-                                Ok(_response) => {
-                                },
-                                Err(_e) => {
-                                }
-                            }
-                        }
-                    }
-                }
-            };
-    
-            // Using wasm_bindgen_futures to spawn the local future
-            wasm_bindgen_futures::spawn_local(future);
-    
-            // Cleanup function (currently no cleanup required for this future)
-            || ()
-        }
-    });
-    
-    
     // Effect for incrementing user listen time
     // Effect for incrementing user listen time
     let state_increment_clone = audio_state.clone();
     use_effect_with((), {
-        let server_name = server_name.clone(); // Make sure `server_name` is cloned from the parent scope
-        let api_key = api_key.clone(); // Make sure `api_key` is cloned from the parent scope
+        let client = client.clone();
         let user_id = user_id.clone(); // Make sure `user_id` is cloned from the parent scope
 
         move |_| {
             let interval_handle = Interval::new(60000, move || {
                 // Check if audio is playing before making the API call
                 if state_increment_clone.audio_playing.unwrap_or_default() {
-                    let server_name = server_name.clone();
-                    let api_key = api_key.clone();
+                    let client = client.clone();
                     let user_id = user_id.clone();
                     
                     // Spawn a new async task for the API call
                     wasm_bindgen_futures::spawn_local(async move {
-                        match call_increment_listen_time(&server_name.unwrap(), &api_key.unwrap().unwrap(), user_id.unwrap()).await {
+                        match client.increment_listen_time(user_id.unwrap()).await {
                             Ok(_response) => {
                             },
                             Err(_e) => {
@@ -229,79 +178,31 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
         }
     });
 
-    // Effect for managing queued episodes
+    // Wrap up the episode when it ends, moving on through the queue
     use_effect_with(audio_ref.clone(), {
         let audio_dispatch = _audio_dispatch.clone();
-        let server_name = server_name.clone();
-        let api_key = api_key.clone();
+        let client = client.clone();
         let user_id = user_id.clone();
-        let current_episode_id = episode_id.clone(); // Assuming this is correctly obtained elsewhere
-        let audio_state = audio_state.clone();
+        let mark_completed = state
+            .server_details
+            .as_ref()
+            .map_or(false, |details| details.supports(Capability::ContinuousPlayback));
         let audio_state_cloned = audio_state.clone();
 
         move |_| {
             if let Some(audio_element) = audio_state_cloned.audio_element.clone() {
-            // if let Some(audio_element) = audio_ref.cast::<HtmlAudioElement>() {
-                // Clone all necessary data to be used inside the closure to avoid FnOnce limitation.
-
                 let ended_closure = Closure::wrap(Box::new(move || {
-                    let server_name = server_name.clone();
-                    let api_key = api_key.clone();
-                    let user_id = user_id.clone();
-                    let audio_dispatch = audio_dispatch.clone();
-                    let current_episode_id = current_episode_id.clone();
-                    let audio_state = audio_state.clone();
-                    // Closure::wrap(Box::new(move |_| {
-                    wasm_bindgen_futures::spawn_local(async move {
-                        let queued_episodes_result = call_get_queued_episodes(&server_name.clone().unwrap(), &api_key.clone().unwrap(), &user_id.clone().unwrap()).await;
-                        match queued_episodes_result {
-                            Ok(episodes) => {
-                                if let Some(current_episode) = episodes.iter().find(|ep| ep.EpisodeID == current_episode_id.unwrap()) {
-                                    let current_queue_position = current_episode.QueuePosition.unwrap_or_default();
-                                    // Remove the currently playing episode from the queue
-                                    let request = QueuePodcastRequest {
-                                        episode_id: current_episode_id.clone().unwrap(),
-                                        user_id: user_id.clone().unwrap(), // replace with the actual user ID
-                                    };
-                                    let remove_result = call_remove_queued_episode(&server_name.clone().unwrap(), &api_key.clone().unwrap(), &request).await;
-                                    match remove_result {
-                                        Ok(_) => {
-                                            // web_sys::console::log_1(&"Successfully removed episode from queue".into());
-                                        },
-                                        Err(_e) => {
-                                            // web_sys::console::log_1(&format!("Failed to remove episode from queue: {:?}", e).into());
-                                        }
-                                    }
-                                    if let Some(next_episode) = episodes.iter().find(|ep| ep.QueuePosition == Some(current_queue_position + 1)) {
-                                        on_play_click(
-                                            next_episode.EpisodeURL.clone(),
-                                            next_episode.EpisodeTitle.clone(),
-                                            next_episode.EpisodeArtwork.clone(),
-                                            next_episode.EpisodeDuration,
-                                            next_episode.EpisodeID,
-                                            next_episode.ListenDuration,
-                                            api_key.clone().unwrap().unwrap(),
-                                            user_id.unwrap(),
-                                            server_name.clone().unwrap(),
-                                            audio_dispatch.clone(),
-                                            audio_state.clone(),
-                                            None,
-                                        ).emit(MouseEvent::new("click").unwrap());
-                                    } else {
-                                        audio_dispatch.reduce_mut(|state| {
-                                            state.audio_playing = Some(false);
-                                        });
-                                    }
-                                }
-                            },
-                            Err(_e) => {
-                                // web_sys::console::log_1(&format!("Failed to fetch queued episodes: {:?}", e).into());
-                            }
-                        }
-                    });
-                    // }) as Box<dyn FnMut()>);
-    
-
+                    // A sleep timer set for the end of the episode stops here, not in the queue
+                    let sleep_at_end = audio_dispatch
+                        .get()
+                        .sleep_timer
+                        .as_ref()
+                        .map_or(false, |timer| timer.mode == SleepMode::EndOfEpisode);
+                    if sleep_at_end {
+                        expire_sleep_timer(&audio_dispatch, &client, user_id);
+                        return;
+                    }
+                    finish_episode(&audio_dispatch, &client, user_id, mark_completed);
                 }) as Box<dyn FnMut()>);
                 // Setting and forgetting the closure must be done within the same scope
                 audio_element.set_onended(Some(ended_closure.as_ref().unchecked_ref()));
@@ -328,29 +229,6 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
     // // Add a new state for the current time in seconds
     //     let current_time_seconds = use_state(|| 0.0);
 
-    let update_time = {
-        let audio_dispatch = _audio_dispatch.clone();
-        Callback::from(move |e: InputEvent| {
-            // Get the value from the target of the InputEvent
-            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
-                if let Ok(value) = input.value().parse::<f64>() {
-                    // Update the state using dispatch
-                    audio_dispatch.reduce_mut(move |state| {
-                        if let Some(audio_element) = state.audio_element.as_ref() {
-                            audio_element.set_current_time(value);
-                            state.current_time_seconds = value;
-
-                            // Update formatted time
-                            let hours = (value / 3600.0).floor() as i32;
-                            let minutes = ((value % 3600.0) / 60.0).floor() as i32;
-                            let seconds = (value % 60.0).floor() as i32;
-                            state.current_time_formatted = format!("{:02}:{:02}:{:02}", hours, minutes, seconds);
-                        }
-                    });
-                }
-            }
-        })
-    };
     let speed_state = audio_state.clone();
     let speed_dispatch = _audio_dispatch.clone();
 
@@ -376,72 +254,44 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
     };
 
 // Skip forward
-    let skip_state = audio_state.clone();
+    let skip_forward_secs = audio_state.playback_settings.skip_forward as f64;
+    let skip_back_secs = audio_state.playback_settings.skip_back as f64;
     let skip_forward = {
         // let dispatch = _dispatch.clone();
         let audio_dispatch = _audio_dispatch.clone();
         Callback::from(move |_| {
-            if let Some(audio_element) = skip_state.audio_element.as_ref() {
-                let new_time = audio_element.current_time() + 15.0;
-                audio_element.set_current_time(new_time);
-                audio_dispatch.reduce_mut(|state| state.update_current_time(new_time));
-            }
+            audio_dispatch.reduce_mut(|state| state.skip_by(skip_forward_secs));
         })
     };
 
-    let backward_state = audio_state.clone();
     let skip_backward = {
         // let dispatch = _dispatch.clone();
         let audio_dispatch = _audio_dispatch.clone();
         Callback::from(move |_| {
-            if let Some(audio_element) = backward_state.audio_element.as_ref() {
-                let new_time = audio_element.current_time() - 15.0;
-                audio_element.set_current_time(new_time);
-                audio_dispatch.reduce_mut(|state| state.update_current_time(new_time));
-            }
+            audio_dispatch.reduce_mut(|state| state.skip_by(-skip_back_secs));
         })
     };
 
     let skip_episode = {
         let audio_dispatch = _audio_dispatch.clone();
-        let server_name = server_name.clone();
-        let api_key = api_key.clone();
+        let client = client.clone();
         let user_id = user_id.clone();
-        let current_episode_id = episode_id.clone(); // Assuming this is correctly obtained elsewhere
-        let audio_state = audio_state.clone();
     
         Callback::from(move |_: MouseEvent| {
-            let server_name = server_name.clone();
-            let api_key = api_key.clone();
+            let client = client.clone();
             let audio_dispatch = audio_dispatch.clone();
-            let audio_state = audio_state.clone();
+            let Some(user_id) = user_id else { return };
+            // Read when clicked, as the player stays mounted from one episode to the next
+            let Some(current_episode_id) = audio_dispatch.get().currently_playing.as_ref().map(|playing| playing.episode_id) else { return };
             wasm_bindgen_futures::spawn_local(async move {
-        
-                let episodes_result = call_get_queued_episodes(&server_name.clone().unwrap(), &api_key.clone().unwrap(), &user_id.clone().unwrap()).await;
+                let episodes_result = client.get_queued_episodes(&user_id).await;
                 if let Ok(episodes) = episodes_result {
-                    if let Some(current_episode) = episodes.iter().find(|ep| ep.EpisodeID == current_episode_id.unwrap()) {
-                        let current_queue_position = current_episode.QueuePosition.unwrap_or_default();
-    
-                        if let Some(next_episode) = episodes.iter().find(|ep| ep.QueuePosition == Some(current_queue_position + 1)) {
-                            on_play_click(
-                                next_episode.EpisodeURL.clone(),
-                                next_episode.EpisodeTitle.clone(),
-                                next_episode.EpisodeArtwork.clone(),
-                                next_episode.EpisodeDuration,
-                                next_episode.EpisodeID,
-                                next_episode.ListenDuration,
-                                api_key.clone().unwrap().unwrap(),
-                                user_id.unwrap(),
-                                server_name.clone().unwrap(),
-                                audio_dispatch.clone(),
-                                audio_state.clone(),
-                                None,
-                            ).emit(MouseEvent::new("click").unwrap());
-                        } else {
-                            audio_dispatch.reduce_mut(|state| {
-                                state.audio_playing = Some(false);
-                            });
-                        }
+                    if let Some(next_episode) = next_in_queue(&episodes, current_episode_id) {
+                        play_queued(next_episode, &client, user_id, &audio_dispatch);
+                    } else {
+                        audio_dispatch.reduce_mut(|state| {
+                            state.audio_playing = Some(false);
+                        });
                     }
                 } else {
                     // Handle the error, maybe log it or show a user-facing message
@@ -452,6 +302,135 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
     };
     
 
+    use_keyboard_shortcuts(skip_episode.clone());
+
+    // Show the episode in the OS media controls and answer media keys, headset
+    // buttons and lock screen controls the same way as the on-screen buttons
+    use_effect_with(
+        (props.title.clone(), props.podcast_name.clone(), props.artwork_url.clone()),
+        |(title, podcast_name, artwork_url)| {
+            media_session::set_metadata(title, podcast_name, artwork_url);
+            || ()
+        },
+    );
+
+    // Only once the player goes away; between episodes the playing state would be lost
+    use_effect_with((), |_| media_session::clear_metadata);
+
+    use_effect_with(audio_state.audio_playing, |playing| {
+        media_session::set_playing(playing.unwrap_or(false));
+        || ()
+    });
+
+    use_effect_with(episode_id, {
+        let audio_dispatch = _audio_dispatch.clone();
+        let skip_episode = skip_episode.clone();
+        move |_| {
+            let seek = {
+                let audio_dispatch = audio_dispatch.clone();
+                move |target: &dyn Fn(f64) -> f64| {
+                    audio_dispatch.reduce_mut(|state| {
+                        if let Some(current_time) = state.audio_element.as_ref().map(|audio| audio.current_time()) {
+                            state.seek_to(target(current_time));
+                        }
+                    });
+                }
+            };
+            let handlers = ActionHandlers::register(move |action, seconds| match action {
+                Action::Play => audio_dispatch.reduce_mut(|state| {
+                    if !state.audio_playing.unwrap_or(false) {
+                        state.toggle_playback();
+                    }
+                }),
+                Action::Pause => audio_dispatch.reduce_mut(|state| {
+                    if state.audio_playing.unwrap_or(false) {
+                        state.toggle_playback();
+                    }
+                }),
+                Action::SeekBackward => {
+                    let step = seconds.unwrap_or(audio_dispatch.get().playback_settings.skip_back as f64);
+                    seek(&|current| current - step)
+                }
+                Action::SeekForward => {
+                    let step = seconds.unwrap_or(audio_dispatch.get().playback_settings.skip_forward as f64);
+                    seek(&|current| current + step)
+                }
+                Action::SeekTo => {
+                    if let Some(seek_time) = seconds {
+                        seek(&|_| seek_time);
+                    }
+                }
+                Action::NextTrack => skip_episode.emit(MouseEvent::new("click").unwrap()),
+            });
+            move || drop(handlers)
+        }
+    });
+
+    // Load the chapters of a newly started episode. Episodes in the database are looked
+    // up in their podcast's feed; others were started from the feed that's open.
+    use_effect_with(props.src.clone(), {
+        let audio_dispatch = _audio_dispatch.clone();
+        let client = client.clone();
+        let episode_id = props.episode_id;
+        let feed_url = state.clicked_podcast_info.as_ref().map(|info| info.podcast_url.clone());
+        move |src: &String| {
+            let loaded = audio_dispatch.get().chapters.as_ref().map_or(false, |chapters| &chapters.src == src);
+            if !loaded && !src.is_empty() {
+                let src = src.clone();
+                // Claim the episode straight away so a redraw meanwhile doesn't fetch again
+                let pending = EpisodeChapters { src: src.clone(), chapters: Vec::new() };
+                audio_dispatch.reduce_mut(move |state| state.chapters = Some(pending));
+                wasm_bindgen_futures::spawn_local(async move {
+                    let source = match (episode_id, user_id) {
+                        (episode_id, Some(user_id)) if episode_id != 0 => client
+                            .get_episode_metadata(&EpisodeRequest { episode_id, user_id })
+                            .await
+                            .ok()
+                            .and_then(|episode| Some((episode.FeedURL?, episode.EpisodeURL))),
+                        _ => feed_url.map(|feed_url| (feed_url, src.clone())),
+                    };
+                    let chapters = match source {
+                        Some((feed_url, episode_url)) => client
+                            .get_episode_chapters(&feed_url, &episode_url)
+                            .await
+                            .unwrap_or_default(),
+                        None => Vec::new(),
+                    };
+                    audio_dispatch.reduce_mut(move |state| {
+                        // Another episode may have started while these loaded
+                        if state.chapters.as_ref().map_or(false, |pending| pending.src == src) {
+                            state.chapters = Some(EpisodeChapters { src, chapters });
+                        }
+                    });
+                });
+            }
+            || ()
+        }
+    });
+
+    let previous_chapter = {
+        let audio_dispatch = _audio_dispatch.clone();
+        Callback::from(move |_: MouseEvent| {
+            audio_dispatch.reduce_mut(|state| {
+                let start = state.current_chapters().and_then(|chapters| chapters.previous_start(state.current_time_seconds));
+                if let Some(start) = start {
+                    state.seek_to(start);
+                }
+            });
+        })
+    };
+
+    let next_chapter = {
+        let audio_dispatch = _audio_dispatch.clone();
+        Callback::from(move |_: MouseEvent| {
+            audio_dispatch.reduce_mut(|state| {
+                let start = state.current_chapters().and_then(|chapters| chapters.next_start(state.current_time_seconds));
+                if let Some(start) = start {
+                    state.seek_to(start);
+                }
+            });
+        })
+    };
 
     let audio_state = _audio_dispatch.get();
 
@@ -464,6 +443,56 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
         let duration_minutes = ((audio_props.duration_sec % 3600.0) / 60.0).floor() as i32;
         let duration_seconds = (audio_props.duration_sec % 60.0).floor() as i32;
         let formatted_duration = format!("{:02}:{:02}:{:02}", duration_hours, duration_minutes, duration_seconds);
+        let chapters = audio_state.current_chapters().map(|chapters| chapters.chapters.clone()).unwrap_or_default();
+        let current_chapter_index = audio_state
+            .current_chapters()
+            .and_then(|chapters| chapters.current(audio_state.current_time_seconds));
+        let current_chapter = current_chapter_index.map(|index| &chapters[index]);
+        let expanded_artwork = current_chapter
+            .and_then(|chapter| chapter.img.clone())
+            .unwrap_or_else(|| audio_props.artwork_url.clone());
+        let chapter_markers = if audio_props.duration_sec > 0.0 {
+            chapters
+                .iter()
+                .filter(|chapter| chapter.start_time > 0.0 && chapter.start_time < audio_props.duration_sec)
+                .map(|chapter| {
+                    let left = chapter.start_time / audio_props.duration_sec * 100.0;
+                    html! { <div class="chapter-marker" style={format!("left: {}%;", left)}></div> }
+                })
+                .collect::<Html>()
+        } else {
+            html! {}
+        };
+        let bookmark_markers = if audio_props.duration_sec > 0.0 {
+            bookmarks
+                .iter()
+                .filter(|bookmark| (bookmark.time as f64) < audio_props.duration_sec)
+                .map(|bookmark| {
+                    let left = bookmark.time as f64 / audio_props.duration_sec * 100.0;
+                    html! { <div class="bookmark-marker" style={format!("left: {}%;", left)} title={bookmark.note.clone()}></div> }
+                })
+                .collect::<Html>()
+        } else {
+            html! {}
+        };
+        let chapter_list = chapters
+            .iter()
+            .enumerate()
+            .map(|(index, chapter)| {
+                let seek = {
+                    let audio_dispatch = _audio_dispatch.clone();
+                    let start = chapter.start_time;
+                    Callback::from(move |_: MouseEvent| audio_dispatch.reduce_mut(|state| state.seek_to(start)))
+                };
+                let item_class = classes!("chapter-item", (Some(index) == current_chapter_index).then_some("current"));
+                html! {
+                    <button onclick={seek} class={item_class}>
+                        <span class="chapter-time">{ format_time(chapter.start_time) }</span>
+                        <span class="chapter-name">{ chapter.title.clone().unwrap_or_else(|| format!("Chapter {}", index + 1)) }</span>
+                    </button>
+                }
+            })
+            .collect::<Html>();
         let on_shownotes_click = {
             let history = history_clone.clone();
             let dispatch = _dispatch.clone();
@@ -477,52 +506,67 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
                         dispatch_clone.reduce_mut(move |state| {
                             state.selected_episode_id = Some(episode_id);
                         });
-                        history_clone.push("/episode"); // Use the route path
+                        history_clone.push(&Route::EpisodeLink { id: episode_id }.to_path());
                     });
                 }
             })
         };
 
-        // let progress: f64 = 0.0; // Assuming 'progress' is defined here as an example
-        let track_width_px: f64 = 300.0; // Explicitly typing the variable
-        let pixel_offset: f64 = 60.0; // Explicitly typing the variable
-        let offset_percentage: f64 = (pixel_offset / track_width_px) * 100.0; // This will also be f64
-        
-        let progress_style = {
-            let progress_percentage: f64 = *progress; // Ensure this variable is typed as f64
-            let start: f64 = (progress_percentage - offset_percentage).max(0.0); // Using max on f64
-            let end: f64 = (progress_percentage + offset_percentage).min(100.0); // Using min on f64
-            format!(
-                "background: linear-gradient(to right, #007BFF {}%, #E9ECEF {}%);",
-                start, end
-            )
+        // Links to the moment the player is at, for sharing or coming back to later
+        let copy_link = {
+            let audio_dispatch = _audio_dispatch.clone();
+            Callback::from(move |_: MouseEvent| {
+                let state = audio_dispatch.get();
+                let (Some(playing), Some(window)) = (state.currently_playing.as_ref(), web_sys::window()) else { return };
+                let at = state.audio_element.as_ref().map_or(0.0, |audio| audio.current_time()) as u32;
+                let link = format!(
+                    "{}{}?t={}",
+                    window.location().origin().unwrap_or_default(),
+                    Route::EpisodeLink { id: playing.episode_id }.to_path(),
+                    at
+                );
+                let copied = wasm_bindgen_futures::JsFuture::from(window.navigator().clipboard().write_text(&link));
+                let audio_dispatch = audio_dispatch.clone();
+                spawn_local(async move {
+                    let result = copied.await;
+                    audio_dispatch.reduce_mut(move |state| match result {
+                        Ok(_) => state.info_message = Some(format!("Link to {} copied", format_time(at as f64))),
+                        Err(_) => state.error_message = Some(format!("Couldn't copy the link: {}", link)),
+                    });
+                });
+            })
         };
-        
+
         let audio_bar_class = classes!("audio-player", "border", "border-solid", "border-color", "fixed", "bottom-0", "z-50", "w-full", if audio_state.is_expanded { "expanded" } else { "" });
         let update_playback_closure = update_playback_speed.clone();
         html! {
             <div class={audio_bar_class} ref={container_ref.clone()}>
+                <ShortcutHelp />
+                <UpNextCard />
                 <div class="top-section">
                     <div>
                     <button onclick={title_click.clone()} class="retract-button">
                         <span class="material-icons">{"expand_more"}</span>
                     </button>
                     <div class="audio-image-container">
-                    <img onclick={title_click.clone()} src={audio_props.artwork_url.clone()} />
+                        <VideoSurface>
+                            <img onclick={title_click.clone()} src={expanded_artwork} />
+                        </VideoSurface>
                     </div>
                     <div class="title" onclick={title_click.clone()}>{ &audio_props.title }
                     </div>
+                    {
+                        if let Some(title) = current_chapter.and_then(|chapter| chapter.title.clone()) {
+                            html! { <div class="chapter-title">{ title }</div> }
+                        } else {
+                            html! {}
+                        }
+                    }
                     <div class="scrub-bar">
-                        <span>{audio_state.current_time_formatted.clone()}</span>
-                        <input type="range"
-                            class="flex-grow h-1 cursor-pointer"
-                            min="0.0"
-                            max={audio_props.duration_sec.to_string().clone()}
-                            value={audio_state.current_time_seconds.to_string()}
-                            oninput={update_time.clone()}
-                            style={progress_style}
-                        />
-                        <span>{formatted_duration.clone()}</span>
+                        <ScrubBar duration_sec={audio_props.duration_sec} duration_label={formatted_duration.clone()}>
+                            { chapter_markers.clone() }
+                            { bookmark_markers.clone() }
+                        </ScrubBar>
                     </div>
 
                     <div class="episode-button-container flex items-center justify-center">
@@ -535,10 +579,16 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
                                     <button onclick={toggle_slider_visibility.clone()} class="skip-button audio-top-button selector-button font-bold py-2 px-4 rounded-full w-10 h-10 flex items-center justify-center">
                                         <span class="material-icons">{"speed"}</span>
                                     </button>
+                                    <SleepTimerControl />
                                 </>
                             }
                         }
-                        <button onclick={skip_backward.clone()} class="rewind-button audio-top-button selector-button font-bold py-2 px-4 rounded-full w-10 h-10 flex items-center justify-center">
+                        if !chapters.is_empty() {
+                            <button onclick={previous_chapter} title="Previous chapter" class="skip-button audio-top-button selector-button font-bold py-2 px-4 rounded-full w-10 h-10 flex items-center justify-center">
+                                <span class="material-icons">{"navigate_before"}</span>
+                            </button>
+                        }
+                        <button onclick={skip_backward.clone()} title={format!("Back {} seconds", skip_back_secs)} class="rewind-button audio-top-button selector-button font-bold py-2 px-4 rounded-full w-10 h-10 flex items-center justify-center">
                             <span class="material-icons">{"fast_rewind"}</span>
                         </button>
                         <button onclick={toggle_playback.clone()} class="audio-top-button selector-button font-bold py-2 px-4 rounded-full w-10 h-10 flex items-center justify-center">
@@ -546,9 +596,14 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
                                 { if audio_state.audio_playing.unwrap_or(false) { "pause" } else { "play_arrow" } }
                             </span>
                         </button>
-                        <button onclick={skip_forward.clone()} class="skip-button audio-top-button selector-button font-bold py-2 px-4 rounded-full w-10 h-10 flex items-center justify-center">
+                        <button onclick={skip_forward.clone()} title={format!("Forward {} seconds", skip_forward_secs)} class="skip-button audio-top-button selector-button font-bold py-2 px-4 rounded-full w-10 h-10 flex items-center justify-center">
                             <span class="material-icons">{"fast_forward"}</span>
                         </button>
+                        if !chapters.is_empty() {
+                            <button onclick={next_chapter} title="Next chapter" class="skip-button audio-top-button selector-button font-bold py-2 px-4 rounded-full w-10 h-10 flex items-center justify-center">
+                                <span class="material-icons">{"navigate_next"}</span>
+                            </button>
+                        }
                         <button onclick={skip_episode.clone()} class="skip-button audio-top-button selector-button font-bold py-2 px-4 rounded-full w-10 h-10 flex items-center justify-center">
                             <span class="material-icons">{"skip_next"}</span>
                         </button>
@@ -581,12 +636,18 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
                     {
                         if episode_in_db {
                             html! {
+                                <>
                                 <button onclick={Callback::from(move |e: MouseEvent| {
                                     on_shownotes_click.emit(e.clone());
                                     title_click_emit.emit(e);
                                 })} class="audio-top-button audio-full-button border-solid border selector-button font-bold py-2 px-4 mt-3 rounded-full flex items-center justify-center">
                                     { "Shownotes" }
                                 </button>
+                                <button onclick={copy_link} title="Copy link at current time" class="audio-top-button audio-full-button border-solid border selector-button font-bold py-2 px-4 mt-3 rounded-full flex items-center justify-center">
+                                    <span class="material-icons">{"link"}</span>
+                                </button>
+                                <BookmarkButton />
+                                </>
                             }
                         } else {
                             html! {
@@ -597,6 +658,12 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
                         }
                     }
                     </div>
+                    if !chapters.is_empty() {
+                        <div class="chapter-list">
+                            <h3 class="chapter-list-header">{ "Chapters" }</h3>
+                            { chapter_list }
+                        </div>
+                    }
                     </div>
                     
                 </div>
@@ -608,24 +675,25 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
                     </div>
                 </div>
                 <div class="right-group">
+                    if let Some(countdown) = sleep_countdown(&audio_state) {
+                        <span class="time-display sleep-timer-badge" title={countdown}>
+                            <i class="material-icons">{"bedtime"}</i>
+                        </span>
+                    }
                     <button onclick={toggle_playback} class="audio-top-button selector-button font-bold py-2 px-4 rounded-full w-10 h-10 flex items-center justify-center">
                         <span class="material-icons">
                             { if audio_state.audio_playing.unwrap_or(false) { "pause" } else { "play_arrow" } }
                         </span>
                     </button>
-                    <button onclick={skip_forward} class="audio-top-button selector-button font-bold py-2 px-4 rounded-full w-10 h-10 flex items-center justify-center">
+                    <button onclick={skip_forward} title={format!("Forward {} seconds", skip_forward_secs)} class="audio-top-button selector-button font-bold py-2 px-4 rounded-full w-10 h-10 flex items-center justify-center">
                         <span class="material-icons">{"fast_forward"}</span>
                     </button>
                     <div class="flex-grow flex items-center sm:block hidden">
                         <div class="flex items-center flex-nowrap">
-                            <span class="time-display px-2">{audio_state.current_time_formatted.clone()}</span>
-                            <input type="range"
-                                class="flex-grow h-1 cursor-pointer"
-                                min="0.0"
-                                max={audio_props.duration_sec.to_string().clone()}
-                                value={audio_state.current_time_seconds.to_string()}
-                                oninput={update_time.clone()} />
-                            <span class="time-display px-2">{formatted_duration}</span>
+                            <ScrubBar duration_sec={audio_props.duration_sec} duration_label={formatted_duration} time_class={classes!("time-display", "px-2")}>
+                                { chapter_markers }
+                                { bookmark_markers }
+                            </ScrubBar>
                         </div>
                     </div>
                 </div>
@@ -641,16 +709,73 @@ pub fn audio_player(props: &AudioPlayerProps) -> Html {
 pub fn on_play_click(
     episode_url_for_closure: String,
     episode_title_for_closure: String,
+    podcast_name_for_closure: String,
     episode_artwork_for_closure: String,
     episode_duration_for_closure: i32,
     episode_id_for_closure: i32,
     listen_duration_for_closure: Option<i32>,
-    api_key: String,
+    client: PinepodsClient,
     user_id: i32,
-    server_name: String,
     audio_dispatch: Dispatch<UIState>,
     _audio_state: Rc<UIState>,
     is_local: Option<bool>,
+    // The enclosure's MIME type, when the episode came straight from its feed
+    enclosure_type: Option<String>,
+) -> Callback<MouseEvent> {
+    play_episode(
+        episode_url_for_closure,
+        episode_title_for_closure,
+        podcast_name_for_closure,
+        episode_artwork_for_closure,
+        episode_duration_for_closure,
+        episode_id_for_closure,
+        listen_duration_for_closure,
+        client,
+        user_id,
+        audio_dispatch,
+        is_local,
+        enclosure_type,
+        true,
+    )
+}
+
+/// Loads an episode someone opened a link to, at the time the link points to. Only
+/// opening a link doesn't count as a play: the episode isn't queued, added to history,
+/// or counted.
+pub fn open_episode_link(episode: &EpisodeInfo, start_at: i32, client: &PinepodsClient, user_id: i32, audio_dispatch: &Dispatch<UIState>) {
+    play_episode(
+        episode.EpisodeURL.clone(),
+        episode.EpisodeTitle.clone(),
+        episode.PodcastName.clone(),
+        episode.EpisodeArtwork.clone(),
+        episode.EpisodeDuration,
+        episode.EpisodeID,
+        Some(start_at),
+        client.clone(),
+        user_id,
+        audio_dispatch.clone(),
+        None,
+        None,
+        false,
+    )
+    .emit(MouseEvent::new("click").unwrap());
+}
+
+fn play_episode(
+    episode_url_for_closure: String,
+    episode_title_for_closure: String,
+    podcast_name_for_closure: String,
+    episode_artwork_for_closure: String,
+    episode_duration_for_closure: i32,
+    episode_id_for_closure: i32,
+    listen_duration_for_closure: Option<i32>,
+    client: PinepodsClient,
+    user_id: i32,
+    audio_dispatch: Dispatch<UIState>,
+    is_local: Option<bool>,
+    enclosure_type: Option<String>,
+    // Whether this counts as a play: queued, added to history and counted
+    record_play: bool,
 ) -> Callback<MouseEvent> {
 
     Callback::from(move |_: MouseEvent| {
@@ -671,22 +796,23 @@ pub fn on_play_click(
     
         let episode_url_for_closure = episode_url_for_closure.clone();
         let episode_title_for_closure = episode_title_for_closure.clone();
+        let podcast_name_for_closure = podcast_name_for_closure.clone();
         let episode_artwork_for_closure = episode_artwork_for_closure.clone();
         let episode_duration_for_closure = episode_duration_for_closure.clone();
         let listen_duration_for_closure = listen_duration_for_closure.clone();
         let episode_id_for_closure = episode_id_for_closure.clone();
-        let api_key = api_key.clone();
+        let client = client.clone();
         let user_id = user_id.clone();
-        let server_name = server_name.clone();
         let audio_dispatch = audio_dispatch.clone();
+        // Downloads stream from the server under another URL, so the episode's own decides
+        let media_kind = MediaKind::of(enclosure_type.as_deref(), &episode_url_for_closure);
     
         let formatted_duration = parse_duration_to_seconds(&episode_duration_for_closure);
         let episode_pos: f32 = 0.0;
         let episode_id = episode_id_for_closure.clone();
         
         let call_ep_url = episode_url_for_closure.clone();
-        let check_server_name = server_name.clone();
-        let check_api_key = api_key.clone();
+        let check_client = client.clone();
         let check_user_id = user_id.clone();
         let episode_title_for_wasm = episode_title_for_closure.clone();
         let episode_url_for_wasm = call_ep_url.clone();
@@ -697,9 +823,7 @@ pub fn on_play_click(
         let episode_url = episode_url_for_wasm.clone();
         let episode_title = episode_title_for_wasm.clone();
         spawn_local(async move {
-            let episode_exists = call_check_episode_in_db(
-                &check_server_name.clone(),
-                &check_api_key.clone(),
+            let episode_exists = check_client.check_episode_in_db(
                 check_user_id.clone(),
                 &episode_title.clone(),
                 &episode_url.clone()
@@ -707,51 +831,36 @@ pub fn on_play_click(
             app_dispatch.reduce_mut(move |global_state| {
                 global_state.episode_in_db = Some(episode_exists);
             });
-            if episode_exists {
-                let history_server_name = check_server_name.clone();
-                let history_api_key = check_api_key.clone();
-
+            if episode_exists && record_play {
                 let history_add = HistoryAddRequest{
                     episode_id,
                     episode_pos,
                     user_id,
                 };
 
-                let add_history_future = call_add_history(
-                    &history_server_name,
-                    history_api_key, 
-                    &history_add
-                );
+                let add_history_future = check_client.add_history(&history_add);
                 match add_history_future.await {
                     Ok(_) => {
                         // web_sys::console::log_1(&"Successfully added history".into());
                     },
-                    Err(_e) => {
+                    Err(e) => {
+                        defer_if_offline(&check_client, &e, Mutation::AddHistory(history_add));
                         // web_sys::console::log_1(&format!("Failed to add history: {:?}", e).into());
                     }
                 }
 
-                let queue_server_name = check_server_name.clone();
-                let queue_api_key = check_api_key.clone();
-        
                 let request = QueuePodcastRequest {
                     episode_id,
                     user_id, // replace with the actual user ID
                 };
 
-
-                let queue_api = Option::from(queue_api_key);
-
-                let add_queue_future = call_queue_episode(
-                    &queue_server_name,
-                    &queue_api, 
-                    &request
-                );
+                let add_queue_future = check_client.queue_episode(&request);
                 match add_queue_future.await {
                     Ok(_) => {
                         // web_sys::console::log_1(&"Successfully Added Episode to Queue".into());
                     },
-                    Err(_e) => {
+                    Err(e) => {
+                        defer_if_offline(&check_client, &e, Mutation::QueueEpisode(request));
                         // web_sys::console::log_1(&format!("Failed to add to queue: {:?}", e).into());
                     }
                 }
@@ -759,15 +868,13 @@ pub fn on_play_click(
         });
 
 
-        let increment_server_name = server_name.clone();
-        let increment_api_key = api_key.clone();
+        let increment_client = client.clone();
         let increment_user_id = user_id.clone();
         spawn_local(async move {
-            let add_history_future = call_increment_played(
-                &increment_server_name,
-                &increment_api_key, 
-                increment_user_id
-            );
+            if !record_play {
+                return;
+            }
+            let add_history_future = increment_client.increment_played(increment_user_id);
             match add_history_future.await {
                 Ok(_) => {
                     // web_sys::console::log_1(&"Successfully incremented playcount".into());
@@ -779,32 +886,81 @@ pub fn on_play_click(
         });
         let src = if let Some(_local) = is_local {
             // Construct the URL for streaming from the local server
-            let src = format!("{}/api/data/stream/{}?api_key={}&user_id={}", server_name, episode_id, api_key, user_id);
-            src
+            client.stream_url(episode_id, user_id)
         } else {
             // Use the provided URL for streaming
             let src = episode_url_for_wasm.clone();
             src
         };
 
+        let source_client = client.clone();
+        let playing_dispatch = audio_dispatch.clone();
         audio_dispatch.reduce_mut(move |audio_state| {
+            save_outgoing_position(audio_state, &source_client, user_id);
+            // The end of a chapter means nothing for another episode
+            if let Some(timer) = audio_state.sleep_timer.take_if(|timer| matches!(timer.mode, SleepMode::EndOfChapter { .. })) {
+                if let Some(audio) = &audio_state.audio_element {
+                    audio.set_volume(timer.volume);
+                }
+            }
             audio_state.audio_playing = Some(true);
             audio_state.playback_speed = 1.0;
+            audio_state.podcast_playback = None;
+            audio_state.up_next = None;
             audio_state.currently_playing = Some(AudioPlayerProps {
                 src: src.clone(),
                 title: episode_title_for_wasm.clone(),
+                podcast_name: podcast_name_for_closure.clone(),
                 artwork_url: episode_artwork_for_wasm.clone(),
                 duration: episode_duration_for_wasm.clone().to_string(),
                 episode_id: episode_id_for_wasm.clone(),
                 duration_sec: formatted_duration,
                 start_pos_sec: listen_duration_for_closure.unwrap_or(0) as f64, 
+                media_kind,
             });
-            audio_state.set_audio_source(src.to_string());
+            load_source(audio_state, &source_client, &src);
             if let Some(audio) = &audio_state.audio_element {
+                audio.set_default_playback_rate(1.0);
                 audio.set_current_time(listen_duration_for_closure.unwrap_or(0) as f64);
-                let _ = audio.play();
+                // Browsers won't start audio the user didn't ask for, like from a link;
+                // the player then waits, paused, for them to press play
+                if let Ok(started) = audio.play() {
+                    let audio = audio.clone();
+                    spawn_local(async move {
+                        if wasm_bindgen_futures::JsFuture::from(started).await.is_err() && audio.paused() {
+                            playing_dispatch.reduce_mut(|state| state.audio_playing = Some(false));
+                        }
+                    });
+                }
             }
             audio_state.audio_playing = Some(true);
         });
+        apply_podcast_playback(audio_dispatch.clone(), client.clone(), user_id, episode_id);
     })
+}
+
+/// Applies the playback defaults of the episode's podcast: its speed, and skipping the
+/// intro when the episode starts inside it. The outro is cut while the episode plays.
+fn apply_podcast_playback(audio_dispatch: Dispatch<UIState>, client: PinepodsClient, user_id: i32, episode_id: i32) {
+    spawn_local(async move {
+        // Servers without per-podcast settings, and episodes outside the database, play as they are
+        let Ok(settings) = client.get_episode_playback_settings(user_id, episode_id).await else { return };
+        audio_dispatch.reduce_mut(move |audio_state| {
+            let still_playing = audio_state.currently_playing.as_ref().map(|playing| playing.episode_id) == Some(episode_id);
+            if !still_playing {
+                return;
+            }
+            audio_state.playback_speed = settings.playback_speed;
+            if let Some(audio) = audio_state.audio_element.clone() {
+                // The default rate survives the source loading, which resets the current rate
+                audio.set_default_playback_rate(settings.playback_speed);
+                audio.set_playback_rate(settings.playback_speed);
+                let intro = settings.skip_intro as f64;
+                if audio.current_time() < intro {
+                    audio_state.seek_to(intro);
+                }
+            }
+            audio_state.podcast_playback = Some(settings);
+        });
+    });
 }
\ No newline at end of file
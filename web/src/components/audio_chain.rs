@@ -0,0 +1,330 @@
+use std::cell::RefCell;
+use gloo_timers::callback::Interval;
+use wasm_bindgen::JsValue;
+use web_sys::{AnalyserNode, AudioContext, AudioContextState, DynamicsCompressorNode, GainNode, HtmlMediaElement, MediaElementAudioSourceNode};
+use yew::prelude::*;
+use yewdux::prelude::*;
+use crate::components::context::{AppState, UIState};
+use crate::requests::client::PinepodsClient;
+use crate::requests::outbox::{defer_if_offline, Mutation};
+use crate::requests::search_pods::MediaKind;
+use crate::requests::setting_reqs::AudioEffects;
+use crate::requests::stat_reqs::SilenceTimeSavedRequest;
+
+/// Milliseconds between looks at the audio.
+const TICK_MS: u32 = 100;
+/// Below this level, in dBFS, the audio counts as silence.
+const SILENCE_DB: f32 = -45.0;
+/// Quiet ticks in a row before speeding up, so gaps between words are left alone.
+const SILENT_TICKS: u32 = 3;
+/// How much faster than the chosen speed silences play, up to `MAX_SKIP_RATE`.
+const SILENCE_SPEEDUP: f64 = 4.0;
+const MAX_SKIP_RATE: f64 = 8.0;
+/// The speech level loudness normalization aims for, in dBFS.
+const TARGET_DB: f32 = -20.0;
+/// How quickly the measured loudness follows the audio; lower is steadier.
+const LOUDNESS_SMOOTHING: f32 = 0.02;
+/// Limits on the normalization gain, so near silence isn't blown up.
+const MIN_GAIN: f32 = 0.25;
+const MAX_GAIN: f32 = 4.0;
+/// Gain added after the compressor when voice boost is on.
+const VOICE_BOOST_GAIN: f32 = 2.0;
+/// Time saved is sent once this many seconds have built up.
+const REPORT_EVERY_SECS: f64 = 30.0;
+
+thread_local! {
+    // The graph can only be built once per audio element, so it lives as long as the page
+    static CHAIN: RefCell<Option<AudioChain>> = RefCell::new(None);
+}
+
+/// The processing the episode that's playing gets: the user's settings, with any its
+/// podcast overrides.
+pub fn effects_for(state: &UIState) -> AudioEffects {
+    let user = state.playback_settings.audio_effects;
+    match &state.podcast_playback {
+        Some(podcast) => AudioEffects {
+            voice_boost: podcast.voice_boost.unwrap_or(user.voice_boost),
+            normalize_loudness: podcast.normalize_loudness.unwrap_or(user.normalize_loudness),
+            skip_silence: podcast.skip_silence.unwrap_or(user.skip_silence),
+        },
+        None => user,
+    }
+}
+
+fn is_attached() -> bool {
+    CHAIN.with(|chain| chain.borrow().is_some())
+}
+
+/// HLS playlists can't be relayed, since their segments are fetched from wherever the
+/// playlist points. They play straight from their host and don't start the processing.
+fn is_hls(audio_state: &UIState) -> bool {
+    audio_state
+        .currently_playing
+        .as_ref()
+        .map_or(false, |playing| playing.media_kind == MediaKind::Hls)
+}
+
+/// Web Audio can only read audio the browser lets it see, which most podcast hosts
+/// don't allow from another origin. Those are relayed through the server instead.
+fn relayed_src(client: &PinepodsClient, src: &str) -> String {
+    if src.starts_with(&client.server_name()) {
+        return src.to_string();
+    }
+    client.relay_url(src, chrono::Utc::now().timestamp())
+}
+
+/// Where the player will load a new episode at `src` from, and whether that's a CORS
+/// request, as `load_source` would pick it. Episodes start on the user's own effects.
+pub fn planned_source(audio_state: &UIState, client: &PinepodsClient, src: &str) -> (String, bool) {
+    if is_attached() || audio_state.playback_settings.audio_effects.any() {
+        (relayed_src(client, src), true)
+    } else {
+        (src.to_string(), false)
+    }
+}
+
+/// Loads `src` into the player, through the server when the audio is or will be
+/// processed. Once the element feeds the graph, everything it plays has to.
+pub fn load_source(audio_state: &mut UIState, client: &PinepodsClient, src: &str) {
+    if is_hls(audio_state) {
+        // Once the element feeds the graph, the playlist's host has to allow CORS
+        audio_state.set_audio_source(src.to_string(), is_attached());
+        let playable = audio_state
+            .audio_element
+            .as_ref()
+            .map_or(false, |audio| !audio.can_play_type("application/vnd.apple.mpegurl").is_empty());
+        if !playable {
+            audio_state.error_message = Some("This episode is an HLS stream, which this browser can't play".to_string());
+        }
+    } else if is_attached() || effects_for(audio_state).any() {
+        audio_state.set_audio_source(relayed_src(client, src), true);
+        // Starting here keeps the context inside the click that started playback
+        attach(audio_state, client);
+    } else {
+        audio_state.set_audio_source(src.to_string(), false);
+    }
+}
+
+/// Routes the player through the processing graph, moving the episode onto a source
+/// the graph can read if it isn't on one already.
+fn attach(audio_state: &UIState, client: &PinepodsClient) {
+    if is_attached() || is_hls(audio_state) {
+        return;
+    }
+    let (Some(audio), Some(playing)) = (audio_state.audio_element.as_ref(), audio_state.currently_playing.as_ref()) else { return };
+    let src = relayed_src(client, &playing.src);
+    // Relay URLs change by the hour, so any from the server will do
+    if !audio.src().starts_with(&client.server_name()) || audio.cross_origin().is_none() {
+        let resume_at = audio.current_time();
+        let was_playing = !audio.paused();
+        audio.set_cross_origin(Some("anonymous"));
+        audio.set_src(&src);
+        audio.set_current_time(resume_at);
+        if was_playing {
+            let _ = audio.play();
+        }
+    }
+    match AudioChain::new(audio) {
+        Ok(chain) => CHAIN.with(|cell| *cell.borrow_mut() = Some(chain)),
+        Err(e) => web_sys::console::log_1(&format!("Couldn't set up audio processing: {:?}", e).into()),
+    }
+}
+
+struct AudioChain {
+    context: AudioContext,
+    // Dropping the source would take the element out of the graph
+    _source: MediaElementAudioSourceNode,
+    normalizer: GainNode,
+    compressor: DynamicsCompressorNode,
+    boost: GainNode,
+    analyser: AnalyserNode,
+    samples: Vec<f32>,
+    applied: Option<AudioEffects>,
+    /// The source being measured, so a new episode starts its loudness afresh.
+    src: String,
+    /// Running level of the speech heard so far, in dBFS.
+    loudness: Option<f32>,
+    quiet_ticks: u32,
+    skipping: bool,
+    last_time: f64,
+    /// Seconds saved that haven't been sent to the server yet.
+    unreported: f64,
+}
+
+impl AudioChain {
+    /// element -> normalizer -> compressor -> boost -> speakers, with the analyser
+    /// listening to the element before any of it.
+    fn new(audio: &HtmlMediaElement) -> Result<Self, JsValue> {
+        let context = AudioContext::new()?;
+        let source = context.create_media_element_source(audio)?;
+        let normalizer = context.create_gain()?;
+        let compressor = context.create_dynamics_compressor()?;
+        let boost = context.create_gain()?;
+        let analyser = context.create_analyser()?;
+        analyser.set_fft_size(2048);
+
+        source.connect_with_audio_node(&normalizer)?;
+        normalizer.connect_with_audio_node(&compressor)?;
+        compressor.connect_with_audio_node(&boost)?;
+        boost.connect_with_audio_node(&context.destination())?;
+        source.connect_with_audio_node(&analyser)?;
+
+        Ok(AudioChain {
+            samples: vec![0.0; analyser.fft_size() as usize],
+            context,
+            _source: source,
+            normalizer,
+            compressor,
+            boost,
+            analyser,
+            applied: None,
+            src: String::new(),
+            loudness: None,
+            quiet_ticks: 0,
+            skipping: false,
+            last_time: 0.0,
+            unreported: 0.0,
+        })
+    }
+
+    fn apply_voice_boost(&mut self, effects: AudioEffects) {
+        if self.applied.map(|applied| applied.voice_boost) == Some(effects.voice_boost) {
+            return;
+        }
+        // A 1:1 ratio leaves the audio as it is
+        let (threshold, ratio, gain) = if effects.voice_boost { (-30.0, 6.0, VOICE_BOOST_GAIN) } else { (0.0, 1.0, 1.0) };
+        self.compressor.threshold().set_value(threshold);
+        self.compressor.knee().set_value(20.0);
+        self.compressor.ratio().set_value(ratio);
+        self.compressor.attack().set_value(0.003);
+        self.compressor.release().set_value(0.25);
+        self.boost.gain().set_value(gain);
+    }
+
+    /// Level of the last moment of audio, in dBFS.
+    fn level(&mut self) -> f32 {
+        self.analyser.get_float_time_domain_data(&mut self.samples);
+        let power = self.samples.iter().map(|sample| sample * sample).sum::<f32>() / self.samples.len() as f32;
+        10.0 * power.max(1e-12).log10()
+    }
+
+    fn normalize(&mut self, effects: AudioEffects, level: f32) {
+        if level > SILENCE_DB {
+            self.loudness = Some(match self.loudness {
+                Some(loudness) => loudness + (level - loudness) * LOUDNESS_SMOOTHING,
+                None => level,
+            });
+        }
+        let gain = match self.loudness {
+            Some(loudness) if effects.normalize_loudness => {
+                10f32.powf((TARGET_DB - loudness) / 20.0).clamp(MIN_GAIN, MAX_GAIN)
+            }
+            _ => 1.0,
+        };
+        let _ = self.normalizer.gain().set_target_at_time(gain, self.context.current_time(), 0.5);
+    }
+
+    fn stop_skipping(&mut self, audio: &HtmlMediaElement, speed: f64) {
+        if self.skipping {
+            audio.set_playback_rate(speed);
+            self.skipping = false;
+        }
+        self.quiet_ticks = 0;
+    }
+
+    /// Looks at the last moment of audio and adjusts the processing to it.
+    fn tick(&mut self, audio: &HtmlMediaElement, effects: AudioEffects, speed: f64) {
+        self.apply_voice_boost(effects);
+        self.applied = Some(effects);
+        let speed = if speed > 0.0 { speed } else { 1.0 };
+        let src = audio.src();
+        if self.src != src {
+            self.src = src;
+            self.loudness = None;
+            self.stop_skipping(audio, speed);
+            self.last_time = audio.current_time();
+        }
+        if audio.paused() {
+            self.stop_skipping(audio, speed);
+            self.last_time = audio.current_time();
+            return;
+        }
+        if self.context.state() == AudioContextState::Suspended {
+            let _ = self.context.resume();
+        }
+
+        let level = self.level();
+        self.normalize(effects, level);
+
+        let now = audio.current_time();
+        let advanced = now - self.last_time;
+        self.last_time = now;
+        // Seeks aren't playback, so they save nothing
+        if self.skipping && advanced > 0.0 && advanced < 2.0 {
+            let rate = audio.playback_rate();
+            self.unreported += advanced / speed - advanced / rate;
+        }
+
+        if effects.skip_silence && level < SILENCE_DB {
+            self.quiet_ticks += 1;
+        } else {
+            self.stop_skipping(audio, speed);
+        }
+        if self.quiet_ticks >= SILENT_TICKS {
+            let rate = (speed * SILENCE_SPEEDUP).min(MAX_SKIP_RATE);
+            if audio.playback_rate() != rate {
+                audio.set_playback_rate(rate);
+            }
+            self.skipping = true;
+        }
+    }
+
+    /// Whole seconds saved, once enough have built up to be worth sending.
+    fn take_report(&mut self) -> Option<u32> {
+        if self.unreported < REPORT_EVERY_SECS {
+            return None;
+        }
+        let seconds = self.unreported.floor();
+        self.unreported -= seconds;
+        Some(seconds as u32)
+    }
+}
+
+/// Runs the processing while the player is up, and reports the time silence skipping saves.
+#[hook]
+pub fn use_audio_processing() {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (_audio_state, audio_dispatch) = use_store::<UIState>();
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+
+    use_effect_with((client, user_id), move |(client, user_id)| {
+        let client = client.clone();
+        let user_id = *user_id;
+        let interval = Interval::new(TICK_MS, move || {
+            let state = audio_dispatch.get();
+            let Some(audio) = state.audio_element.clone() else { return };
+            let effects = effects_for(&state);
+            // Turned on partway through an episode, by the settings or its podcast's
+            if effects.any() && !is_attached() {
+                attach(&state, &client);
+            }
+            let report = CHAIN.with(|chain| {
+                let mut chain = chain.borrow_mut();
+                let chain = chain.as_mut()?;
+                chain.tick(&audio, effects, state.playback_speed);
+                chain.take_report()
+            });
+            if let (Some(seconds), Some(user_id)) = (report, user_id) {
+                let client = client.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let request = SilenceTimeSavedRequest { user_id, seconds };
+                    if let Err(e) = client.add_silence_time_saved(&request).await {
+                        defer_if_offline(&client, &e, Mutation::AddSilenceTimeSaved(request));
+                    }
+                });
+            }
+        });
+        move || drop(interval)
+    });
+}
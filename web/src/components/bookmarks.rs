@@ -0,0 +1,418 @@
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{window, Blob, BlobPropertyBag, HtmlInputElement, Url};
+use yew::prelude::*;
+use yew_router::history::{BrowserHistory, History};
+use yew_router::Routable;
+use yewdux::prelude::*;
+use super::app_drawer::App_drawer;
+use super::gen_components::{empty_message, Search_nav};
+use crate::components::audio::AudioPlayer;
+use crate::components::context::{AppState, BookmarkState, UIState};
+use crate::components::gen_funcs::format_time;
+use crate::components::routes::Route;
+use crate::requests::bookmarks::{bookmarks_to_markdown, AddBookmarkRequest, Bookmark, DeleteBookmarkRequest};
+use crate::requests::client::PinepodsClient;
+use crate::requests::login_requests::Capability;
+
+fn bookmarks_supported(state: &AppState) -> bool {
+    state
+        .server_details
+        .as_ref()
+        .map_or(false, |details| details.supports(Capability::Bookmarks))
+}
+
+/// The bookmarks in an episode, loading them the first time they're asked for.
+#[hook]
+pub fn use_episode_bookmarks(episode_id: Option<i32>) -> Vec<Bookmark> {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (bookmark_state, bookmark_dispatch) = use_store::<BookmarkState>();
+    let audio_dispatch = use_store::<UIState>().1;
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+    let supported = bookmarks_supported(&state);
+
+    use_effect_with((client, user_id, episode_id, supported), move |(client, user_id, episode_id, supported)| {
+        if let (Some(user_id), Some(episode_id), true) = (*user_id, *episode_id, *supported) {
+            let account = Some((client.server_name().to_string(), user_id));
+            let loaded = {
+                let bookmarks = bookmark_dispatch.get();
+                bookmarks.account == account && bookmarks.by_episode.contains_key(&episode_id)
+            };
+            if episode_id != 0 && !loaded {
+                // Claim the episode straight away so a redraw meanwhile doesn't fetch again
+                bookmark_dispatch.reduce_mut(|bookmarks| {
+                    if bookmarks.account != account {
+                        *bookmarks = BookmarkState { account: account.clone(), ..BookmarkState::default() };
+                    }
+                    bookmarks.by_episode.insert(episode_id, Vec::new());
+                });
+                let client = client.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    match client.get_bookmarks(user_id, Some(episode_id)).await {
+                        Ok(loaded) => bookmark_dispatch.reduce_mut(move |bookmarks| {
+                            bookmarks.by_episode.insert(episode_id, loaded);
+                        }),
+                        Err(e) => {
+                            // Give up the claim, so the episode is fetched again next time
+                            bookmark_dispatch.reduce_mut(|bookmarks| {
+                                if bookmarks.account == account {
+                                    bookmarks.by_episode.remove(&episode_id);
+                                }
+                            });
+                            audio_dispatch.reduce_mut(|state| state.error_message = Some(e.to_string()));
+                        }
+                    }
+                });
+            }
+        }
+        || ()
+    });
+
+    episode_id
+        .and_then(|episode_id| bookmark_state.by_episode.get(&episode_id).cloned())
+        .unwrap_or_default()
+}
+
+/// Deletes `bookmark`, dropping it from the loaded bookmarks once the server agrees.
+fn delete_bookmark(
+    client: &PinepodsClient,
+    user_id: i32,
+    bookmark: &Bookmark,
+    audio_dispatch: &Dispatch<UIState>,
+    bookmark_dispatch: &Dispatch<BookmarkState>,
+    on_deleted: Callback<i32>,
+) {
+    let client = client.clone();
+    let audio_dispatch = audio_dispatch.clone();
+    let bookmark_dispatch = bookmark_dispatch.clone();
+    let request = DeleteBookmarkRequest { user_id, bookmark_id: bookmark.bookmark_id };
+    let episode_id = bookmark.episode_id;
+    wasm_bindgen_futures::spawn_local(async move {
+        match client.delete_bookmark(&request).await {
+            Ok(()) => {
+                bookmark_dispatch.reduce_mut(|bookmarks| {
+                    if let Some(episode) = bookmarks.by_episode.get_mut(&episode_id) {
+                        episode.retain(|bookmark| bookmark.bookmark_id != request.bookmark_id);
+                    }
+                });
+                on_deleted.emit(request.bookmark_id);
+            }
+            Err(e) => audio_dispatch.reduce_mut(|state| state.error_message = Some(e.to_string())),
+        }
+    });
+}
+
+/// Bookmarks the moment the player is at, with an optional note.
+#[function_component(BookmarkButton)]
+pub fn bookmark_button() -> Html {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (audio_state, audio_dispatch) = use_store::<UIState>();
+    let bookmark_dispatch = use_store::<BookmarkState>().1;
+    // The time being bookmarked and the note typed so far
+    let draft = use_state(|| None::<(i32, String)>);
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+
+    let (Some(user_id), Some(playing), true) = (user_id, audio_state.currently_playing.clone(), bookmarks_supported(&state)) else {
+        return html! {};
+    };
+
+    let start = {
+        let draft = draft.clone();
+        let audio_dispatch = audio_dispatch.clone();
+        Callback::from(move |_: MouseEvent| {
+            let time = audio_dispatch.get().audio_element.as_ref().map_or(0.0, |audio| audio.current_time());
+            draft.set(Some((time as i32, String::new())));
+        })
+    };
+    let cancel = {
+        let draft = draft.clone();
+        Callback::from(move |_: MouseEvent| draft.set(None))
+    };
+    let save = {
+        let draft = draft.clone();
+        Callback::from(move |_: ()| {
+            let Some((time, note)) = (*draft).clone() else { return };
+            draft.set(None);
+            let request = AddBookmarkRequest {
+                user_id,
+                episode_id: playing.episode_id,
+                time,
+                note: Some(note.trim().to_string()).filter(|note| !note.is_empty()),
+            };
+            let client = client.clone();
+            let audio_dispatch = audio_dispatch.clone();
+            let bookmark_dispatch = bookmark_dispatch.clone();
+            let playing = playing.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.add_bookmark(&request).await {
+                    Ok(bookmark_id) => {
+                        let bookmark = Bookmark {
+                            bookmark_id,
+                            episode_id: request.episode_id,
+                            time: request.time,
+                            note: request.note,
+                            created_at: None,
+                            episode_title: playing.title,
+                            podcast_name: playing.podcast_name,
+                        };
+                        bookmark_dispatch.reduce_mut(move |bookmarks| {
+                            let episode = bookmarks.by_episode.entry(bookmark.episode_id).or_default();
+                            episode.push(bookmark);
+                            episode.sort_by_key(|bookmark| bookmark.time);
+                        });
+                        audio_dispatch.reduce_mut(|state| state.info_message = Some(format!("Bookmarked {}", format_time(time as f64))));
+                    }
+                    Err(e) => audio_dispatch.reduce_mut(|state| state.error_message = Some(e.to_string())),
+                }
+            });
+        })
+    };
+
+    match (*draft).clone() {
+        Some((time, note)) => {
+            let on_input = {
+                let draft = draft.clone();
+                Callback::from(move |e: InputEvent| {
+                    let input: HtmlInputElement = e.target_unchecked_into();
+                    draft.set(Some((time, input.value())));
+                })
+            };
+            let on_keydown = {
+                let draft = draft.clone();
+                let save = save.clone();
+                Callback::from(move |e: KeyboardEvent| match e.key().as_str() {
+                    "Enter" => save.emit(()),
+                    "Escape" => draft.set(None),
+                    _ => {}
+                })
+            };
+            html! {
+                <div class="bookmark-form">
+                    <span class="bookmark-time">{ format_time(time as f64) }</span>
+                    <input
+                        type="text"
+                        class="search-bar-input bookmark-note-input"
+                        placeholder="Add a note (optional)"
+                        value={note}
+                        oninput={on_input}
+                        onkeydown={on_keydown}
+                    />
+                    <button onclick={save.reform(|_: MouseEvent| ())} title="Save bookmark" class="audio-top-button selector-button rounded-full w-10 h-10 flex items-center justify-center">
+                        <span class="material-icons">{"check"}</span>
+                    </button>
+                    <button onclick={cancel} title="Cancel" class="audio-top-button selector-button rounded-full w-10 h-10 flex items-center justify-center">
+                        <span class="material-icons">{"close"}</span>
+                    </button>
+                </div>
+            }
+        }
+        None => html! {
+            <button onclick={start} title="Bookmark this moment" class="audio-top-button audio-full-button border-solid border selector-button font-bold py-2 px-4 mt-3 rounded-full flex items-center justify-center">
+                <span class="material-icons">{"bookmark_add"}</span>
+            </button>
+        },
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct EpisodeBookmarkListProps {
+    pub episode_id: i32,
+    /// Plays the episode from the given second.
+    pub on_jump: Callback<i32>,
+}
+
+/// The user's bookmarks in an episode, each jumping to its moment.
+#[function_component(EpisodeBookmarkList)]
+pub fn episode_bookmark_list(props: &EpisodeBookmarkListProps) -> Html {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (_audio_state, audio_dispatch) = use_store::<UIState>();
+    let bookmark_dispatch = use_store::<BookmarkState>().1;
+    let bookmarks = use_episode_bookmarks(Some(props.episode_id));
+    let client = state.client();
+    let Some(user_id) = state.user_details.as_ref().map(|ud| ud.UserID) else { return html! {} };
+    if bookmarks.is_empty() {
+        return html! {};
+    }
+
+    html! {
+        <div class="bookmark-list">
+            <h3 class="item_container-text">{ "Bookmarks" }</h3>
+            {
+                bookmarks.iter().map(|bookmark| {
+                    let jump = props.on_jump.reform({
+                        let time = bookmark.time;
+                        move |_: MouseEvent| time
+                    });
+                    let delete = {
+                        let client = client.clone();
+                        let audio_dispatch = audio_dispatch.clone();
+                        let bookmark_dispatch = bookmark_dispatch.clone();
+                        let bookmark = bookmark.clone();
+                        Callback::from(move |_: MouseEvent| delete_bookmark(&client, user_id, &bookmark, &audio_dispatch, &bookmark_dispatch, Callback::noop()))
+                    };
+                    html! {
+                        <div class="bookmark-item">
+                            <button onclick={jump} class="bookmark-jump">
+                                <span class="bookmark-time">{ format_time(bookmark.time as f64) }</span>
+                                <span class="bookmark-note item_container-text">{ bookmark.note.clone().unwrap_or_default() }</span>
+                            </button>
+                            <button onclick={delete} title="Delete bookmark" class="bookmark-delete item_container-text">
+                                <span class="material-icons">{"delete"}</span>
+                            </button>
+                        </div>
+                    }
+                }).collect::<Html>()
+            }
+        </div>
+    }
+}
+
+/// Saves `markdown` as a file through the browser's download.
+fn download_markdown(markdown: &str, file_name: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(markdown));
+    let options = BlobPropertyBag::new();
+    options.set_type("text/markdown");
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+    let document = window().and_then(|w| w.document()).ok_or("no document")?;
+    let link = document.create_element("a")?.dyn_into::<web_sys::HtmlAnchorElement>()?;
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+    Url::revoke_object_url(&url)
+}
+
+/// Every bookmark the user has made, by episode, with a Markdown export.
+#[function_component(Bookmarks)]
+pub fn bookmarks() -> Html {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (audio_state, audio_dispatch) = use_store::<UIState>();
+    let bookmark_dispatch = use_store::<BookmarkState>().1;
+    let bookmarks = use_state(|| None::<Vec<Bookmark>>);
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+    let supported = bookmarks_supported(&state);
+
+    {
+        let bookmarks = bookmarks.clone();
+        let audio_dispatch = audio_dispatch.clone();
+        use_effect_with((client.clone(), user_id, supported), move |(client, user_id, supported)| {
+            if let (Some(user_id), true) = (*user_id, *supported) {
+                let client = client.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    match client.get_bookmarks(user_id, None).await {
+                        Ok(loaded) => bookmarks.set(Some(loaded)),
+                        Err(e) => {
+                            bookmarks.set(Some(Vec::new()));
+                            audio_dispatch.reduce_mut(|state| state.error_message = Some(e.to_string()));
+                        }
+                    }
+                });
+            }
+            || ()
+        });
+    }
+
+    let on_export = {
+        let bookmarks = bookmarks.clone();
+        let audio_dispatch = audio_dispatch.clone();
+        Callback::from(move |_: MouseEvent| {
+            let link_base = window().and_then(|w| w.location().origin().ok()).unwrap_or_default();
+            let markdown = bookmarks_to_markdown(bookmarks.as_deref().unwrap_or_default(), &link_base);
+            if let Err(e) = download_markdown(&markdown, "bookmarks.md") {
+                audio_dispatch.reduce_mut(|state| state.error_message = Some(format!("Error exporting bookmarks: {:?}", e)));
+            }
+        })
+    };
+
+    let content = match (user_id, bookmarks.as_ref()) {
+        _ if !supported => empty_message(
+            "Bookmarks Unavailable",
+            "This server doesn't keep bookmarks yet. Bookmarks will show up here once it's updated.",
+        ),
+        (Some(user_id), Some(list)) if !list.is_empty() => {
+            let history = BrowserHistory::new();
+            let mut episode = None;
+            list.iter().map(|bookmark| {
+                let header = (episode != Some(bookmark.episode_id)).then(|| {
+                    episode = Some(bookmark.episode_id);
+                    html! {
+                        <h3 class="bookmark-episode item_container-text">
+                            <span class="bookmark-podcast">{ &bookmark.podcast_name }</span>
+                            { &bookmark.episode_title }
+                        </h3>
+                    }
+                });
+                // Opening the episode's link plays it from the bookmark
+                let jump = {
+                    let history = history.clone();
+                    let path = format!("{}?t={}", Route::EpisodeLink { id: bookmark.episode_id }.to_path(), bookmark.time);
+                    Callback::from(move |_: MouseEvent| history.push(&path))
+                };
+                let delete = {
+                    let client = client.clone();
+                    let audio_dispatch = audio_dispatch.clone();
+                    let bookmark_dispatch = bookmark_dispatch.clone();
+                    let bookmark = bookmark.clone();
+                    let bookmarks = bookmarks.clone();
+                    let on_deleted = Callback::from(move |bookmark_id: i32| {
+                        let mut remaining = (*bookmarks).clone().unwrap_or_default();
+                        remaining.retain(|bookmark| bookmark.bookmark_id != bookmark_id);
+                        bookmarks.set(Some(remaining));
+                    });
+                    Callback::from(move |_: MouseEvent| delete_bookmark(&client, user_id, &bookmark, &audio_dispatch, &bookmark_dispatch, on_deleted.clone()))
+                };
+                html! {
+                    <>
+                    { header.unwrap_or_default() }
+                    <div class="bookmark-item">
+                        <button onclick={jump} class="bookmark-jump">
+                            <span class="bookmark-time">{ format_time(bookmark.time as f64) }</span>
+                            <span class="bookmark-note item_container-text">{ bookmark.note.clone().unwrap_or_default() }</span>
+                        </button>
+                        <button onclick={delete} title="Delete bookmark" class="bookmark-delete item_container-text">
+                            <span class="material-icons">{"delete"}</span>
+                        </button>
+                    </div>
+                    </>
+                }
+            }).collect::<Html>()
+        }
+        (_, Some(_)) => empty_message(
+            "No Bookmarks Yet",
+            "Bookmark a moment from the player while you listen, and it will show up here with your note.",
+        ),
+        (_, None) => html! { <p class="item_container-text">{ "Loading bookmarks..." }</p> },
+    };
+
+    html! {
+        <>
+        <div class="main-container">
+            <Search_nav />
+            <div class="bookmark-page-header">
+                <h1 class="text-2xl item_container-text font-bold">{ "My Bookmarks" }</h1>
+                if bookmarks.as_ref().map_or(false, |list| !list.is_empty()) {
+                    <button onclick={on_export} class="settings-button font-bold py-2 px-4 rounded">
+                        { "Export as Markdown" }
+                    </button>
+                }
+            </div>
+            <div class="bookmark-list">
+                { content }
+            </div>
+            if let Some(error) = audio_state.error_message.clone() {
+                <div class="error-snackbar">{ error }</div>
+            }
+        {
+            if let Some(audio_props) = &audio_state.currently_playing {
+                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} podcast_name={audio_props.podcast_name.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
+            } else {
+                html! {}
+            }
+        }
+        </div>
+        <App_drawer />
+        </>
+    }
+}
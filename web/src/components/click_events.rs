@@ -6,13 +6,14 @@ use yewdux::prelude::*; // or wherever your Dispatch type is defined
 use crate::components::context::{AppState};
 use yew_router::history::{BrowserHistory, History};
 use crate::components::podcast_layout::ClickedFeedURL;
-use crate::requests::search_pods::{call_get_podcast_episodes, call_parse_podcast_url};
-use crate::requests::pod_req::{call_get_podcast_id, call_check_podcast};
+use crate::components::routes::Route;
+use yew_router::Routable;
+use crate::requests::client::PinepodsClient;
+use crate::requests::pod_req::Page;
 
 pub fn create_on_title_click(
     dispatch: Dispatch<AppState>,
-    server_name: String,
-    api_key: Option<Option<String>>,
+    client: PinepodsClient,
     history: &BrowserHistory,
     podcast_title: String,
     podcast_url: String,
@@ -34,8 +35,7 @@ pub fn create_on_title_click(
             state.podcast_added = Some(false); // Set podcast_added to false here
         });
         let title_wasm = podcast_title.clone();
-        let server_clone = server_name.clone();
-        let api_clone = api_key.clone().unwrap();
+        let client = client.clone();
         let podcast_url_call = podcast_url.clone();
         let podcast_values = ClickedFeedURL {
             podcast_title: podcast_title.clone(),
@@ -53,17 +53,17 @@ pub fn create_on_title_click(
         let dispatch = dispatch.clone();
         let history = history.clone(); // Clone again for use inside async block
         wasm_bindgen_futures::spawn_local(async move {
-            match call_check_podcast(&server_clone, &api_clone.clone().unwrap(), user_id, &title_wasm, &podcast_url_call).await {
+            match client.check_podcast(user_id, &title_wasm, &podcast_url_call).await {
                 Ok(response) => {
                     if response.exists {
                         // The podcast exists in the database
                         // Get the podcast id
                         web_sys::console::log_1(&format!("Podcast exists: {:?}", response).into());
-                        match call_get_podcast_id(&server_clone, &api_clone, &user_id, &podcast_url_call, &title_wasm).await {
+                        match client.get_podcast_id(&user_id, &podcast_url_call, &title_wasm).await {
                             Ok(podcast_id) => {
                                 web_sys::console::log_1(&format!("Podcast IDs: {:?}", podcast_id).into());
 
-                                match call_get_podcast_episodes(&server_clone, &api_clone, &user_id, &podcast_id).await {
+                                match client.get_podcast_episodes(&user_id, &podcast_id, Page::first()).await {
                                     Ok(podcast_feed_results) => {
                                         dispatch.reduce_mut(move |state| {
                                             state.podcast_added = Some(true);
@@ -71,7 +71,8 @@ pub fn create_on_title_click(
                                             state.clicked_podcast_info = Some(podcast_values);
                                         });
                                         dispatch.reduce_mut(|state| state.is_loading = Some(false));
-                                        history.push("/episode_layout"); // Navigate to episode_layout
+                                        // Podcasts in the database get a page that can be linked to
+                                        history.push(&Route::PodcastLink { id: podcast_id }.to_path());
                                     },
                                     Err(e) => {
                                         web_sys::console::log_1(&format!("Error fetching episodes: {:?}", e).into());
@@ -84,7 +85,7 @@ pub fn create_on_title_click(
                             }
                         }
                     } else {
-                        match call_parse_podcast_url(server_clone, &api_clone, &podcast_url_call).await {
+                        match client.parse_podcast_url(&podcast_url_call).await {
                             Ok(podcast_feed_results) => {
                                 dispatch.reduce_mut(move |state| {
                                     state.podcast_added = Some(false);
@@ -0,0 +1,161 @@
+use gloo_events::EventListener;
+use std::cell::Cell;
+use yew::prelude::*;
+use yewdux::prelude::*;
+use crate::components::context::{AppState, CompletionState, UIState};
+use crate::requests::login_requests::Capability;
+use crate::requests::outbox::{defer_if_offline, Mutation};
+use crate::requests::pod_req::MarkCompletedRequest;
+
+pub fn completion_supported(state: &AppState) -> bool {
+    state
+        .server_details
+        .as_ref()
+        .map_or(false, |details| details.supports(Capability::Completion))
+}
+
+/// The server and user played marks belong to.
+pub fn completion_account(state: &AppState) -> Option<(String, i32)> {
+    let user_id = state.user_details.as_ref()?.UserID;
+    Some((state.client().server_name().to_string(), user_id))
+}
+
+/// Marks the episode played or unplayed, on the server and in every list showing it.
+/// Offline, the mark waits in the outbox and the lists show it straight away.
+pub fn set_completed(
+    state: &AppState,
+    episode_id: i32,
+    completed: bool,
+    audio_dispatch: &Dispatch<UIState>,
+    completion_dispatch: &Dispatch<CompletionState>,
+) {
+    let Some(account) = completion_account(state) else { return };
+    let user_id = account.1;
+    let client = state.client();
+    let audio_dispatch = audio_dispatch.clone();
+    let completion_dispatch = completion_dispatch.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        let request = MarkCompletedRequest { episode_id, user_id };
+        let sent = if completed {
+            client.mark_episode_completed(&request).await
+        } else {
+            client.mark_episode_uncompleted(&request).await
+        };
+        let marked = match sent {
+            Ok(()) => true,
+            Err(e) => {
+                let mutation = if completed { Mutation::MarkCompleted(request) } else { Mutation::MarkUncompleted(request) };
+                let deferred = defer_if_offline(&client, &e, mutation);
+                if !deferred {
+                    audio_dispatch.reduce_mut(|state| state.error_message = Some(e.to_string()));
+                }
+                deferred
+            }
+        };
+        if marked {
+            completion_dispatch.reduce_mut(|completion| completion.mark(Some(account), &[episode_id], completed));
+        }
+    });
+}
+
+/// Marks every episode of the podcast published before this one played.
+pub fn mark_older_completed(
+    state: &AppState,
+    episode_id: i32,
+    audio_dispatch: &Dispatch<UIState>,
+    completion_dispatch: &Dispatch<CompletionState>,
+) {
+    let Some(account) = completion_account(state) else { return };
+    let user_id = account.1;
+    let client = state.client();
+    let audio_dispatch = audio_dispatch.clone();
+    let completion_dispatch = completion_dispatch.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        let request = MarkCompletedRequest { episode_id, user_id };
+        match client.mark_older_episodes_completed(&request).await {
+            Ok(marked) => {
+                let message = match marked.len() {
+                    0 => "Every older episode was already played".to_string(),
+                    1 => "Marked 1 older episode as played".to_string(),
+                    count => format!("Marked {} older episodes as played", count),
+                };
+                completion_dispatch.reduce_mut(|completion| completion.mark(Some(account), &marked, true));
+                audio_dispatch.reduce_mut(|state| state.info_message = Some(message));
+            }
+            Err(e) => audio_dispatch.reduce_mut(|state| state.error_message = Some(e.to_string())),
+        }
+    });
+}
+
+/// Marks the playing episode played once it passes the user's completion threshold,
+/// rather than waiting for an end that outros and credits often keep it from reaching.
+#[hook]
+pub fn use_mark_played_at_threshold() {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (audio_state, _audio_dispatch) = use_store::<UIState>();
+    let completion_dispatch = use_store::<CompletionState>().1;
+    let client = state.client();
+    let account = completion_account(&state).filter(|_| completion_supported(&state));
+    let episode_id = audio_state
+        .currently_playing
+        .as_ref()
+        .map(|playing| playing.episode_id)
+        .filter(|_| audio_state.episode_in_db.unwrap_or(false));
+    let threshold = audio_state.playback_settings.completion;
+
+    use_effect_with(
+        (audio_state.audio_element.clone(), client, account, episode_id, threshold),
+        move |(audio, client, account, episode_id, threshold)| {
+            let listener = match (audio.clone(), account.clone(), *episode_id) {
+                (Some(audio), Some(account), Some(episode_id)) => {
+                    let (client, threshold) = (client.clone(), *threshold);
+                    // Once per episode is enough
+                    let marked = Cell::new(false);
+                    Some(EventListener::new(&audio.clone(), "timeupdate", move |_| {
+                        if marked.get() || !threshold.reached(audio.current_time(), audio.duration()) {
+                            return;
+                        }
+                        marked.set(true);
+                        let client = client.clone();
+                        let account = account.clone();
+                        let completion_dispatch = completion_dispatch.clone();
+                        wasm_bindgen_futures::spawn_local(async move {
+                            let request = MarkCompletedRequest { episode_id, user_id: account.1 };
+                            if let Err(e) = client.mark_episode_completed(&request).await {
+                                if !defer_if_offline(&client, &e, Mutation::MarkCompleted(request)) {
+                                    return;
+                                }
+                            }
+                            completion_dispatch.reduce_mut(|completion| completion.mark(Some(account), &[episode_id], true));
+                        });
+                    }))
+                }
+                _ => None,
+            };
+            move || drop(listener)
+        },
+    );
+}
+
+#[derive(Properties, PartialEq)]
+pub struct PlayedBadgeProps {
+    pub episode_id: i32,
+    /// Whether the list the episode is in had it as played.
+    pub completed: bool,
+}
+
+/// Shows that an episode is played, including marks made since its list loaded.
+#[function_component(PlayedBadge)]
+pub fn played_badge(props: &PlayedBadgeProps) -> Html {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (completion, _completion_dispatch) = use_store::<CompletionState>();
+    if !completion.is_completed(&completion_account(&state), props.episode_id, props.completed) {
+        return html! {};
+    }
+    html! {
+        <span class="played-badge inline-flex items-center px-2.5 py-0.5 rounded me-2" title="Played">
+            <span class="material-icons">{"check_circle"}</span>
+            {"Played"}
+        </span>
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use serde::Deserialize;
 use wasm_bindgen::closure::Closure;
@@ -6,17 +6,23 @@ use wasm_bindgen::JsCast;
 use crate::requests::login_requests::AddUserRequest;
 use crate::requests::login_requests::GetUserDetails;
 use crate::requests::login_requests::LoginServerRequest;
+use crate::requests::client::PinepodsClient;
 use crate::requests::login_requests::{GetApiDetails, TimeZoneInfo};
 use crate::components::audio::AudioPlayerProps;
 use crate::requests::setting_reqs::{AddSettingsUserRequest, EditSettingsUserRequest};
 use crate::requests::search_pods::{PodcastFeedResult, PodcastSearchResult, SearchResponse};
-use crate::requests::pod_req::{Episode, RecentEps, Podcast, PodcastResponse, QueuedEpisodesResponse, SavedEpisodesResponse, HistoryDataResponse, EpisodeDownloadResponse, EpisodeMetadataResponse};
+use crate::requests::chapters::EpisodeChapters;
+use crate::components::sleep_timer::SleepTimer;
+use crate::components::up_next::UpNext;
+use crate::requests::setting_reqs::PlaybackSettings;
+use crate::requests::pod_req::{Episode, RecentEps, Podcast, PodcastResponse, QueuedEpisodesResponse, SavedEpisodesResponse, HistoryDataResponse, EpisodeDownloadResponse, EpisodeMetadataResponse, PodcastPlaybackSettings};
 use yewdux::prelude::*;
-use web_sys::HtmlAudioElement;
+use web_sys::HtmlVideoElement;
 use serde_json::{json, from_str};
 use web_sys::window;
 use crate::components::podcast_layout::ClickedFeedURL;
 use crate::requests::stat_reqs::UserStats;
+use crate::requests::bookmarks::Bookmark;
 
 #[allow(dead_code)]
 #[allow(dead_code)]
@@ -109,6 +115,53 @@ pub struct UserStatsStore {
     pub stats: Option<UserStats>,
 }
 
+/// Number of changes waiting in the offline outbox.
+#[derive(Default, Clone, PartialEq, Store, Debug)]
+pub struct OutboxState {
+    pub pending: usize,
+}
+
+/// Bookmarks loaded so far, by episode, shared by the player and the pages that list
+/// them. `account` is the server and user they belong to.
+#[derive(Default, Clone, PartialEq, Store, Debug)]
+pub struct BookmarkState {
+    pub account: Option<(String, i32)>,
+    pub by_episode: HashMap<i32, Vec<Bookmark>>,
+}
+
+/// Episodes marked played or unplayed since their lists were loaded, which the lists
+/// show over what they were loaded with. `account` is the server and user they belong to.
+#[derive(Default, Clone, PartialEq, Store, Debug)]
+pub struct CompletionState {
+    pub account: Option<(String, i32)>,
+    pub marked: HashMap<i32, bool>,
+}
+
+impl CompletionState {
+    pub fn is_completed(&self, account: &Option<(String, i32)>, episode_id: i32, loaded: bool) -> bool {
+        match self.marked.get(&episode_id) {
+            Some(&completed) if &self.account == account => completed,
+            _ => loaded,
+        }
+    }
+
+    pub fn mark(&mut self, account: Option<(String, i32)>, episode_ids: &[i32], completed: bool) {
+        if self.account != account {
+            *self = CompletionState { account, ..CompletionState::default() };
+        }
+        self.marked.extend(episode_ids.iter().map(|&episode_id| (episode_id, completed)));
+    }
+}
+
+/// The waveform of the episode that's playing, shared by the mini and expanded scrub
+/// bars. `src` is set as soon as decoding starts, so it only happens once.
+#[derive(Default, Clone, PartialEq, Store, Debug)]
+pub struct WaveformState {
+    pub src: Option<String>,
+    /// Bar heights from 0 to 1, once decoded.
+    pub peaks: Option<Rc<Vec<f32>>>,
+}
+
 #[derive(Default, Deserialize, Clone, PartialEq, Store, Debug)]
 pub struct SettingsState {
     pub active_tab: Option<String>,
@@ -118,7 +171,9 @@ pub struct SettingsState {
 pub struct UIState {
     pub audio_playing: Option<bool>,
     pub currently_playing: Option<AudioPlayerProps>,
-    pub audio_element: Option<HtmlAudioElement>,
+    /// The player. It's a `<video>` so video episodes have a picture to show; audio plays
+    /// in it just the same.
+    pub audio_element: Option<HtmlVideoElement>,
     pub current_time_seconds: f64,
     pub current_time_formatted: String,
     pub duration: f64,
@@ -128,6 +183,14 @@ pub struct UIState {
     pub is_expanded: bool,
     pub episode_in_db: Option<bool>,
     pub playback_speed: f64,
+    pub chapters: Option<EpisodeChapters>,
+    pub sleep_timer: Option<SleepTimer>,
+    pub playback_settings: PlaybackSettings,
+    pub show_shortcut_help: bool,
+    /// The playing episode's podcast settings, once they've loaded.
+    pub podcast_playback: Option<PodcastPlaybackSettings>,
+    /// The queued episode counting down to start after the last one ended.
+    pub up_next: Option<UpNext>,
     // pub start_pos_sec: f64,
 }
 
@@ -142,6 +205,29 @@ impl UIState {
         let seconds = (new_time_seconds % 60.0).floor() as i32;
         self.current_time_formatted = format!("{:02}:{:02}:{:02}", hours, minutes, seconds);
     }
+    pub fn seek_to(&mut self, time_seconds: f64) {
+        if let Some(audio) = self.audio_element.clone() {
+            let time_seconds = time_seconds.max(0.0);
+            audio.set_current_time(time_seconds);
+            self.update_current_time(time_seconds);
+        }
+    }
+
+    /// Where the episode that's playing stops: its end, less any outro its podcast cuts.
+    pub fn episode_end(&self) -> f64 {
+        let duration = self.audio_element.as_ref().map_or(f64::NAN, |audio| audio.duration());
+        match self.podcast_playback.as_ref().map(|settings| settings.skip_outro as f64) {
+            Some(outro) if outro > 0.0 && duration > outro => duration - outro,
+            _ => duration,
+        }
+    }
+
+    /// The chapters of the episode that's playing, once they've loaded.
+    pub fn current_chapters(&self) -> Option<&EpisodeChapters> {
+        let src = &self.currently_playing.as_ref()?.src;
+        self.chapters.as_ref().filter(|chapters| &chapters.src == src)
+    }
+
     pub fn toggle_playback(&mut self) {
         if let Some(audio) = &self.audio_element {
             if self.audio_playing.unwrap_or(false) {
@@ -154,10 +240,17 @@ impl UIState {
         }
     }
 
-    pub fn set_audio_source(&mut self, src: String) {
+    /// Points the player at `src`. Audio that goes through the processing graph has to be
+    /// fetched with CORS, or the browser hands the graph silence.
+    pub fn set_audio_source(&mut self, src: String, cross_origin: bool) {
         if self.audio_element.is_none() {
-            self.audio_element = HtmlAudioElement::new().ok();
+            self.audio_element = window()
+                .and_then(|w| w.document())
+                .and_then(|document| document.create_element("video").ok())
+                .and_then(|element| element.dyn_into::<HtmlVideoElement>().ok());
             if let Some(audio) = &self.audio_element {
+                // Phones otherwise take a video straight to fullscreen when it plays
+                let _ = audio.set_attribute("playsinline", "");
                 let closure = Closure::wrap(Box::new(move || {
                     // Code to handle the audio being ready to play
                 }) as Box<dyn Fn()>);
@@ -166,6 +259,7 @@ impl UIState {
             }
         }
         if let Some(audio) = &self.audio_element {
+            audio.set_cross_origin(cross_origin.then_some("anonymous"));
             audio.set_src(&src);
         }
     }
@@ -184,6 +278,15 @@ impl AppState {
         from_str(serialized_state)
     }
 
+    /// Client for the server the user is logged into. Before login the client has
+    /// no server or key, so authenticated calls fail with "API key is missing".
+    pub fn client(&self) -> PinepodsClient {
+        self.auth_details
+            .as_ref()
+            .map(PinepodsClient::from)
+            .unwrap_or_default()
+    }
+
     pub fn store_app_state(&self) {
         if let Some(window) = window() {
             if let Some(local_storage) = window.local_storage().unwrap() {
@@ -198,6 +301,7 @@ impl AppState {
                 let _ = local_storage.set_item(server_key, &server_state);
             }
         }
+        crate::components::server_profiles::remember_profile(self);
     }
 
     // pub fn load_app_state(key: &str) -> Option<AppState> {
@@ -2,8 +2,10 @@ use yew::{function_component, Html, html};
 use yew::prelude::*;
 use super::app_drawer::App_drawer;
 use super::gen_components::{UseScrollToTop, Search_nav, empty_message, episode_item, on_shownotes_click};
-use crate::requests::pod_req::{self, EpisodeDownloadResponse, DownloadEpisodeRequest, call_remove_downloaded_episode};
+use crate::requests::cache::CacheKey;
+use crate::requests::pod_req::{EpisodeDownload, EpisodeDownloadResponse, DownloadEpisodeRequest};
 use yewdux::prelude::*;
+use crate::requests::http::use_abort_handle;
 use crate::components::context::{AppState, UIState};
 use yew_router::history::BrowserHistory;
 use crate::components::audio::AudioPlayer;
@@ -16,7 +18,7 @@ use wasm_bindgen::closure::Closure;
 use web_sys::window;
 use wasm_bindgen::JsCast;
 use std::borrow::Borrow;
-use crate::requests::login_requests::use_check_authentication;
+use crate::requests::login_requests::{use_check_authentication, Capability};
 
 #[function_component(Downloads)]
 pub fn downloads() -> Html {
@@ -62,9 +64,12 @@ pub fn downloads() -> Html {
     let error_message = audio_state.error_message.clone();
     let info_message = audio_state.info_message.clone();
     let page_state = use_state(|| PageState::Normal);
-    let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = post_state.client();
     let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
+    let supports_stream = post_state
+        .server_details
+        .as_ref()
+        .map_or(false, |details| details.supports(Capability::Stream));
     let loading = use_state(|| true);
 
     {
@@ -89,28 +94,38 @@ pub fn downloads() -> Html {
     }
 
 
-    // Fetch episodes on component mount
+    // Fetch episodes on component mount. Leaving the view cancels the fetch, so a
+    // late response can't overwrite the list of the view the user moved on to.
+    let abort = use_abort_handle();
     let loading_ep = loading.clone();
     {
         // let episodes = episodes.clone();
         let error = error.clone();
-        let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+        let client = post_state.client().with_abort(abort.clone());
         let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-        let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
 
         let effect_dispatch = dispatch.clone();
 
-        // fetch_episodes(api_key.flatten(), user_id, server_name, dispatch, error, pod_req::call_get_recent_eps);
 
         use_effect_with(
-            (api_key.clone(), user_id.clone(), server_name.clone()),
+            (client.clone(), user_id.clone()),
             move |_| {
                 let error_clone = error.clone();
-                if let (Some(api_key), Some(user_id), Some(server_name)) = (api_key.clone(), user_id.clone(), server_name.clone()) {
+                if let Some(user_id) = user_id.clone() {
+                    let client = client.clone();
                     let dispatch = effect_dispatch.clone();
     
+                    // Render the last fetched list right away, then refresh it in the background
+                    let cached = client.cached::<Vec<EpisodeDownload>>(CacheKey::Downloads(user_id));
+                    let has_cached = cached.is_some();
+                    if let Some(cached_list) = cached {
+                        dispatch.reduce_mut(move |state| {
+                            state.downloaded_episodes = Some(EpisodeDownloadResponse { episodes: cached_list });
+                        });
+                        loading_ep.set(false);
+                    }
                     wasm_bindgen_futures::spawn_local(async move {
-                        match pod_req::call_get_episode_downloads(&server_name, &api_key, &user_id).await {
+                        match client.get_episode_downloads(&user_id).await {
                             Ok(fetched_episodes) => {
                                 dispatch.reduce_mut(move |state| {
                                     state.downloaded_episodes = Some(EpisodeDownloadResponse { episodes: fetched_episodes });
@@ -119,7 +134,10 @@ pub fn downloads() -> Html {
                                 // web_sys::console::log_1(&format!("State after update: {:?}", state).into()); // Log state after update
                             },
                             Err(e) => {
-                                error_clone.set(Some(e.to_string()));
+                                // Keep showing the cached list when only the refresh failed
+                                if !has_cached {
+                                    error_clone.set(Some(e.to_string()));
+                                }
                                 loading_ep.set(false);
                             },
                         }
@@ -166,16 +184,14 @@ pub fn downloads() -> Html {
     let delete_selected_episodes = {
         let dispatch = dispatch.clone();
         let page_state = page_state.clone();
-        let server_name = server_name.clone();
-        let api_key = api_key.clone();
+        let client = client.clone();
         let user_id = user_id.clone(); // Make sure this is cloned from a state or props where it's guaranteed to exist.
     
         Callback::from(move |_: MouseEvent| {
             // Clone values for use inside the async block
             let dispatch_cloned = dispatch.clone();
             let page_state_cloned = page_state.clone();
-            let server_name_cloned = server_name.clone().unwrap(); // Assuming you've ensured these are present
-            let api_key_cloned = api_key.clone().unwrap();
+            let client_cloned = client.clone();
             let user_id_cloned = user_id.unwrap();
     
             dispatch.reduce_mut(move |state| {
@@ -188,10 +204,9 @@ pub fn downloads() -> Html {
                         episode_id,
                         user_id: user_id_cloned,
                     };
-                    let server_name_cloned = server_name_cloned.clone();
-                    let api_key_cloned = api_key_cloned.clone();
+                    let client_cloned = client_cloned.clone();
                     let future = async move {
-                        match call_remove_downloaded_episode(&server_name_cloned, &api_key_cloned, &request).await {
+                        match client_cloned.remove_downloaded_episode(&request).await {
                             Ok(success_message) => Some((success_message, episode_id)),
                             Err(_) => None,
                         }
@@ -275,9 +290,8 @@ pub fn downloads() -> Html {
                     {
                     if let Some(download_eps) = state.downloaded_episodes.clone() {
                         let int_download_eps = download_eps.clone();
-                            let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+                            let client = post_state.client();
                             let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-                            let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
                             let history_clone = history.clone();
 
                             if int_download_eps.episodes.is_empty() {
@@ -334,24 +348,25 @@ pub fn downloads() -> Html {
                                 let listener_duration_for_closure = episode_listened_clone.clone();
                                 let episode_id_for_closure = episode_id_clone.clone();
                                 let user_id_play = user_id.clone();
-                                let server_name_play = server_name.clone();
-                                let api_key_play = api_key.clone();
+                                let client_play = client.clone();
                                 let audio_dispatch = audio_dispatch.clone();
-                                let is_local = Option::from(true);
+                                // Servers that can't stream their downloads play them from the feed
+                                let is_local = supports_stream.then_some(true);
                                 
                                 let on_play_click = on_play_click(
                                     episode_url_for_closure.clone(),
                                     episode_title_for_closure.clone(),
+                                    episode.PodcastName.clone(),
                                     episode_artwork_for_closure.clone(),
                                     episode_duration_for_closure.clone(),
                                     episode_id_for_closure.clone(),
                                     listener_duration_for_closure.clone(),
-                                    api_key_play.unwrap().unwrap(),
+                                    client_play.clone(),
                                     user_id_play.unwrap(),
-                                    server_name_play.unwrap(),
                                     audio_dispatch.clone(),
                                     audio_state.clone(),
                                     is_local,
+                                    None,
                                 );
 
                                 let on_shownotes_click = on_shownotes_click(
@@ -397,7 +412,7 @@ pub fn downloads() -> Html {
             }
         {
             if let Some(audio_props) = &audio_state.currently_playing {
-                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
+                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} podcast_name={audio_props.podcast_name.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
             } else {
                 html! {}
             }
@@ -2,14 +2,16 @@ use yew::{function_component, Html, html};
 use yew::prelude::*;
 use super::app_drawer::App_drawer;
 use super::gen_components::{Search_nav, empty_message, UseScrollToTop};
-use crate::requests::pod_req;
 use yewdux::prelude::*;
 use crate::components::context::{AppState, UIState};
 use crate::components::audio::AudioPlayer;
 use crate::components::gen_funcs::{sanitize_html_with_blank_target, format_datetime, format_time, match_date_format, parse_date};
-use crate::requests::pod_req::{EpisodeRequest, EpisodeMetadataResponse, QueuePodcastRequest, call_queue_episode, SavePodcastRequest, call_save_episode, DownloadEpisodeRequest, call_download_episode};
-use crate::components::audio::on_play_click;
+use crate::requests::outbox::{defer_if_offline, Mutation};
+use crate::requests::pod_req::{EpisodeRequest, EpisodeMetadataResponse, QueuePodcastRequest, SavePodcastRequest, DownloadEpisodeRequest};
+use crate::components::audio::{on_play_click, open_episode_link};
 use crate::components::episodes_layout::SafeHtml;
+use crate::components::transcript::TranscriptView;
+use crate::components::bookmarks::EpisodeBookmarkList;
 use crate::components::episodes_layout::UIStateMsg;
 use crate::components::click_events::create_on_title_click;
 use wasm_bindgen::closure::Closure;
@@ -19,9 +21,23 @@ use crate::requests::login_requests::use_check_authentication;
 use yew_router::history::{BrowserHistory, History};
 use std::collections::HashMap;
 use wasm_bindgen::JsValue;
+use serde::Deserialize;
+
+#[derive(Properties, PartialEq)]
+pub struct EpisodeProps {
+    /// Set when the page is opened from a link; otherwise it shows the selected episode.
+    #[prop_or_default]
+    pub episode_id: Option<i32>,
+}
+
+/// The `?t=` of a link to a moment in the episode, in seconds.
+#[derive(Deserialize, Default)]
+struct EpisodeLinkQuery {
+    t: Option<u32>,
+}
 
 #[function_component(Episode)]
-pub fn epsiode() -> Html {
+pub fn epsiode(props: &EpisodeProps) -> Html {
     let (state, dispatch) = use_store::<AppState>();
 
     let session_dispatch = dispatch.clone();
@@ -60,12 +76,12 @@ pub fn epsiode() -> Html {
     let error = use_state(|| None);
     let (post_state, _post_dispatch) = use_store::<AppState>();
     let (audio_state, audio_dispatch) = use_store::<UIState>();
-    let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = post_state.client();
     let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
     let error_message = audio_state.error_message.clone();
     let info_message = audio_state.info_message.clone();
     let history = BrowserHistory::new();
+    let start_at = history.location().query::<EpisodeLinkQuery>().unwrap_or_default().t;
 
     {
         let ui_dispatch = audio_dispatch.clone();
@@ -92,31 +108,43 @@ pub fn epsiode() -> Html {
     {
         // let episodes = episodes.clone();
         let error = error.clone();
-        let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+        let client = post_state.client();
         let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-        let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
         let effect_dispatch = dispatch.clone();
 
-        let episode_id = state.selected_episode_id.clone();
+        let episode_id = props.episode_id.or(state.selected_episode_id);
+        let effect_audio_dispatch = audio_dispatch.clone();
+        let history = history.clone();
 
-        // fetch_episodes(api_key.flatten(), user_id, server_name, dispatch, error, pod_req::call_get_recent_eps);
 
         use_effect_with(
-            (api_key.clone(), user_id.clone(), server_name.clone()),
+            (client.clone(), user_id.clone(), episode_id),
             move |_| {
                 let error_clone = error.clone();
-                if let (Some(api_key), Some(user_id), Some(server_name)) = (api_key.clone(), user_id.clone(), server_name.clone()) {
+                if let (Some(user_id), Some(episode_id)) = (user_id.clone(), episode_id) {
+                    let client = client.clone();
                     let dispatch = effect_dispatch.clone();
+                    let audio_dispatch = effect_audio_dispatch.clone();
+                    // Taking the time out of the address starts the episode once per visit
+                    // to the link, not again when this reloads or the user comes back to it
+                    if start_at.is_some() {
+                        history.replace(history.location().path());
+                    }
     
                     let episode_request = EpisodeRequest {
-                        episode_id: episode_id.clone().unwrap(),
+                        episode_id,
                         user_id: user_id.clone(),
                     };
         
                     wasm_bindgen_futures::spawn_local(async move {
-                        match pod_req::call_get_episode_metadata(&server_name, api_key, &episode_request).await {
+                        match client.get_episode_metadata(&episode_request).await {
                             Ok(fetched_episode) => {
+                                // A link to a moment in the episode starts it there
+                                if let Some(start_at) = start_at {
+                                    open_episode_link(&fetched_episode, start_at as i32, &client, user_id, &audio_dispatch);
+                                }
                                 dispatch.reduce_mut(move |state| {
+                                    state.selected_episode_id = Some(episode_id);
                                     state.fetched_episode = Some(EpisodeMetadataResponse { episode: fetched_episode });
                                 });
                             },
@@ -143,7 +171,7 @@ pub fn epsiode() -> Html {
                     let episode_artwork_clone = episode.episode.EpisodeArtwork.clone();
                     let episode_duration_clone = episode.episode.EpisodeDuration.clone();
                     let podcast_of_episode = episode.episode.PodcastID.clone();
-                    let episode_listened_clone = Option::from(0);
+                    let episode_listened_clone = Option::from(start_at.unwrap_or(0) as i32);
                     let episode_id_clone = episode.episode.EpisodeID.clone();
     
                     let sanitized_description = sanitize_html_with_blank_target(&episode.episode.EpisodeDescription.clone());
@@ -156,51 +184,88 @@ pub fn epsiode() -> Html {
                     let episode_id_for_closure = episode_id_clone.clone();
                     let listener_duration_for_closure = episode_listened_clone.clone();
 
+                    // A bookmark seeks if this episode is the one playing, otherwise starts it there
+                    let on_bookmark_jump = {
+                        let client = client.clone();
+                        let audio_dispatch = audio_dispatch.clone();
+                        let episode = episode.episode.clone();
+                        Callback::from(move |time: i32| {
+                            let playing = audio_dispatch.get().currently_playing.as_ref().map(|playing| playing.episode_id);
+                            if playing == Some(episode.EpisodeID) {
+                                audio_dispatch.reduce_mut(|state| {
+                                    state.seek_to(time as f64);
+                                    if let Some(audio) = &state.audio_element {
+                                        let _ = audio.play();
+                                        state.audio_playing = Some(true);
+                                    }
+                                });
+                            } else if let Some(user_id) = user_id {
+                                on_play_click(
+                                    episode.EpisodeURL.clone(),
+                                    episode.EpisodeTitle.clone(),
+                                    episode.PodcastName.clone(),
+                                    episode.EpisodeArtwork.clone(),
+                                    episode.EpisodeDuration,
+                                    episode.EpisodeID,
+                                    Some(time),
+                                    client.clone(),
+                                    user_id,
+                                    audio_dispatch.clone(),
+                                    audio_dispatch.get(),
+                                    None,
+                                    None,
+                                )
+                                .emit(MouseEvent::new("click").unwrap());
+                            }
+                        })
+                    };
+
                     let user_id_play = user_id.clone();
-                    let server_name_play = server_name.clone();
-                    let api_key_play = api_key.clone();
+                    let client_play = client.clone();
                     let audio_dispatch = audio_dispatch.clone();
 
                     let on_play_click = on_play_click(
                         episode_url_for_closure.clone(),
                         episode_title_for_closure.clone(),
+                        episode.episode.PodcastName.clone(),
                         episode_artwork_for_closure.clone(),
                         episode_duration_for_closure.clone(),
                         episode_id_for_closure.clone(),
                         listener_duration_for_closure.clone(),
-                        api_key_play.unwrap().unwrap(),
+                        client_play.clone(),
                         user_id_play.unwrap(),
-                        server_name_play.unwrap(),
                         audio_dispatch.clone(),
                         audio_state.clone(),
                         None,
+                        None,
                     );
 
                     let user_id_queue = user_id.clone();
-                    let server_name_queue = server_name.clone();
-                    let api_key_queue = api_key.clone();
+                    let client_queue = client.clone();
                     let audio_dispatch_queue = audio_dispatch.clone();
 
                     let on_add_to_queue = {
                         Callback::from(move |_: MouseEvent| {
-                            let server_name_copy = server_name_queue.clone();
-                            let api_key_copy = api_key_queue.clone();
+                            let client_copy = client_queue.clone();
                             let queue_post = audio_dispatch_queue.clone();
                             let request = QueuePodcastRequest {
                                 episode_id: episode_id_for_closure,
                                 user_id: user_id_queue.unwrap(), // replace with the actual user ID
                             };
-                            let server_name = server_name_copy; // replace with the actual server name
-                            let api_key = api_key_copy; // replace with the actual API key
+                            let client = client_copy;
                             let future = async move {
-                                // let _ = call_queue_episode(&server_name.unwrap(), &api_key.flatten(), &request).await;
+                                // let _ = client.queue_episode(&request).await;
                                 // queue_post.reduce_mut(|state| state.info_message = Option::from(format!("Episode added to Queue!")));
-                                match call_queue_episode(&server_name.unwrap(), &api_key.flatten(), &request).await {
+                                match client.queue_episode(&request).await {
                                     Ok(success_message) => {
                                         queue_post.reduce_mut(|state| state.info_message = Option::from(format!("{}", success_message)));
                                     },
                                     Err(e) => {
-                                        queue_post.reduce_mut(|state| state.error_message = Option::from(format!("{}", e)));
+                                        if defer_if_offline(&client, &e, Mutation::QueueEpisode(request.clone())) {
+                                            queue_post.reduce_mut(|state| state.info_message = Option::from("You're offline. The episode will be queued once the connection is back.".to_string()));
+                                        } else {
+                                            queue_post.reduce_mut(|state| state.error_message = Option::from(format!("{}", e)));
+                                        }
                                         // Handle error, e.g., display the error message
                                     }
                                 }
@@ -210,31 +275,32 @@ pub fn epsiode() -> Html {
                         })
                     };
 
-                    let saved_server_name = server_name.clone();
-                    let saved_api_key = api_key.clone();
+                    let saved_client = client.clone();
                     let save_post = audio_dispatch.clone();
                     let user_id_save = user_id.clone();
 
                     let on_save_episode = {
                         Callback::from(move |_: MouseEvent| {
-                            let server_name_copy = saved_server_name.clone();
-                            let api_key_copy = saved_api_key.clone();
+                            let client_copy = saved_client.clone();
                             let post_state = save_post.clone();
                             let request = SavePodcastRequest {
                                 episode_id: episode_id_for_closure, // changed from episode_title
                                 user_id: user_id_save.unwrap(), // replace with the actual user ID
                             };
-                            let server_name = server_name_copy; // replace with the actual server name
-                            let api_key = api_key_copy; // replace with the actual API key
+                            let client = client_copy;
                             let future = async move {
-                                // let return_mes = call_save_episode(&server_name.unwrap(), &api_key.flatten(), &request).await;
+                                // let return_mes = client.save_episode(&request).await;
                                 // post_state.reduce_mut(|state| state.info_message = Option::from(format!("Episode saved successfully")));
-                                match call_save_episode(&server_name.unwrap(), &api_key.flatten(), &request).await {
+                                match client.save_episode(&request).await {
                                     Ok(success_message) => {
                                         post_state.reduce_mut(|state| state.info_message = Option::from(format!("{}", success_message)));
                                     },
                                     Err(e) => {
-                                        post_state.reduce_mut(|state| state.error_message = Option::from(format!("{}", e)));
+                                        if defer_if_offline(&client, &e, Mutation::SaveEpisode(request.clone())) {
+                                            post_state.reduce_mut(|state| state.info_message = Option::from("You're offline. The episode will be saved once the connection is back.".to_string()));
+                                        } else {
+                                            post_state.reduce_mut(|state| state.error_message = Option::from(format!("{}", e)));
+                                        }
                                         // Handle error, e.g., display the error message
                                     }
                                 }
@@ -244,26 +310,23 @@ pub fn epsiode() -> Html {
                         })
                     };
 
-                    let download_server_name = server_name.clone();
-                    let download_api_key = api_key.clone();
+                    let download_client = client.clone();
                     let download_post = audio_dispatch.clone();
                     let user_id_download = user_id.clone();
 
                     let on_download_episode = {
                         Callback::from(move |_: MouseEvent| {
                             let post_state = download_post.clone();
-                            let server_name_copy = download_server_name.clone();
-                            let api_key_copy = download_api_key.clone();
+                            let client_copy = download_client.clone();
                             let request = DownloadEpisodeRequest {
                                 episode_id: episode_id_for_closure,
                                 user_id: user_id_download.unwrap(), // replace with the actual user ID
                             };
-                            let server_name = server_name_copy; // replace with the actual server name
-                            let api_key = api_key_copy; // replace with the actual API key
+                            let client = client_copy;
                             let future = async move {
-                                // let _ = call_download_episode(&server_name.unwrap(), &api_key.flatten(), &request).await;
+                                // let _ = client.download_episode(&request).await;
                                 // post_state.reduce_mut(|state| state.info_message = Option::from(format!("Episode now downloading!")));
-                                match call_download_episode(&server_name.unwrap(), &api_key.flatten(), &request).await {
+                                match client.download_episode(&request).await {
                                     Ok(success_message) => {
                                         post_state.reduce_mut(|state| state.info_message = Option::from(format!("{}", success_message)));
                                     },
@@ -285,22 +348,20 @@ pub fn epsiode() -> Html {
 
                     let on_title_click = {
                         let dispatch = dispatch.clone();
-                        let server_name = server_name.clone();
-                        let api_key = api_key.clone();
+                        let client = client.clone();
                         let podcast_id = podcast_of_episode.clone();
                         let user_id = user_id.clone();
                         let history = history.clone();
                     
                         Callback::from(move |event: MouseEvent| {
                             let dispatch = dispatch.clone();
-                            let server_name = server_name.clone();
-                            let api_key = api_key.clone();
+                            let client = client.clone();
                             let podcast_id = podcast_id.clone();
                             let user_id = user_id.clone();
                             let history = history.clone();
                     
                             wasm_bindgen_futures::spawn_local(async move {
-                                match pod_req::call_get_podcast_details(&server_name.clone().unwrap(), &api_key.clone().unwrap().unwrap(), user_id.unwrap(), &podcast_id).await {
+                                match client.get_podcast_details(user_id.unwrap(), &podcast_id).await {
                                     Ok(details) => {
                                         let mut categories_map: HashMap<String, String> = HashMap::new();
                                         for (index, category) in details.categories.split(',').enumerate() {
@@ -309,8 +370,7 @@ pub fn epsiode() -> Html {
                                         // Assuming details contain all necessary podcast info
                                         let final_click_action = create_on_title_click(
                                             dispatch.clone(),
-                                            server_name.unwrap(),
-                                            api_key,
+                                            client.clone(),
                                             &history,
                                             details.podcast_name,
                                             details.feed_url,
@@ -391,6 +451,15 @@ pub fn epsiode() -> Html {
                                 <SafeHtml html={description} />
                             </div>
                             </div>
+                            <EpisodeBookmarkList
+                                episode_id={episode.episode.EpisodeID}
+                                on_jump={on_bookmark_jump}
+                            />
+                            <TranscriptView
+                                episode_id={episode.episode.EpisodeID}
+                                episode_url={episode.episode.EpisodeURL.clone()}
+                                feed_url={episode.episode.FeedURL.clone()}
+                            />
                         </div>
                     }
                     // item
@@ -404,7 +473,7 @@ pub fn epsiode() -> Html {
             }
         {
             if let Some(audio_props) = &audio_state.currently_playing {
-                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
+                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} podcast_name={audio_props.podcast_name.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
             } else {
                 html! {}
             }
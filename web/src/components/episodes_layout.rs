@@ -5,15 +5,21 @@ use yew::prelude::*;
 use web_sys::{Event, MouseEvent, window};
 use yew_router::history::{BrowserHistory, History};
 use yewdux::prelude::*;
+use crate::requests::http::use_abort_handle;
 use crate::components::context::{AppState, UIState};
 use crate::components::audio::{AudioPlayer, on_play_click};
-use super::gen_components::{UseScrollToTop, Search_nav, EpisodeTrait};
+use crate::components::podcast_playback::PodcastPlaybackModal;
+use crate::requests::login_requests::Capability;
+use super::gen_components::{UseScrollToTop, Search_nav, EpisodeTrait, load_more_indicator, empty_message};
+use crate::components::podcast_layout::ClickedFeedURL;
+use crate::requests::pod_req::{use_paged_list, Page};
 use super::app_drawer::App_drawer;
-use crate::requests::pod_req::{call_add_podcast, PodcastValues, call_check_podcast, call_remove_podcasts_name, RemovePodcastValuesName};
+use crate::requests::pod_req::{PodcastValues, RemovePodcastValuesName};
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use yew::Properties;
 use super::gen_components::ContextButton;
+use crate::components::completion::PlayedBadge;
 use super::gen_funcs::{parse_date, format_datetime, match_date_format};
 use crate::components::gen_funcs::format_time;
 use crate::requests::login_requests::use_check_authentication;
@@ -130,15 +136,26 @@ pub fn episode_layout() -> Html {
     let (search_state, _search_dispatch) = use_store::<AppState>();
     let podcast_feed_results = search_state.podcast_feed_results.clone();
     let clicked_podcast_info = search_state.clicked_podcast_info.clone();
+    let podcast_name = clicked_podcast_info.as_ref().map(|info| info.podcast_title.clone()).unwrap_or_default();
     let history = BrowserHistory::new();
     // let node_ref = use_node_ref();
     let user_id = search_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let api_key = search_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
-    let server_name = search_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
+    let client = search_state.client();
 
     let session_dispatch = _search_dispatch.clone();
     let session_state = search_state.clone();
     let podcast_added = search_state.podcast_added.unwrap_or_default();
+    // Playback settings are kept with the podcast, so they need it to be in the database
+    let saved_podcast_id = podcast_feed_results
+        .as_ref()
+        .and_then(|feed| feed.podcast_id)
+        .filter(|_| {
+            search_state
+                .server_details
+                .as_ref()
+                .map_or(false, |details| details.supports(Capability::PodcastPlayback))
+        });
+    let show_playback_settings = use_state(|| false);
 
 
     use_effect_with((), move |_| {
@@ -170,16 +187,18 @@ pub fn episode_layout() -> Html {
         || ()
     });
 
+    // Reads are cancelled when the view goes away; adding or removing the podcast is not
+    let abort = use_abort_handle();
+
     // On mount, check if the podcast is in the database
     let effect_user_id = user_id.unwrap().clone();
-    let effect_api_key = api_key.clone();
+    let effect_client = client.with_abort(abort.clone());
 
     {
         let is_added = is_added.clone();
         let podcast = clicked_podcast_info.clone();
         let user_id = effect_user_id.clone();
-        let api_key = effect_api_key.clone();
-        let server_name = server_name.clone();
+        let client = effect_client.clone();
 
         use_effect_with(
             &(),
@@ -187,7 +206,7 @@ pub fn episode_layout() -> Html {
                 let is_added = is_added.clone();
                 let podcast = podcast.clone();
                 wasm_bindgen_futures::spawn_local(async move {
-                    let added = call_check_podcast(&server_name.unwrap(), &api_key.unwrap().unwrap(), user_id, podcast.clone().unwrap().podcast_title.as_str(), podcast.clone().unwrap().podcast_url.as_str()).await.unwrap_or_default().exists;
+                    let added = client.check_podcast(user_id, podcast.clone().unwrap().podcast_title.as_str(), podcast.clone().unwrap().podcast_url.as_str()).await.unwrap_or_default().exists;
                     is_added.set(added);
                 });
                 || ()
@@ -272,9 +291,7 @@ pub fn episode_layout() -> Html {
         let pod_explicit_og = pod_values.clone().unwrap().podcast_explicit.clone();
         let user_id_og = user_id.unwrap().clone();
 
-        let api_key_clone = api_key.clone();
-        let server_name_clone = server_name.clone();
-        let user_id_clone = user_id.clone();
+        let client_clone = client.clone();
         let dispatch = add_dispatch.clone();
         let app_dispatch = _search_dispatch.clone();
 
@@ -293,15 +310,13 @@ pub fn episode_layout() -> Html {
                 podcast_url: pod_feed_url,
                 user_id: user_id
             };
-            let api_key_call = api_key_clone.clone();
-            let server_name_call = server_name_clone.clone();
+            let client_call = client_clone.clone();
             let app_dispatch = app_dispatch.clone();
             wasm_bindgen_futures::spawn_local(async move {
                 let dispatch_wasm = call_dispatch.clone();
-                let api_key_wasm = api_key_call.clone().unwrap();
-                let server_name_wasm = server_name_call.clone();
+                let client_wasm = client_call.clone();
                 let pod_values_clone = podcast_values.clone(); // Make sure you clone the podcast values
-                match call_remove_podcasts_name(&server_name_wasm.unwrap(), &api_key_wasm, &pod_values_clone).await {
+                match client_wasm.remove_podcasts_name(&pod_values_clone).await {
                     Ok(success) => {
                         if success {
                             dispatch_wasm.reduce_mut(|state| state.info_message = Option::from("Podcast successfully removed".to_string()));
@@ -348,18 +363,14 @@ pub fn episode_layout() -> Html {
                     pod_explicit,
                     user_id
                 };
-                let api_key_call = api_key_clone.clone();
-                let server_name_call = server_name_clone.clone();
-                let user_id_call = user_id_clone.clone();
+                let client_call = client_clone.clone();
 
                 wasm_bindgen_futures::spawn_local(async move {
                     let dispatch_wasm = call_dispatch.clone();
-                    let api_key_wasm = api_key_call.clone().unwrap();
-                    let user_id_wasm = user_id_call.clone().unwrap();
-                    let server_name_wasm = server_name_call.clone();
+                    let client_wasm = client_call.clone();
                     let pod_values_clone = podcast_values.clone(); // Make sure you clone the podcast values
 
-                    match call_add_podcast(&server_name_wasm.unwrap(), &api_key_wasm, user_id_wasm, &pod_values_clone).await {
+                    match client_wasm.add_podcast(&pod_values_clone).await {
                         Ok(success) => {
                             if success {
                                 dispatch_wasm.reduce_mut(|state| state.info_message = Option::from("Podcast successfully added".to_string()));
@@ -394,7 +405,23 @@ pub fn episode_layout() -> Html {
         fn toggle_description(guid: &str);
     }
 
-    
+    // Episodes from the server come a page at a time; feeds parsed from RSS arrive whole
+    let more_episodes = {
+        let client = client.with_abort(abort.clone());
+        let podcast_id = podcast_feed_results.as_ref().and_then(|feed| feed.podcast_id);
+        use_paged_list(
+            podcast_feed_results.as_ref().and_then(|feed| feed.next_page),
+            // Drop the page if the user opened another podcast meanwhile
+            move |state: &mut AppState| state.podcast_feed_results.as_mut().filter(|feed| feed.podcast_id == podcast_id),
+            move |page| {
+                let client = client.clone();
+                user_id.zip(podcast_id).map(|(user_id, podcast_id)| async move {
+                    client.get_podcast_episodes(&user_id, &podcast_id, page).await
+                })
+            },
+        )
+    };
+
     html! {
         <div class="main-container">
             <Search_nav />
@@ -453,6 +480,24 @@ pub fn episode_layout() -> Html {
                                 <button onclick={toggle_podcast} title="Click to add or remove podcast from feed" class={"item-container-button selector-button font-bold py-2 px-4 rounded-full self-center mr-8"} style="width: 60px; height: 60px;">
                                     { button_content }
                                 </button>
+                                if let Some(podcast_id) = saved_podcast_id {
+                                    <button onclick={{
+                                        let show_playback_settings = show_playback_settings.clone();
+                                        Callback::from(move |_: MouseEvent| show_playback_settings.set(true))
+                                    }} title="Playback settings for this podcast" class={"item-container-button selector-button font-bold py-2 px-4 rounded-full self-center mr-8"} style="width: 60px; height: 60px;">
+                                        <span class="material-icons">{"tune"}</span>
+                                    </button>
+                                    if *show_playback_settings {
+                                        <PodcastPlaybackModal
+                                            podcast_id={podcast_id}
+                                            podcast_title={podcast_info.podcast_title.clone()}
+                                            on_close={{
+                                                let show_playback_settings = show_playback_settings.clone();
+                                                Callback::from(move |_| show_playback_settings.set(false))
+                                            }}
+                                        />
+                                    }
+                                }
                             </div>
 
                             // <p class="item-header-description">{ &podcast_info.podcast_description }</p>
@@ -510,9 +555,8 @@ pub fn episode_layout() -> Html {
                                     }
                                 };
                                 let episode_id_clone = episode.episode_id.unwrap_or(0);
-                                let server_name_play = server_name.clone();
+                                let client_play = client.clone();
                                 let user_id_play = user_id.clone();
-                                let api_key_play = api_key.clone();
 
                                 let is_expanded = search_state.expanded_descriptions.contains(
                                     &episode.guid.clone().unwrap()
@@ -549,16 +593,17 @@ pub fn episode_layout() -> Html {
                                 let on_play_click = on_play_click(
                                     episode_url_clone.clone(),
                                     episode_title_clone.clone(),
+                                    podcast_name.clone(),
                                     episode_artwork_clone.clone(),
                                     episode_duration_in_seconds,
                                     episode_id_clone.clone(),
                                     Some(0),
-                                    api_key_play.unwrap().unwrap(),
+                                    client_play.clone(),
                                     user_id_play.unwrap(),
-                                    server_name_play.unwrap(),
                                     dispatch.clone(),
                                     state.clone(),
                                     None,
+                                    episode.enclosure_type.clone(),
                                 );
 
                                 let description_class = if is_expanded {
@@ -593,12 +638,17 @@ pub fn episode_layout() -> Html {
                                                     </div>
                                                 }
                                             }
-                                            <span class="episode-time-badge inline-flex items-center px-2.5 py-0.5 rounded me-2">
-                                                <svg class="time-icon w-2.5 h-2.5 me-1.5" aria-hidden="true" xmlns="http://www.w3.org/2000/svg" fill="currentColor" viewBox="0 0 20 20">
-                                                    <path d="M10 0a10 10 0 1 0 10 10A10.011 10.011 0 0 0 10 0Zm3.982 13.982a1 1 0 0 1-1.414 0l-3.274-3.274A1.012 1.012 0 0 1 9 10V6a1 1 0 0 1 2 0v3.586l2.982 2.982a1 1 0 0 1 0 1.414Z"/>
-                                                </svg>
-                                                { format_release }
-                                            </span>
+                                            <div class="flex items-center">
+                                                <span class="episode-time-badge inline-flex items-center px-2.5 py-0.5 rounded me-2">
+                                                    <svg class="time-icon w-2.5 h-2.5 me-1.5" aria-hidden="true" xmlns="http://www.w3.org/2000/svg" fill="currentColor" viewBox="0 0 20 20">
+                                                        <path d="M10 0a10 10 0 1 0 10 10A10.011 10.011 0 0 0 10 0Zm3.982 13.982a1 1 0 0 1-1.414 0l-3.274-3.274A1.012 1.012 0 0 1 9 10V6a1 1 0 0 1 2 0v3.586l2.982 2.982a1 1 0 0 1 0 1.414Z"/>
+                                                    </svg>
+                                                    { format_release }
+                                                </span>
+                                                if let Some(episode_id) = episode.episode_id {
+                                                    <PlayedBadge {episode_id} completed={episode.completed} />
+                                                }
+                                            </div>
                                             {
                                                 // if formatted_listen_duration.is_some() {
                                                 //     html! {
@@ -664,6 +714,7 @@ pub fn episode_layout() -> Html {
                     }
                 }
             }
+            { load_more_indicator(&more_episodes) }
         <App_drawer />
         // Conditional rendering for the error banner
         {
@@ -696,7 +747,7 @@ pub fn episode_layout() -> Html {
         // }
         {
             if let Some(audio_props) = &state.currently_playing {
-                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
+                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} podcast_name={audio_props.podcast_name.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
             } else {
                 html! {}
             }
@@ -706,3 +757,120 @@ pub fn episode_layout() -> Html {
     }
 }
 
+
+#[derive(Properties, PartialEq)]
+pub struct PodcastLinkProps {
+    pub podcast_id: i32,
+}
+
+/// The podcast page reached by its id, so links to it work from anywhere. Loads the
+/// podcast into the state `EpisodeLayout` reads, then shows it as usual.
+#[function_component(PodcastLink)]
+pub fn podcast_link(props: &PodcastLinkProps) -> Html {
+    let (state, dispatch) = use_store::<AppState>();
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+    let podcast_id = props.podcast_id;
+    let loaded = state.clicked_podcast_info.is_some()
+        && state.podcast_feed_results.as_ref().and_then(|feed| feed.podcast_id) == Some(podcast_id);
+    let error = use_state(|| None::<String>);
+
+    {
+        let session_dispatch = dispatch.clone();
+        let reload_occured = state.reload_occured.unwrap_or(false);
+        use_effect_with((), move |_| {
+            if !reload_occured {
+                let window = web_sys::window().expect("no global `window` exists");
+                if window.performance().map_or(false, |performance| performance.navigation().type_() == 1) {
+                    let session_storage = window.session_storage().unwrap().unwrap();
+                    session_storage.set_item("isAuthenticated", "false").unwrap();
+                }
+                let current_route = window.location().href().unwrap_or_default();
+                use_check_authentication(session_dispatch.clone(), &current_route);
+                session_dispatch.reduce_mut(|state| state.reload_occured = Some(true));
+            }
+            || ()
+        });
+    }
+
+    let effect_error = error.clone();
+    use_effect_with((client, user_id, podcast_id), move |(client, user_id, podcast_id)| {
+        let error = effect_error;
+        let already_loaded = {
+            let state = dispatch.get();
+            state.clicked_podcast_info.is_some()
+                && state.podcast_feed_results.as_ref().and_then(|feed| feed.podcast_id) == Some(*podcast_id)
+        };
+        if let (Some(user_id), false) = (*user_id, already_loaded) {
+            let client = client.clone();
+            let podcast_id = *podcast_id;
+            // Another podcast's episodes shouldn't show while this one loads
+            dispatch.reduce_mut(|state| {
+                state.clicked_podcast_info = None;
+                state.podcast_feed_results = None;
+            });
+            wasm_bindgen_futures::spawn_local(async move {
+                let details = client.get_podcast_details(user_id, &podcast_id).await;
+                let episodes = client.get_podcast_episodes(&user_id, &podcast_id, Page::first()).await;
+                match (details, episodes) {
+                    (Ok(details), Ok(episodes)) => {
+                        let categories = details
+                            .categories
+                            .split(',')
+                            .enumerate()
+                            .map(|(index, category)| (index.to_string(), category.trim().to_string()))
+                            .collect();
+                        let podcast_values = ClickedFeedURL {
+                            podcast_title: details.podcast_name,
+                            podcast_url: details.feed_url,
+                            podcast_description: details.description,
+                            podcast_author: details.author,
+                            podcast_artwork: details.artwork_url,
+                            podcast_explicit: details.explicit,
+                            podcast_episode_count: details.episode_count,
+                            podcast_categories: Some(categories),
+                            podcast_link: details.website_url,
+                        };
+                        dispatch.reduce_mut(move |state| {
+                            state.podcast_added = Some(true);
+                            state.podcast_feed_results = Some(episodes);
+                            state.clicked_podcast_info = Some(podcast_values);
+                        });
+                    }
+                    (Err(e), _) | (_, Err(e)) => {
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        }
+        || ()
+    });
+
+    if loaded && user_id.is_some() {
+        return html! { <EpisodeLayout /> };
+    }
+    html! {
+        <>
+        <div class="main-container">
+            <Search_nav />
+            {
+                if let Some(error) = (*error).clone() {
+                    empty_message("Unable to display podcast", &error)
+                } else {
+                    html! {
+                        <div class="loading-animation">
+                            <div class="frame1"></div>
+                            <div class="frame2"></div>
+                            <div class="frame3"></div>
+                            <div class="frame4"></div>
+                            <div class="frame5"></div>
+                            <div class="frame6"></div>
+                        </div>
+                    }
+                }
+            }
+        </div>
+        <App_drawer />
+        </>
+    }
+}
@@ -1,16 +1,19 @@
-use std::rc::Rc;
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::JsValue;
 use yew::prelude::*;
 use yew_router::history::{BrowserHistory, History};
+use yew_router::Routable;
+use crate::components::routes::Route;
 use crate::requests::search_pods::{call_get_podcast_info, test_connection};
 use web_sys::{console, window, HtmlInputElement, MouseEvent};
 use yewdux::prelude::*;
-use crate::components::context::{AppState, UIState};
+use crate::components::completion::{completion_account, completion_supported, mark_older_completed, set_completed, PlayedBadge};
+use crate::components::context::{AppState, CompletionState, UIState};
 use crate::components::episodes_layout::SafeHtml;
 use yew::Callback;
-use crate::requests::pod_req::{call_download_episode, call_queue_episode, call_save_episode, DownloadEpisodeRequest, Episode, EpisodeDownload, HistoryEpisode, QueuePodcastRequest, QueuedEpisode, SavePodcastRequest, SavedEpisode, call_remove_downloaded_episode, call_remove_queued_episode, call_remove_saved_episode};
+use crate::requests::outbox::{defer_if_offline, Mutation};
+use crate::requests::pod_req::{DownloadEpisodeRequest, Episode, EpisodeDownload, HistoryEpisode, MorePages, QueuePodcastRequest, QueuedEpisode, SavePodcastRequest, SavedEpisode};
 use crate::requests::search_pods::SearchEpisode;
 use crate::requests::search_pods::Episode as SearchNewEpisode;
 use std::any::Any;
@@ -40,6 +43,56 @@ pub fn use_scroll_to_top() -> Html {
     html! {}
 }
 
+/// Calls `on_near_bottom` while the page is scrolled to within a screen of its end,
+/// so list views can fetch their next page before the user runs out of episodes.
+#[hook]
+pub fn use_infinite_scroll(on_near_bottom: Callback<()>) {
+    // The listener outlives renders, so it reads the newest callback through this ref
+    let latest = use_mut_ref(|| on_near_bottom.clone());
+    *latest.borrow_mut() = on_near_bottom;
+
+    use_effect_with((), move |_| {
+        let window = window().unwrap();
+        let listener = gloo_events::EventListener::new(&window, "scroll", move |_| {
+            let Some(window) = web_sys::window() else { return };
+            let viewport = window.inner_height().ok().and_then(|h| h.as_f64()).unwrap_or(0.0);
+            let scrolled = window.scroll_y().unwrap_or(0.0);
+            let total = window
+                .document()
+                .and_then(|document| document.document_element())
+                .map(|element| element.scroll_height() as f64)
+                .unwrap_or(0.0);
+            if scrolled + viewport * 2.0 >= total {
+                latest.borrow().emit(());
+            }
+        });
+        move || drop(listener)
+    });
+}
+
+/// Shown under a paged list while its next page loads, or with a retry button when
+/// that page failed.
+pub fn load_more_indicator(more: &MorePages) -> Html {
+    if more.loading {
+        html! {
+            <div class="flex justify-center py-4">
+                <p class="page-paragraphs">{ "Loading more episodes..." }</p>
+            </div>
+        }
+    } else if let Some(error) = more.error.as_ref() {
+        html! {
+            <div class="flex flex-col items-center py-4">
+                <p class="page-paragraphs">{ format!("Couldn't load more episodes: {}", error) }</p>
+                <button onclick={more.retry.clone()} class="theme-submit-button mt-2 font-bold py-2 px-4 rounded focus:outline-none focus:shadow-outline" type="button">
+                    {"Try again"}
+                </button>
+            </div>
+        }
+    } else {
+        html! {}
+    }
+}
+
 
 #[function_component(ErrorMessage)]
 pub fn error_message(props: &ErrorMessageProps) -> Html {
@@ -85,11 +138,9 @@ pub fn error_message(props: &ErrorMessageProps) -> Html {
 #[function_component(Search_nav)]
 pub fn search_bar() -> Html {
     let history = BrowserHistory::new();
-    let dispatch = Dispatch::<AppState>::global();
-    let state: Rc<AppState> = dispatch.get();
+    let (state, dispatch) = use_store::<AppState>();
     let podcast_value = use_state(|| "".to_string());
     let search_index = use_state(|| "podcast_index".to_string()); // Default to "podcast_index"
-    let (_app_state, dispatch) = use_store::<AppState>();
 
     let history_clone = history.clone();
     let podcast_value_clone = podcast_value.clone();
@@ -334,9 +385,9 @@ pub fn context_button(props: &ContextButtonProps) -> Html {
     let dropdown_open = use_state(|| false);
     let (post_state, post_dispatch) = use_store::<AppState>();
     let (_audio_state, audio_dispatch) = use_store::<UIState>();
-    let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let (completion, completion_dispatch) = use_store::<CompletionState>();
+    let client = post_state.client();
     let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
     let dropdown_ref = NodeRef::default();
     
     let toggle_dropdown = {
@@ -388,31 +439,32 @@ pub fn context_button(props: &ContextButtonProps) -> Html {
     
     
 
-    let queue_api_key = api_key.clone();
-    let queue_server_name = server_name.clone();
+    let queue_client = client.clone();
     let queue_post = audio_dispatch.clone();
     // let server_name = server_name.clone();
     let on_add_to_queue = {
         let episode = props.episode.clone();
         Callback::from(move |_| {
-            let server_name_copy = queue_server_name.clone();
-            let api_key_copy = queue_api_key.clone();
+            let client_copy = queue_client.clone();
             let queue_post = queue_post.clone();
             let request = QueuePodcastRequest {
                 episode_id: episode.get_episode_id(),
                 user_id: user_id.unwrap(), // replace with the actual user ID
             };
-            let server_name = server_name_copy; // replace with the actual server name
-            let api_key = api_key_copy; // replace with the actual API key
+            let client = client_copy;
             let future = async move {
-                // let _ = call_queue_episode(&server_name.unwrap(), &api_key.flatten(), &request).await;
+                // let _ = client.queue_episode(&request).await;
                 // queue_post.reduce_mut(|state| state.info_message = Option::from(format!("Episode added to Queue!")));
-                match call_queue_episode(&server_name.unwrap(), &api_key.flatten(), &request).await {
+                match client.queue_episode(&request).await {
                     Ok(success_message) => {
                         queue_post.reduce_mut(|state| state.info_message = Option::from(format!("{}", success_message)));
                     },
                     Err(e) => {
-                        queue_post.reduce_mut(|state| state.error_message = Option::from(format!("{}", e)));
+                        if defer_if_offline(&client, &e, Mutation::QueueEpisode(request.clone())) {
+                            queue_post.reduce_mut(|state| state.info_message = Option::from("You're offline. The episode will be queued once the connection is back.".to_string()));
+                        } else {
+                            queue_post.reduce_mut(|state| state.error_message = Option::from(format!("{}", e)));
+                        }
                         // Handle error, e.g., display the error message
                     }
                 }
@@ -422,8 +474,7 @@ pub fn context_button(props: &ContextButtonProps) -> Html {
         })
     };
 
-    let remove_queue_api_key = api_key.clone();
-    let remove_queue_server_name = server_name.clone();
+    let remove_queue_client = client.clone();
     let remove_queue_post = audio_dispatch.clone();
     let dispatch_clone = post_dispatch.clone();
     // let server_name = server_name.clone();
@@ -432,19 +483,17 @@ pub fn context_button(props: &ContextButtonProps) -> Html {
         let episode_id = props.episode.get_episode_id();
         Callback::from(move |_| {
             let post_dispatch = dispatch_clone.clone();
-            let server_name_copy = remove_queue_server_name.clone();
-            let api_key_copy = remove_queue_api_key.clone();
+            let client_copy = remove_queue_client.clone();
             let queue_post = remove_queue_post.clone();
             let request = QueuePodcastRequest {
                 episode_id: episode.get_episode_id(),
                 user_id: user_id.unwrap(), // replace with the actual user ID
             };
-            let server_name = server_name_copy; // replace with the actual server name
-            let api_key = api_key_copy; // replace with the actual API key
+            let client = client_copy;
             let future = async move {
-                // let _ = call_queue_episode(&server_name.unwrap(), &api_key.flatten(), &request).await;
+                // let _ = client.queue_episode(&request).await;
                 // queue_post.reduce_mut(|state| state.info_message = Option::from(format!("Episode added to Queue!")));
-                match call_remove_queued_episode(&server_name.unwrap(), &api_key.flatten(), &request).await {
+                match client.remove_queued_episode(&request).await {
                     Ok(success_message) => {
                         // queue_post.reduce_mut(|state| state.info_message = Option::from(format!("{}", success_message)));
                         post_dispatch.reduce_mut(|state| {
@@ -467,30 +516,31 @@ pub fn context_button(props: &ContextButtonProps) -> Html {
         })
     };
 
-    let saved_api_key = api_key.clone();
-    let saved_server_name = server_name.clone();
+    let saved_client = client.clone();
     let save_post = audio_dispatch.clone();
     let on_save_episode = {
         let episode = props.episode.clone();
         Callback::from(move |_| {
-            let server_name_copy = saved_server_name.clone();
-            let api_key_copy = saved_api_key.clone();
+            let client_copy = saved_client.clone();
             let post_state = save_post.clone();
             let request = SavePodcastRequest {
                 episode_id: episode.get_episode_id(), // changed from episode_title
                 user_id: user_id.unwrap(), // replace with the actual user ID
             };
-            let server_name = server_name_copy; // replace with the actual server name
-            let api_key = api_key_copy; // replace with the actual API key
+            let client = client_copy;
             let future = async move {
-                // let return_mes = call_save_episode(&server_name.unwrap(), &api_key.flatten(), &request).await;
+                // let return_mes = client.save_episode(&request).await;
                 // post_state.reduce_mut(|state| state.info_message = Option::from(format!("Episode saved successfully")));
-                match call_save_episode(&server_name.unwrap(), &api_key.flatten(), &request).await {
+                match client.save_episode(&request).await {
                     Ok(success_message) => {
                         post_state.reduce_mut(|state| state.info_message = Option::from(format!("{}", success_message)));
                     },
                     Err(e) => {
-                        post_state.reduce_mut(|state| state.error_message = Option::from(format!("{}", e)));
+                        if defer_if_offline(&client, &e, Mutation::SaveEpisode(request.clone())) {
+                            post_state.reduce_mut(|state| state.info_message = Option::from("You're offline. The episode will be saved once the connection is back.".to_string()));
+                        } else {
+                            post_state.reduce_mut(|state| state.error_message = Option::from(format!("{}", e)));
+                        }
                         // Handle error, e.g., display the error message
                     }
                 }
@@ -500,8 +550,7 @@ pub fn context_button(props: &ContextButtonProps) -> Html {
         })
     };
 
-    let remove_saved_api_key = api_key.clone();
-    let remove_saved_server_name = server_name.clone();
+    let remove_saved_client = client.clone();
     let remove_save_post = audio_dispatch.clone();
     let dispatch_clone = post_dispatch.clone();
     let on_remove_saved_episode = {
@@ -509,17 +558,15 @@ pub fn context_button(props: &ContextButtonProps) -> Html {
         let episode_id = props.episode.get_episode_id();
         Callback::from(move |_| {
             let post_dispatch = dispatch_clone.clone();
-            let server_name_copy = remove_saved_server_name.clone();
-            let api_key_copy = remove_saved_api_key.clone();
+            let client_copy = remove_saved_client.clone();
             let post_state = remove_save_post.clone();
             let request = SavePodcastRequest {
                 episode_id: episode.get_episode_id(),
                 user_id: user_id.unwrap(),
             };
-            let server_name = server_name_copy; // replace with the actual server name
-            let api_key = api_key_copy; // replace with the actual API key
+            let client = client_copy;
             let future = async move {
-                match call_remove_saved_episode(&server_name.unwrap(), &api_key.flatten(), &request).await {
+                match client.remove_saved_episode(&request).await {
                     Ok(success_message) => {
                         // queue_post.reduce_mut(|state| state.info_message = Option::from(format!("{}", success_message)));
                         post_dispatch.reduce_mut(|state| {
@@ -542,25 +589,22 @@ pub fn context_button(props: &ContextButtonProps) -> Html {
         })
     };
 
-    let download_api_key = api_key.clone();
-    let download_server_name = server_name.clone();
+    let download_client = client.clone();
     let download_post = audio_dispatch.clone();
     let on_download_episode = {
         let episode = props.episode.clone();
         Callback::from(move |_| {
             let post_state = download_post.clone();
-            let server_name_copy = download_server_name.clone();
-            let api_key_copy = download_api_key.clone();
+            let client_copy = download_client.clone();
             let request = DownloadEpisodeRequest {
                 episode_id: episode.get_episode_id(),
                 user_id: user_id.unwrap(), // replace with the actual user ID
             };
-            let server_name = server_name_copy; // replace with the actual server name
-            let api_key = api_key_copy; // replace with the actual API key
+            let client = client_copy;
             let future = async move {
-                // let _ = call_download_episode(&server_name.unwrap(), &api_key.flatten(), &request).await;
+                // let _ = client.download_episode(&request).await;
                 // post_state.reduce_mut(|state| state.info_message = Option::from(format!("Episode now downloading!")));
-                match call_download_episode(&server_name.unwrap(), &api_key.flatten(), &request).await {
+                match client.download_episode(&request).await {
                     Ok(success_message) => {
                         post_state.reduce_mut(|state| state.info_message = Option::from(format!("{}", success_message)));
                     },
@@ -575,8 +619,7 @@ pub fn context_button(props: &ContextButtonProps) -> Html {
         })
     };
 
-    let remove_download_api_key = api_key.clone();
-    let remove_download_server_name = server_name.clone();
+    let remove_download_client = client.clone();
     let remove_download_post = audio_dispatch.clone();
     let dispatch_clone = post_dispatch.clone();
     let on_remove_downloaded_episode = {
@@ -585,18 +628,16 @@ pub fn context_button(props: &ContextButtonProps) -> Html {
         Callback::from(move |_| {
             let post_dispatch = dispatch_clone.clone();
             let post_state = remove_download_post.clone();
-            let server_name_copy = remove_download_server_name.clone();
-            let api_key_copy = remove_download_api_key.clone();
+            let client_copy = remove_download_client.clone();
             let request = DownloadEpisodeRequest {
                 episode_id: episode.get_episode_id(),
                 user_id: user_id.unwrap(), // replace with the actual user ID
             };
-            let server_name = server_name_copy; // replace with the actual server name
-            let api_key = api_key_copy; // replace with the actual API key
+            let client = client_copy;
             let future = async move {
-                // let _ = call_download_episode(&server_name.unwrap(), &api_key.flatten(), &request).await;
+                // let _ = client.download_episode(&request).await;
                 // post_state.reduce_mut(|state| state.info_message = Option::from(format!("Episode now downloading!")));
-                match call_remove_downloaded_episode(&server_name.unwrap(), &api_key.flatten(), &request).await {
+                match client.remove_downloaded_episode(&request).await {
                     Ok(success_message) => {
                         // queue_post.reduce_mut(|state| state.info_message = Option::from(format!("{}", success_message)));
                         post_dispatch.reduce_mut(|state| {
@@ -619,6 +660,34 @@ pub fn context_button(props: &ContextButtonProps) -> Html {
         })
     };
 
+    let completed = completion.is_completed(&completion_account(&post_state), props.episode.get_episode_id(), props.episode.is_completed());
+    let on_toggle_completed = {
+        let post_state = post_state.clone();
+        let audio_dispatch = audio_dispatch.clone();
+        let completion_dispatch = completion_dispatch.clone();
+        let episode_id = props.episode.get_episode_id();
+        Callback::from(move |_| set_completed(&post_state, episode_id, !completed, &audio_dispatch, &completion_dispatch))
+    };
+    let on_mark_older_completed = {
+        let post_state = post_state.clone();
+        let audio_dispatch = audio_dispatch.clone();
+        let episode_id = props.episode.get_episode_id();
+        Callback::from(move |_| mark_older_completed(&post_state, episode_id, &audio_dispatch, &completion_dispatch))
+    };
+    let completion_buttons = if completion_supported(&post_state) {
+        html! {
+            <>
+                <li class="dropdown-option" onclick={on_toggle_completed}>{ if completed { "Mark as Unplayed" } else { "Mark as Played" } }</li>
+                // Only the podcast's own page lists its episodes in release order
+                if props.page_type == "episode_layout" {
+                    <li class="dropdown-option" onclick={on_mark_older_completed}>{ "Mark Older as Played" }</li>
+                }
+            </>
+        }
+    } else {
+        html! {}
+    };
+
     let action_buttons = match props.page_type.as_str() {
         "saved" => html! {
             <>
@@ -669,6 +738,7 @@ pub fn context_button(props: &ContextButtonProps) -> Html {
                         <div ref={dropdown_ref.clone()} class="dropdown-content-class border border-solid absolute z-10 divide-y rounded-lg shadow w-48">
                             <ul class="dropdown-container py-2 text-sm text-gray-700">
                                 { action_buttons }
+                                { completion_buttons }
                             </ul>
                         </div>
                     }
@@ -697,6 +767,8 @@ pub trait EpisodeTrait {
     fn get_episode_artwork(&self) -> String;
     fn get_episode_title(&self) -> String;
     fn get_episode_id(&self) -> i32;
+    /// Whether the episode was played when its list loaded.
+    fn is_completed(&self) -> bool;
     fn clone_box(&self) -> Box<dyn EpisodeTrait>;
     // fn eq(&self, other: &dyn EpisodeTrait) -> bool;
     fn as_any(&self) -> &dyn Any;
@@ -743,6 +815,10 @@ impl EpisodeTrait for Episode {
         self.EpisodeID.clone()
     }
 
+    fn is_completed(&self) -> bool {
+        self.Completed
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -766,6 +842,10 @@ impl EpisodeTrait for QueuedEpisode {
         self.EpisodeID.clone()
     }
 
+    fn is_completed(&self) -> bool {
+        self.Completed
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -789,6 +869,10 @@ impl EpisodeTrait for SavedEpisode {
         self.EpisodeID.clone()
     }
 
+    fn is_completed(&self) -> bool {
+        self.Completed
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -811,6 +895,10 @@ impl EpisodeTrait for HistoryEpisode {
         self.EpisodeID.clone()
     }
 
+    fn is_completed(&self) -> bool {
+        self.Completed
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -829,6 +917,10 @@ impl EpisodeTrait for EpisodeDownload {
         self.EpisodeID.clone()
     }
 
+    fn is_completed(&self) -> bool {
+        self.Completed
+    }
+
     fn clone_box(&self) -> Box<dyn EpisodeTrait> {
         Box::new(self.clone())
     }
@@ -851,6 +943,10 @@ impl EpisodeTrait for SearchEpisode {
         self.EpisodeID.clone()
     }
 
+    fn is_completed(&self) -> bool {
+        self.Completed
+    }
+
     fn clone_box(&self) -> Box<dyn EpisodeTrait> {
         Box::new(self.clone())
     }
@@ -873,6 +969,10 @@ impl EpisodeTrait for SearchNewEpisode {
         self.episode_id.clone().unwrap()
     }
 
+    fn is_completed(&self) -> bool {
+        self.completed
+    }
+
     fn clone_box(&self) -> Box<dyn EpisodeTrait> {
         Box::new(self.clone())
     }
@@ -896,7 +996,7 @@ pub fn on_shownotes_click(
             dispatch_clone.reduce_mut(move |state| {
                 state.selected_episode_id = Some(episode_id);
             });
-            history_clone.push("/episode"); // Use the route path
+            history_clone.push(&Route::EpisodeLink { id: episode_id }.to_path());
         });
     })
 }
@@ -979,12 +1079,15 @@ pub fn episode_item(
                             </div>
                         }
                     }
-                    <span class="episode-time-badge inline-flex items-center px-2.5 py-0.5 rounded me-2" style="flex-grow: 0; flex-shrink: 0; width: auto;">
-                        <svg class="time-icon w-2.5 h-2.5 me-1.5" aria-hidden="true" xmlns="http://www.w3.org/2000/svg" fill="currentColor" viewBox="0 0 20 20">
-                            <path d="M10 0a10 10 0 1 0 10 10A10.011 10.011 0 0 0 10 0Zm3.982 13.982a1 1 0 0 1-1.414 0l-3.274-3.274A1.012 1.012 0 0 1 9 10V6a1 1 0 0 1 2 0v3.586l2.982 2.982a1 1 0 0 1 0 1.414Z"/>
-                        </svg>
-                        { format_release }
-                    </span>
+                    <div class="flex items-center">
+                        <span class="episode-time-badge inline-flex items-center px-2.5 py-0.5 rounded me-2" style="flex-grow: 0; flex-shrink: 0; width: auto;">
+                            <svg class="time-icon w-2.5 h-2.5 me-1.5" aria-hidden="true" xmlns="http://www.w3.org/2000/svg" fill="currentColor" viewBox="0 0 20 20">
+                                <path d="M10 0a10 10 0 1 0 10 10A10.011 10.011 0 0 0 10 0Zm3.982 13.982a1 1 0 0 1-1.414 0l-3.274-3.274A1.012 1.012 0 0 1 9 10V6a1 1 0 0 1 2 0v3.586l2.982 2.982a1 1 0 0 1 0 1.414Z"/>
+                            </svg>
+                            { format_release }
+                        </span>
+                        <PlayedBadge episode_id={episode.get_episode_id()} completed={episode.is_completed()} />
+                    </div>
                     {
                         if formatted_listen_duration.is_some() {
                             html! {
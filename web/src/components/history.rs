@@ -1,9 +1,11 @@
 use yew::{function_component, Html, html};
 use yew::prelude::*;
 use super::app_drawer::App_drawer;
-use super::gen_components::{UseScrollToTop, Search_nav, empty_message, episode_item, on_shownotes_click};
-use crate::requests::pod_req::{self, HistoryDataResponse};
+use super::gen_components::{UseScrollToTop, Search_nav, empty_message, episode_item, on_shownotes_click, load_more_indicator};
+use crate::requests::cache::CacheKey;
+use crate::requests::pod_req::{use_paged_list, HistoryDataResponse, HistoryEpisode, Page};
 use yewdux::prelude::*;
+use crate::requests::http::use_abort_handle;
 use crate::components::context::{AppState, UIState};
 use yew_router::history::BrowserHistory;
 use crate::components::audio::AudioPlayer;
@@ -65,36 +67,57 @@ pub fn history() -> Html {
     };
 
 
-    // Fetch episodes on component mount
+    // Fetch episodes on component mount. Leaving the view cancels the fetch, so a
+    // late response can't overwrite the list of the view the user moved on to.
+    let abort = use_abort_handle();
     let loading_ep = loading.clone();
     {
         // let episodes = episodes.clone();
         let error = error.clone();
-        let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+        let client = post_state.client().with_abort(abort.clone());
         let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-        let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
 
         let effect_dispatch = dispatch.clone();
 
-        // fetch_episodes(api_key.flatten(), user_id, server_name, dispatch, error, pod_req::call_get_recent_eps);
 
         use_effect_with(
-            (api_key.clone(), user_id.clone(), server_name.clone()),
+            (client.clone(), user_id.clone()),
             move |_| {
                 let error_clone = error.clone();
-                if let (Some(api_key), Some(user_id), Some(server_name)) = (api_key.clone(), user_id.clone(), server_name.clone()) {
+                if let Some(user_id) = user_id.clone() {
+                    let client = client.clone();
                     let dispatch = effect_dispatch.clone();
     
+                    // Render the last fetched list right away, then refresh it in the background
+                    let cached = client.cached::<Vec<HistoryEpisode>>(CacheKey::History(user_id));
+                    let has_cached = cached.is_some();
+                    if let Some(cached_list) = cached {
+                        dispatch.reduce_mut(move |state| {
+                            let next_page = Page::first().after(cached_list.len());
+                            state.episode_history = Some(HistoryDataResponse { data: cached_list, next_page });
+                        });
+                        loading_ep.set(false);
+                    }
                     wasm_bindgen_futures::spawn_local(async move {
-                        match pod_req::call_get_user_history(&server_name, &api_key, &user_id).await {
+                        match client.get_user_history(&user_id, Page::first()).await {
                             Ok(fetched_episodes) => {
                                 dispatch.reduce_mut(move |state| {
-                                    state.episode_history = Some(HistoryDataResponse { data: fetched_episodes });
+                                    // Once later pages are in, or on their way, a refreshed first page would drop them
+                                    if let Some(list) = state.episode_history.as_ref() {
+                                        if !Page::first_only(list.data.len(), list.next_page) {
+                                            return;
+                                        }
+                                    }
+                                    let next_page = Page::first().after(fetched_episodes.len());
+                                    state.episode_history = Some(HistoryDataResponse { data: fetched_episodes, next_page });
                                 });
                                 loading_ep.set(false);
                             },
                             Err(e) => {
-                                error_clone.set(Some(e.to_string()));
+                                // Keep showing the cached list when only the refresh failed
+                                if !has_cached {
+                                    error_clone.set(Some(e.to_string()));
+                                }
                                 loading_ep.set(false);
                             },
                         }
@@ -105,6 +128,19 @@ pub fn history() -> Html {
         );
     }
 
+    let more_episodes = {
+        let client = post_state.client().with_abort(abort.clone());
+        let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID);
+        use_paged_list(
+            state.episode_history.as_ref().and_then(|list| list.next_page),
+            |state: &mut AppState| state.episode_history.as_mut(),
+            move |page| {
+                let client = client.clone();
+                user_id.map(|user_id| async move { client.get_user_history(&user_id, page).await })
+            },
+        )
+    };
+
     html! {
         <>
         <div class="main-container">
@@ -145,9 +181,8 @@ pub fn history() -> Html {
                                 } else {
                                     
                                     history_eps.data.into_iter().map(|episode| {
-                                        let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+                                        let client = post_state.client();
                                         let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-                                        let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
                                         let history_clone = history.clone();
                                         let id_string = &episode.EpisodeID.to_string();
                 
@@ -195,23 +230,23 @@ pub fn history() -> Html {
                                         let listener_duration_for_closure = episode_listened_clone.clone();
 
                                         let user_id_play = user_id.clone();
-                                        let server_name_play = server_name.clone();
-                                        let api_key_play = api_key.clone();
+                                        let client_play = client.clone();
                                         let audio_dispatch = audio_dispatch.clone();
 
                                         let on_play_click = on_play_click(
                                             episode_url_for_closure.clone(),
                                             episode_title_for_closure.clone(),
+                                            episode.PodcastName.clone(),
                                             episode_artwork_for_closure.clone(),
                                             episode_duration_for_closure.clone(),
                                             episode_id_for_closure.clone(),
                                             listener_duration_for_closure.clone(),
-                                            api_key_play.unwrap().unwrap(),
+                                            client_play.clone(),
                                             user_id_play.unwrap(),
-                                            server_name_play.unwrap(),
                                             audio_dispatch.clone(),
                                             audio_state.clone(),
                                             None,
+                                            None,
                                         );
 
                                         let on_shownotes_click = on_shownotes_click(
@@ -252,10 +287,10 @@ pub fn history() -> Html {
                             }
                         }
                     }
-                
+            { load_more_indicator(&more_episodes) }
             {
                 if let Some(audio_props) = &audio_state.currently_playing {
-                    html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
+                    html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} podcast_name={audio_props.podcast_name.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
                 } else {
                     html! {}
                 }
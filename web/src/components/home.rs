@@ -1,14 +1,15 @@
 use yew::{function_component, Html, html};
 use yew::prelude::*;
 use super::app_drawer::App_drawer;
-use super::gen_components::{Search_nav, empty_message, episode_item, on_shownotes_click, UseScrollToTop};
-use crate::requests::pod_req;
+use super::gen_components::{Search_nav, empty_message, episode_item, on_shownotes_click, UseScrollToTop, load_more_indicator};
 use yewdux::prelude::*;
+use crate::requests::http::use_abort_handle;
 use yew_router::history::BrowserHistory;
 use crate::components::context::{AppState, UIState, ExpandedDescriptions};
 use crate::components::audio::AudioPlayer;
 use crate::components::gen_funcs::{sanitize_html_with_blank_target, truncate_description, format_datetime, parse_date, DateFormat};
-use crate::requests::pod_req::RecentEps;
+use crate::requests::cache::CacheKey;
+use crate::requests::pod_req::{use_paged_list, Page, RecentEps};
 use crate::requests::pod_req::Episode as EpisodeData; 
 use crate::components::audio::on_play_click;
 use crate::components::desc_impl::AppStateMsg;
@@ -90,36 +91,56 @@ pub fn home() -> Html {
     }
 
 
-    // Fetch episodes on component mount
+    // Fetch episodes on component mount. Leaving the view cancels the fetch, so a
+    // late response can't overwrite the list of the view the user moved on to.
+    let abort = use_abort_handle();
     let loading_ep = loading.clone();
     {
         // let episodes = episodes.clone();
         let error = error.clone();
-        let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+        let client = post_state.client().with_abort(abort.clone());
         let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-        let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
 
         let effect_dispatch = dispatch.clone();
 
-        // fetch_episodes(api_key.flatten(), user_id, server_name, dispatch, error, pod_req::call_get_recent_eps);
-
         use_effect_with(
-            (api_key.clone(), user_id.clone(), server_name.clone()),
+            (client.clone(), user_id.clone()),
             move |_| {
                 let error_clone = error.clone();
-                if let (Some(api_key), Some(user_id), Some(server_name)) = (api_key.clone(), user_id.clone(), server_name.clone()) {
+                if let Some(user_id) = user_id.clone() {
                     let dispatch = effect_dispatch.clone();
+                    let client = client.clone();
 
+                    // Render the last fetched list right away, then refresh it in the background
+                    let cached = client.cached::<Vec<EpisodeData>>(CacheKey::RecentEpisodes(user_id));
+                    let has_cached = cached.is_some();
+                    if let Some(cached_list) = cached {
+                        dispatch.reduce_mut(move |state| {
+                            let next_page = Page::first().after(cached_list.len());
+                            state.server_feed_results = Some(RecentEps { episodes: Some(cached_list), next_page });
+                        });
+                        loading_ep.set(false);
+                    }
                     wasm_bindgen_futures::spawn_local(async move {
-                        match pod_req::call_get_recent_eps(&server_name, &api_key, &user_id).await {
+                        match client.get_recent_eps(&user_id, Page::first()).await {
                             Ok(fetched_episodes) => {
                                 dispatch.reduce_mut(move |state| {
-                                    state.server_feed_results = Some(RecentEps { episodes: Some(fetched_episodes) });
+                                    // Once later pages are in, or on their way, a refreshed first page would drop them
+                                    if let Some(feed) = state.server_feed_results.as_ref() {
+                                        if !Page::first_only(feed.episodes.as_ref().map_or(0, Vec::len), feed.next_page) {
+                                            return;
+                                        }
+                                    }
+                                    let next_page = Page::first().after(fetched_episodes.len());
+                                    state.server_feed_results = Some(RecentEps { episodes: Some(fetched_episodes), next_page });
                                 });
                                 loading_ep.set(false);
                             },
                             Err(e) => {
-                                error_clone.set(Some(e.to_string()));
+                                // Keep showing the cached list when only the refresh failed
+                                if !has_cached {
+                                    error_clone.set(Some(e.to_string()));
+                                }
                                 loading_ep.set(false); // Set loading to false here
                             },
                         }
@@ -130,6 +151,19 @@ pub fn home() -> Html {
         );
     }
 
+    let more_episodes = {
+        let client = post_state.client().with_abort(abort.clone());
+        let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID);
+        use_paged_list(
+            state.server_feed_results.as_ref().and_then(|feed| feed.next_page),
+            |state: &mut AppState| state.server_feed_results.as_mut(),
+            move |page| {
+                let client = client.clone();
+                user_id.map(|user_id| async move { client.get_recent_eps(&user_id, page).await })
+            },
+        )
+    };
+
     html! {
         <>
         <div class="main-container">
@@ -159,13 +193,20 @@ pub fn home() -> Html {
                                     "You can add new podcasts by using the search bar above. Search for your favorite podcast and click the plus button to add it."
                                 )
                             } else {
-                                episodes.into_iter().map(|episode| {
-                                    html! {
-                                        <Episode
-                                            episode={episode.clone()}
-                                        />
+                                html! {
+                                    <>
+                                    {
+                                        episodes.into_iter().map(|episode| {
+                                            html! {
+                                                <Episode
+                                                    episode={episode.clone()}
+                                                />
+                                            }
+                                        }).collect::<Html>()
                                     }
-                                }).collect::<Html>()
+                                    { load_more_indicator(&more_episodes) }
+                                    </>
+                                }
                             }
                         } else {
                             empty_message(
@@ -183,7 +224,7 @@ pub fn home() -> Html {
             }
         {
             if let Some(audio_props) = &audio_state.currently_playing {
-                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
+                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} podcast_name={audio_props.podcast_name.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
             } else {
                 html! {}
             }
@@ -214,9 +255,8 @@ pub fn episode(props: &EpisodeProps) -> Html {
     // let (post_state, _post_dispatch) = use_store::<AppState>();
     let (audio_state, audio_dispatch) = use_store::<UIState>();
     let (desc_state, desc_dispatch) = use_store::<ExpandedDescriptions>();
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = state.client();
     let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
     let id_string = &props.episode.EpisodeID.to_string();
     let history = BrowserHistory::new();
     let history_clone = history.clone();
@@ -275,23 +315,23 @@ pub fn episode(props: &EpisodeProps) -> Html {
     let listener_duration_for_closure = episode_listened_clone.clone();
     let episode_id_for_closure = episode_id_clone.clone();
     let user_id_play = user_id.clone();
-    let server_name_play = server_name.clone();
-    let api_key_play = api_key.clone();
+    let client_play = client.clone();
     let audio_dispatch = audio_dispatch.clone();
 
     let on_play_click = on_play_click(
         episode_url_for_closure.clone(),
         episode_title_for_closure.clone(),
+        props.episode.PodcastName.clone(),
         episode_artwork_for_closure.clone(),
         episode_duration_for_closure.clone(),
         episode_id_for_closure.clone(),
         listener_duration_for_closure.clone(),
-        api_key_play.unwrap().unwrap(),
+        client_play.clone(),
         user_id_play.unwrap(),
-        server_name_play.unwrap(),
         audio_dispatch.clone(),
         audio_state.clone(),
         None,
+        None,
     );
 
     let on_shownotes_click = on_shownotes_click(
@@ -3,13 +3,14 @@ use web_sys::{console, window};
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use yew_router::history::{BrowserHistory, History};
-use crate::requests::login_requests::{self, call_check_mfa_enabled};
-use crate::requests::login_requests::{TimeZoneInfo, call_first_login_done, call_setup_timezone_info, call_verify_mfa, call_self_service_login_status, call_reset_password_create_code, ResetCodePayload, ResetForgotPasswordPayload, call_verify_and_reset_password, call_get_time_info, call_verify_key};
+use crate::requests::client::PinepodsClient;
+use crate::requests::login_requests;
+use crate::requests::login_requests::{TimeZoneInfo, ResetCodePayload, ResetForgotPasswordPayload};
 use crate::components::context::{AppState, UIState};
+use crate::components::server_profiles;
 use md5;
 use yewdux::prelude::*;
-use crate::requests::login_requests::{AddUserRequest, call_add_login_user};
-use crate::requests::setting_reqs::call_get_theme;
+use crate::requests::login_requests::AddUserRequest;
 use crate::components::gen_funcs::{encode_password, validate_user_input, ValidationError};
 use crate::components::episodes_layout::UIStateMsg;
 use chrono_tz::{TZ_VARIANTS, Tz};
@@ -66,7 +67,7 @@ pub fn login() -> Html {
                 let window = web_sys::window().expect("no global `window` exists");
                 let location = window.location();
                 let server_name = location.href().expect("should have a href").trim_end_matches('/').to_string();
-                match call_self_service_login_status(server_name).await {
+                match PinepodsClient::new(server_name, None).self_service_login_status().await {
                     Ok(status) => {
                         self_service_enabled.set(status);
                     }
@@ -146,7 +147,7 @@ pub fn login() -> Html {
                                                             let wasm_email = email.clone();
                                                             let wasm_user_id = user_id.clone();
                                                             wasm_bindgen_futures::spawn_local(async move {
-                                                                match call_verify_key(&server_name.clone(), &api_key.clone()).await {
+                                                                match PinepodsClient::new(server_name.clone(), Some(api_key.clone())).verify_key().await {
                                                                     Ok(_) => {
                                                                         // API key is valid, user can stay logged in
                                                                         let final_dispatch = effect_displatch.clone();
@@ -168,7 +169,7 @@ pub fn login() -> Html {
                                                                         let theme_api = api_key.clone();
                                                                         let theme_server = server_name.clone();
                                                                         wasm_bindgen_futures::spawn_local(async move {
-                                                                            match call_get_theme(theme_server, theme_api, &wasm_user_id).await{
+                                                                            match PinepodsClient::new(theme_server, Some(theme_api)).get_theme(&wasm_user_id).await{
                                                                                 Ok(theme) => {
                                                                                     crate::components::setting_components::theme_options::changeTheme(&theme);
                                                                                     if let Some(window) = web_sys::window() {
@@ -186,7 +187,7 @@ pub fn login() -> Html {
                                                                             }
                                                                         });
                                                                         wasm_bindgen_futures::spawn_local(async move {
-                                                                            match call_get_time_info(server_name, api_key, &wasm_user_id).await{
+                                                                            match PinepodsClient::new(server_name, Some(api_key)).get_time_info(&wasm_user_id).await{
                                                                                 Ok(tz_response) => {
                                                                                     effect_displatch.reduce_mut(move |state| {
                                                                                         state.user_tz = Some(tz_response.timezone);
@@ -286,7 +287,7 @@ pub fn login() -> Html {
                 let server_name = location.href().expect("should have a href");
                 let server_name = server_name.trim_end_matches('/').to_string();
                 let page_state = page_state.clone();
-                match login_requests::login_new_server(server_name.clone(), username.to_string(), password.to_string()).await {
+                match PinepodsClient::new(server_name.clone(), None).login_new_server(username.to_string(), password.to_string()).await {
                     Ok((user_details, login_request, server_details)) => {
                         // After user login, update the image URL with user's email from user_details
                         let gravatar_url = generate_gravatar_url(&user_details.Email, 80); // 80 is the image size
@@ -310,10 +311,10 @@ pub fn login() -> Html {
                         temp_api_key.set(api_key.clone().unwrap());
                         temp_user_id.set(user_id.clone());
 
-                        match call_first_login_done(server_name.clone(), api_key.clone().unwrap(), &user_id).await {
+                        match PinepodsClient::new(server_name.clone(), api_key.clone()).first_login_done(&user_id).await {
                             Ok(first_login_done) => {
                                 if first_login_done {
-                                    match call_check_mfa_enabled(server_name.clone(), api_key.clone().unwrap(), &user_id).await {
+                                    match PinepodsClient::new(server_name.clone(), api_key.clone()).check_mfa_enabled(&user_id).await {
                                         Ok(response) => {
                                             if response.mfa_enabled {
                                                 page_state.set(PageState::MFAPrompt);
@@ -321,7 +322,7 @@ pub fn login() -> Html {
                                                 let theme_api = api_key.clone();
                                                 let theme_server = server_name.clone();
                                                 wasm_bindgen_futures::spawn_local(async move {
-                                                    match call_get_theme(theme_server, theme_api.unwrap(), &user_id).await{
+                                                    match PinepodsClient::new(theme_server, theme_api).get_theme(&user_id).await{
                                                         Ok(theme) => {
                                                             crate::components::setting_components::theme_options::changeTheme(&theme);
                                                             // Update the local storage with the new theme
@@ -340,7 +341,7 @@ pub fn login() -> Html {
                                                     }
                                                 });
                                                 wasm_bindgen_futures::spawn_local(async move {
-                                                    match call_get_time_info(server_name, api_key.unwrap(), &user_id).await{
+                                                    match PinepodsClient::new(server_name, api_key).get_time_info(&user_id).await{
                                                         Ok(tz_response) => {
                                                             dispatch.reduce_mut(move |state| {
                                                                 state.user_tz = Some(tz_response.timezone);
@@ -517,11 +518,8 @@ pub fn login() -> Html {
                             email: email.clone(),
                             hash_pw: hash_pw.clone(),
                         };
-                        let add_user_request = Some(user_settings);
-            
-                        // let add_user_request = add_user_request.clone();
                         wasm_bindgen_futures::spawn_local(async move {
-                            match call_add_login_user(server_name, &add_user_request).await {
+                            match PinepodsClient::new(server_name, None).add_login_user(&user_settings).await {
                                 Ok(success) => {
                                     if success {
                                         page_state.set(PageState::Default);
@@ -650,7 +648,7 @@ pub fn login() -> Html {
             });
     
             wasm_bindgen_futures::spawn_local(async move {
-                match call_reset_password_create_code(server_name, &reset_code_request.unwrap()).await {
+                match PinepodsClient::new(server_name, None).reset_password_create_code(&reset_code_request.unwrap()).await {
                     Ok(success) => {
                         if success {
                             page_state.set(PageState::EnterCode);
@@ -742,7 +740,7 @@ pub fn login() -> Html {
                         new_password: hash_pw.clone(),
                     });
                     wasm_bindgen_futures::spawn_local(async move {
-                        match call_verify_and_reset_password(server_name, &reset_password_request.unwrap()).await {
+                        match PinepodsClient::new(server_name, None).verify_and_reset_password(&reset_password_request.unwrap()).await {
                             Ok(success) => {
                                 if success.message == "Password Reset Successfully" {
                                     page_state.set(PageState::Default);
@@ -870,11 +868,11 @@ pub fn login() -> Html {
             
             wasm_bindgen_futures::spawn_local(async move {
                 // Directly use timezone_info without checking it against time_zone_setup
-                match call_setup_timezone_info(server_name.clone(), api_key.clone(), timezone_info).await {
+                match PinepodsClient::new(server_name.clone(), Some(api_key.clone())).setup_timezone_info(&timezone_info).await {
                     Ok(success) => {
                         if success.success {
                             page_state.set(PageState::Default);
-                            match call_check_mfa_enabled(server_name.clone(), api_key.clone(), &user_id).await {
+                            match PinepodsClient::new(server_name.clone(), Some(api_key.clone())).check_mfa_enabled(&user_id).await {
                                 Ok(response) => {
                                     if response.mfa_enabled {
                                         page_state.set(PageState::MFAPrompt);
@@ -994,14 +992,14 @@ pub fn login() -> Html {
                 //     user_id: user_id,
                 //     mfa_code: mfa_code,
                 // };
-                match call_verify_mfa(&server_name.clone().unwrap(), &api_key.clone().unwrap().unwrap(), user_id.clone().unwrap(), (*mfa_code).clone()).await {
+                match PinepodsClient::new(server_name.clone().unwrap(), api_key.clone().unwrap()).verify_mfa(user_id.clone().unwrap(), (*mfa_code).clone()).await {
                     Ok(response) => {
                         if response.verified {
                             page_state.set(PageState::Default);
                             let theme_api = api_key.clone();
                             let theme_server = server_name.clone();
                             wasm_bindgen_futures::spawn_local(async move {
-                                match call_get_theme(theme_server.unwrap(), theme_api.unwrap().unwrap(), &user_id.unwrap()).await{
+                                match PinepodsClient::new(theme_server.unwrap(), theme_api.unwrap()).get_theme(&user_id.unwrap()).await{
                                     Ok(theme) => {
                                         crate::components::setting_components::theme_options::changeTheme(&theme);
                                     }
@@ -1011,7 +1009,7 @@ pub fn login() -> Html {
                                 }
                             });
                             wasm_bindgen_futures::spawn_local(async move {
-                                match call_get_time_info(server_name.unwrap(), api_key.unwrap().unwrap(), &user_id.unwrap()).await{
+                                match PinepodsClient::new(server_name.unwrap(), api_key.unwrap()).get_time_info(&user_id.unwrap()).await{
                                     Ok(tz_response) => {
                                         dispatch.reduce_mut(move |state| {
                                             state.user_tz = Some(tz_response.timezone);
@@ -1276,7 +1274,7 @@ pub fn login() -> Html {
                 // let server_name = location.href().expect("should have a href");
                 let server_name = server_name.clone();
                 let page_state = page_state.clone();
-                match login_requests::login_new_server(server_name.to_string(), username.to_string(), password.to_string()).await {
+                match PinepodsClient::new(server_name.to_string(), None).login_new_server(username.to_string(), password.to_string()).await {
                     Ok((user_details, login_request, server_details)) => {
                         // After user login, update the image URL with user's email from user_details
                         let gravatar_url = generate_gravatar_url(&user_details.Email, 80); // 80 is the image size
@@ -1300,10 +1298,10 @@ pub fn login() -> Html {
                         temp_api_key.set(api_key.clone().unwrap());
                         temp_user_id.set(user_id.clone());
 
-                        match call_first_login_done(server_name.clone(), api_key.clone().unwrap(), &user_id).await {
+                        match PinepodsClient::new(server_name.clone(), api_key.clone()).first_login_done(&user_id).await {
                             Ok(first_login_done) => {
                                 if first_login_done {
-                                    match call_check_mfa_enabled(server_name.clone(), api_key.clone().unwrap(), &user_id).await {
+                                    match PinepodsClient::new(server_name.clone(), api_key.clone()).check_mfa_enabled(&user_id).await {
                                         Ok(response) => {
                                             if response.mfa_enabled {
                                                 page_state.set(PageState::MFAPrompt);
@@ -1311,7 +1309,7 @@ pub fn login() -> Html {
                                                 let theme_api = api_key.clone();
                                                 let theme_server = server_name.clone();
                                                 wasm_bindgen_futures::spawn_local(async move {
-                                                    match call_get_theme(theme_server, theme_api.unwrap(), &user_id).await{
+                                                    match PinepodsClient::new(theme_server, theme_api).get_theme(&user_id).await{
                                                         Ok(theme) => {
                                                             crate::components::setting_components::theme_options::changeTheme(&theme);
                                                             if let Some(window) = web_sys::window() {
@@ -1329,7 +1327,7 @@ pub fn login() -> Html {
                                                     }
                                                 });
                                                 wasm_bindgen_futures::spawn_local(async move {
-                                                    match call_get_time_info(server_name, api_key.unwrap(), &user_id).await{
+                                                    match PinepodsClient::new(server_name, api_key).get_time_info(&user_id).await{
                                                         Ok(tz_response) => {
                                                             dispatch.reduce_mut(move |state| {
                                                                 state.user_tz = Some(tz_response.timezone);
@@ -1469,11 +1467,11 @@ pub fn login() -> Html {
             
             wasm_bindgen_futures::spawn_local(async move {
                 // Directly use timezone_info without checking it against time_zone_setup
-                match call_setup_timezone_info(server_name.clone(), api_key.clone(), timezone_info).await {
+                match PinepodsClient::new(server_name.clone(), Some(api_key.clone())).setup_timezone_info(&timezone_info).await {
                     Ok(success) => {
                         if success.success {
                             page_state.set(PageState::Default);
-                            match call_check_mfa_enabled(server_name.clone(), api_key.clone(), &user_id).await {
+                            match PinepodsClient::new(server_name.clone(), Some(api_key.clone())).check_mfa_enabled(&user_id).await {
                                 Ok(response) => {
                                     if response.mfa_enabled {
                                         page_state.set(PageState::MFAPrompt);
@@ -1590,14 +1588,14 @@ pub fn login() -> Html {
             e.prevent_default();
 
             wasm_bindgen_futures::spawn_local(async move {
-                match call_verify_mfa(&server_name.clone().unwrap(), &api_key.clone().unwrap().unwrap(), user_id.clone().unwrap(), (*mfa_code).clone()).await {
+                match PinepodsClient::new(server_name.clone().unwrap(), api_key.clone().unwrap()).verify_mfa(user_id.clone().unwrap(), (*mfa_code).clone()).await {
                     Ok(response) => {
                         if response.verified {
                             page_state.set(PageState::Default);
                             let theme_api = api_key.clone();
                             let theme_server = server_name.clone();
                             wasm_bindgen_futures::spawn_local(async move {
-                                match call_get_theme(theme_server.unwrap(), theme_api.unwrap().unwrap(), &user_id.unwrap()).await{
+                                match PinepodsClient::new(theme_server.unwrap(), theme_api.unwrap()).get_theme(&user_id.unwrap()).await{
                                     Ok(theme) => {
                                         crate::components::setting_components::theme_options::changeTheme(&theme);
                                         if let Some(window) = web_sys::window() {
@@ -1615,7 +1613,7 @@ pub fn login() -> Html {
                                 }
                             });
                             wasm_bindgen_futures::spawn_local(async move {
-                                match call_get_time_info(server_name.unwrap(), api_key.unwrap().unwrap(), &user_id.unwrap()).await{
+                                match PinepodsClient::new(server_name.unwrap(), api_key.unwrap()).get_time_info(&user_id.unwrap()).await{
                                     Ok(tz_response) => {
                                         dispatch.reduce_mut(move |state| {
                                             state.user_tz = Some(tz_response.timezone);
@@ -1741,6 +1739,13 @@ pub fn login() -> Html {
 #[function_component(LogOut)]
 pub fn logout() -> Html {
     let history = BrowserHistory::new();
+    let (state, _dispatch) = use_store::<AppState>();
+
+    // Signing out only forgets this account; other saved server profiles stay
+    if let Some(auth_details) = state.auth_details.as_ref() {
+        server_profiles::forget_profile(auth_details);
+    }
+    let profiles = server_profiles::load_profiles();
 
     // Clear local and session storage except for 'user_theme'
     let window = web_sys::window().expect("no global `window` exists");
@@ -1758,6 +1763,7 @@ pub fn logout() -> Html {
     if let Some(theme) = selected_theme {
         local_storage.set_item("selected_theme", &theme).expect("failed to set 'selected_theme'");
     }
+    server_profiles::store_profiles(&profiles);
 
     // Redirect to root path
     history.push("/");
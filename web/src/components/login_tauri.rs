@@ -3,13 +3,14 @@ use web_sys::{console, window};
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use yew_router::history::{BrowserHistory, History};
-use crate::requests::login_requests::{self, call_check_mfa_enabled};
-use crate::requests::login_requests::{TimeZoneInfo, call_first_login_done, call_setup_timezone_info, call_verify_mfa, call_self_service_login_status, call_reset_password_create_code, ResetCodePayload, ResetForgotPasswordPayload, call_verify_and_reset_password, call_get_time_info, call_verify_key};
+use crate::requests::client::PinepodsClient;
+use crate::requests::login_requests;
+use crate::requests::login_requests::{TimeZoneInfo, ResetCodePayload, ResetForgotPasswordPayload};
 use crate::components::context::{AppState, UIState};
+use crate::components::server_profiles;
 use md5;
 use yewdux::prelude::*;
-use crate::requests::login_requests::{AddUserRequest, call_add_login_user};
-use crate::requests::setting_reqs::call_get_theme;
+use crate::requests::login_requests::AddUserRequest;
 use crate::components::gen_funcs::{encode_password, validate_user_input, ValidationError};
 use crate::components::episodes_layout::UIStateMsg;
 use chrono_tz::{TZ_VARIANTS, Tz};
@@ -67,7 +68,7 @@ pub fn login() -> Html {
                 let window = web_sys::window().expect("no global `window` exists");
                 let location = window.location();
                 let server_name = location.href().expect("should have a href").trim_end_matches('/').to_string();
-                match call_self_service_login_status(server_name).await {
+                match PinepodsClient::new(server_name, None).self_service_login_status().await {
                     Ok(status) => {
                         self_service_enabled.set(status);
                     }
@@ -147,7 +148,7 @@ pub fn login() -> Html {
                                                             let wasm_email = email.clone();
                                                             let wasm_user_id = user_id.clone();
                                                             wasm_bindgen_futures::spawn_local(async move {
-                                                                match call_verify_key(&server_name.clone(), &api_key.clone()).await {
+                                                                match PinepodsClient::new(server_name.clone(), Some(api_key.clone())).verify_key().await {
                                                                     Ok(_) => {
                                                                         // API key is valid, user can stay logged in
                                                                         let final_dispatch = effect_displatch.clone();
@@ -169,7 +170,7 @@ pub fn login() -> Html {
                                                                         let theme_api = api_key.clone();
                                                                         let theme_server = server_name.clone();
                                                                         wasm_bindgen_futures::spawn_local(async move {
-                                                                            match call_get_theme(theme_server, theme_api, &wasm_user_id).await{
+                                                                            match PinepodsClient::new(theme_server, Some(theme_api)).get_theme(&wasm_user_id).await{
                                                                                 Ok(theme) => {
                                                                                     crate::components::setting_components::theme_options::changeTheme(&theme);
                                                                                     if let Some(window) = web_sys::window() {
@@ -187,7 +188,7 @@ pub fn login() -> Html {
                                                                             }
                                                                         });
                                                                         wasm_bindgen_futures::spawn_local(async move {
-                                                                            match call_get_time_info(server_name, api_key, &wasm_user_id).await{
+                                                                            match PinepodsClient::new(server_name, Some(api_key)).get_time_info(&wasm_user_id).await{
                                                                                 Ok(tz_response) => {
                                                                                     effect_displatch.reduce_mut(move |state| {
                                                                                         state.user_tz = Some(tz_response.timezone);
@@ -293,7 +294,7 @@ pub fn login() -> Html {
                 // let server_name = location.href().expect("should have a href");
                 let server_name = server_name.clone();
                 let page_state = page_state.clone();
-                match login_requests::login_new_server(server_name.to_string(), username.to_string(), password.to_string()).await {
+                match PinepodsClient::new(server_name.to_string(), None).login_new_server(username.to_string(), password.to_string()).await {
                     Ok((user_details, login_request, server_details)) => {
                         // After user login, update the image URL with user's email from user_details
                         let gravatar_url = generate_gravatar_url(&user_details.Email, 80); // 80 is the image size
@@ -317,10 +318,10 @@ pub fn login() -> Html {
                         temp_api_key.set(api_key.clone().unwrap());
                         temp_user_id.set(user_id.clone());
 
-                        match call_first_login_done(server_name.clone(), api_key.clone().unwrap(), &user_id).await {
+                        match PinepodsClient::new(server_name.clone(), api_key.clone()).first_login_done(&user_id).await {
                             Ok(first_login_done) => {
                                 if first_login_done {
-                                    match call_check_mfa_enabled(server_name.clone(), api_key.clone().unwrap(), &user_id).await {
+                                    match PinepodsClient::new(server_name.clone(), api_key.clone()).check_mfa_enabled(&user_id).await {
                                         Ok(response) => {
                                             if response.mfa_enabled {
                                                 page_state.set(PageState::MFAPrompt);
@@ -328,7 +329,7 @@ pub fn login() -> Html {
                                                 let theme_api = api_key.clone();
                                                 let theme_server = server_name.clone();
                                                 wasm_bindgen_futures::spawn_local(async move {
-                                                    match call_get_theme(theme_server, theme_api.unwrap(), &user_id).await{
+                                                    match PinepodsClient::new(theme_server, theme_api).get_theme(&user_id).await{
                                                         Ok(theme) => {
                                                             crate::components::setting_components::theme_options::changeTheme(&theme);
                                                             if let Some(window) = web_sys::window() {
@@ -346,7 +347,7 @@ pub fn login() -> Html {
                                                     }
                                                 });
                                                 wasm_bindgen_futures::spawn_local(async move {
-                                                    match call_get_time_info(server_name, api_key.unwrap(), &user_id).await{
+                                                    match PinepodsClient::new(server_name, api_key).get_time_info(&user_id).await{
                                                         Ok(tz_response) => {
                                                             dispatch.reduce_mut(move |state| {
                                                                 state.user_tz = Some(tz_response.timezone);
@@ -481,11 +482,11 @@ pub fn login() -> Html {
             
             wasm_bindgen_futures::spawn_local(async move {
                 // Directly use timezone_info without checking it against time_zone_setup
-                match call_setup_timezone_info(server_name.clone(), api_key.clone(), timezone_info).await {
+                match PinepodsClient::new(server_name.clone(), Some(api_key.clone())).setup_timezone_info(&timezone_info).await {
                     Ok(success) => {
                         if success.success {
                             page_state.set(PageState::Default);
-                            match call_check_mfa_enabled(server_name.clone(), api_key.clone(), &user_id).await {
+                            match PinepodsClient::new(server_name.clone(), Some(api_key.clone())).check_mfa_enabled(&user_id).await {
                                 Ok(response) => {
                                     if response.mfa_enabled {
                                         page_state.set(PageState::MFAPrompt);
@@ -602,14 +603,14 @@ pub fn login() -> Html {
             e.prevent_default();
 
             wasm_bindgen_futures::spawn_local(async move {
-                match call_verify_mfa(&server_name.clone().unwrap(), &api_key.clone().unwrap().unwrap(), user_id.clone().unwrap(), (*mfa_code).clone()).await {
+                match PinepodsClient::new(server_name.clone().unwrap(), api_key.clone().unwrap()).verify_mfa(user_id.clone().unwrap(), (*mfa_code).clone()).await {
                     Ok(response) => {
                         if response.verified {
                             page_state.set(PageState::Default);
                             let theme_api = api_key.clone();
                             let theme_server = server_name.clone();
                             wasm_bindgen_futures::spawn_local(async move {
-                                match call_get_theme(theme_server.unwrap(), theme_api.unwrap().unwrap(), &user_id.unwrap()).await{
+                                match PinepodsClient::new(theme_server.unwrap(), theme_api.unwrap()).get_theme(&user_id.unwrap()).await{
                                     Ok(theme) => {
                                         crate::components::setting_components::theme_options::changeTheme(&theme);
                                         if let Some(window) = web_sys::window() {
@@ -627,7 +628,7 @@ pub fn login() -> Html {
                                 }
                             });
                             wasm_bindgen_futures::spawn_local(async move {
-                                match call_get_time_info(server_name.unwrap(), api_key.unwrap().unwrap(), &user_id.unwrap()).await{
+                                match PinepodsClient::new(server_name.unwrap(), api_key.unwrap()).get_time_info(&user_id.unwrap()).await{
                                     Ok(tz_response) => {
                                         dispatch.reduce_mut(move |state| {
                                             state.user_tz = Some(tz_response.timezone);
@@ -753,6 +754,13 @@ pub fn login() -> Html {
 #[function_component(LogOut)]
 pub fn logout() -> Html {
     let history = BrowserHistory::new();
+    let (state, _dispatch) = use_store::<AppState>();
+
+    // Signing out only forgets this account; other saved server profiles stay
+    if let Some(auth_details) = state.auth_details.as_ref() {
+        server_profiles::forget_profile(auth_details);
+    }
+    let profiles = server_profiles::load_profiles();
 
     // Clear local and session storage except for 'user_theme'
     let window = web_sys::window().expect("no global `window` exists");
@@ -770,6 +778,7 @@ pub fn logout() -> Html {
     if let Some(theme) = selected_theme {
         local_storage.set_item("selected_theme", &theme).expect("failed to set 'selected_theme'");
     }
+    server_profiles::store_profiles(&profiles);
 
     // Redirect to root path
     history.push("/");
@@ -0,0 +1,183 @@
+//! Bindings for `navigator.mediaSession`, which lets media keys, headset buttons and
+//! lock screens control the player. web-sys only exposes it behind
+//! `web_sys_unstable_apis`, so the few pieces the player needs are bound here.
+use serde::Serialize;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::window;
+
+#[wasm_bindgen]
+extern "C" {
+    type MediaSession;
+
+    #[wasm_bindgen(method, setter)]
+    fn set_metadata(this: &MediaSession, metadata: Option<&MediaMetadata>);
+
+    #[wasm_bindgen(method, setter = playbackState)]
+    fn set_playback_state(this: &MediaSession, state: &str);
+
+    #[wasm_bindgen(method, catch, js_name = setActionHandler)]
+    fn set_action_handler(this: &MediaSession, action: &str, handler: Option<&js_sys::Function>) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, catch, js_name = setPositionState)]
+    fn set_position_state(this: &MediaSession, state: &JsValue) -> Result<(), JsValue>;
+
+    type MediaMetadata;
+
+    #[wasm_bindgen(constructor, catch)]
+    fn new(init: &JsValue) -> Result<MediaMetadata, JsValue>;
+}
+
+#[derive(Serialize)]
+struct MetadataInit<'a> {
+    title: &'a str,
+    artist: &'a str,
+    album: &'a str,
+    artwork: Vec<Artwork<'a>>,
+}
+
+#[derive(Serialize)]
+struct Artwork<'a> {
+    src: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PositionState {
+    duration: f64,
+    playback_rate: f64,
+    position: f64,
+}
+
+/// The actions the player answers to. Browsers show controls only for actions that
+/// have a handler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Play,
+    Pause,
+    SeekBackward,
+    SeekForward,
+    SeekTo,
+    NextTrack,
+}
+
+impl Action {
+    pub const ALL: [Action; 6] = [
+        Action::Play,
+        Action::Pause,
+        Action::SeekBackward,
+        Action::SeekForward,
+        Action::SeekTo,
+        Action::NextTrack,
+    ];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Action::Play => "play",
+            Action::Pause => "pause",
+            Action::SeekBackward => "seekbackward",
+            Action::SeekForward => "seekforward",
+            Action::SeekTo => "seekto",
+            Action::NextTrack => "nexttrack",
+        }
+    }
+}
+
+fn session() -> Option<MediaSession> {
+    let navigator = window()?.navigator();
+    let session = js_sys::Reflect::get(&navigator, &JsValue::from_str("mediaSession")).ok()?;
+    if session.is_undefined() || session.is_null() {
+        return None;
+    }
+    Some(session.unchecked_into())
+}
+
+fn to_js<T: Serialize>(value: &T) -> Option<JsValue> {
+    value.serialize(&serde_wasm_bindgen::Serializer::json_compatible()).ok()
+}
+
+/// Shows the episode on the lock screen and in the OS media controls.
+pub fn set_metadata(title: &str, podcast_name: &str, artwork_url: &str) {
+    let Some(session) = session() else { return };
+    let mut artwork = Vec::new();
+    if !artwork_url.is_empty() {
+        artwork.push(Artwork { src: artwork_url });
+    }
+    let init = MetadataInit { title, artist: podcast_name, album: podcast_name, artwork };
+    if let Some(metadata) = to_js(&init).and_then(|init| MediaMetadata::new(&init).ok()) {
+        session.set_metadata(Some(&metadata));
+    }
+}
+
+pub fn clear_metadata() {
+    if let Some(session) = session() {
+        session.set_metadata(None);
+        session.set_playback_state("none");
+    }
+}
+
+pub fn set_playing(playing: bool) {
+    if let Some(session) = session() {
+        session.set_playback_state(if playing { "playing" } else { "paused" });
+    }
+}
+
+/// Keeps the OS scrubber in step with the player. Ignored until the duration is known.
+pub fn set_position(duration: f64, playback_rate: f64, position: f64) {
+    if !duration.is_finite() || duration <= 0.0 || playback_rate <= 0.0 {
+        return;
+    }
+    let Some(session) = session() else { return };
+    let state = PositionState {
+        duration,
+        playback_rate,
+        position: position.clamp(0.0, duration),
+    };
+    if let Some(state) = to_js(&state) {
+        let _ = session.set_position_state(&state);
+    }
+}
+
+/// Action handlers registered with the browser. Dropping them unregisters them, so
+/// a handler never outlives the episode it was created for.
+pub struct ActionHandlers {
+    closures: Vec<(Action, Closure<dyn FnMut(JsValue)>)>,
+}
+
+impl ActionHandlers {
+    /// Registers `handler` for every action in [`Action::ALL`]. Seek actions pass
+    /// the seek offset or target time in seconds when the browser supplies one.
+    pub fn register(handler: impl Fn(Action, Option<f64>) + Clone + 'static) -> Self {
+        let mut closures = Vec::new();
+        let Some(session) = session() else { return ActionHandlers { closures } };
+        for action in Action::ALL {
+            let handler = handler.clone();
+            let closure = Closure::wrap(Box::new(move |details: JsValue| {
+                let field = match action {
+                    Action::SeekTo => "seekTime",
+                    _ => "seekOffset",
+                };
+                let seconds = js_sys::Reflect::get(&details, &JsValue::from_str(field))
+                    .ok()
+                    .and_then(|value| value.as_f64());
+                handler(action, seconds);
+            }) as Box<dyn FnMut(JsValue)>);
+            // Browsers throw for actions they don't support; those simply stay unbound
+            if session.set_action_handler(action.as_str(), Some(closure.as_ref().unchecked_ref())).is_ok() {
+                closures.push((action, closure));
+            }
+        }
+        ActionHandlers { closures }
+    }
+}
+
+impl Drop for ActionHandlers {
+    fn drop(&mut self) {
+        if let Some(session) = session() {
+            for (action, _) in &self.closures {
+                let _ = session.set_action_handler(action.as_str(), None);
+            }
+        }
+    }
+}
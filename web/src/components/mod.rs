@@ -12,6 +12,7 @@ pub(crate) mod routes;
 pub(crate) mod home;
 
 pub(crate) mod context;
+pub(crate) mod server_profiles;
 pub(crate) mod gen_components;
 pub(crate) mod desc_impl;
 pub(crate) mod search_new;
@@ -19,9 +20,23 @@ pub(crate) mod podcast_layout;
 pub(crate) mod episodes_layout;
 pub(crate) mod podcasts;
 mod audio;
+mod audio_chain;
+mod media_session;
+mod sleep_timer;
+mod shortcuts;
+mod up_next;
+mod position_sync;
+mod scrub_bar;
+mod video;
+mod prefetch;
+pub(crate) mod player_session;
 mod click_events;
 pub mod gen_funcs;
 pub(crate) mod episode;
+pub(crate) mod transcript;
+pub(crate) mod bookmarks;
+pub(crate) mod completion;
+pub(crate) mod podcast_playback;
 pub mod setting_components;
 
 #[cfg(feature = "server_build")]
@@ -0,0 +1,131 @@
+use gloo_events::EventListener;
+use gloo_timers::callback::Interval;
+use serde::{Deserialize, Serialize};
+use web_sys::{window, HtmlMediaElement};
+use yew::prelude::*;
+use yewdux::prelude::*;
+use crate::components::audio::AudioPlayerProps;
+use crate::components::audio_chain::load_source;
+use crate::components::context::{AppState, UIState};
+use crate::requests::client::PinepodsClient;
+
+const SESSION_KEY: &str = "playerSession";
+/// Milliseconds between snapshots of the player while it's up.
+const SAVE_EVERY_MS: u32 = 5_000;
+
+/// What was playing, and where, so a reload picks up in the same place.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct PlayerSession {
+    server_name: String,
+    user_id: i32,
+    episode: AudioPlayerProps,
+    position: f64,
+    speed: f64,
+    episode_in_db: Option<bool>,
+}
+
+impl PlayerSession {
+    fn capture(state: &UIState, server_name: &str, user_id: i32) -> Option<Self> {
+        let episode = state.currently_playing.clone()?;
+        // Until the episode's metadata is in, the element doesn't know where it is
+        let position = match state.audio_element.as_ref() {
+            Some(audio) if audio.ready_state() >= HtmlMediaElement::HAVE_METADATA => audio.current_time(),
+            _ => episode.start_pos_sec,
+        };
+        Some(PlayerSession {
+            server_name: server_name.to_string(),
+            user_id,
+            episode,
+            position,
+            speed: if state.playback_speed > 0.0 { state.playback_speed } else { 1.0 },
+            episode_in_db: state.episode_in_db,
+        })
+    }
+}
+
+fn load() -> Option<PlayerSession> {
+    window()
+        .and_then(|w| w.local_storage().ok().flatten())
+        .and_then(|storage| storage.get_item(SESSION_KEY).ok().flatten())
+        .and_then(|stored| serde_json::from_str(&stored).ok())
+}
+
+fn store(session: &PlayerSession) {
+    if let (Some(storage), Ok(session)) = (window().and_then(|w| w.local_storage().ok().flatten()), serde_json::to_string(session)) {
+        let _ = storage.set_item(SESSION_KEY, &session);
+    }
+}
+
+/// Brings back the saved episode in the mini player, paused at its saved position.
+fn restore(audio_dispatch: &Dispatch<UIState>, client: &PinepodsClient, session: PlayerSession) {
+    let episode_id = session.episode.episode_id;
+    let user_id = session.user_id;
+    let source_client = client.clone();
+    audio_dispatch.reduce_mut(move |audio_state| {
+        // Something was started before the session got here
+        if audio_state.currently_playing.is_some() {
+            return;
+        }
+        let src = session.episode.src.clone();
+        audio_state.currently_playing = Some(AudioPlayerProps { start_pos_sec: session.position, ..session.episode });
+        audio_state.audio_playing = Some(false);
+        audio_state.is_expanded = false;
+        audio_state.episode_in_db = session.episode_in_db;
+        audio_state.playback_speed = session.speed;
+        audio_state.podcast_playback = None;
+        audio_state.up_next = None;
+        load_source(audio_state, &source_client, &src);
+        if let Some(audio) = &audio_state.audio_element {
+            let _ = audio.pause();
+            audio.set_default_playback_rate(session.speed);
+            audio.set_current_time(session.position);
+        }
+        audio_state.update_current_time(session.position);
+    });
+
+    // The podcast's settings still cut its outro and pick its processing, but the speed
+    // stays as it was when the page closed
+    let audio_dispatch = audio_dispatch.clone();
+    let client = client.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        let Ok(settings) = client.get_episode_playback_settings(user_id, episode_id).await else { return };
+        audio_dispatch.reduce_mut(move |audio_state| {
+            if audio_state.currently_playing.as_ref().map(|playing| playing.episode_id) == Some(episode_id) {
+                audio_state.podcast_playback = Some(settings);
+            }
+        });
+    });
+}
+
+/// Keeps a snapshot of the player in local storage, and restores it after a reload or
+/// restart of the app for the same account.
+#[hook]
+pub fn use_player_session() {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (_audio_state, audio_dispatch) = use_store::<UIState>();
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+
+    use_effect_with((client, user_id), move |(client, user_id)| {
+        let mut interval = None;
+        let mut listener = None;
+        if let (Some(user_id), true) = (*user_id, client.is_authenticated()) {
+            let server_name = client.server_name().to_string();
+            if let Some(session) = load().filter(|session| session.server_name == server_name && session.user_id == user_id) {
+                restore(&audio_dispatch, client, session);
+            }
+
+            let save = move || {
+                if let Some(session) = PlayerSession::capture(&audio_dispatch.get(), &server_name, user_id) {
+                    store(&session);
+                }
+            };
+            interval = Some(Interval::new(SAVE_EVERY_MS, save.clone()));
+            listener = window().map(|w| EventListener::new(&w, "pagehide", move |_| save()));
+        }
+        move || {
+            drop(interval);
+            drop(listener);
+        }
+    });
+}
@@ -9,8 +9,8 @@ use super::app_drawer::App_drawer;
 use super::gen_components::{UseScrollToTop, Search_nav};
 use crate::components::context::{AppState, UIState, ExpandedDescriptions};
 use crate::components::audio::AudioPlayer;
-use crate::requests::search_pods::{call_parse_podcast_url, Podcast, UnifiedPodcast};
-use crate::requests::pod_req::{call_check_podcast, call_add_podcast, call_remove_podcasts_name, RemovePodcastValuesName, PodcastValues};
+use crate::requests::search_pods::{Podcast, UnifiedPodcast};
+use crate::requests::pod_req::{RemovePodcastValuesName, PodcastValues};
 use std::collections::HashSet;
 use crate::components::episodes_layout::SafeHtml;
 use crate::requests::login_requests::use_check_authentication;
@@ -120,7 +120,7 @@ pub fn pod_layout() -> Html {
             </div>
             {
                 if let Some(audio_props) = &audio_state.currently_playing {
-                    html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
+                    html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} podcast_name={audio_props.podcast_name.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
                 } else {
                     html! {}
                 }
@@ -142,9 +142,8 @@ pub fn podcast_item(props: &PodcastProps) -> Html {
     let podcast = props.podcast.clone();
     let (state, dispatch) = use_store::<AppState>();
     let (desc_state, desc_dispatch) = use_store::<ExpandedDescriptions>();
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = state.client();
     let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
     let history = BrowserHistory::new();
     let history_clone = history.clone();
     // let api_key_feed = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
@@ -156,16 +155,14 @@ pub fn podcast_item(props: &PodcastProps) -> Html {
 
     // On mount, check if the podcast is in the database
     let effect_user_id = user_id.unwrap().clone();
-    let effect_api_key = api_key.clone();
+    let effect_client = client.clone();
     let added_clone = added_podcasts.clone();
-    let server_name_mount = server_name.clone();
     // let api_key_mount = api_key.clone();
     {
         let is_added = is_added.clone();
         let podcast = podcast.clone();
         let user_id = effect_user_id.clone();
-        let api_key = effect_api_key.clone();
-        let server_name = server_name_mount.clone();
+        let client = effect_client.clone();
         let added_podcasts = added_clone.clone(); // Clone this for use in the effect
 
         use_effect_with(
@@ -174,7 +171,7 @@ pub fn podcast_item(props: &PodcastProps) -> Html {
                 let is_added = is_added.clone();
                 let podcast = podcast.clone();
                 wasm_bindgen_futures::spawn_local(async move {
-                    let added = call_check_podcast(&server_name.unwrap(), &api_key.unwrap().unwrap(), user_id, &podcast.title, &podcast.url).await.unwrap_or_default().exists;
+                    let added = client.check_podcast(user_id, &podcast.title, &podcast.url).await.unwrap_or_default().exists;
                     is_added.set(added);
                     let mut new_set = (*added_podcasts).clone();
                     if added {
@@ -204,8 +201,7 @@ pub fn podcast_item(props: &PodcastProps) -> Html {
         let pod_website_og = podcast_add.link.clone();
         let pod_explicit_og = podcast_add.explicit.clone();
 
-        let api_key_clone = api_key.clone();
-        let server_name_clone = server_name.clone();
+        let client_clone = client.clone();
         let user_id_clone = user_id.clone();
 
         let added_podcasts = added_podcasts.clone();
@@ -217,8 +213,7 @@ pub fn podcast_item(props: &PodcastProps) -> Html {
             dispatch.reduce_mut(|state| state.is_loading = Some(true));
             // Create a new set from the current state for modifications.
             let user_id = user_id_clone.clone();
-            let api_key = api_key_clone.clone();
-            let server_name = server_name_clone.clone();
+            let client = client_clone.clone();
 
             let current_set = (*added_podcasts).clone();
 
@@ -242,7 +237,7 @@ pub fn podcast_item(props: &PodcastProps) -> Html {
                         podcast_url: pod_feed_url,
                         user_id: value_id
                     };
-                    match call_remove_podcasts_name(&server_name.unwrap(), &api_key.unwrap(), &podcast_values).await {
+                    match client.remove_podcasts_name(&podcast_values).await {
                         Ok(_) => {
                             // If successful, update the state to remove the podcast
                             let mut new_set = current_set.clone();
@@ -296,7 +291,7 @@ pub fn podcast_item(props: &PodcastProps) -> Html {
                         pod_explicit,
                         user_id: value_id
                     };
-                    match call_add_podcast(&server_name.unwrap(), &api_key.unwrap(), user_id.unwrap(), &podcast_values).await {
+                    match client.add_podcast(&podcast_values).await {
                         Ok(_) => {
                             // If successful, update the state to add the podcast
                             let mut new_set = current_set.clone();
@@ -341,8 +336,7 @@ pub fn podcast_item(props: &PodcastProps) -> Html {
 
         Callback::from(move |e: MouseEvent| {
             dispatch.reduce_mut(|state| state.is_loading = Some(true));
-            let server_name_click = server_name.clone();
-            let api_key_click = api_key.clone();
+            let client_click = client.clone();
             let podcast_title = podcast_title_clone.clone();
             let podcast_url = podcast_url_clone.clone();
             let podcast_description = podcast_description_clone.clone();
@@ -367,7 +361,7 @@ pub fn podcast_item(props: &PodcastProps) -> Html {
             let dispatch = dispatch.clone();
             let history = history.clone(); // Clone again for use inside async block
             wasm_bindgen_futures::spawn_local(async move {
-                match call_parse_podcast_url(server_name_click.unwrap(), &api_key_click.unwrap(), &podcast_url).await {
+                match client_click.parse_podcast_url(&podcast_url).await {
                     Ok(podcast_feed_results) => {
                         dispatch.reduce_mut(move |state| {
                             state.podcast_feed_results = Some(podcast_feed_results);
@@ -0,0 +1,185 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewdux::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use crate::components::context::{AppState, UIState};
+use crate::requests::login_requests::Capability;
+use crate::requests::pod_req::{PodcastPlaybackSettings, SetPodcastPlaybackRequest};
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct PodcastPlaybackModalProps {
+    pub podcast_id: i32,
+    pub podcast_title: String,
+    pub on_close: Callback<()>,
+}
+
+/// Reads a whole number of seconds from a number input, treating anything else as 0.
+fn seconds_from(e: &InputEvent) -> u32 {
+    let input: HtmlInputElement = e.target_unchecked_into();
+    let seconds = input.value_as_number();
+    if seconds.is_finite() && seconds > 0.0 { seconds.min(3600.0) as u32 } else { 0 }
+}
+
+/// A choice between following the user's own audio processing setting, or turning it
+/// on or off for this podcast.
+fn override_select(label: &str, value: Option<bool>, onchange: Callback<Option<bool>>) -> Html {
+    let onchange = Callback::from(move |e: Event| {
+        let select: HtmlSelectElement = e.target_unchecked_into();
+        onchange.emit(match select.value().as_str() {
+            "on" => Some(true),
+            "off" => Some(false),
+            _ => None,
+        });
+    });
+    html! {
+        <label class="item_container-text flex items-center justify-between gap-2">
+            { label }
+            <select class="search-bar-input px-2 py-1 rounded" {onchange}>
+                <option value="default" selected={value.is_none()}>{ "My default" }</option>
+                <option value="on" selected={value == Some(true)}>{ "On" }</option>
+                <option value="off" selected={value == Some(false)}>{ "Off" }</option>
+            </select>
+        </label>
+    }
+}
+
+/// Edits the speed, intro/outro skips and audio processing used for every episode of one podcast.
+#[function_component(PodcastPlaybackModal)]
+pub fn podcast_playback_modal(props: &PodcastPlaybackModalProps) -> Html {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (_audio_state, audio_dispatch) = use_store::<UIState>();
+    let settings = use_state(PodcastPlaybackSettings::default);
+    let loading = use_state(|| true);
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+    let audio_processing = state
+        .server_details
+        .as_ref()
+        .map_or(false, |details| details.supports(Capability::AudioProcessing));
+
+    {
+        let settings = settings.clone();
+        let loading = loading.clone();
+        let client = client.clone();
+        use_effect_with((props.podcast_id, user_id), move |(podcast_id, user_id)| {
+            if let Some(user_id) = *user_id {
+                let podcast_id = *podcast_id;
+                spawn_local(async move {
+                    if let Ok(loaded) = client.get_podcast_playback_settings(user_id, podcast_id).await {
+                        settings.set(loaded);
+                    }
+                    loading.set(false);
+                });
+            }
+            || ()
+        });
+    }
+
+    let on_speed = {
+        let settings = settings.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let speed = input.value_as_number();
+            if speed.is_finite() {
+                settings.set(PodcastPlaybackSettings { playback_speed: speed, ..(*settings).clone() });
+            }
+        })
+    };
+    let on_intro = {
+        let settings = settings.clone();
+        Callback::from(move |e: InputEvent| {
+            settings.set(PodcastPlaybackSettings { skip_intro: seconds_from(&e), ..(*settings).clone() })
+        })
+    };
+    let on_outro = {
+        let settings = settings.clone();
+        Callback::from(move |e: InputEvent| {
+            settings.set(PodcastPlaybackSettings { skip_outro: seconds_from(&e), ..(*settings).clone() })
+        })
+    };
+
+    let on_override = |apply: fn(&mut PodcastPlaybackSettings, Option<bool>)| {
+        let settings = settings.clone();
+        Callback::from(move |value: Option<bool>| {
+            let mut updated = (*settings).clone();
+            apply(&mut updated, value);
+            settings.set(updated);
+        })
+    };
+
+    let on_close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_: MouseEvent| on_close.emit(()))
+    };
+
+    let on_save = {
+        let settings = settings.clone();
+        let on_close = props.on_close.clone();
+        let podcast_id = props.podcast_id;
+        Callback::from(move |_: MouseEvent| {
+            let Some(user_id) = user_id else { return };
+            let request = SetPodcastPlaybackRequest {
+                user_id,
+                podcast_id,
+                settings: (*settings).clone(),
+            };
+            let client = client.clone();
+            let audio_dispatch = audio_dispatch.clone();
+            let on_close = on_close.clone();
+            spawn_local(async move {
+                match client.set_podcast_playback_settings(&request).await {
+                    Ok(()) => {
+                        audio_dispatch.reduce_mut(|audio_state| {
+                            audio_state.info_message = Some("Podcast playback settings saved!".to_string())
+                        });
+                        on_close.emit(());
+                    }
+                    Err(e) => audio_dispatch.reduce_mut(|audio_state| {
+                        audio_state.error_message = Some(format!("Error saving podcast playback settings: {}", e))
+                    }),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div tabindex="-1" class="fixed top-0 right-0 left-0 z-50 flex justify-center items-center w-full h-full bg-black bg-opacity-25" onclick={on_close.clone()}>
+            <div class="modal-container relative p-4 w-full max-w-md max-h-full rounded-lg shadow" onclick={Callback::from(|e: MouseEvent| e.stop_propagation())}>
+                <div class="flex items-center justify-between mb-4">
+                    <h3 class="item_container-text text-lg font-bold">{ format!("Playback for {}", props.podcast_title) }</h3>
+                    <button onclick={on_close} class="end-2.5 text-gray-400 bg-transparent hover:bg-gray-200 hover:text-gray-900 rounded-lg text-sm w-8 h-8 ms-auto inline-flex justify-center items-center">
+                        <span class="material-icons">{"close"}</span>
+                        <span class="sr-only">{"Close"}</span>
+                    </button>
+                </div>
+                if *loading {
+                    <p class="item_container-text">{ "Loading..." }</p>
+                } else {
+                    <div class="flex flex-col gap-4">
+                        <label class="item_container-text flex flex-col gap-1">
+                            { format!("Default speed: {:.1}x", settings.playback_speed) }
+                            <input type="range" class="slider" min="0.5" max="2.0" step="0.1" value={settings.playback_speed.to_string()} oninput={on_speed} />
+                        </label>
+                        <label class="item_container-text flex items-center justify-between gap-2">
+                            { "Skip intro (seconds)" }
+                            <input type="number" min="0" class="search-bar-input w-24 px-2 py-1 rounded" value={settings.skip_intro.to_string()} oninput={on_intro} />
+                        </label>
+                        <label class="item_container-text flex items-center justify-between gap-2">
+                            { "Cut outro (seconds)" }
+                            <input type="number" min="0" class="search-bar-input w-24 px-2 py-1 rounded" value={settings.skip_outro.to_string()} oninput={on_outro} />
+                        </label>
+                        if audio_processing {
+                            { override_select("Voice boost", settings.voice_boost, on_override(|settings, value| settings.voice_boost = value)) }
+                            { override_select("Normalize loudness", settings.normalize_loudness, on_override(|settings, value| settings.normalize_loudness = value)) }
+                            { override_select("Skip silence", settings.skip_silence, on_override(|settings, value| settings.skip_silence = value)) }
+                        }
+                        <p class="item_container-text text-sm">{ "Applied whenever an episode of this podcast starts playing." }</p>
+                        <button onclick={on_save} class="theme-submit-button font-bold py-2 px-4 rounded focus:outline-none focus:shadow-outline" type="button">
+                            { "Save" }
+                        </button>
+                    </div>
+                }
+            </div>
+        </div>
+    }
+}
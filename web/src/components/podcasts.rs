@@ -4,10 +4,11 @@ use std::rc::Rc;
 use yew::{function_component, Html, html};
 use yew::prelude::*;
 use yewdux::prelude::*;
+use crate::requests::http::use_abort_handle;
 use super::app_drawer::App_drawer;
 use crate::components::gen_components::{UseScrollToTop, Search_nav};
-use crate::requests::pod_req::{PodcastResponse, RemovePodcastValues, call_remove_podcasts};
-use crate::requests::pod_req;
+use crate::requests::cache::CacheKey;
+use crate::requests::pod_req::{Podcast, PodcastResponse, RemovePodcastValues};
 use web_sys::console;
 use crate::components::context::{AppState, UIState, ExpandedDescriptions};
 use yew_router::history::BrowserHistory;
@@ -88,32 +89,40 @@ pub fn podcasts() -> Html {
         || ()
     });
 
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = state.client();
     let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
+    // Reads are cancelled when the view goes away, so they can't overwrite the next
+    // view's state. Removing a podcast uses the plain client and always finishes.
+    let abort = use_abort_handle();
     // Fetch episodes on component mount
     {
-        let api_key = api_key.clone();
+        let client = client.with_abort(abort.clone());
         let user_id = user_id.clone();
-        let server_name = server_name.clone();
         // let episodes = episodes.clone();
 
-        let server_name_effect = server_name.clone();
         let user_id_effect = user_id.clone();
-        let api_key_effect = api_key.clone();
+        let client_effect = client.clone();
         let effect_dispatch = dispatch.clone();
 
         use_effect_with(
-            (api_key_effect, user_id_effect, server_name_effect),
+            (client_effect.clone(), user_id_effect.clone()),
             move |_| {
                 // let episodes_clone = episodes.clone();
                 // let error_clone = error.clone();
 
-                if let (Some(api_key), Some(user_id), Some(server_name)) = (api_key.clone(), user_id.clone(), server_name.clone()) {
+                if let Some(user_id) = user_id.clone() {
+                    let client = client.clone();
                     let dispatch = effect_dispatch.clone();
 
+                    // Render the last fetched list right away, then refresh it in the background
+                    let cached = client.cached::<Vec<Podcast>>(CacheKey::Podcasts(user_id));
+                    if let Some(cached_list) = cached {
+                        dispatch.reduce_mut(move |state| {
+                            state.podcast_feed_return = Some(PodcastResponse { pods: Some(cached_list) });
+                        });
+                    }
                     wasm_bindgen_futures::spawn_local(async move {
-                        match pod_req::call_get_podcasts(&server_name, &api_key, &user_id).await {
+                        match client.get_podcasts(&user_id).await {
                             Ok(fetched_podcasts) => {
                                 dispatch.reduce_mut(move |state| {
                                     state.podcast_feed_return = Some(PodcastResponse { pods: Some(fetched_podcasts) });
@@ -150,8 +159,7 @@ pub fn podcasts() -> Html {
                         pods.into_iter().map(|podcast| {
                             // let state_ep = state.clone();
                             // let audio_state_ep = audio_state.clone();
-                            let api_key_iter = api_key.clone();
-                            let server_name_iter = server_name.clone().unwrap();
+                            let client_iter = client.clone();
                             let history = history_clone.clone();
 
                             // let id_string = &podcast.PodcastID.to_string();
@@ -169,20 +177,17 @@ pub fn podcasts() -> Html {
                                 let podcast_feed_return = podcast_feed_return.clone();
                                 let user_id = user_id.unwrap();
 
-                                let api_key_rm = api_key_iter.clone();
-                                let server_name = server_name.clone();
+                                let client_rm = client_iter.clone();
 
                                 Callback::from(move |_: MouseEvent| {
                                     let dispatch_call = dispatch_remove.clone();
-                                    let api_key_call = api_key_rm.clone();
-                                    let server_name_call = server_name.clone();
+                                    let client_call = client_rm.clone();
                                     let user_id = user_id;
 
                                     if let Some(podcasts) = &podcast_feed_return {
                                         for _podcast in &podcasts.pods {
                                             let dispatch_for = dispatch_call.clone();
-                                            let api_key_for = api_key_call.clone();
-                                            let server_name_for = server_name_call.clone();
+                                            let client_for = client_call.clone();
                                             let podcast_id = podcast_id_loop.clone(); // Use the correct podcast ID
 
                                             let remove_values = RemovePodcastValues {
@@ -192,9 +197,8 @@ pub fn podcasts() -> Html {
 
                                             wasm_bindgen_futures::spawn_local(async move {
                                                 let dispatch_clone = dispatch_for.clone();
-                                                let api_key_wasm = api_key_for.clone();
-                                                let server_name_wasm = server_name_for.clone();
-                                                match call_remove_podcasts(&server_name_wasm.unwrap(), &api_key_wasm.unwrap(), &remove_values).await {
+                                                let client_wasm = client_for.clone();
+                                                match client_wasm.remove_podcasts(&remove_values).await {
                                                     Ok(success) => {
                                                         if success {
                                                             dispatch_clone.apply(AppStateMsg::RemovePodcast(podcast_id));
@@ -222,8 +226,7 @@ pub fn podcasts() -> Html {
                                 .unwrap_or_else(|_| HashMap::new());
                             let on_title_click = create_on_title_click(
                                 dispatch.clone(),
-                                server_name_iter,
-                                api_key_iter,
+                                client_iter.with_abort(abort.clone()),
                                 &history,
                                 podcast.PodcastName.clone(),
                                 podcast.FeedURL.clone(),
@@ -331,7 +334,7 @@ pub fn podcasts() -> Html {
         </div>
         {
             if let Some(audio_props) = &audio_state.currently_playing {
-                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
+                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} podcast_name={audio_props.podcast_name.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
             } else {
                 html! {}
             }
@@ -0,0 +1,145 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use gloo_events::EventListener;
+use gloo_timers::callback::{Interval, Timeout};
+use web_sys::{window, HtmlMediaElement, VisibilityState};
+use yew::prelude::*;
+use yewdux::prelude::*;
+use crate::components::context::{AppState, UIState};
+use crate::requests::client::PinepodsClient;
+use crate::requests::login_requests::Capability;
+use crate::requests::outbox::{defer_if_offline, Mutation};
+use crate::requests::pod_req::RecordListenDurationRequest;
+
+/// Milliseconds between saves while the episode plays.
+const SAVE_EVERY_MS: u32 = 30_000;
+/// Seeks are saved once the listener has stopped dragging for this long.
+const SEEK_SETTLE_MS: u32 = 1_000;
+
+/// How a position is sent to the server.
+#[derive(Clone, Copy, PartialEq)]
+enum Delivery {
+    Request,
+    /// The page may be going away before a request would finish.
+    Beacon,
+}
+
+/// What the sync needs to save a position, shared by its timer and listeners.
+struct PositionSync {
+    audio_dispatch: Dispatch<UIState>,
+    client: PinepodsClient,
+    user_id: i32,
+    beacons: bool,
+    /// The last position saved, so pausing after a seek doesn't save it twice.
+    last_saved: RefCell<Option<(i32, u32)>>,
+}
+
+/// Where the player is in the episode it has loaded, if it's far enough along to know.
+fn current_position(state: &UIState) -> Option<(i32, f64)> {
+    let (audio, playing) = (state.audio_element.as_ref()?, state.currently_playing.as_ref()?);
+    // Until the new episode's metadata is in, the element's position is meaningless
+    if playing.episode_id == 0 || audio.ready_state() < HtmlMediaElement::HAVE_METADATA {
+        return None;
+    }
+    Some((playing.episode_id, audio.current_time()))
+}
+
+fn send_position(client: &PinepodsClient, request: RecordListenDurationRequest) {
+    let client = client.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(e) = client.record_listen_duration(&request).await {
+            defer_if_offline(&client, &e, Mutation::RecordListenDuration(request));
+        }
+    });
+}
+
+/// Saves where the episode in the player got to, before another one takes its place.
+/// Its `pause` comes too late for the sync, once the player already has the new episode.
+pub fn save_outgoing_position(state: &UIState, client: &PinepodsClient, user_id: i32) {
+    if let Some((episode_id, listen_duration)) = current_position(state) {
+        send_position(client, RecordListenDurationRequest::new(episode_id, user_id, listen_duration));
+    }
+}
+
+impl PositionSync {
+    fn save(&self, delivery: Delivery) {
+        let Some((episode_id, listen_duration)) = current_position(&self.audio_dispatch.get()) else { return };
+        let saved = Some((episode_id, listen_duration as u32));
+        if *self.last_saved.borrow() == saved {
+            return;
+        }
+        *self.last_saved.borrow_mut() = saved;
+
+        let request = RecordListenDurationRequest::new(episode_id, self.user_id, listen_duration);
+        if delivery == Delivery::Beacon && self.beacons && self.client.beacon_listen_duration(&request) {
+            return;
+        }
+        send_position(&self.client, request);
+    }
+}
+
+/// Keeps the server's position for the playing episode current: every so often while it
+/// plays, and straight away when it's paused, seeked, or the page is hidden or closed.
+#[hook]
+pub fn use_position_sync() {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (audio_state, audio_dispatch) = use_store::<UIState>();
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+    let beacons = state
+        .server_details
+        .as_ref()
+        .map_or(false, |details| details.supports(Capability::PositionSync));
+    let audio = audio_state.audio_element.clone();
+
+    use_effect_with((client, user_id, beacons, audio), move |(client, user_id, beacons, audio)| {
+        let mut listeners = Vec::new();
+        let mut interval = None;
+        if let (Some(user_id), Some(audio), Some(window)) = (*user_id, audio.clone(), window()) {
+            let sync = Rc::new(PositionSync {
+                audio_dispatch,
+                client: client.clone(),
+                user_id,
+                beacons: *beacons,
+                last_saved: RefCell::new(None),
+            });
+
+            interval = Some(Interval::new(SAVE_EVERY_MS, {
+                let sync = sync.clone();
+                let audio = audio.clone();
+                move || {
+                    if !audio.paused() {
+                        sync.save(Delivery::Request);
+                    }
+                }
+            }));
+            listeners.push(EventListener::new(&audio, "pause", {
+                let sync = sync.clone();
+                move |_| sync.save(Delivery::Request)
+            }));
+            let settling = Rc::new(RefCell::new(None));
+            listeners.push(EventListener::new(&audio, "seeked", {
+                let sync = sync.clone();
+                move |_| {
+                    let sync = sync.clone();
+                    *settling.borrow_mut() = Some(Timeout::new(SEEK_SETTLE_MS, move || sync.save(Delivery::Request)));
+                }
+            }));
+            if let Some(document) = window.document() {
+                listeners.push(EventListener::new(&document.clone(), "visibilitychange", {
+                    let sync = sync.clone();
+                    move |_| {
+                        if document.visibility_state() == VisibilityState::Hidden {
+                            sync.save(Delivery::Beacon);
+                        }
+                    }
+                }));
+            }
+            listeners.push(EventListener::new(&window, "pagehide", move |_| sync.save(Delivery::Beacon)));
+        }
+        move || {
+            drop(interval);
+            drop(listeners);
+        }
+    });
+}
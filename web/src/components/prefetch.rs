@@ -0,0 +1,213 @@
+//! Warms up the next queued episode while the current one finishes, so starting it
+//! doesn't wait on a cold request to a slow host. A detached `<audio>` element reads
+//! its metadata and a ranged request fetches its first couple of megabytes, from the
+//! same source the player will use.
+use gloo_events::EventListener;
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{window, AbortController, Headers, HtmlAudioElement, HtmlMediaElement, Navigator, RequestInit, RequestMode, Response};
+use yew::prelude::*;
+use yewdux::prelude::*;
+use crate::components::audio_chain::planned_source;
+use crate::components::context::{AppState, UIState};
+use crate::requests::pod_req::next_in_queue;
+use crate::requests::search_pods::MediaKind;
+
+const DATA_SAVER_KEY: &str = "dataSaver";
+/// Seconds before the end of an episode that the next one starts loading.
+const PREFETCH_LEAD_SECS: f64 = 120.0;
+/// How much of the start of the next episode is fetched ahead of time.
+const PREFETCH_BYTES: u32 = 2 * 1024 * 1024;
+
+// web-sys only exposes the connection's type, not whether the user asked to save data
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends = Navigator)]
+    type ConnectionNavigator;
+
+    #[wasm_bindgen(method, getter)]
+    fn connection(this: &ConnectionNavigator) -> Option<NetworkInformation>;
+
+    type NetworkInformation;
+
+    #[wasm_bindgen(method, getter = saveData)]
+    fn save_data(this: &NetworkInformation) -> Option<bool>;
+}
+
+/// The episode being prefetched, and the one that was playing when it started.
+struct Prefetch {
+    after: i32,
+    episode_id: i32,
+    audio: HtmlAudioElement,
+    abort: Option<AbortController>,
+}
+
+thread_local! {
+    // Outlives the player, which remounts on every page
+    static PREFETCHED: RefCell<Option<Prefetch>> = RefCell::new(None);
+}
+
+/// Whether the browser says the connection is metered or the user wants to save data.
+fn browser_saves_data() -> bool {
+    window()
+        .and_then(|w| w.navigator().unchecked_into::<ConnectionNavigator>().connection())
+        .and_then(|connection| connection.save_data())
+        .unwrap_or(false)
+}
+
+/// Whether this device skips prefetching. Until it's set here, it follows the
+/// browser's own data saver.
+pub fn data_saver() -> bool {
+    let stored = window()
+        .and_then(|w| w.local_storage().ok().flatten())
+        .and_then(|storage| storage.get_item(DATA_SAVER_KEY).ok().flatten());
+    match stored.as_deref() {
+        Some("on") => true,
+        Some("off") => false,
+        _ => browser_saves_data(),
+    }
+}
+
+pub fn set_data_saver(enabled: bool) {
+    if let Some(storage) = window().and_then(|w| w.local_storage().ok().flatten()) {
+        let _ = storage.set_item(DATA_SAVER_KEY, if enabled { "on" } else { "off" });
+    }
+    if enabled {
+        release();
+    }
+}
+
+/// Starts loading the start of `src`, dropping whatever was loading before.
+fn prefetch(after: i32, episode_id: i32, src: &str, cross_origin: bool) {
+    release();
+    let Ok(audio) = HtmlAudioElement::new() else { return };
+    audio.set_preload("metadata");
+    audio.set_muted(true);
+    audio.set_cross_origin(cross_origin.then_some("anonymous"));
+    audio.set_src(src);
+    audio.load();
+    let abort = AbortController::new().ok();
+    fetch_start(src, abort.clone());
+    PREFETCHED.with(|prefetched| *prefetched.borrow_mut() = Some(Prefetch { after, episode_id, audio, abort }));
+}
+
+/// Fetches the first `PREFETCH_BYTES` of `src`, which leaves them in the browser's cache.
+fn fetch_start(src: &str, abort: Option<AbortController>) {
+    let Some(window) = window() else { return };
+    let Ok(headers) = Headers::new() else { return };
+    // A plain byte range is a safelisted header, so this needs no preflight
+    if headers.set("Range", &format!("bytes=0-{}", PREFETCH_BYTES - 1)).is_err() {
+        return;
+    }
+    let init = RequestInit::new();
+    init.set_method("GET");
+    init.set_headers(&headers);
+    init.set_mode(RequestMode::Cors);
+    init.set_signal(abort.as_ref().map(|abort| abort.signal()).as_ref());
+    let request = window.fetch_with_str_and_init(src, &init);
+    wasm_bindgen_futures::spawn_local(async move {
+        let Ok(response) = JsFuture::from(request).await else { return };
+        let Ok(response) = response.dyn_into::<Response>() else { return };
+        // A host that ignores the range sends the whole episode
+        if response.status() != 206 {
+            if let Some(abort) = abort {
+                abort.abort();
+            }
+            return;
+        }
+        if let Ok(body) = response.array_buffer() {
+            let _ = JsFuture::from(body).await;
+        }
+    });
+}
+
+/// Stops the prefetch, if there is one. Emptying the source is what aborts the element's download.
+fn release() {
+    if let Some(prefetch) = PREFETCHED.with(|prefetched| prefetched.borrow_mut().take()) {
+        if let Some(abort) = prefetch.abort {
+            abort.abort();
+        }
+        prefetch.audio.remove_attribute("src").ok();
+        prefetch.audio.load();
+    }
+}
+
+/// Drops the prefetch once it's no longer needed: the player moved on to something
+/// other than the prefetched episode, or started playing it and has its own connection.
+fn release_if_done(playing: Option<i32>, player: &HtmlMediaElement) {
+    let needed = PREFETCHED.with(|prefetched| {
+        prefetched.borrow().as_ref().map_or(true, |prefetch| {
+            Some(prefetch.after) == playing || (Some(prefetch.episode_id) == playing && player.paused())
+        })
+    });
+    if !needed {
+        release();
+    }
+}
+
+fn prefetched_after(episode_id: i32) -> bool {
+    PREFETCHED.with(|prefetched| prefetched.borrow().as_ref().map_or(false, |prefetch| prefetch.after == episode_id))
+}
+
+/// Prefetches the next episode in the queue once the playing one is nearly over.
+#[hook]
+pub fn use_prefetch_next() {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (audio_state, audio_dispatch) = use_store::<UIState>();
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+    let episode_id = audio_state.currently_playing.as_ref().map(|playing| playing.episode_id);
+
+    use_effect_with(
+        (audio_state.audio_element.clone(), client, user_id, episode_id),
+        move |(audio, client, user_id, episode_id)| {
+            let mut listeners = Vec::new();
+            if let Some(audio) = audio.clone() {
+                release_if_done(*episode_id, &audio);
+                listeners.push(EventListener::new(&audio.clone(), "playing", {
+                    let audio = audio.clone();
+                    let episode_id = *episode_id;
+                    move |_| release_if_done(episode_id, &audio)
+                }));
+                if let (Some(user_id), Some(episode_id)) = (*user_id, *episode_id) {
+                    let client = client.clone();
+                    // Once per episode is enough
+                    let started = Cell::new(false);
+                    listeners.push(EventListener::new(&audio.clone(), "timeupdate", move |_| {
+                        let remaining = audio.duration() - audio.current_time();
+                        if started.get() || !remaining.is_finite() || remaining > PREFETCH_LEAD_SECS {
+                            return;
+                        }
+                        // Without auto-play the next episode may never start. A player remounted
+                        // by navigation finds the prefetch it already started.
+                        let auto_play_next = audio_dispatch.get().playback_settings.queue_playback.auto_play_next;
+                        if !auto_play_next || data_saver() || prefetched_after(episode_id) {
+                            return;
+                        }
+                        started.set(true);
+                        let client = client.clone();
+                        let audio_dispatch = audio_dispatch.clone();
+                        wasm_bindgen_futures::spawn_local(async move {
+                            let Ok(queue) = client.get_queued_episodes(&user_id).await else { return };
+                            let Some(next) = next_in_queue(&queue, episode_id) else { return };
+                            // Playlists point at their segments, so there's no start to load
+                            if next.EpisodeID == episode_id || MediaKind::of(None, &next.EpisodeURL) == MediaKind::Hls {
+                                return;
+                            }
+                            let state = audio_dispatch.get();
+                            // The player moved on while the queue loaded
+                            if state.currently_playing.as_ref().map(|playing| playing.episode_id) != Some(episode_id) {
+                                return;
+                            }
+                            let (src, cross_origin) = planned_source(&state, &client, &next.EpisodeURL);
+                            prefetch(episode_id, next.EpisodeID, &src, cross_origin);
+                        });
+                    }));
+                }
+            }
+            move || drop(listeners)
+        },
+    );
+}
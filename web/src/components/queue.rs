@@ -2,13 +2,14 @@ use yew::{function_component, Html, html};
 use yew::prelude::*;
 use super::app_drawer::App_drawer;
 use super::gen_components::{UseScrollToTop, Search_nav, empty_message, episode_item, on_shownotes_click};
-use crate::requests::pod_req;
 use yewdux::prelude::*;
+use crate::requests::http::use_abort_handle;
 use crate::components::context::{AppState, UIState};
 use yew_router::history::BrowserHistory;
 use crate::components::audio::AudioPlayer;
 use crate::components::gen_funcs::{sanitize_html_with_blank_target, truncate_description, parse_date, format_datetime, match_date_format};
-use crate::requests::pod_req::QueuedEpisodesResponse;
+use crate::requests::cache::CacheKey;
+use crate::requests::pod_req::{QueuedEpisode, QueuedEpisodesResponse};
 use crate::components::audio::on_play_click;
 use crate::components::episodes_layout::AppStateMsg;
 // use crate::components::gen_funcs::check_auth;
@@ -87,27 +88,37 @@ pub fn queue() -> Html {
     }
 
 
-    // Fetch episodes on component mount
+    // Fetch episodes on component mount. Leaving the view cancels the fetch, so a
+    // late response can't overwrite the list of the view the user moved on to.
+    let abort = use_abort_handle();
     let loading_ep = loading.clone();
     {
         // let episodes = episodes.clone();
         let error = error.clone();
-        let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+        let client = post_state.client().with_abort(abort.clone());
         let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-        let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
         let effect_dispatch = dispatch.clone();
 
-        // fetch_episodes(api_key.flatten(), user_id, server_name, dispatch, error, pod_req::call_get_recent_eps);
 
         use_effect_with(
-            (api_key.clone(), user_id.clone(), server_name.clone()),
+            (client.clone(), user_id.clone()),
             move |_| {
                 let error_clone = error.clone();
-                if let (Some(api_key), Some(user_id), Some(server_name)) = (api_key.clone(), user_id.clone(), server_name.clone()) {
+                if let Some(user_id) = user_id.clone() {
+                    let client = client.clone();
                     let dispatch = effect_dispatch.clone();
     
+                    // Render the last fetched list right away, then refresh it in the background
+                    let cached = client.cached::<Vec<QueuedEpisode>>(CacheKey::Queue(user_id));
+                    let has_cached = cached.is_some();
+                    if let Some(cached_list) = cached {
+                        dispatch.reduce_mut(move |state| {
+                            state.queued_episodes = Some(QueuedEpisodesResponse { episodes: cached_list });
+                        });
+                        loading_ep.set(false);
+                    }
                     wasm_bindgen_futures::spawn_local(async move {
-                        match pod_req::call_get_queued_episodes(&server_name, &api_key, &user_id).await {
+                        match client.get_queued_episodes(&user_id).await {
                             Ok(fetched_episodes) => {
                                 dispatch.reduce_mut(move |state| {
                                     state.queued_episodes = Some(QueuedEpisodesResponse { episodes: fetched_episodes });
@@ -116,7 +127,10 @@ pub fn queue() -> Html {
                                 // web_sys::console::log_1(&format!("State after update: {:?}", state).into()); // Log state after update
                             },
                             Err(e) => {
-                                error_clone.set(Some(e.to_string()));
+                                // Keep showing the cached list when only the refresh failed
+                                if !has_cached {
+                                    error_clone.set(Some(e.to_string()));
+                                }
                                 loading_ep.set(false);
                             },
                         }
@@ -164,9 +178,8 @@ pub fn queue() -> Html {
                                 )
                             } else {
                                 queued_eps.episodes.into_iter().map(|episode| {
-                            let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+                            let client = post_state.client();
                             let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-                            let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
                             let history_clone = history.clone();
                             let id_string = &episode.EpisodeID.to_string();
     
@@ -214,23 +227,23 @@ pub fn queue() -> Html {
                             let listener_duration_for_closure = episode_listened_clone.clone();
 
                             let user_id_play = user_id.clone();
-                            let server_name_play = server_name.clone();
-                            let api_key_play = api_key.clone();
+                            let client_play = client.clone();
                             let audio_dispatch = audio_dispatch.clone();
 
                             let on_play_click = on_play_click(
                                 episode_url_for_closure.clone(),
                                 episode_title_for_closure.clone(),
+                                episode.PodcastName.clone(),
                                 episode_artwork_for_closure.clone(),
                                 episode_duration_for_closure.clone(),
                                 episode_id_for_closure.clone(),
                                 listener_duration_for_closure.clone(),
-                                api_key_play.unwrap().unwrap(),
+                                client_play.clone(),
                                 user_id_play.unwrap(),
-                                server_name_play.unwrap(),
                                 audio_dispatch.clone(),
                                 audio_state.clone(),
                                 None,
+                                None,
                             );
 
                             let on_shownotes_click = on_shownotes_click(
@@ -272,7 +285,7 @@ pub fn queue() -> Html {
             }
         {
             if let Some(audio_props) = &audio_state.currently_playing {
-                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
+                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} podcast_name={audio_props.podcast_name.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
             } else {
                 html! {}
             }
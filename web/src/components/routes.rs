@@ -19,6 +19,8 @@ pub enum Route {
     Settings,
     #[at("/history")]
     PodHistory,
+    #[at("/bookmarks")]
+    Bookmarks,
     #[at("/downloads")]
     Downloads,
     #[at("/search")]
@@ -37,4 +39,10 @@ pub enum Route {
     EpisodeLayout,
     #[at("/episode")]
     Episode,
+    /// A shareable link to an episode, optionally with `?t=<seconds>` to play from.
+    #[at("/episode/:id")]
+    EpisodeLink { id: i32 },
+    /// A shareable link to a podcast in the database.
+    #[at("/podcast/:id")]
+    PodcastLink { id: i32 },
 }
@@ -1,14 +1,15 @@
 use yew::{function_component, Html, html};
 use yew::prelude::*;
 use super::app_drawer::App_drawer;
-use super::gen_components::{UseScrollToTop, Search_nav, empty_message, episode_item, on_shownotes_click};
-use crate::requests::pod_req;
+use super::gen_components::{UseScrollToTop, Search_nav, empty_message, episode_item, on_shownotes_click, load_more_indicator};
 use yewdux::prelude::*;
+use crate::requests::http::use_abort_handle;
 use yew_router::history::BrowserHistory;
 use crate::components::context::{AppState, UIState};
 use crate::components::audio::AudioPlayer;
 use crate::components::gen_funcs::{sanitize_html_with_blank_target, truncate_description, parse_date, format_datetime, match_date_format};
-use crate::requests::pod_req::SavedEpisodesResponse;
+use crate::requests::cache::CacheKey;
+use crate::requests::pod_req::{use_paged_list, Page, SavedEpisode, SavedEpisodesResponse};
 use crate::components::audio::on_play_click;
 use crate::components::episodes_layout::AppStateMsg;
 // use crate::components::gen_funcs::check_auth;
@@ -94,37 +95,58 @@ pub fn saved() -> Html {
     }
 
 
-    // Fetch episodes on component mount
+    // Fetch episodes on component mount. Leaving the view cancels the fetch, so a
+    // late response can't overwrite the list of the view the user moved on to.
+    let abort = use_abort_handle();
     let loading_ep = loading.clone();
     {
         // let episodes = episodes.clone();
         let error = error.clone();
-        let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+        let client = post_state.client().with_abort(abort.clone());
         let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-        let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
 
         let effect_dispatch = dispatch.clone();
 
-        // fetch_episodes(api_key.flatten(), user_id, server_name, dispatch, error, pod_req::call_get_recent_eps);
 
         use_effect_with(
-            (api_key.clone(), user_id.clone(), server_name.clone()),
+            (client.clone(), user_id.clone()),
             move |_| {
                 let error_clone = error.clone();
-                if let (Some(api_key), Some(user_id), Some(server_name)) = (api_key.clone(), user_id.clone(), server_name.clone()) {
+                if let Some(user_id) = user_id.clone() {
+                    let client = client.clone();
                     let dispatch = effect_dispatch.clone();
     
+                    // Render the last fetched list right away, then refresh it in the background
+                    let cached = client.cached::<Vec<SavedEpisode>>(CacheKey::Saved(user_id));
+                    let has_cached = cached.is_some();
+                    if let Some(cached_list) = cached {
+                        dispatch.reduce_mut(move |state| {
+                            let next_page = Page::first().after(cached_list.len());
+                            state.saved_episodes = Some(SavedEpisodesResponse { episodes: cached_list, next_page });
+                        });
+                        loading_ep.set(false);
+                    }
                     wasm_bindgen_futures::spawn_local(async move {
-                        match pod_req::call_get_saved_episodes(&server_name, &api_key, &user_id).await {
+                        match client.get_saved_episodes(&user_id, Page::first()).await {
                             Ok(fetched_episodes) => {
                                 dispatch.reduce_mut(move |state| {
-                                    state.saved_episodes = Some(SavedEpisodesResponse { episodes: fetched_episodes });
+                                    // Once later pages are in, or on their way, a refreshed first page would drop them
+                                    if let Some(list) = state.saved_episodes.as_ref() {
+                                        if !Page::first_only(list.episodes.len(), list.next_page) {
+                                            return;
+                                        }
+                                    }
+                                    let next_page = Page::first().after(fetched_episodes.len());
+                                    state.saved_episodes = Some(SavedEpisodesResponse { episodes: fetched_episodes, next_page });
                                 });
                                 loading_ep.set(false);
                                 // web_sys::console::log_1(&format!("State after update: {:?}", state).into()); // Log state after update
                             },
                             Err(e) => {
-                                error_clone.set(Some(e.to_string()));
+                                // Keep showing the cached list when only the refresh failed
+                                if !has_cached {
+                                    error_clone.set(Some(e.to_string()));
+                                }
                                 loading_ep.set(false);
                             },
                         }
@@ -135,6 +157,19 @@ pub fn saved() -> Html {
         );
     }
 
+    let more_episodes = {
+        let client = post_state.client().with_abort(abort.clone());
+        let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID);
+        use_paged_list(
+            state.saved_episodes.as_ref().and_then(|list| list.next_page),
+            |state: &mut AppState| state.saved_episodes.as_mut(),
+            move |page| {
+                let client = client.clone();
+                user_id.map(|user_id| async move { client.get_saved_episodes(&user_id, page).await })
+            },
+        )
+    };
+
     html! {
         <>
         <div class="main-container">
@@ -174,9 +209,8 @@ pub fn saved() -> Html {
                             )
                         } else {
                             saved_eps.episodes.into_iter().map(|episode| {
-                                let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+                                let client = post_state.client();
                                 let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-                                let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
                                 let history_clone = history.clone();
                                 let id_string = &episode.EpisodeID.to_string();
         
@@ -224,23 +258,23 @@ pub fn saved() -> Html {
                                 let listener_duration_for_closure = episode_listened_clone.clone();
 
                                 let user_id_play = user_id.clone();
-                                let server_name_play = server_name.clone();
-                                let api_key_play = api_key.clone();
+                                let client_play = client.clone();
                                 let audio_dispatch = audio_dispatch.clone();
 
                                 let on_play_click = on_play_click(
                                     episode_url_for_closure.clone(),
                                     episode_title_for_closure.clone(),
+                                    episode.PodcastName.clone(),
                                     episode_artwork_for_closure.clone(),
                                     episode_duration_for_closure.clone(),
                                     episode_id_for_closure.clone(),
                                     listener_duration_for_closure.clone(),
-                                    api_key_play.unwrap().unwrap(),
+                                    client_play.clone(),
                                     user_id_play.unwrap(),
-                                    server_name_play.unwrap(),
                                     audio_dispatch.clone(),
                                     audio_state.clone(),
                                     None,
+                                    None,
                                 );
 
                                 let on_shownotes_click = on_shownotes_click(
@@ -281,6 +315,7 @@ pub fn saved() -> Html {
                     }
                 }
             }
+            { load_more_indicator(&more_episodes) }
         // Conditional rendering for the error banner
         if let Some(error) = error_message {
             <div class="error-snackbar">{ error }</div>
@@ -290,7 +325,7 @@ pub fn saved() -> Html {
         }
         {
             if let Some(audio_props) = &audio_state.currently_playing {
-                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
+                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} podcast_name={audio_props.podcast_name.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
             } else {
                 html! {}
             }
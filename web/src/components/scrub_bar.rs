@@ -0,0 +1,279 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use gloo::render::{request_animation_frame, AnimationFrame};
+use gloo_events::EventListener;
+use js_sys::ArrayBuffer;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{window, AudioBuffer, Element, HtmlInputElement, HtmlMediaElement, HtmlVideoElement, OfflineAudioContext, Response};
+use yew::prelude::*;
+use yewdux::prelude::*;
+use crate::components::context::{AppState, UIState, WaveformState};
+use crate::components::gen_funcs::format_time;
+
+/// Bars across the waveform.
+const WAVEFORM_BARS: usize = 400;
+/// Episodes are decoded at this rate for their waveform, the lowest browsers allow. It's
+/// plenty for the shape, and keeps a decoded hour to about 43 MB.
+const DECODE_SAMPLE_RATE: f32 = 3_000.0;
+/// Longer episodes get the plain bar; decoding them would take too much memory on phones.
+const MAX_WAVEFORM_SECS: f64 = 3600.0;
+/// Files larger than this get the plain bar too, whatever the episode claims to last.
+const MAX_WAVEFORM_BYTES: f64 = 100_000_000.0;
+
+/// What the element has buffered, as (start, end) seconds.
+fn buffered_ranges(audio: &HtmlMediaElement) -> Vec<(f64, f64)> {
+    let ranges = audio.buffered();
+    (0..ranges.length())
+        .filter_map(|index| Some((ranges.start(index).ok()?, ranges.end(index).ok()?)))
+        .collect()
+}
+
+/// Calls `refresh` every frame until the element pauses.
+fn animate(frame: Rc<RefCell<Option<AnimationFrame>>>, audio: HtmlVideoElement, refresh: Rc<dyn Fn()>) {
+    let next = frame.clone();
+    *frame.borrow_mut() = Some(request_animation_frame(move |_| {
+        refresh();
+        if !audio.paused() {
+            animate(next, audio, refresh);
+        }
+    }));
+}
+
+/// The loudest sample in each bar, scaled so the loudest bar is full height.
+fn peaks(audio: &AudioBuffer) -> Vec<f32> {
+    let bar_len = (audio.length() as usize / WAVEFORM_BARS).max(1);
+    // Reading a bar at a time keeps a second copy of the episode out of memory
+    let mut samples = vec![0.0; bar_len];
+    let mut peaks: Vec<f32> = (0..WAVEFORM_BARS)
+        .map(|bar| match audio.copy_from_channel_with_start_in_channel(&mut samples, 0, (bar * bar_len) as u32) {
+            Ok(()) => samples.iter().fold(0.0, |peak: f32, sample| peak.max(sample.abs())),
+            Err(_) => 0.0,
+        })
+        .collect();
+    let loudest = peaks.iter().copied().fold(0.0, f32::max);
+    if loudest > 0.0 {
+        peaks.iter_mut().for_each(|peak| *peak /= loudest);
+    }
+    peaks
+}
+
+async fn decode_peaks(src: &str) -> Result<Vec<f32>, JsValue> {
+    let window = window().ok_or("no window")?;
+    let response: Response = JsFuture::from(window.fetch_with_str(src)).await?.dyn_into()?;
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!("{} fetching audio", response.status())));
+    }
+    let length = response.headers().get("content-length")?.and_then(|length| length.parse::<f64>().ok());
+    if length.map_or(false, |length| length > MAX_WAVEFORM_BYTES) {
+        // Stop the download rather than let it finish for nothing
+        if let Some(body) = response.body() {
+            let _ = body.cancel();
+        }
+        return Err(JsValue::from_str("audio too large for a waveform"));
+    }
+    let bytes: ArrayBuffer = JsFuture::from(response.array_buffer()?).await?.dyn_into()?;
+    let context = OfflineAudioContext::new_with_number_of_channels_and_length_and_sample_rate(1, 1, DECODE_SAMPLE_RATE)?;
+    let audio: AudioBuffer = JsFuture::from(context.decode_audio_data(&bytes)?).await?.dyn_into()?;
+    Ok(peaks(&audio))
+}
+
+/// The waveform of the playing episode, when its audio is already downloaded. Streams
+/// from the podcast's host would have to be fetched in full a second time, so they go
+/// without.
+#[hook]
+fn use_waveform(duration_sec: f64) -> Option<Rc<Vec<f32>>> {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (audio_state, _audio_dispatch) = use_store::<UIState>();
+    let (waveform, waveform_dispatch) = use_store::<WaveformState>();
+    let client = state.client();
+    let src = audio_state
+        .currently_playing
+        .as_ref()
+        .map(|playing| playing.src.clone())
+        .filter(|src| client.is_stream_url(src) || src.starts_with("blob:"))
+        .filter(|_| duration_sec > 0.0 && duration_sec <= MAX_WAVEFORM_SECS);
+
+    use_effect_with(src.clone(), move |src| {
+        if let Some(src) = src.clone().filter(|src| waveform_dispatch.get().src.as_ref() != Some(src)) {
+            waveform_dispatch.set(WaveformState { src: Some(src.clone()), peaks: None });
+            wasm_bindgen_futures::spawn_local(async move {
+                let peaks = decode_peaks(&src).await.ok().map(Rc::new);
+                waveform_dispatch.reduce_mut(move |waveform| {
+                    if waveform.src.as_ref() == Some(&src) {
+                        waveform.peaks = peaks;
+                    }
+                });
+            });
+        }
+        || ()
+    });
+
+    waveform.peaks.clone().filter(|_| src.is_some() && waveform.src == src)
+}
+
+fn waveform_path(peaks: &[f32]) -> String {
+    peaks
+        .iter()
+        .enumerate()
+        .map(|(bar, peak)| {
+            let height = (peak * 100.0).max(2.0);
+            format!("M{} {:.1}h0.7v{:.1}h-0.7z", bar, (100.0 - height) / 2.0, height)
+        })
+        .collect()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ScrubBarProps {
+    pub duration_sec: f64,
+    pub duration_label: String,
+    /// Classes for the elapsed and total times either side of the bar.
+    #[prop_or_default]
+    pub time_class: Classes,
+    /// Markers drawn over the bar, like chapter starts.
+    #[prop_or_default]
+    pub children: Html,
+}
+
+/// The player's scrub bar, with the elapsed and total times either side. It follows
+/// playback every frame, shows what's buffered (or the waveform, for downloads), and
+/// previews the time under the pointer.
+#[function_component(ScrubBar)]
+pub fn scrub_bar(props: &ScrubBarProps) -> Html {
+    let (audio_state, audio_dispatch) = use_store::<UIState>();
+    let position = use_state_eq(|| audio_state.current_time_seconds);
+    let buffered = use_state_eq(Vec::<(f64, f64)>::new);
+    // Where the thumb is being dragged to; the seek waits for it to be let go
+    let scrubbing = use_state_eq(|| None::<f64>);
+    let hover = use_state_eq(|| None::<f64>);
+    let peaks = use_waveform(props.duration_sec);
+    let path = use_memo(peaks.clone(), |peaks| peaks.as_deref().map(|peaks| waveform_path(peaks)));
+
+    {
+        let position = position.clone();
+        let buffered = buffered.clone();
+        use_effect_with(audio_state.audio_element.clone(), move |audio| {
+            let mut listeners = Vec::new();
+            let frame = Rc::new(RefCell::new(None));
+            if let Some(audio) = audio.clone() {
+                let refresh: Rc<dyn Fn()> = {
+                    let audio = audio.clone();
+                    Rc::new(move || {
+                        position.set(audio.current_time());
+                        buffered.set(buffered_ranges(&audio));
+                    })
+                };
+                // Paused, the element only moves when it seeks, loads or buffers more
+                for event in ["timeupdate", "seeked", "progress", "loadedmetadata", "emptied"] {
+                    let refresh = refresh.clone();
+                    listeners.push(EventListener::new(&audio, event, move |_| refresh()));
+                }
+                listeners.push(EventListener::new(&audio, "play", {
+                    let frame = frame.clone();
+                    let audio = audio.clone();
+                    let refresh = refresh.clone();
+                    move |_| animate(frame.clone(), audio.clone(), refresh.clone())
+                }));
+                refresh();
+                if !audio.paused() {
+                    animate(frame.clone(), audio, refresh);
+                }
+            }
+            move || {
+                drop(listeners);
+                frame.borrow_mut().take();
+            }
+        });
+    }
+
+    let duration = props.duration_sec;
+    let percent = move |time: f64| if duration > 0.0 { (time / duration * 100.0).clamp(0.0, 100.0) } else { 0.0 };
+    let shown = (*scrubbing).unwrap_or(*position);
+    let played = percent(shown);
+
+    let on_input = {
+        let scrubbing = scrubbing.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(value) = input.value().parse::<f64>() {
+                scrubbing.set(Some(value));
+            }
+        })
+    };
+    let on_change = {
+        let scrubbing = scrubbing.clone();
+        let position = position.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(value) = input.value().parse::<f64>() {
+                audio_dispatch.reduce_mut(|state| state.seek_to(value));
+                position.set(value);
+            }
+            scrubbing.set(None);
+        })
+    };
+    let on_hover = {
+        let hover = hover.clone();
+        Callback::from(move |e: MouseEvent| {
+            let Some(track) = e.current_target().and_then(|target| target.dyn_into::<Element>().ok()) else { return };
+            let rect = track.get_bounding_client_rect();
+            if rect.width() > 0.0 {
+                let fraction = ((e.client_x() as f64 - rect.left()) / rect.width()).clamp(0.0, 1.0);
+                hover.set(Some(fraction * duration));
+            }
+        })
+    };
+    let on_leave = {
+        let hover = hover.clone();
+        Callback::from(move |_: MouseEvent| hover.set(None))
+    };
+
+    let rail = match (peaks.as_ref(), path.as_ref()) {
+        (Some(peaks), Some(path)) => {
+            let view_box = format!("0 0 {} 100", peaks.len());
+            html! {
+                <>
+                <svg class="scrub-waveform" viewBox={view_box.clone()} preserveAspectRatio="none">
+                    <path d={path.clone()} />
+                </svg>
+                <svg class="scrub-waveform played" viewBox={view_box} preserveAspectRatio="none" style={format!("clip-path: inset(0 {}% 0 0);", 100.0 - played)}>
+                    <path d={path.clone()} />
+                </svg>
+                </>
+            }
+        }
+        _ => html! {
+            <div class="scrub-rail">
+                {
+                    buffered.iter().map(|(start, end)| html! {
+                        <div class="scrub-buffered" style={format!("left: {}%; width: {}%;", percent(*start), percent(*end) - percent(*start))}></div>
+                    }).collect::<Html>()
+                }
+                <div class="scrub-played" style={format!("width: {}%;", played)}></div>
+            </div>
+        },
+    };
+
+    html! {
+        <>
+        <span class={props.time_class.clone()}>{ format_time(shown) }</span>
+        <div class={classes!("scrub-track", peaks.is_some().then_some("with-waveform"))} onmousemove={on_hover} onmouseleave={on_leave}>
+            { rail }
+            <input type="range"
+                class="flex-grow h-1 cursor-pointer"
+                min="0.0"
+                max={duration.to_string()}
+                step="any"
+                value={shown.to_string()}
+                oninput={on_input}
+                onchange={on_change}
+            />
+            { props.children.clone() }
+            if let Some(time) = *hover {
+                <div class="scrub-hover-time" style={format!("left: {}%;", percent(time))}>{ format_time(time) }</div>
+            }
+        </div>
+        <span class={props.time_class.clone()}>{ props.duration_label.clone() }</span>
+        </>
+    }
+}
@@ -2,7 +2,7 @@ use yew::{function_component, html, use_node_ref, Html, Properties, Callback, Mo
 use yew::prelude::*;
 use super::app_drawer::App_drawer;
 use super::gen_components::{UseScrollToTop, Search_nav, empty_message, episode_item, on_shownotes_click};
-use crate::requests::search_pods::{call_search_database, SearchRequest, SearchResponse};
+use crate::requests::search_pods::{SearchRequest, SearchResponse};
 use yewdux::prelude::*;
 use crate::components::context::{AppState, UIState};
 use yew_router::history::BrowserHistory;
@@ -103,9 +103,8 @@ pub fn search(_props: &SearchProps) -> Html {
     let container_ref = use_node_ref();
     let container_ref_clone1 = container_ref.clone();
 
-    let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = post_state.client();
     let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
 
     // let on_click = Callback::from(move |_| {
     //     if let Some(form) = input_ref_clone1.cast::<HtmlElement>() {
@@ -113,9 +112,8 @@ pub fn search(_props: &SearchProps) -> Html {
     //     }
     // });
 
-    let api_key_submit = api_key.clone();
+    let client_submit = client.clone();
     let user_id_submit = user_id.clone();
-    let server_name_submit = server_name.clone();
 
 
     let on_submit = Callback::from(move |event: SubmitEvent| {
@@ -132,8 +130,7 @@ pub fn search(_props: &SearchProps) -> Html {
         }
 
         // Clone the necessary variables
-        let server_name_submit = server_name_submit.clone();
-        let api_key_submit = api_key_submit.clone();
+        let client_submit = client_submit.clone();
         let user_id_submit = user_id_submit.clone();
         // let search_results = search_results_clone.clone();
         let mut search_request = None;
@@ -154,7 +151,7 @@ pub fn search(_props: &SearchProps) -> Html {
             }
             if let Some(search_request) = search_request {
                 let dispatch = future_dispatch.clone();
-                match call_search_database(&server_name_submit.unwrap(), &api_key_submit.flatten(), &search_request).await {
+                match client_submit.search_database(&search_request).await {
                     Ok(results) => {
                         dispatch.reduce_mut(move |state| {
                             state.search_episodes = Some(SearchResponse { data: results });
@@ -251,23 +248,23 @@ pub fn search(_props: &SearchProps) -> Html {
                                     let listener_duration_for_closure = episode_listened_clone.clone();
 
                                     let user_id_play = user_id.clone();
-                                    let server_name_play = server_name.clone();
-                                    let api_key_play = api_key.clone();
+                                    let client_play = client.clone();
                                     let audio_dispatch = audio_dispatch.clone();
         
                                     let on_play_click = on_play_click(
                                         episode_url_for_closure.clone(),
                                         episode_title_for_closure.clone(),
+                                        episode.PodcastName.clone(),
                                         episode_artwork_for_closure.clone(),
                                         episode_duration_for_closure.clone(),
                                         episode_id_for_closure.clone(),
                                         listener_duration_for_closure.clone(),
-                                        api_key_play.unwrap().unwrap(),
+                                        client_play.clone(),
                                         user_id_play.unwrap(),
-                                        server_name_play.unwrap(),
                                         audio_dispatch.clone(),
                                         audio_state.clone(),
                                         None,
+                                        None,
                                     );
 
                                     let on_shownotes_click = on_shownotes_click(
@@ -312,7 +309,7 @@ pub fn search(_props: &SearchProps) -> Html {
             <App_drawer />
             {
                 if let Some(audio_props) = &audio_state.currently_playing {
-                    html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
+                    html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} podcast_name={audio_props.podcast_name.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
                 } else {
                     html! {}
                 }
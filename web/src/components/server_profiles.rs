@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use web_sys::window;
+use yew_router::history::{BrowserHistory, History};
+use yewdux::prelude::*;
+use crate::components::context::{AppState, UIState};
+use crate::requests::login_requests::{GetApiDetails, GetUserDetails, LoginServerRequest};
+
+const PROFILES_KEY: &str = "serverProfiles";
+
+/// A server the user has logged into, kept so they can switch back to it without
+/// logging in again.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ServerProfile {
+    /// The password is never stored here; the API key is enough to switch back.
+    pub auth_details: LoginServerRequest,
+    pub user_details: GetUserDetails,
+    pub server_details: GetApiDetails,
+    pub gravatar_url: Option<String>,
+    pub theme: Option<String>,
+}
+
+impl ServerProfile {
+    fn from_state(state: &AppState) -> Option<Self> {
+        let mut auth_details = state.auth_details.clone()?;
+        auth_details.password.clear();
+        Some(ServerProfile {
+            auth_details,
+            user_details: state.user_details.clone()?,
+            server_details: state.server_details.clone()?,
+            gravatar_url: state.gravatar_url.clone(),
+            theme: selected_theme(),
+        })
+    }
+
+    /// Whether this profile is the same account as `auth`.
+    pub fn is_for(&self, auth: &LoginServerRequest) -> bool {
+        self.auth_details.server_name == auth.server_name && self.auth_details.username == auth.username
+    }
+
+    /// Short name for the switcher, e.g. "pine @ pods.example.com".
+    pub fn label(&self) -> String {
+        let host = self
+            .auth_details
+            .server_name
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches('/');
+        format!("{} @ {}", self.auth_details.username, host)
+    }
+}
+
+fn selected_theme() -> Option<String> {
+    window()?.local_storage().ok()??.get_item("selected_theme").ok()?
+}
+
+pub fn load_profiles() -> Vec<ServerProfile> {
+    window()
+        .and_then(|w| w.local_storage().ok().flatten())
+        .and_then(|storage| storage.get_item(PROFILES_KEY).ok().flatten())
+        .and_then(|stored| serde_json::from_str(&stored).ok())
+        .unwrap_or_default()
+}
+
+pub fn store_profiles(profiles: &[ServerProfile]) {
+    if let Some(storage) = window().and_then(|w| w.local_storage().ok().flatten()) {
+        if let Ok(serialized) = serde_json::to_string(profiles) {
+            let _ = storage.set_item(PROFILES_KEY, &serialized);
+        }
+    }
+}
+
+/// Adds or refreshes the profile for the account `state` is logged into.
+pub fn remember_profile(state: &AppState) {
+    let Some(profile) = ServerProfile::from_state(state) else { return };
+    let mut profiles = load_profiles();
+    match profiles.iter_mut().find(|p| p.is_for(&profile.auth_details)) {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+    store_profiles(&profiles);
+}
+
+/// Removes the profile for `auth`, e.g. when the user signs out of it.
+pub fn forget_profile(auth: &LoginServerRequest) {
+    let mut profiles = load_profiles();
+    profiles.retain(|p| !p.is_for(auth));
+    store_profiles(&profiles);
+}
+
+/// Opens the login page for another account. The current one stays saved as a profile,
+/// but the login page must not find its session and log straight back into it.
+pub fn add_account(state: &AppState) {
+    remember_profile(state);
+    if let Some(window) = window() {
+        if let Ok(Some(local_storage)) = window.local_storage() {
+            for key in ["userState", "userAuthState", "serverState"] {
+                let _ = local_storage.remove_item(key);
+            }
+        }
+        if let Ok(Some(session_storage)) = window.session_storage() {
+            let _ = session_storage.set_item("isAuthenticated", "false");
+        }
+    }
+    BrowserHistory::new().push("/");
+}
+
+/// Makes `profile` the active account. Everything loaded from the previous server,
+/// like the queue, search results and the playing episode, is dropped.
+pub fn switch_profile(dispatch: &Dispatch<AppState>, ui_dispatch: &Dispatch<UIState>, profile: ServerProfile) {
+    // Keep the outgoing profile's theme, which may have changed since it was saved
+    remember_profile(&dispatch.get());
+
+    if let Some(theme) = profile.theme.as_ref() {
+        crate::components::setting_components::theme_options::changeTheme(theme);
+        if let Some(storage) = window().and_then(|w| w.local_storage().ok().flatten()) {
+            let _ = storage.set_item("selected_theme", theme);
+        }
+    }
+
+    ui_dispatch.reduce_mut(|ui_state| {
+        if let Some(audio) = ui_state.audio_element.as_ref() {
+            let _ = audio.pause();
+        }
+        ui_state.audio_playing = Some(false);
+        ui_state.currently_playing = None;
+    });
+
+    let user_id = profile.user_details.UserID;
+    dispatch.reduce_mut(move |state| {
+        *state = AppState {
+            user_details: Some(profile.user_details),
+            auth_details: Some(profile.auth_details),
+            server_details: Some(profile.server_details),
+            gravatar_url: profile.gravatar_url,
+            selected_theme: profile.theme,
+            reload_occured: state.reload_occured,
+            ..AppState::default()
+        };
+        state.store_app_state();
+    });
+    BrowserHistory::new().push("/home");
+
+    // The saved key may have been revoked since; the user then has to log in again
+    let client = dispatch.get().client();
+    let dispatch = dispatch.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        if client.verify_key().await.is_err() {
+            BrowserHistory::new().push("/");
+            return;
+        }
+        if let Ok(tz_response) = client.get_time_info(&user_id).await {
+            dispatch.reduce_mut(move |state| {
+                state.user_tz = Some(tz_response.timezone);
+                state.hour_preference = Some(tz_response.hour_pref);
+                state.date_format = Some(tz_response.date_format);
+            });
+        }
+    });
+}
@@ -1,7 +1,7 @@
 use yew::prelude::*;
 use yewdux::prelude::*;
 use crate::components::context::{AppState, UIState};
-use crate::requests::setting_reqs::{call_get_api_info, call_create_api_key, call_delete_api_key, DeleteAPIRequest};
+use crate::requests::setting_reqs::DeleteAPIRequest;
 // use crate::gen_components::_ErrorMessageProps::error_message;
 
 #[function_component(APIKeys)]
@@ -10,8 +10,7 @@ pub fn api_keys() -> Html {
     let (state, _dispatch) = use_store::<AppState>();
     let (audio_state, audio_dispatch) = use_store::<UIState>();
     let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = state.client();
     let api_infos = use_state(|| Vec::new());
     let new_api_key = use_state(|| String::new());
     let selected_api_key_id: UseStateHandle<Option<i32>> = use_state(|| None);
@@ -24,27 +23,20 @@ pub fn api_keys() -> Html {
 
     {
         let api_infos = api_infos.clone();
-        let api_key = api_key.clone();
-        let server_name = server_name.clone();
         let user_id = user_id.clone();
 
-
-
-        use_effect_with((api_key, server_name), move |(api_key, server_name)| {
+        use_effect_with(client.clone(), move |client| {
             let api_infos = api_infos.clone();
-            let api_key_cloned = api_key.clone();
-            let server_name_cloned = server_name.clone();
+            let client = client.clone();
     
             wasm_bindgen_futures::spawn_local(async move {
-                if let Some(api_key) = api_key_cloned {
-                    if let Some(server_name) = server_name_cloned {
-                        match call_get_api_info(server_name, user_id.unwrap(), api_key.unwrap()).await {
-                            Ok(response) => {
-                                api_infos.set(response.api_info);
-                            },
-                            Err(e) => {
-                                audio_dispatch_effect.reduce_mut(|audio_state| audio_state.error_message = Option::from(format!("Error getting API Info: {}", e)));
-                            }
+                if client.is_authenticated() {
+                    match client.get_api_info(user_id.unwrap()).await {
+                        Ok(response) => {
+                            api_infos.set(response.api_info);
+                        },
+                        Err(e) => {
+                            audio_dispatch_effect.reduce_mut(|audio_state| audio_state.error_message = Option::from(format!("Error getting API Info: {}", e)));
                         }
                     }
                 }
@@ -80,17 +72,15 @@ pub fn api_keys() -> Html {
     let request_api_key = {
         let page_state = page_state.clone();
         let new_api_key = new_api_key.clone();
-        let api_key = api_key.clone();
-        let server_name = server_name.clone();
+        let client = client.clone();
         Callback::from(move |_| {
             let audio_dispatch = audio_dispatch.clone();
-            let api_key = api_key.clone();
+            let client = client.clone();
             let user_id = request_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-            let server_name = server_name.clone();
             let page_state = page_state.clone();
             let new_api_key = new_api_key.clone();
             wasm_bindgen_futures::spawn_local(async move {
-                match call_create_api_key(&server_name.unwrap(), user_id.unwrap(), &api_key.unwrap().unwrap()).await {
+                match client.create_api_key(user_id.unwrap()).await {
                     Ok(response) => {
                         new_api_key.set(response.api_key);
                         page_state.set(PageState::Shown); // Move to the edit page state
@@ -106,16 +96,14 @@ pub fn api_keys() -> Html {
     // Define the function to open the modal and request a new API key
     let delete_api_key = {
         let page_state = page_state.clone();
-        let api_key = api_key.clone();
-        let server_name = server_name.clone();
+        let client = client.clone();
         let api_id = selected_api_key_id.clone();
         // Assume you have user_id and api_key from context or props
         let user_id = 1; // Example user_id
         Callback::from(move |_| {
             let audio_dispatch = audio_dispatch_call.clone();
-            let api_key = api_key.clone();
+            let client = client.clone();
             // let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-            let server_name = server_name.clone();
             let page_state = page_state.clone();
             let user_id = user_id.clone();
             let api_id = api_id.clone();
@@ -124,7 +112,7 @@ pub fn api_keys() -> Html {
                 api_id: api_id.unwrap().to_string(),
             };
             wasm_bindgen_futures::spawn_local(async move {
-                match call_delete_api_key(&server_name.unwrap(), delete_body, &api_key.unwrap().unwrap()).await {
+                match client.delete_api_key(&delete_body).await {
                     Ok(_) => {
                         audio_dispatch.reduce_mut(|audio_state| audio_state.info_message = Option::from(format!("API key deleted successfully")));
                         // Update UI accordingly, e.g., remove the deleted API key from the list
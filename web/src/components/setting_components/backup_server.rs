@@ -4,21 +4,18 @@ use web_sys::{window, Blob, Url, BlobPropertyBag};
 use wasm_bindgen::JsValue;
 use yewdux::prelude::*;
 use crate::components::context::{UIState, AppState};
-use crate::requests::setting_reqs::call_backup_server;
 
 #[function_component(BackupServer)]
 pub fn backup_server() -> Html {
     let database_password = use_state(|| "".to_string());
     let (state, _dispatch) = use_store::<AppState>();
     let (_audio_state, audio_dispatch) = use_store::<UIState>();
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
+    let client = state.client();
     let blob_property_bag = BlobPropertyBag::new();
 
     let on_download_click = {
         let database_password = database_password.clone();
-        let api_key = api_key.clone();
-        let server_name = server_name.clone();
+        let client = client.clone();
         let blob_property_bag = blob_property_bag.clone();
         let audio_dispatch_call = audio_dispatch.clone();
         Callback::from(move |_| {
@@ -29,12 +26,11 @@ pub fn backup_server() -> Html {
                 audio_dispatch.reduce_mut(|audio_state| audio_state.error_message = Option::from("Database password cannot be empty.".to_string()));
                 return;
             }
-            let api_key = api_key.clone().unwrap_or_default();
-            let server_name = server_name.clone().unwrap_or_default();
+            let client = client.clone();
             let bloberty_bag = blob_property_bag.clone();
 
             wasm_bindgen_futures::spawn_local(async move {
-                match call_backup_server(&server_name, &db_pass, &api_key.unwrap()).await {
+                match client.backup_server(&db_pass).await {
                     Ok(backup_data) => {
                         let array = js_sys::Array::new();
                         array.push(&JsValue::from_str(&backup_data));
@@ -1,7 +1,6 @@
 use yew::prelude::*;
 use yewdux::prelude::*;
 use crate::components::context::AppState;
-use crate::requests::setting_reqs::call_add_custom_feed;
 use web_sys::HtmlInputElement;
 use gloo_timers::callback::Timeout;
 
@@ -13,9 +12,8 @@ pub fn custom_feed() -> Html {
 
     // API key, server name, and other data can be fetched from AppState if required
     let (state, _) = use_store::<AppState>();
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = state.client();
     let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
 
     // Correct setup for `on_password_change`
     let update_feed = {
@@ -42,8 +40,7 @@ pub fn custom_feed() -> Html {
 
     // Ensure `onclick_restore` is correctly used
     let add_custom_feed = {
-        let api_key = api_key.unwrap_or_default();
-        let server_name = server_name.unwrap_or_default();
+        let client = client.clone();
         let user_id = user_id;
         let feed_url = (*feed_url).clone();
         let error_message = error_message.clone();
@@ -53,13 +50,12 @@ pub fn custom_feed() -> Html {
         Callback::from(move |_| {
             let clear_info = clear_info.clone();
             let clear_error = clear_error.clone();
-            let server_name = server_name.clone();
-            let api_key = api_key.clone();
+            let client = client.clone();
             let feed_url = feed_url.clone();
             let error_message = error_message.clone();
             let info_message = info_message.clone();
             wasm_bindgen_futures::spawn_local(async move {
-                match call_add_custom_feed(&server_name, &feed_url, &user_id.unwrap(), &api_key.unwrap()).await {
+                match client.add_custom_feed(&feed_url, &user_id.unwrap()).await {
                     Ok(message) => {
                         info_message.set(Some(message));
                         Timeout::new(5000, move || { clear_info.emit(()) }).forget();
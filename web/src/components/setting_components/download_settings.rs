@@ -2,29 +2,26 @@ use yew::prelude::*;
 use yewdux::prelude::*;
 use crate::components::context::{AppState, UIState};
 use yew::platform::spawn_local;
-use crate::requests::setting_reqs::{call_download_status, call_enable_disable_downloads};
 use std::borrow::Borrow;
 
 #[function_component(DownloadSettings)]
 pub fn download_settings() -> Html {
     let (state, _dispatch) = use_store::<AppState>();
     let (_audio_state, audio_dispatch) = use_store::<UIState>();
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = state.client();
     let _user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
     let _error_message = state.error_message.clone();
     let download_status = use_state(|| false);
     let audio_dispatch_effect = audio_dispatch.clone();
 
     {
         let download_status = download_status.clone();
-        use_effect_with((api_key.clone(), server_name.clone()), move |(api_key, server_name)| {
+        use_effect_with(client.clone(), move |client| {
             let download_status = download_status.clone();
-            let api_key = api_key.clone();
-            let server_name = server_name.clone();
+            let client = client.clone();
             let future = async move {
-                if let (Some(api_key), Some(server_name)) = (api_key, server_name) {
-                    let response = call_download_status(server_name, api_key.unwrap()).await;
+                if client.is_authenticated() {
+                    let response = client.download_status().await;
                     match response {
                         Ok(download_status_response) => {
                             download_status.set(download_status_response);
@@ -51,15 +48,14 @@ pub fn download_settings() -> Html {
             <p class="item_container-text text-md mb-4">{"You can choose to enable or disable server downloads here. This does not effect local downloads. There's two types of downloads in Pinepods. Local and Server. Local downloads would be where a user clicks download and it downloads the podcast to their local machine. A server download is when a user downloads the podcast to the server specifically. This is meant as an archival option. If you're concerned the podcast may not be always available you may want to archive it using this option. See the Pinepods documentation for mapping a specific location (like a NAS) as the location server downloads download to. You might want to turn this option off if you have self service enabled or your Pinepods server accessible to the internet. You wouldn't want any random user filling up your server."}</p> // Styled paragraph
             <label class="relative inline-flex items-center cursor-pointer">
             <input type="checkbox" disabled={**loading.borrow()} checked={**download_status.borrow()} class="sr-only peer" onclick={Callback::from(move |_| {
-                let api_key = api_key.clone();
-                let server_name = server_name.clone();
+                let client = client.clone();
                 let download_status = html_download.clone();
                 let audio_dispatch = audio_dispatch.clone();
                 let loading = loading.clone();
                 let future = async move {
                     loading.set(true);
-                    if let (Some(api_key), Some(server_name)) = (api_key, server_name) {
-                        let response = call_enable_disable_downloads(server_name, api_key.unwrap()).await;
+                    if client.is_authenticated() {
+                        let response = client.enable_disable_downloads().await;
                         match response {
                             Ok(_) => {
                                 let current_status = download_status.borrow().clone();
@@ -2,7 +2,7 @@ use yew::prelude::*;
 use yewdux::prelude::*;
 use crate::components::context::{AppState, UIState};
 use yew::platform::spawn_local;
-use crate::requests::setting_reqs::{call_get_email_settings, EmailSettingsResponse, SendEmailSettings, call_save_email_settings, call_send_test_email, call_send_email, TestEmailSettings};
+use crate::requests::setting_reqs::{EmailSettingsResponse, SendEmailSettings, TestEmailSettings};
 use std::ops::Deref;
 // use crate::gen_components::_ErrorMessageProps::error_message;
 
@@ -10,8 +10,7 @@ use std::ops::Deref;
 pub fn email_settings() -> Html {
     let (state, _dispatch) = use_store::<AppState>();
     let (audio_state, audio_dispatch) = use_store::<UIState>();
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
+    let client = state.client();
     let _user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
     let user_email = state.user_details.as_ref().map(|ud| ud.Email.clone());
     let _error_message = audio_state.error_message.clone();
@@ -28,13 +27,12 @@ pub fn email_settings() -> Html {
 
     {
         let email_values = email_values.clone();
-        use_effect_with((api_key.clone(), server_name.clone()), move |(api_key, server_name)| {
+        use_effect_with(client.clone(), move |client| {
             let email_values = email_values.clone();
-            let api_key = api_key.clone();
-            let server_name = server_name.clone();
+            let client = client.clone();
             let future = async move {
-                if let (Some(api_key), Some(server_name)) = (api_key, server_name) {
-                    let response = call_get_email_settings(server_name, api_key.unwrap()).await;
+                if client.is_authenticated() {
+                    let response = client.get_email_settings().await;
                     match response {
                         Ok(email_info) => {
                             email_values.set(email_info);
@@ -129,8 +127,7 @@ pub fn email_settings() -> Html {
             page_state.set(PageState::Hidden);
         })
     };
-    let edit_api_key = api_key.clone();
-    let edit_server_name = server_name.clone();
+    let edit_client = client.clone();
 
     let on_edit_submit = {
         let server_name_ref = server_name_ref.clone();
@@ -144,7 +141,6 @@ pub fn email_settings() -> Html {
         let page_state = page_state.clone();
         let audio_dispatch_call = audio_dispatch.clone();
         Callback::from(move |_: MouseEvent| {
-            let server_name = edit_server_name.clone();
             let server_name_ref = server_name_ref.clone().deref().to_string();
             // let server_name = server_name_ref.clone().deref().to_string();
             let server_port = server_port_ref.clone().deref().to_string();
@@ -166,9 +162,9 @@ pub fn email_settings() -> Html {
                 email_password: email_password.clone(),
             };
             // let server_name = server_name_ref.deref().clone();
-            let api_key = edit_api_key.clone().unwrap_or_default();
+            let client = edit_client.clone();
             let future = async move {
-                let _ = call_save_email_settings(server_name.unwrap(), api_key.unwrap().clone(), email_settings).await;
+                let _ = client.save_email_settings(email_settings).await;
             };
             spawn_local(future);
             page_state.set(PageState::Hidden);
@@ -206,10 +202,9 @@ pub fn email_settings() -> Html {
         </div>
     };
     let audio_send_test = audio_dispatch.clone();
-    let api_test = api_key.clone();
+    let client_test = client.clone();
     let submit_email = user_email.clone();
     let on_submit = {
-        let server_name = server_name.clone();
         let server_port_ref = server_port_ref.clone();
         let from_email_ref = from_email_ref.clone();
         let send_mode_ref = send_mode_ref.clone();
@@ -220,7 +215,6 @@ pub fn email_settings() -> Html {
         let page_state = page_state_edit.clone();
         Callback::from(move |_: MouseEvent| {
             let audio_dispatch_call = audio_send_test.clone();
-            let server_name = server_name.clone();
             let server_name_ref = server_name_ref.clone().deref().to_string();
             let server_port = server_port_ref.clone().deref().to_string();
             let from_email = from_email_ref.clone().deref().clone();
@@ -242,10 +236,9 @@ pub fn email_settings() -> Html {
                 to_email: submit_email.clone().unwrap().unwrap(),
                 message: "If you got this email Pinepods emailing works! Be sure to verify your settings to confirm!".to_string(),
             };
-            let server_name = server_name.clone();
-            let api_key = api_test.clone().unwrap_or_default();
+            let client = client_test.clone();
             let future = async move {
-                let send_email_result = call_send_test_email(server_name.clone().unwrap(), api_key.clone().unwrap(), test_email_settings.clone()).await;
+                let send_email_result = client.send_test_email(&test_email_settings).await;
                 match send_email_result {
                     Ok(_) => {
                         page_state.set(PageState::Shown);
@@ -261,13 +254,11 @@ pub fn email_settings() -> Html {
     };
 
     let on_test_email_send = {
-        let server_name = server_name.clone(); // Assuming you have these values in your component's state
-        let api_key = api_key.clone(); // Assuming you have API key in your component's state
+        let client = client.clone();
         let audio_dispatch_call = audio_dispatch.clone();
         
         Callback::from(move |_: MouseEvent| {
-            let api_key = api_key.clone();
-            let server_name = server_name.clone().unwrap_or_default(); // Ensure server_name has a default value if it's an Option
+            let client = client.clone();
             let audio_dispatch_call = audio_dispatch_call.clone();
             // Setting up the email settings. Adjust these values as necessary.
             let email_settings = SendEmailSettings {
@@ -277,7 +268,7 @@ pub fn email_settings() -> Html {
             };
     
             let future = async move {
-                match call_send_email(server_name, api_key.unwrap_or_default().unwrap(), email_settings).await {
+                match client.send_email(&email_settings).await {
                     Ok(_) => {
                         audio_dispatch_call.reduce_mut(|audio_state| audio_state.info_message = Option::from("Email sent successfully!".to_string()));
                         // Optionally, use dispatch_callback to update a global state or trigger other app-wide effects
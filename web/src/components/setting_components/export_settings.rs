@@ -4,15 +4,13 @@ use yewdux::prelude::*;
 use crate::components::context::{AppState, UIState};
 use web_sys::{window, Blob, Url, BlobPropertyBag};
 use wasm_bindgen::JsValue;
-use crate::requests::setting_reqs::{call_backup_user};
 
 #[function_component(ExportOptions)]
 pub fn export_options() -> Html {
     let (state, _dispatch) = use_store::<AppState>();
     let (_audio_state, audio_dispatch) = use_store::<UIState>();
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = state.client();
     let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
         
     let mut blob_property_bag = BlobPropertyBag::new();
     blob_property_bag.type_("text/xml");
@@ -22,10 +20,9 @@ pub fn export_options() -> Html {
         Callback::from(move |_| {
             let audio_dispatch = audio_dispatch.clone();
             let bloberty_bag = blob_property_bag.clone();
-            let api_key = api_key.clone();
-            let server_name = server_name.clone();
+            let client = client.clone();
             wasm_bindgen_futures::spawn_local(async move {
-                match call_backup_user(&server_name.unwrap(), user_id.unwrap(), &api_key.unwrap().unwrap()).await {
+                match client.backup_user(user_id.unwrap()).await {
                     Ok(opml_content) => {
                         // Wrap the OPML content in an array and convert to JsValue
                         let array = js_sys::Array::new();
@@ -2,7 +2,6 @@ use yew::prelude::*;
 use yewdux::prelude::*;
 use crate::components::context::{AppState, UIState};
 use yew::platform::spawn_local;
-use crate::requests::setting_reqs::{call_guest_status, call_enable_disable_guest};
 use std::borrow::Borrow;
 
 
@@ -10,22 +9,20 @@ use std::borrow::Borrow;
 pub fn guest_settings() -> Html {
     let (state, _dispatch) = use_store::<AppState>();
     let (_audio_state, audio_dispatch) = use_store::<UIState>();
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = state.client();
     let _user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
     let _error_message = state.error_message.clone();
     let guest_status = use_state(|| false);
     let audio_dispatch_effect = audio_dispatch.clone();
 
     {
         let guest_status = guest_status.clone();
-        use_effect_with((api_key.clone(), server_name.clone()), move |(api_key, server_name)| {
+        use_effect_with(client.clone(), move |client| {
             let guest_status = guest_status.clone();
-            let api_key = api_key.clone();
-            let server_name = server_name.clone();
+            let client = client.clone();
             let future = async move {
-                if let (Some(api_key), Some(server_name)) = (api_key, server_name) {
-                    let response = call_guest_status(server_name, api_key.unwrap()).await;
+                if client.is_authenticated() {
+                    let response = client.guest_status().await;
                     match response {
                         Ok(guest_status_response) => {
                             guest_status.set(guest_status_response);
@@ -54,14 +51,13 @@ pub fn guest_settings() -> Html {
             <label class="relative inline-flex items-center cursor-pointer">
             <input type="checkbox" disabled={**loading.borrow()} checked={**guest_status.borrow()} class="sr-only peer" onclick={Callback::from(move |_| {
                 let audio_dispatch = audio_dispatch.clone();
-                let api_key = api_key.clone();
-                let server_name = server_name.clone();
+                let client = client.clone();
                 let guest_status = html_guest.clone();
                 let loading = loading.clone();
                 let future = async move {
                     loading.set(true);
-                    if let (Some(api_key), Some(server_name)) = (api_key, server_name) {
-                        let response = call_enable_disable_guest(server_name, api_key.unwrap()).await;
+                    if client.is_authenticated() {
+                        let response = client.enable_disable_guest().await;
                         match response {
                             Ok(_) => {
                                 let current_status = guest_status.borrow().clone();
@@ -6,7 +6,8 @@ use crate::components::context::{UIState, AppState};
 use web_sys::{FileReader, HtmlInputElement};
 use wasm_bindgen::closure::Closure;
 use crate::components::gen_funcs::parse_opml;
-use crate::requests::pod_req::{call_add_podcast, PodcastValues};
+use crate::requests::client::PinepodsClient;
+use crate::requests::pod_req::PodcastValues;
 use crate::requests::search_pods::{call_parse_podcast_channel_info, PodcastInfo};
 
 
@@ -58,7 +59,7 @@ struct PodcastToImport {
 }
 
 
-async fn add_podcasts(server_name: &str, api_key: &Option<String>, user_id: i32, podcasts: Vec<PodcastToAdd>) {
+async fn add_podcasts(client: &PinepodsClient, user_id: i32, podcasts: Vec<PodcastToAdd>) {
     for podcast in podcasts.into_iter() {
         // Parse podcast URL to get feed details
         match call_parse_podcast_channel_info(&podcast.xml_url).await {
@@ -71,7 +72,7 @@ async fn add_podcasts(server_name: &str, api_key: &Option<String>, user_id: i32,
                 let podcast_values = transform_feed_result_to_values(feed_result, &add_podcast, user_id);
 
                 // Add podcast to the server
-                match call_add_podcast(server_name, api_key, user_id, &podcast_values).await {
+                match client.add_podcast(&podcast_values).await {
                     Ok(_) => log::info!("Podcast added successfully: {}", podcast.title.clone()),
                     Err(e) => log::error!("Failed to add podcast {}: {:?}", podcast.title.clone(), e),
                 }
@@ -87,8 +88,7 @@ async fn add_podcasts(server_name: &str, api_key: &Option<String>, user_id: i32,
 pub fn import_options() -> Html {
     let (state, _dispatch) = use_store::<AppState>();
     let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = state.client();
     let import_pods = use_state(|| Vec::new());
     let show_verification = use_state(|| false);
     let (_audio_state, audio_dispatch) = use_store::<UIState>();
@@ -125,18 +125,15 @@ pub fn import_options() -> Html {
         })
     };
     
-    let server_name_confirm = server_name.clone();
     let dispatch_wasm = _dispatch.clone();
     let on_confirm = {
         let import_pods = import_pods.clone();
-        let server_name = server_name_confirm.clone();
-        let api_key = api_key.clone();
+        let client = client.clone();
         let user_id = user_id.clone();
         Callback::from(move |_| {
             dispatch_wasm.reduce_mut(|state| state.is_loading = Some(true));
             // Filter for selected podcasts
-            let server_name = server_name.clone();
-            let api_key = api_key.clone();
+            let client = client.clone();
             let dispatch_wasm = dispatch_wasm.clone();
             let audio_dispatch = audio_dispatch.clone();
             let selected_podcasts: Vec<PodcastToAdd> = (*import_pods)
@@ -147,8 +144,8 @@ pub fn import_options() -> Html {
     
             wasm_bindgen_futures::spawn_local(async move {
                 // Your existing logic to add podcasts
-                if let (Some(server_name), Some(api_key), Some(user_id)) = (server_name.as_ref(), api_key.as_ref(), user_id) {
-                    add_podcasts(server_name, &Some(api_key.clone().unwrap()), user_id, selected_podcasts.clone()).await;
+                if let (true, Some(user_id)) = (client.is_authenticated(), user_id) {
+                    add_podcasts(&client, user_id, selected_podcasts.clone()).await;
                 }
                 dispatch_wasm.reduce_mut(|state| state.is_loading = Some(false));
                 audio_dispatch.reduce_mut(|audio_state| audio_state.info_message = Option::from("Selected Podcasts Added!".to_string()));
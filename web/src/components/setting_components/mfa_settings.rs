@@ -3,7 +3,6 @@ use yewdux::prelude::*;
 use crate::components::context::{AppState, UIState};
 use yew::platform::spawn_local;
 use crate::components::episodes_layout::SafeHtml;
-use crate::requests::setting_reqs::{call_mfa_settings, call_generate_mfa_secret, call_verify_temp_mfa};
 use std::borrow::Borrow;
 
 
@@ -11,27 +10,24 @@ use std::borrow::Borrow;
 pub fn mfa_options() -> Html {
     let (state, _dispatch) = use_store::<AppState>();
     let (_audio_state, audio_dispatch) = use_store::<UIState>();
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = state.client();
     let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
     let _error_message = state.error_message.clone();
     let mfa_status = use_state(|| false);
     let code = use_state(|| "".to_string());
 
     let effect_user_id = user_id.clone();
-    let effect_api_key = api_key.clone();
-    let effect_server_name = server_name.clone();
+    let effect_client = client.clone();
     let audio_dispatch_effect = audio_dispatch.clone();
     {
         let mfa_status = mfa_status.clone();
-        use_effect_with((effect_api_key.clone(), effect_server_name.clone()), move |(_api_key, _server_name)| {
+        use_effect_with(client.clone(), move |_| {
             let mfa_status = mfa_status.clone();
-            let api_key = effect_api_key.clone();
-            let server_name = effect_server_name.clone();
+            let client = effect_client.clone();
             let user_id = effect_user_id.clone();
             let future = async move {
-                if let (Some(api_key), Some(server_name)) = (api_key, server_name) {
-                    let response = call_mfa_settings(server_name, api_key.unwrap(), user_id.unwrap()).await;
+                if client.is_authenticated() {
+                    let response = client.mfa_settings(user_id.unwrap()).await;
                     match response {
                         Ok(mfa_settings_response) => {
                             mfa_status.set(mfa_settings_response);
@@ -75,21 +71,19 @@ pub fn mfa_options() -> Html {
         let mfa_code = mfa_code.clone();
         let page_state = page_state.clone();
         let mfa_secret = mfa_secret.clone();
-        let server_name = server_name.clone(); // Replace with actual server name
-        let api_key = api_key.clone(); // Replace with actual API key
+        let client = client.clone();
         let user_id = user_id.clone(); // Replace with actual user ID
     
         Callback::from(move |_| {
             let mfa_code = mfa_code.clone();
             let page_state = page_state.clone();
             let mfa_secret = mfa_secret.clone();
-            let server_name = server_name.clone();
-            let api_key = api_key.clone();
+            let client = client.clone();
             let user_id = user_id;
     
             // Now call the API to generate the TOTP secret
             wasm_bindgen_futures::spawn_local(async move {
-                match call_generate_mfa_secret(server_name.unwrap(), api_key.unwrap().unwrap(), user_id.unwrap()).await {
+                match client.generate_mfa_secret(user_id.unwrap()).await {
                     Ok(response) => {
                         mfa_secret.set(response.secret);
                         mfa_code.set(response.qr_code_svg); // Directly use the SVG QR code
@@ -107,15 +101,13 @@ pub fn mfa_options() -> Html {
     // Define the function to close the modal
     let verify_code = {
         let page_state = page_state.clone();
-        let api_key = api_key.clone();
+        let client = client.clone();
         let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-        let server_name = server_name.clone();
         let code = code.clone();
 
         Callback::from(move |_| {
-            let api_key = api_key.clone();
+            let client = client.clone();
             let user_id = user_id.clone();
-            let server_name = server_name.clone();
             let page_state = page_state.clone();
             let code = code.clone();
             let audio_dispatch = audio_dispatch.clone();
@@ -123,7 +115,7 @@ pub fn mfa_options() -> Html {
 
             wasm_bindgen_futures::spawn_local(async move {
 
-                match call_verify_temp_mfa(&server_name.unwrap(), &api_key.unwrap().unwrap(), user_id.unwrap(), (*code).clone()).await {
+                match client.verify_temp_mfa(user_id.unwrap(), (*code).clone()).await {
                     Ok(response) => {
                         if response.verified {
                             // Handle successful verification, e.g., updating UI state or navigating
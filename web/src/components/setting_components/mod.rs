@@ -1,4 +1,5 @@
 pub mod theme_options;
+pub mod playback_settings;
 pub mod mfa_settings;
 pub mod export_settings;
 pub mod import_options;
@@ -2,7 +2,7 @@ use serde::Deserialize;
 use yew::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{Request, RequestInit, RequestMode, Response, HtmlInputElement, console};
-use crate::{requests::setting_reqs::{call_add_nextcloud_server, call_check_nextcloud_server, call_get_nextcloud_server, NextcloudAuthRequest, initiate_nextcloud_login, NextcloudInitiateResponse}};
+use crate::{requests::setting_reqs::{NextcloudAuthRequest, NextcloudInitiateResponse}};
 use wasm_bindgen_futures::JsFuture;
 use yewdux::use_store;
 use crate::components::context::{AppState, UIState};
@@ -79,9 +79,8 @@ async fn open_nextcloud_login(url: &str) -> Result<(), JsValue> {
 pub fn nextcloud_options() -> Html {
     let (state, _dispatch) = use_store::<AppState>();
     let (audio_state, audio_dispatch) = use_store::<UIState>();
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = state.client();
     let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
     let server_url = use_state(|| String::new());
     let auth_status = use_state(|| String::new());
     let nextcloud_url = use_state(|| String::new()); // State to hold the Nextcloud server URL
@@ -102,15 +101,14 @@ pub fn nextcloud_options() -> Html {
     {
         let nextcloud_url = nextcloud_url.clone();
         let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-        let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
-        let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
+        let client = state.client();
 
         use_effect_with(&(), move |_| {
             let nextcloud_url = nextcloud_url.clone();
             let user_id = user_id.clone().unwrap_or_default(); // Make sure user_id is available
 
             wasm_bindgen_futures::spawn_local(async move {
-                match call_get_nextcloud_server(&server_name.clone().unwrap(), &api_key.clone().unwrap().unwrap(), user_id).await {
+                match client.get_nextcloud_server(user_id).await {
                     Ok(server) => {
                         nextcloud_url.set(server);
                     },
@@ -129,23 +127,21 @@ pub fn nextcloud_options() -> Html {
         let server_url = server_url.clone();
         let server_url_initiate = server_url.clone();
         // let audio_dispatch = audio_dispatch.clone();
-        let server_name = server_name.clone();
-        let api_key = api_key.clone();
+        let client = client.clone();
         let user_id = user_id.clone();
         let auth_status = auth_status.clone();
         Callback::from(move |_| {
             let audio_dispatch = audio_dispatch.clone();
             let auth_status = auth_status.clone();
             let server = (*server_url_initiate).clone().trim().to_string();
-            let server_name = server_name.clone();
-            let api_key = api_key.clone();
+            let client = client.clone();
             let user_id = user_id.clone();
 
 
 
             if !server.trim().is_empty() {
                 wasm_bindgen_futures::spawn_local(async move {
-                    match initiate_nextcloud_login(&server, &server_name.clone().unwrap(), &api_key.clone().unwrap().unwrap(), user_id.clone().unwrap()).await {
+                    match client.initiate_nextcloud_login(&server, user_id.clone().unwrap()).await {
                         Ok(login_data) => {
                             match open_nextcloud_login(&login_data.login).await {
                                 Ok(_) => println!("Opened login URL in new tab"),
@@ -158,12 +154,12 @@ pub fn nextcloud_options() -> Html {
                                 poll_endpoint: login_data.poll.endpoint,
                                 nextcloud_url: server.clone(),
                             };
-                            match call_add_nextcloud_server(&server_name.clone().unwrap(), &api_key.clone().unwrap().unwrap(), auth_request).await {
+                            match client.add_nextcloud_server(&auth_request).await {
                                 Ok(_) => {
                                     log::info!("pinepods server now polling nextcloud");
                                     // Start polling the check_gpodder_settings endpoint
                                     loop {
-                                        match call_check_nextcloud_server(&server_name.clone().unwrap(), &api_key.clone().unwrap().unwrap(), user_id.clone().unwrap()).await {
+                                        match client.check_nextcloud_server(user_id.clone().unwrap()).await {
                                             Ok(response) => {
                                                 if response.data {
                                                     log::info!("gPodder settings have been set up");
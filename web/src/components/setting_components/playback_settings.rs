@@ -0,0 +1,297 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yewdux::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use crate::components::context::{AppState, UIState};
+use crate::components::prefetch::{data_saver, set_data_saver};
+use crate::requests::login_requests::Capability;
+use crate::requests::setting_reqs::{key_label, AudioEffects, CompletionThreshold, PlaybackSettings, QueuePlayback, SetPlaybackSettingsRequest, ShortcutAction};
+
+// Keys that only modify others, or that keep their usual meaning while capturing
+const UNBINDABLE_KEYS: [&str; 6] = ["Shift", "Control", "Alt", "Meta", "Tab", "Escape"];
+
+#[function_component(PlaybackOptions)]
+pub fn playback_options() -> Html {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (audio_state, audio_dispatch) = use_store::<UIState>();
+    let draft = use_state(|| audio_state.playback_settings.clone());
+    // The action waiting for a key press, if any
+    let capturing: UseStateHandle<Option<ShortcutAction>> = use_state(|| None);
+    // Kept on this device rather than with the account, so it takes effect straight away
+    let saving_data = use_state(data_saver);
+    let audio_processing = state
+        .server_details
+        .as_ref()
+        .map_or(false, |details| details.supports(Capability::AudioProcessing));
+    let continuous_playback = state
+        .server_details
+        .as_ref()
+        .map_or(false, |details| details.supports(Capability::ContinuousPlayback));
+    let completion = state
+        .server_details
+        .as_ref()
+        .map_or(false, |details| details.supports(Capability::Completion));
+
+    {
+        let draft = draft.clone();
+        use_effect_with(audio_state.playback_settings.clone(), move |settings| {
+            draft.set(settings.clone());
+            || ()
+        });
+    }
+
+    let on_skip_input = |back: bool| {
+        let draft = draft.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let seconds = input.value_as_number();
+            if seconds.is_finite() && seconds >= 1.0 {
+                let mut settings = (*draft).clone();
+                let seconds = seconds.min(300.0) as u32;
+                if back {
+                    settings.skip_back = seconds;
+                } else {
+                    settings.skip_forward = seconds;
+                }
+                draft.set(settings);
+            }
+        })
+    };
+
+    let on_effect_toggle = |toggle: fn(&mut AudioEffects)| {
+        let draft = draft.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut settings = (*draft).clone();
+            toggle(&mut settings.audio_effects);
+            draft.set(settings);
+        })
+    };
+
+    let on_queue_toggle = |toggle: fn(&mut QueuePlayback)| {
+        let draft = draft.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut settings = (*draft).clone();
+            toggle(&mut settings.queue_playback);
+            draft.set(settings);
+        })
+    };
+
+    let on_completion_input = |set: fn(&mut CompletionThreshold, u32), min: f64, max: f64| {
+        let draft = draft.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let value = input.value_as_number();
+            if value.is_finite() && value >= min {
+                let mut settings = (*draft).clone();
+                set(&mut settings.completion, value.min(max) as u32);
+                draft.set(settings);
+            }
+        })
+    };
+
+    let on_data_saver_toggle = {
+        let saving_data = saving_data.clone();
+        Callback::from(move |_: MouseEvent| {
+            set_data_saver(!*saving_data);
+            saving_data.set(!*saving_data);
+        })
+    };
+
+    let on_capture_key = {
+        let draft = draft.clone();
+        let capturing = capturing.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            let Some(action) = *capturing else { return };
+            let key = e.key();
+            if key == "Tab" {
+                return;
+            }
+            // Keep the key press from reaching the player's own shortcuts
+            e.prevent_default();
+            e.stop_propagation();
+            if !UNBINDABLE_KEYS.contains(&key.as_str()) {
+                let mut settings = (*draft).clone();
+                settings.bind(action, &key);
+                draft.set(settings);
+            }
+            if key != "Shift" {
+                capturing.set(None);
+            }
+        })
+    };
+
+    let on_reset = {
+        let draft = draft.clone();
+        Callback::from(move |_: MouseEvent| draft.set(PlaybackSettings::default()))
+    };
+
+    let on_submit = {
+        let draft = draft.clone();
+        let client = state.client();
+        let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+        Callback::from(move |_: MouseEvent| {
+            let Some(user_id) = user_id else { return };
+            let settings = (*draft).clone();
+            let request = SetPlaybackSettingsRequest {
+                user_id,
+                skip_back: settings.skip_back,
+                skip_forward: settings.skip_forward,
+                key_bindings: settings.key_bindings.clone(),
+                audio_effects: settings.audio_effects,
+                queue_playback: settings.queue_playback,
+                completion: settings.completion,
+            };
+            let client = client.clone();
+            let audio_dispatch = audio_dispatch.clone();
+            spawn_local(async move {
+                match client.set_playback_settings(&request).await {
+                    Ok(()) => audio_dispatch.reduce_mut(|audio_state| {
+                        audio_state.playback_settings = settings;
+                        audio_state.info_message = Some("Playback settings updated!".to_string());
+                    }),
+                    Err(e) => audio_dispatch.reduce_mut(|audio_state| {
+                        audio_state.error_message = Some(format!("Error updating playback settings: {}", e));
+                    }),
+                }
+            });
+        })
+    };
+
+    let rows = ShortcutAction::ALL
+        .iter()
+        .map(|&action| {
+            let keys = draft
+                .keys_for(action)
+                .into_iter()
+                .map(|key| {
+                    let draft = draft.clone();
+                    let bound = key.to_string();
+                    let remove = Callback::from(move |_: MouseEvent| {
+                        let mut settings = (*draft).clone();
+                        settings.unbind(&bound);
+                        draft.set(settings);
+                    });
+                    html! {
+                        <span class="shortcut-key-chip">
+                            <kbd>{ key_label(key) }</kbd>
+                            <button onclick={remove} title="Remove shortcut" class="shortcut-remove">
+                                <span class="material-icons">{"close"}</span>
+                            </button>
+                        </span>
+                    }
+                })
+                .collect::<Html>();
+            let is_capturing = *capturing == Some(action);
+            let start_capture = {
+                let capturing = capturing.clone();
+                Callback::from(move |_: MouseEvent| {
+                    capturing.set(if is_capturing { None } else { Some(action) })
+                })
+            };
+            html! {
+                <tr>
+                    <td class="item_container-text pr-4">{ action.label() }</td>
+                    <td class="shortcut-keys">
+                        { keys }
+                        <button
+                            onclick={start_capture}
+                            onkeydown={on_capture_key.clone()}
+                            class={classes!("shortcut-add", is_capturing.then_some("capturing"))}
+                        >
+                            { if is_capturing { "Press a key..." } else { "Add key" } }
+                        </button>
+                    </td>
+                </tr>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="p-4">
+            <p class="item_container-text text-lg font-bold mb-4">{"Skip Intervals:"}</p>
+            <p class="item_container-text text-md mb-4">{"How far the skip buttons, media keys and keyboard shortcuts jump, in seconds."}</p>
+            <div class="flex flex-wrap gap-4 mb-6">
+                <label class="item_container-text flex items-center gap-2">
+                    {"Skip back"}
+                    <input type="number" min="1" max="300" class="search-bar-input w-24 px-2 py-1 rounded" value={draft.skip_back.to_string()} oninput={on_skip_input(true)} />
+                </label>
+                <label class="item_container-text flex items-center gap-2">
+                    {"Skip forward"}
+                    <input type="number" min="1" max="300" class="search-bar-input w-24 px-2 py-1 rounded" value={draft.skip_forward.to_string()} oninput={on_skip_input(false)} />
+                </label>
+            </div>
+
+            if audio_processing {
+                <p class="item_container-text text-lg font-bold mb-4">{"Audio Processing:"}</p>
+                <p class="item_container-text text-md mb-4">{"Applied to every episode you play. Each podcast can turn these on or off for its own episodes."}</p>
+                <div class="flex flex-col gap-2 mb-6">
+                    <label class="item_container-text flex items-center gap-2">
+                        <input type="checkbox" checked={draft.audio_effects.voice_boost} onclick={on_effect_toggle(|effects| effects.voice_boost = !effects.voice_boost)} />
+                        {"Voice boost: compress and raise quiet voices"}
+                    </label>
+                    <label class="item_container-text flex items-center gap-2">
+                        <input type="checkbox" checked={draft.audio_effects.normalize_loudness} onclick={on_effect_toggle(|effects| effects.normalize_loudness = !effects.normalize_loudness)} />
+                        {"Normalize loudness: even out quiet and loud episodes"}
+                    </label>
+                    <label class="item_container-text flex items-center gap-2">
+                        <input type="checkbox" checked={draft.audio_effects.skip_silence} onclick={on_effect_toggle(|effects| effects.skip_silence = !effects.skip_silence)} />
+                        {"Skip silence: speed through pauses"}
+                    </label>
+                </div>
+            }
+
+            if continuous_playback {
+                <p class="item_container-text text-lg font-bold mb-4">{"When an Episode Ends:"}</p>
+                <div class="flex flex-col gap-2 mb-6">
+                    <label class="item_container-text flex items-center gap-2">
+                        <input type="checkbox" checked={draft.queue_playback.auto_play_next} onclick={on_queue_toggle(|queue| queue.auto_play_next = !queue.auto_play_next)} />
+                        {"Auto-play the next episode in the queue after a short countdown"}
+                    </label>
+                    <label class="item_container-text flex items-center gap-2">
+                        <input type="checkbox" checked={draft.queue_playback.remove_finished} onclick={on_queue_toggle(|queue| queue.remove_finished = !queue.remove_finished)} />
+                        {"Remove finished episodes from the queue"}
+                    </label>
+                </div>
+            }
+
+            if completion {
+                <p class="item_container-text text-lg font-bold mb-4">{"Played Episodes:"}</p>
+                <p class="item_container-text text-md mb-4">{"An episode is marked played once you reach either point, so outros and credits don't leave it unfinished."}</p>
+                <div class="flex flex-wrap gap-4 mb-6">
+                    <label class="item_container-text flex items-center gap-2">
+                        {"Percent played"}
+                        <input type="number" min="50" max="100" class="search-bar-input w-24 px-2 py-1 rounded" value={draft.completion.completion_percent.to_string()} oninput={on_completion_input(|completion, percent| completion.completion_percent = percent, 50.0, 100.0)} />
+                    </label>
+                    <label class="item_container-text flex items-center gap-2">
+                        {"Seconds left"}
+                        <input type="number" min="0" max="600" class="search-bar-input w-24 px-2 py-1 rounded" value={draft.completion.completion_remaining.to_string()} oninput={on_completion_input(|completion, seconds| completion.completion_remaining = seconds, 0.0, 600.0)} />
+                    </label>
+                </div>
+            }
+
+            <p class="item_container-text text-lg font-bold mb-4">{"Data Saver:"}</p>
+            <p class="item_container-text text-md mb-4">{"Near the end of an episode, the start of the next one in your queue is loaded so it plays without a wait. Turn this on to skip that on metered connections. It applies to this device only, and starts out following your browser's data saver."}</p>
+            <div class="flex flex-col gap-2 mb-6">
+                <label class="item_container-text flex items-center gap-2">
+                    <input type="checkbox" checked={*saving_data} onclick={on_data_saver_toggle} />
+                    {"Don't preload the next queued episode on this device"}
+                </label>
+            </div>
+
+            <p class="item_container-text text-lg font-bold mb-4">{"Keyboard Shortcuts:"}</p>
+            <p class="item_container-text text-md mb-4">{"Shortcuts work anywhere in the app while an episode is loaded, except when typing in a text field. Press ? to see them at any time."}</p>
+            <table class="shortcut-table mb-4">
+                { rows }
+            </table>
+
+            <div class="flex gap-2">
+                <button onclick={on_submit} class="theme-submit-button mt-4 font-bold py-2 px-4 rounded focus:outline-none focus:shadow-outline" type="button">
+                    {"Submit"}
+                </button>
+                <button onclick={on_reset} class="theme-submit-button mt-4 font-bold py-2 px-4 rounded focus:outline-none focus:shadow-outline" type="button">
+                    {"Reset to Defaults"}
+                </button>
+            </div>
+        </div>
+    }
+}
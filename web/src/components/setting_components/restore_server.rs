@@ -3,7 +3,6 @@ use yew::prelude::*;
 use yewdux::prelude::*;
 use yew_router::history::{BrowserHistory, History};
 use crate::components::context::AppState;
-use crate::requests::setting_reqs::call_restore_server;
 use web_sys::{HtmlInputElement, Event};
 use web_sys::{Blob, FileReader};
 use wasm_bindgen::closure::Closure;
@@ -17,8 +16,7 @@ pub fn restore_server() -> Html {
 
     // API key, server name, and other data can be fetched from AppState if required
     let (state, _) = use_store::<AppState>();
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
+    let client = state.client();
 
     // Correct setup for `on_password_change`
     let on_password_change = {
@@ -65,22 +63,20 @@ pub fn restore_server() -> Html {
     // Ensure `onclick_restore` is correctly used
     let onclick_restore = {
         let history = BrowserHistory::new();  // Get the browser history for navigation
-        let api_key = api_key.unwrap_or_default();
-        let server_name = server_name.unwrap_or_default();
+        let client = client.clone();
         let database_password = (*database_password).clone();
         let file_content = (*file_content).clone();
         let error_message = error_message.clone();
         let info_message = info_message.clone();
         Callback::from(move |_| {
-            let server_name = server_name.clone();
-            let api_key = api_key.clone();
+            let client = client.clone();
             let database_password = database_password.clone();
             let file_content = file_content.clone();
             let error_message = error_message.clone();
             let info_message = info_message.clone();
             let history = history.clone();  // Clone history for use in the async block
             wasm_bindgen_futures::spawn_local(async move {
-                match call_restore_server(&server_name, &database_password, &file_content, &api_key.unwrap()).await {
+                match client.restore_server(&database_password, &file_content).await {
                     Ok(message) => {
                         info_message.set(Some(message));
                         // Navigate to the logout route after initiating the restore process
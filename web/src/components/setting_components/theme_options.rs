@@ -5,7 +5,7 @@ use crate::components::{context::{AppState, UIState}};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::spawn_local;
 use web_sys::window;
-use crate::requests::setting_reqs::{call_set_theme, SetThemeRequest};
+use crate::requests::setting_reqs::SetThemeRequest;
 use web_sys::console;
 
 #[function_component(ThemeOptions)]
@@ -50,9 +50,8 @@ pub fn theme() -> Html {
                 let _ = window.local_storage().unwrap().unwrap().set_item("theme", &theme);
             }
 
-            let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone()).flatten().unwrap();
+            let client = state.client();
             let user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone()).unwrap();
-            let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
 
             let request = SetThemeRequest {
                 user_id,
@@ -60,7 +59,7 @@ pub fn theme() -> Html {
             };
 
             spawn_local(async move {
-                if let Ok(_) = call_set_theme(&server_name, &Some(api_key), &request).await {
+                if let Ok(_) = client.set_theme(&request).await {
                     audio_dispatch.reduce_mut(|audio_state| audio_state.info_message = Option::from("Theme Settings Updated!".to_string()));
 
                 } else {
@@ -3,28 +3,25 @@ use yewdux::prelude::*;
 use crate::components::context::{AppState, UIState};
 use yew::platform::spawn_local;
 use web_sys::console;
-use crate::requests::setting_reqs::{call_self_service_status, call_enable_disable_self_service};
 use std::borrow::Borrow;
 
 #[function_component(SelfServiceSettings)]
 pub fn self_service_settings() -> Html {
     let (state, _dispatch) = use_store::<AppState>();
     let (_audio_state, audio_dispatch) = use_store::<UIState>();
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = state.client();
     let _user_id = state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
     let _error_message = state.error_message.clone();
     let self_service_status = use_state(|| false);
 
     {
         let self_service_status = self_service_status.clone();
-        use_effect_with((api_key.clone(), server_name.clone()), move |(api_key, server_name)| {
+        use_effect_with(client.clone(), move |client| {
             let self_service_status = self_service_status.clone();
-            let api_key = api_key.clone();
-            let server_name = server_name.clone();
+            let client = client.clone();
             let future = async move {
-                if let (Some(api_key), Some(server_name)) = (api_key, server_name) {
-                    let response = call_self_service_status(server_name, api_key.unwrap()).await;
+                if client.is_authenticated() {
+                    let response = client.self_service_status().await;
                     match response {
                         Ok(self_service_status_response) => {
                             self_service_status.set(self_service_status_response);
@@ -48,15 +45,14 @@ pub fn self_service_settings() -> Html {
 
             <label class="relative inline-flex items-center cursor-pointer">
                 <input type="checkbox" disabled={**loading.borrow()} checked={**self_service_status.borrow()} class="sr-only peer" onclick={Callback::from(move |_| {
-                    let api_key = api_key.clone();
-                    let server_name = server_name.clone();
+                    let client = client.clone();
                     let self_service_status = html_self_service.clone();
                     let audio_dispatch = audio_dispatch.clone();
                     let loading = loading.clone();
                     let future = async move {
                         loading.set(true);
-                        if let (Some(api_key), Some(server_name)) = (api_key, server_name) {
-                            let response = call_enable_disable_self_service(server_name, api_key.unwrap()).await;
+                        if client.is_authenticated() {
+                            let response = client.enable_disable_self_service().await;
                             match response {
                                 Ok(_) => {
                                     let current_status = self_service_status.borrow().clone();
@@ -2,10 +2,9 @@ use yew::prelude::*;
 use yewdux::prelude::*;
 use crate::components::context::{UIState, AppState};
 use yew::platform::spawn_local;
-use crate::requests::setting_reqs::call_get_user_info;
 use web_sys::console;
 use std::borrow::Borrow;
-use crate::requests::setting_reqs::{SettingsUser, call_add_user, AddSettingsUserRequest, call_set_password, call_set_email, call_set_fullname, call_set_username, call_check_admin, call_set_isadmin};
+use crate::requests::setting_reqs::{SettingsUser, AddSettingsUserRequest};
 use crate::components::gen_funcs::{ValidationError, encode_password, validate_email, validate_username};
 use crate::components::gen_funcs::validate_user_input;
 // use crate::gen_components::_ErrorMessageProps::error_message;
@@ -17,8 +16,7 @@ pub fn user_settings() -> Html {
     let (audio_state, audio_dispatch) = use_store::<UIState>();
     let ui_user = audio_dispatch.clone();
     let ui_wasm = audio_dispatch.clone();
-    let server_name = state.auth_details.as_ref().map(|ud| ud.server_name.clone());
-    let api_key = state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = state.client();
     let new_username = use_state(|| "".to_string());
     let new_password = use_state(|| "".to_string());
     let email = use_state(|| "".to_string());
@@ -37,13 +35,12 @@ pub fn user_settings() -> Html {
     {
         let users = users.clone();
         let update_trigger_effect = update_trigger.clone();
-        use_effect_with((api_key.clone(), server_name.clone(), *update_trigger_effect), move |(api_key, server_name, _update_trigger_effect)| {
+        use_effect_with((client.clone(), *update_trigger_effect), move |(client, _update_trigger_effect)| {
             let users = users.clone();
-            let api_key = api_key.clone();
-            let server_name = server_name.clone();
+            let client = client.clone();
             let future = async move {
-                if let (Some(api_key), Some(server_name)) = (api_key, server_name) {
-                    let response = call_get_user_info(server_name, api_key.unwrap()).await;
+                if client.is_authenticated() {
+                    let response = client.get_user_info().await;
                     match response {
                         Ok(user_info) => {
                             users.set(user_info);
@@ -154,8 +151,7 @@ pub fn user_settings() -> Html {
     let error_message_container_create = error_message_container.clone();
     let on_create_submit = {
         let page_state = page_state.clone();
-        let server_name = server_name.clone();
-        let api_key = api_key.clone();
+        let client = client.clone();
         let fullname = fullname.clone().to_string();
         let new_username = new_username.clone().to_string();
         let email = email.clone().to_string();
@@ -168,8 +164,7 @@ pub fn user_settings() -> Html {
             let error_container = error_container_create.clone();
             let error_message_container = error_message_container_create.clone();
             let update_trigger = on_update_trigger.clone();
-            let call_server = server_name.clone();
-            let call_api = api_key.clone();
+            let call_client = client.clone();
             let new_username = new_username.clone();
             let new_password = new_password.clone();
             let fullname = fullname.clone();
@@ -210,7 +205,7 @@ pub fn user_settings() -> Html {
                         wasm_bindgen_futures::spawn_local(async move {
                             let on_update_trigger = update_trigger.clone();
                             if let Some(add_user_request_value) = add_user_request {
-                                match call_add_user(call_server.unwrap(), call_api.unwrap().unwrap(), &add_user_request_value).await {
+                                match call_client.add_user(&add_user_request_value).await {
                                     Ok(_success) => {
                                         on_update_trigger.set(!*update_trigger);
                                     },
@@ -321,8 +316,7 @@ pub fn user_settings() -> Html {
         let fullname = fullname.clone().to_string();
         let page_state = page_state.clone();
         let new_username = new_username.clone().to_string();
-        let server_name = server_name.clone();
-        let api_key = api_key.clone();
+        let client = client.clone();
         let email = email.clone().to_string();
         let new_password = new_password.clone();
         let dispatch_wasm = ui_wasm.clone();
@@ -369,17 +363,16 @@ pub fn user_settings() -> Html {
             if !fullname.is_empty() {
                 wasm_bindgen_futures::spawn_local({
                     let update_trigger_in_check = update_trigger_name.clone();
-                    let server_name_cloned = server_name.clone();
-                    let api_key_cloned = api_key.clone();
+                    let client_cloned = client.clone();
                     let name_cloned = fullname.clone();
                     let selected_user_id_cloned = call_selected_user_id.clone();
             
                     async move {
-                        if let Some(server_name_unwrapped) = server_name_cloned {
-                            if let Some(api_key_unwrapped) = api_key_cloned.as_ref().and_then(|key| key.as_ref()) {
+                        if !client_cloned.server_name().is_empty() {
+                            if client_cloned.is_authenticated() {
                                 if let Some(user_id) = *selected_user_id_cloned {
                                     page_state_name.set(PageState::Hidden);
-                                    match call_set_fullname(server_name_unwrapped, api_key_unwrapped.clone(), user_id.unwrap(), name_cloned).await {
+                                    match client_cloned.set_fullname(user_id.unwrap(), name_cloned).await {
                                         Ok(_) => {
                                             update_trigger_in_check.set(!*update_trigger_in_check);
                                         },
@@ -404,16 +397,15 @@ pub fn user_settings() -> Html {
             let error_message_container_user = error_message_container.clone();
             if !new_username.is_empty() {
                 wasm_bindgen_futures::spawn_local({
-                    let server_name_cloned = server_name.clone();
-                    let api_key_cloned = api_key.clone();
+                    let client_cloned = client.clone();
                     let update_trigger_in_check = update_trigger_user.clone();
 
                     let user_cloned = new_username.clone();
                     let selected_user_id_cloned = call_selected_user_id.clone();
             
                     async move {
-                        if let Some(server_name_unwrapped) = server_name_cloned {
-                            if let Some(api_key_unwrapped) = api_key_cloned.as_ref().and_then(|key| key.as_ref()) {
+                        if !client_cloned.server_name().is_empty() {
+                            if client_cloned.is_authenticated() {
                                 if let Some(user_id) = *selected_user_id_cloned {
                                     let errors = validate_username(new_username.clone().as_str());
 
@@ -421,7 +413,7 @@ pub fn user_settings() -> Html {
                                         username_error.set(username_error_notice::Shown);
                                     } else {
                                         page_state_user.set(PageState::Hidden);
-                                        match call_set_username(server_name_unwrapped, api_key_unwrapped.clone(), user_id.unwrap(), user_cloned).await {
+                                        match client_cloned.set_username(user_id.unwrap(), user_cloned).await {
                                             Ok(_) => {
                                                 update_trigger_in_check.set(!*update_trigger_in_check);
                                             },
@@ -449,15 +441,14 @@ pub fn user_settings() -> Html {
             let error_message_container_email = error_message_container.clone();
             if !email.is_empty() {
                 wasm_bindgen_futures::spawn_local({
-                    let server_name_cloned = server_name.clone();
-                    let api_key_cloned = api_key.clone();
+                    let client_cloned = client.clone();
                     let email_cloned = email.clone();
                     let selected_user_id_cloned = call_selected_user_id.clone();
                     let update_trigger_in_check = update_trigger_email.clone();
 
                     async move {
-                        if let Some(server_name_unwrapped) = server_name_cloned {
-                            if let Some(api_key_unwrapped) = api_key_cloned.as_ref().and_then(|key| key.as_ref()) {
+                        if !client_cloned.server_name().is_empty() {
+                            if client_cloned.is_authenticated() {
                                 if let Some(user_id) = *selected_user_id_cloned {
                                     let errors = validate_email(email_cloned.clone().as_str());
 
@@ -465,7 +456,7 @@ pub fn user_settings() -> Html {
                                         email_error.set(email_error_notice::Shown);
                                     } else {
                                         page_state_email.set(PageState::Hidden);
-                                        match call_set_email(server_name_unwrapped, api_key_unwrapped.clone(), user_id.unwrap(), email_cloned).await {
+                                        match client_cloned.set_email(user_id.unwrap(), email_cloned).await {
                                             Ok(_) => {
                                                 update_trigger_in_check.set(!*update_trigger_in_check);
                                             },
@@ -494,15 +485,14 @@ pub fn user_settings() -> Html {
             
             if !new_password.is_empty() {
                 wasm_bindgen_futures::spawn_local({
-                    let server_name_cloned = server_name.clone();
-                    let api_key_cloned = api_key.clone();
+                    let client_cloned = client.clone();
                     let new_password_cloned = new_password.clone();
                     let selected_user_id_cloned = (*call_selected_user_id).clone();
                     let update_trigger_in_check = update_trigger_pass.clone();
 
                     async move {
-                        if let Some(server_name_unwrapped) = server_name_cloned {
-                            if let Some(api_key_unwrapped) = api_key_cloned.as_ref().and_then(|key| key.as_ref()) {
+                        if !client_cloned.server_name().is_empty() {
+                            if client_cloned.is_authenticated() {
                                 if let Some(Some(user_id)) = selected_user_id_cloned {
                                     match encode_password(&new_password_cloned) {
                                         Ok(hash_pw) => {
@@ -512,7 +502,7 @@ pub fn user_settings() -> Html {
                                                 password_error.set(password_error_notice::Shown);
                                             } else {
                                                 page_state_pass.set(PageState::Hidden);
-                                                match call_set_password(server_name_unwrapped, api_key_unwrapped.clone(), user_id, hash_pw).await {
+                                                match client_cloned.set_password(user_id, hash_pw).await {
                                                     Ok(_) => {
                                                         update_trigger_in_check.set(!*update_trigger_in_check);
                                                     },
@@ -544,21 +534,20 @@ pub fn user_settings() -> Html {
             let error_message_container_admin = error_message_container.clone();
             if *admin_status == true {
                 wasm_bindgen_futures::spawn_local({
-                    let server_name_cloned = server_name.clone();
-                    let api_key_cloned = api_key.clone();
+                    let client_cloned = client.clone();
                     let admin_status_cloned = admin_status.clone();
                     let selected_user_id_cloned = (*call_selected_user_id).clone();
                     let update_trigger_in_check = update_trigger_true.clone();
 
                     async move {
-                        if let Some(server_name_unwrapped) = server_name_cloned {
-                            if let Some(api_key_unwrapped) = api_key_cloned.as_ref().and_then(|key| key.as_ref()) {
+                        if !client_cloned.server_name().is_empty() {
+                            if client_cloned.is_authenticated() {
                                 if let Some(Some(user_id)) = selected_user_id_cloned {
                                     if *admin_edit_status_call == 0 {
                                         page_state_true.set(PageState::Hidden);
                                     }
                                     // page_state_true.set(PageState::Hidden);
-                                    match call_set_isadmin(server_name_unwrapped, api_key_unwrapped.clone(), user_id, *admin_status_cloned).await {
+                                    match client_cloned.set_isadmin(user_id, *admin_status_cloned).await {
                                         Ok(_) => {
                                             update_trigger_in_check.set(!*update_trigger_in_check);
                                         },
@@ -583,16 +572,15 @@ pub fn user_settings() -> Html {
 
             if *admin_status == false {
                 wasm_bindgen_futures::spawn_local({
-                    let server_name_cloned = server_name.clone();
-                    let api_key_cloned = api_key.clone();
+                    let client_cloned = client.clone();
                     let admin_status_cloned = admin_status.clone();
                     let selected_user_id_cloned = (*call_selected_user_id).clone();
                     let update_trigger_in_check = update_trigger_false.clone();
                     async move {
-                        if let Some(server_name_unwrapped) = server_name_cloned {
-                            if let Some(api_key_unwrapped) = api_key_cloned.as_ref().and_then(|key| key.as_ref()) {
+                        if !client_cloned.server_name().is_empty() {
+                            if client_cloned.is_authenticated() {
                                 if let Some(Some(user_id)) = selected_user_id_cloned {
-                                    match call_check_admin(server_name_unwrapped.clone(), api_key_unwrapped.clone(), user_id).await {
+                                    match client_cloned.check_admin(user_id).await {
                                         Ok(final_admin) => {
                                             if final_admin.final_admin == true {
                                                 error_container.set(error_container_state::Shown);
@@ -602,7 +590,7 @@ pub fn user_settings() -> Html {
                                                     page_state_false.set(PageState::Hidden);
                                                 }
                                                 // page_state_false.set(PageState::Hidden);
-                                                match call_set_isadmin(server_name_unwrapped, api_key_unwrapped.clone(), user_id, *admin_status_cloned).await {
+                                                match client_cloned.set_isadmin(user_id, *admin_status_cloned).await {
                                                     Ok(_) => {
                                                         update_trigger_in_check.set(!*update_trigger_in_check);
                                                     },
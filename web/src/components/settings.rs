@@ -9,8 +9,7 @@ use crate::components::episodes_layout::UIStateMsg;
 use wasm_bindgen::closure::Closure;
 use web_sys::window;
 use wasm_bindgen::JsCast;
-use crate::requests::login_requests::use_check_authentication;
-use crate::requests::setting_reqs::call_user_admin_check;
+use crate::requests::login_requests::{use_check_authentication, Capability};
 // use crate::components::gen_funcs::check_auth;
 
 #[derive(Properties, PartialEq, Clone)]
@@ -101,6 +100,15 @@ pub fn accordion_item(AccordionItemProps { title, content, position }: &Accordio
 #[function_component(Settings)]
 pub fn settings() -> Html {
     let (_post_state, _post_dispatch) = use_store::<AppState>();
+    // Nextcloud sync is set up and checked through the gPodder sync endpoints
+    let supports_sync = _post_state
+        .server_details
+        .as_ref()
+        .map_or(true, |details| details.supports(Capability::Nextcloud) && details.supports(Capability::Gpodder));
+    let supports_playback_settings = _post_state
+        .server_details
+        .as_ref()
+        .map_or(false, |details| details.supports(Capability::PlaybackSettings));
     let (audio_state, audio_dispatch) = use_store::<UIState>();
     let active_tab = use_state(|| "user");
     let error_message = audio_state.error_message.clone();
@@ -108,9 +116,8 @@ pub fn settings() -> Html {
     let session_dispatch = _post_dispatch.clone();
     let session_state = _post_state.clone();
 
-    let api_key = _post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+    let client = _post_state.client();
     let user_id = _post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-    let server_name = _post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
 
     use_effect_with((), move |_| {
         // Check if the page reload action has already occurred to prevent redundant execution
@@ -149,7 +156,7 @@ pub fn settings() -> Html {
 
     use_effect_with((), move |_| {
         wasm_bindgen_futures::spawn_local(async move {
-            match call_user_admin_check(&server_name.unwrap(), &api_key.unwrap().unwrap(), user_id.unwrap()).await {
+            match client.user_admin_check(user_id.unwrap()).await {
                 Ok(response) => {
                     is_admin.set(response.is_admin);
                 }
@@ -223,11 +230,16 @@ pub fn settings() -> Html {
                     html! {
                     <div id="accordion-collapse" data-accordion="collapse" class="bg-custom-light">
                         <AccordionItem title="Change Theme" content={html!{ <setting_components::theme_options::ThemeOptions /> }} position={AccordionItemPosition::First}/>
+                        if supports_playback_settings {
+                            <AccordionItem title="Playback and Keyboard Shortcuts" content={html!{ <setting_components::playback_settings::PlaybackOptions /> }} position={AccordionItemPosition::Middle}/>
+                        }
                         <AccordionItem title="MFA Settings" content={html!{ <setting_components::mfa_settings::MFAOptions /> }} position={AccordionItemPosition::Middle}/>
                         <AccordionItem title="Export/Backup Podcasts" content={html!{ <setting_components::export_settings::ExportOptions /> }} position={AccordionItemPosition::Middle}/>
                         <AccordionItem title="Import Podcasts" content={html!{ <setting_components::import_options::ImportOptions /> }} position={AccordionItemPosition::Middle}/>
                         <AccordionItem title="Add Custom Feed" content={html!{ <setting_components::custom_feed::CustomFeed /> }} position={AccordionItemPosition::Middle}/>
-                        <AccordionItem title="Connect Nextcloud Podcast Sync" content={html!{ <setting_components::nextcloud_options::NextcloudOptions /> }} position={AccordionItemPosition::Middle}/>
+                        if supports_sync {
+                            <AccordionItem title="Connect Nextcloud Podcast Sync" content={html!{ <setting_components::nextcloud_options::NextcloudOptions /> }} position={AccordionItemPosition::Middle}/>
+                        }
                         <AccordionItem title="Api Keys" content={html!{ <setting_components::api_keys::APIKeys /> }} position={AccordionItemPosition::Middle}/>
                     </div>
                     }
@@ -258,7 +270,7 @@ pub fn settings() -> Html {
         }
         {
             if let Some(audio_props) = &audio_state.currently_playing {
-                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
+                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} podcast_name={audio_props.podcast_name.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
             } else {
                 html! {}
             }
@@ -0,0 +1,142 @@
+use gloo_events::{EventListener, EventListenerOptions};
+use wasm_bindgen::JsCast;
+use web_sys::{window, HtmlElement, KeyboardEvent};
+use yew::prelude::*;
+use yewdux::prelude::*;
+use crate::components::context::UIState;
+use crate::requests::setting_reqs::{key_label, ShortcutAction};
+
+const SPEED_STEP: f64 = 0.1;
+const MIN_SPEED: f64 = 0.5;
+const MAX_SPEED: f64 = 2.0;
+
+/// Key presses meant for a text field are left alone.
+fn typing_into_field(event: &KeyboardEvent) -> bool {
+    event
+        .target()
+        .and_then(|target| target.dyn_into::<HtmlElement>().ok())
+        .map_or(false, |target| {
+            target.tag_name().eq_ignore_ascii_case("input") || target.tag_name().eq_ignore_ascii_case("textarea")
+        })
+}
+
+impl UIState {
+    pub fn skip_by(&mut self, seconds: f64) {
+        if let Some(current_time) = self.audio_element.as_ref().map(|audio| audio.current_time()) {
+            self.seek_to(current_time + seconds);
+        }
+    }
+
+    /// Steps the speed up or down, staying within the range of the speed slider.
+    pub fn step_speed(&mut self, steps: f64) {
+        let current = if self.playback_speed > 0.0 { self.playback_speed } else { 1.0 };
+        let speed = ((current + steps * SPEED_STEP) * 10.0).round() / 10.0;
+        self.playback_speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+        if let Some(audio) = &self.audio_element {
+            audio.set_playback_rate(self.playback_speed);
+        }
+    }
+}
+
+/// Answers the user's keyboard shortcuts while the player is mounted. Bindings are
+/// read at each key press, so changes in settings apply straight away.
+#[hook]
+pub fn use_keyboard_shortcuts(next_episode: Callback<MouseEvent>) {
+    let (_audio_state, audio_dispatch) = use_store::<UIState>();
+    // The listener outlives renders, so it reaches the latest callback through here
+    let next_episode_ref = use_mut_ref(|| next_episode.clone());
+    *next_episode_ref.borrow_mut() = next_episode;
+
+    use_effect_with((), move |_| {
+        let listener = window().map(|w| {
+            let options = EventListenerOptions::enable_prevent_default();
+            EventListener::new_with_options(&w, "keydown", options, move |event| {
+                let Some(event) = event.dyn_ref::<KeyboardEvent>() else { return };
+                if event.ctrl_key() || event.meta_key() || event.alt_key() || typing_into_field(event) {
+                    return;
+                }
+                if event.key() == "Escape" && audio_dispatch.get().show_shortcut_help {
+                    audio_dispatch.reduce_mut(|state| state.show_shortcut_help = false);
+                    return;
+                }
+                let Some(action) = audio_dispatch.get().playback_settings.action_for(&event.key()) else { return };
+                event.prevent_default();
+                match action {
+                    ShortcutAction::PlayPause => audio_dispatch.reduce_mut(|state| state.toggle_playback()),
+                    ShortcutAction::SkipBack => audio_dispatch.reduce_mut(|state| {
+                        state.skip_by(-(state.playback_settings.skip_back as f64))
+                    }),
+                    ShortcutAction::SkipForward => audio_dispatch.reduce_mut(|state| {
+                        state.skip_by(state.playback_settings.skip_forward as f64)
+                    }),
+                    ShortcutAction::SpeedUp => audio_dispatch.reduce_mut(|state| state.step_speed(1.0)),
+                    ShortcutAction::SlowDown => audio_dispatch.reduce_mut(|state| state.step_speed(-1.0)),
+                    ShortcutAction::NextEpisode => {
+                        let next_episode = next_episode_ref.borrow().clone();
+                        next_episode.emit(MouseEvent::new("click").unwrap());
+                    }
+                    ShortcutAction::Mute => {
+                        if let Some(audio) = &audio_dispatch.get().audio_element {
+                            audio.set_muted(!audio.muted());
+                        }
+                    }
+                    ShortcutAction::ShowHelp => {
+                        audio_dispatch.reduce_mut(|state| state.show_shortcut_help = !state.show_shortcut_help)
+                    }
+                }
+            })
+        });
+        move || drop(listener)
+    });
+}
+
+/// Overlay listing the current keyboard shortcuts.
+#[function_component(ShortcutHelp)]
+pub fn shortcut_help() -> Html {
+    let (audio_state, audio_dispatch) = use_store::<UIState>();
+    if !audio_state.show_shortcut_help {
+        return html! {};
+    }
+
+    let close = {
+        let audio_dispatch = audio_dispatch.clone();
+        Callback::from(move |_: MouseEvent| audio_dispatch.reduce_mut(|state| state.show_shortcut_help = false))
+    };
+    let settings = &audio_state.playback_settings;
+    let rows = ShortcutAction::ALL
+        .iter()
+        .map(|&action| {
+            let label = match action {
+                ShortcutAction::SkipBack => format!("{} {}s", action.label(), settings.skip_back),
+                ShortcutAction::SkipForward => format!("{} {}s", action.label(), settings.skip_forward),
+                _ => action.label().to_string(),
+            };
+            html! {
+                <tr>
+                    <td class="shortcut-keys">
+                        { for settings.keys_for(action).into_iter().map(|key| html! { <kbd>{ key_label(key) }</kbd> }) }
+                    </td>
+                    <td>{ label }</td>
+                </tr>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div tabindex="-1" class="fixed top-0 right-0 left-0 z-50 flex justify-center items-center w-full h-full bg-black bg-opacity-25" onclick={close.clone()}>
+            <div class="modal-container shortcut-help relative p-4 w-full max-w-md max-h-full rounded-lg shadow" onclick={Callback::from(|e: MouseEvent| e.stop_propagation())}>
+                <div class="flex items-center justify-between mb-4">
+                    <h3 class="item_container-text text-lg font-bold">{ "Keyboard shortcuts" }</h3>
+                    <button onclick={close} class="end-2.5 text-gray-400 bg-transparent hover:bg-gray-200 hover:text-gray-900 rounded-lg text-sm w-8 h-8 ms-auto inline-flex justify-center items-center">
+                        <span class="material-icons">{"close"}</span>
+                        <span class="sr-only">{"Close"}</span>
+                    </button>
+                </div>
+                <table class="shortcut-table item_container-text w-full">
+                    { rows }
+                </table>
+                <p class="item_container-text text-sm mt-4">{ "Shortcuts can be changed in Settings." }</p>
+            </div>
+        </div>
+    }
+}
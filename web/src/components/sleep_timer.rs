@@ -0,0 +1,264 @@
+use gloo_timers::callback::Interval;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yewdux::prelude::*;
+use crate::components::context::{AppState, UIState};
+use crate::components::gen_funcs::format_time;
+use crate::requests::client::PinepodsClient;
+use crate::requests::outbox::{defer_if_offline, Mutation};
+use crate::requests::pod_req::RecordListenDurationRequest;
+
+/// Seconds over which the volume fades out before the timer stops playback.
+const FADE_SECS: f64 = 10.0;
+const PRESET_MINUTES: [u32; 6] = [5, 10, 15, 30, 45, 60];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SleepMode {
+    /// Stops after this many more seconds of playback.
+    Countdown { remaining: f64 },
+    EndOfEpisode,
+    /// Stops when playback reaches `end`, the end of the chapter that was playing
+    /// when the timer was set.
+    EndOfChapter { end: f64 },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SleepTimer {
+    pub mode: SleepMode,
+    /// The volume to go back to once the fade out is over.
+    pub volume: f64,
+}
+
+impl SleepTimer {
+    /// Seconds of listening left before the timer stops playback.
+    pub fn remaining(&self, current_time: f64, duration: f64, playback_rate: f64) -> f64 {
+        let rate = if playback_rate > 0.0 { playback_rate } else { 1.0 };
+        let until = |end: f64| {
+            if end.is_finite() {
+                ((end - current_time) / rate).max(0.0)
+            } else {
+                f64::INFINITY
+            }
+        };
+        match self.mode {
+            SleepMode::Countdown { remaining } => remaining.max(0.0),
+            SleepMode::EndOfEpisode => until(duration),
+            SleepMode::EndOfChapter { end } => until(end),
+        }
+    }
+
+    /// Volume for `remaining` seconds before the timer fires.
+    fn faded_volume(&self, remaining: f64) -> f64 {
+        self.volume * (remaining / FADE_SECS).clamp(0.0, 1.0)
+    }
+
+    fn describe(&self) -> &'static str {
+        match self.mode {
+            SleepMode::Countdown { .. } => "Sleep in",
+            SleepMode::EndOfEpisode => "Sleep at end of episode",
+            SleepMode::EndOfChapter { .. } => "Sleep at end of chapter",
+        }
+    }
+}
+
+/// Stops playback for the sleep timer and saves the position, so the episode resumes
+/// exactly where the listener fell asleep.
+pub fn expire_sleep_timer(audio_dispatch: &Dispatch<UIState>, client: &PinepodsClient, user_id: Option<i32>) {
+    let mut position = None;
+    audio_dispatch.reduce_mut(|state| {
+        if let (Some(audio), Some(timer)) = (state.audio_element.as_ref(), state.sleep_timer.take()) {
+            let _ = audio.pause();
+            audio.set_volume(timer.volume);
+            position = Some(audio.current_time());
+        }
+        state.audio_playing = Some(false);
+    });
+    let episode_id = audio_dispatch.get().currently_playing.as_ref().map(|playing| playing.episode_id);
+    if let (Some(listen_duration), Some(episode_id), Some(user_id)) = (position, episode_id, user_id) {
+        let client = client.clone();
+        let request_data = RecordListenDurationRequest::new(episode_id, user_id, listen_duration);
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = client.record_listen_duration(&request_data).await {
+                defer_if_offline(&client, &e, Mutation::RecordListenDuration(request_data));
+            }
+        });
+    }
+}
+
+/// Runs the sleep timer while the player is mounted: counts down, fades out the
+/// volume and stops playback when the time is up.
+#[hook]
+pub fn use_sleep_timer() {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (_audio_state, audio_dispatch) = use_store::<UIState>();
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+
+    use_effect_with((client, user_id), move |(client, user_id)| {
+        let client = client.clone();
+        let user_id = *user_id;
+        let mut last_tick = js_sys::Date::now();
+        // Playback seconds not yet taken off the stored countdown. The store only gets
+        // whole seconds, so the player isn't redrawn on every tick.
+        let mut pending = 0.0;
+        let mut seen: Option<SleepTimer> = None;
+        let interval = Interval::new(250, move || {
+            let now = js_sys::Date::now();
+            let elapsed = (now - last_tick) / 1000.0;
+            last_tick = now;
+
+            let state = audio_dispatch.get();
+            let (Some(stored), Some(audio)) = (state.sleep_timer.clone(), state.audio_element.clone()) else { return };
+            if seen.as_ref() != Some(&stored) {
+                pending = 0.0;
+                seen = Some(stored.clone());
+            }
+            if !state.audio_playing.unwrap_or(false) {
+                return;
+            }
+            pending += elapsed;
+            let timer = match stored.mode {
+                SleepMode::Countdown { remaining } => SleepTimer {
+                    mode: SleepMode::Countdown { remaining: remaining - pending },
+                    ..stored
+                },
+                _ => stored,
+            };
+            let remaining = timer.remaining(audio.current_time(), state.episode_end(), audio.playback_rate());
+            // Stop just short of the end so the queue doesn't move on to the next episode
+            if remaining <= 0.3 {
+                expire_sleep_timer(&audio_dispatch, &client, user_id);
+                return;
+            }
+            audio.set_volume(timer.faded_volume(remaining));
+            if matches!(timer.mode, SleepMode::Countdown { .. }) && pending >= 1.0 {
+                pending = 0.0;
+                seen = Some(timer.clone());
+                audio_dispatch.reduce_mut(|state| state.sleep_timer = Some(timer));
+            }
+        });
+        move || drop(interval)
+    });
+}
+
+/// What the player shows while a sleep timer is set, e.g. "Sleep in 00:14:59".
+pub fn sleep_countdown(audio_state: &UIState) -> Option<String> {
+    let timer = audio_state.sleep_timer.as_ref()?;
+    let remaining = timer.remaining(audio_state.current_time_seconds, audio_state.episode_end(), audio_state.playback_speed);
+    Some(if remaining.is_finite() {
+        format!("{} {}", timer.describe(), format_time(remaining.ceil()))
+    } else {
+        timer.describe().to_string()
+    })
+}
+
+/// The sleep timer button and its menu of durations.
+#[function_component(SleepTimerControl)]
+pub fn sleep_timer_control() -> Html {
+    let (audio_state, audio_dispatch) = use_store::<UIState>();
+    let menu_open = use_state(|| false);
+    let custom_minutes = use_state(String::new);
+
+    let toggle_menu = {
+        let menu_open = menu_open.clone();
+        Callback::from(move |_: MouseEvent| menu_open.set(!*menu_open))
+    };
+
+    let set_mode = {
+        let audio_dispatch = audio_dispatch.clone();
+        let menu_open = menu_open.clone();
+        Callback::from(move |mode: SleepMode| {
+            audio_dispatch.reduce_mut(|state| {
+                let volume = match (state.sleep_timer.as_ref(), state.audio_element.as_ref()) {
+                    (Some(timer), _) => timer.volume,
+                    (None, Some(audio)) => audio.volume(),
+                    (None, None) => 1.0,
+                };
+                if let Some(audio) = state.audio_element.as_ref() {
+                    audio.set_volume(volume);
+                }
+                state.sleep_timer = Some(SleepTimer { mode, volume });
+            });
+            menu_open.set(false);
+        })
+    };
+
+    let cancel = {
+        let audio_dispatch = audio_dispatch.clone();
+        let menu_open = menu_open.clone();
+        Callback::from(move |_: MouseEvent| {
+            audio_dispatch.reduce_mut(|state| {
+                if let (Some(timer), Some(audio)) = (state.sleep_timer.take(), state.audio_element.as_ref()) {
+                    audio.set_volume(timer.volume);
+                }
+            });
+            menu_open.set(false);
+        })
+    };
+
+    let on_custom_input = {
+        let custom_minutes = custom_minutes.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            custom_minutes.set(input.value());
+        })
+    };
+    let set_custom = {
+        let set_mode = set_mode.clone();
+        let custom_minutes = custom_minutes.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Ok(minutes) = custom_minutes.trim().parse::<f64>() {
+                if minutes > 0.0 {
+                    set_mode.emit(SleepMode::Countdown { remaining: minutes * 60.0 });
+                }
+            }
+        })
+    };
+
+    let duration = audio_state.episode_end();
+    let countdown = sleep_countdown(&audio_state);
+    let chapter_end = audio_state.current_chapters().map(|chapters| {
+        chapters
+            .next_start(audio_state.current_time_seconds)
+            .unwrap_or(duration)
+    });
+    html! {
+        <div class="sleep-timer">
+            <button onclick={toggle_menu} title="Sleep timer" class={classes!("skip-button", "audio-top-button", "selector-button", "font-bold", "py-2", "px-4", "rounded-full", "w-10", "h-10", "flex", "items-center", "justify-center", countdown.is_some().then_some("active"))}>
+                <span class="material-icons">{"bedtime"}</span>
+            </button>
+            if let Some(countdown) = countdown.clone() {
+                <div class="sleep-timer-countdown">{ countdown }</div>
+            }
+            if *menu_open {
+                <div class="sleep-timer-menu">
+                    { for PRESET_MINUTES.iter().map(|&minutes| {
+                        let set_mode = set_mode.clone();
+                        html! {
+                            <button class="sleep-timer-option" onclick={Callback::from(move |_: MouseEvent| set_mode.emit(SleepMode::Countdown { remaining: minutes as f64 * 60.0 }))}>
+                                { format!("{} minutes", minutes) }
+                            </button>
+                        }
+                    }) }
+                    <div class="sleep-timer-custom">
+                        <input type="number" min="1" placeholder="Minutes" class="search-bar-input" value={(*custom_minutes).clone()} oninput={on_custom_input} />
+                        <button class="sleep-timer-option" onclick={set_custom}>{ "Set" }</button>
+                    </div>
+                    <button class="sleep-timer-option" onclick={{
+                        let set_mode = set_mode.clone();
+                        Callback::from(move |_: MouseEvent| set_mode.emit(SleepMode::EndOfEpisode))
+                    }}>{ "End of episode" }</button>
+                    if let Some(end) = chapter_end {
+                        <button class="sleep-timer-option" onclick={{
+                            let set_mode = set_mode.clone();
+                            Callback::from(move |_: MouseEvent| set_mode.emit(SleepMode::EndOfChapter { end }))
+                        }}>{ "End of chapter" }</button>
+                    }
+                    if countdown.is_some() {
+                        <button class="sleep-timer-option" onclick={cancel}>{ "Turn off timer" }</button>
+                    }
+                </div>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,167 @@
+use yew::prelude::*;
+use yewdux::prelude::*;
+use web_sys::{Element, HtmlElement, HtmlInputElement};
+use wasm_bindgen::JsCast;
+use crate::components::context::{AppState, UIState};
+use crate::components::gen_funcs::format_time;
+use crate::requests::http::use_abort_handle;
+use crate::requests::transcripts::Transcript;
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct TranscriptViewProps {
+    pub episode_id: i32,
+    pub episode_url: String,
+    pub feed_url: Option<String>,
+}
+
+/// Splits `text` around the first case-insensitive match of `query`, so the match
+/// can be highlighted. `None` when there's nothing to highlight.
+fn split_match<'a>(text: &'a str, query: &str) -> Option<(&'a str, &'a str, &'a str)> {
+    let query = query.trim();
+    if query.is_empty() {
+        return None;
+    }
+    // Only ASCII lowercasing keeps byte offsets lined up with the original text
+    let start = text.to_ascii_lowercase().find(&query.to_ascii_lowercase())?;
+    let end = start + query.len();
+    Some((&text[..start], &text[start..end], &text[end..]))
+}
+
+/// The episode's transcript, following along while the episode plays.
+#[function_component(TranscriptView)]
+pub fn transcript_view(props: &TranscriptViewProps) -> Html {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (audio_state, audio_dispatch) = use_store::<UIState>();
+    let transcript: UseStateHandle<Option<Transcript>> = use_state(|| None);
+    let loading = use_state(|| true);
+    let query = use_state(String::new);
+    let list_ref = use_node_ref();
+    let abort = use_abort_handle();
+
+    {
+        let transcript = transcript.clone();
+        let loading = loading.clone();
+        let client = state.client().with_abort(abort.clone());
+        use_effect_with((props.feed_url.clone(), props.episode_url.clone()), move |(feed_url, episode_url)| {
+            transcript.set(None);
+            if let Some(feed_url) = feed_url.clone() {
+                let episode_url = episode_url.clone();
+                loading.set(true);
+                wasm_bindgen_futures::spawn_local(async move {
+                    // A transcript that fails to load is treated like a missing one
+                    transcript.set(client.get_episode_transcript(&feed_url, &episode_url).await.ok().flatten());
+                    loading.set(false);
+                });
+            } else {
+                loading.set(false);
+            }
+            || ()
+        });
+    }
+
+    let is_playing_episode = audio_state
+        .currently_playing
+        .as_ref()
+        .map_or(false, |playing| playing.episode_id == props.episode_id);
+    let current = transcript
+        .as_ref()
+        .filter(|_| is_playing_episode)
+        .and_then(|transcript| transcript.current(audio_state.current_time_seconds));
+
+    // Keep the current cue in view without scrolling the page itself
+    use_effect_with((current, (*query).clone()), {
+        let list_ref = list_ref.clone();
+        move |(current, _)| {
+            if current.is_some() {
+                if let Some(list) = list_ref.cast::<HtmlElement>() {
+                    let cue = list
+                        .query_selector(".transcript-cue.current")
+                        .ok()
+                        .flatten()
+                        .and_then(|cue: Element| cue.dyn_into::<HtmlElement>().ok());
+                    if let Some(cue) = cue {
+                        list.set_scroll_top(cue.offset_top() - list.client_height() / 2);
+                    }
+                }
+            }
+            || ()
+        }
+    });
+
+    let on_search = {
+        let query = query.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            query.set(input.value());
+        })
+    };
+
+    let Some(transcript) = transcript.as_ref() else {
+        return if *loading {
+            html! { <p class="transcript-status item_container-text">{ "Loading transcript..." }</p> }
+        } else {
+            html! {}
+        };
+    };
+
+    let matches = transcript.search(&query);
+    let cues = matches
+        .iter()
+        .map(|&index| {
+            let cue = &transcript.cues[index];
+            let can_seek = transcript.timed && is_playing_episode;
+            let onclick = {
+                let audio_dispatch = audio_dispatch.clone();
+                let start = cue.start;
+                Callback::from(move |_: MouseEvent| {
+                    if can_seek {
+                        audio_dispatch.reduce_mut(|state| state.seek_to(start));
+                    }
+                })
+            };
+            let cue_class = classes!(
+                "transcript-cue",
+                (Some(index) == current).then_some("current"),
+                can_seek.then_some("seekable"),
+            );
+            let text = match split_match(&cue.text, &query) {
+                Some((before, found, after)) => html! { <>{ before }<mark>{ found }</mark>{ after }</> },
+                None => html! { { cue.text.clone() } },
+            };
+            html! {
+                <div class={cue_class} {onclick}>
+                    if transcript.timed {
+                        <span class="transcript-time">{ format_time(cue.start) }</span>
+                    }
+                    <p class="transcript-text">
+                        if let Some(speaker) = &cue.speaker {
+                            <span class="transcript-speaker">{ format!("{}: ", speaker) }</span>
+                        }
+                        { text }
+                    </p>
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="transcript-container">
+            <div class="transcript-header">
+                <h3 class="item_container-text">{ "Transcript" }</h3>
+                <input
+                    type="search"
+                    class="search-bar-input transcript-search"
+                    placeholder="Search transcript"
+                    value={(*query).clone()}
+                    oninput={on_search}
+                />
+            </div>
+            if !query.trim().is_empty() {
+                <p class="transcript-status item_container-text">{ format!("{} matching lines", matches.len()) }</p>
+            }
+            <div class="transcript-cues" ref={list_ref}>
+                { cues }
+            </div>
+        </div>
+    }
+}
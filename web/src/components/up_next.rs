@@ -0,0 +1,142 @@
+use gloo_timers::callback::Interval;
+use yew::prelude::*;
+use yewdux::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use crate::components::audio::on_play_click;
+use crate::components::context::{AppState, UIState};
+use crate::requests::client::PinepodsClient;
+use crate::requests::outbox::{defer_if_offline, Mutation};
+use crate::requests::pod_req::{next_in_queue, MarkCompletedRequest, QueuePodcastRequest, QueuedEpisode};
+
+/// Seconds the up next card counts down before starting the next episode.
+const COUNTDOWN_SECS: u32 = 5;
+
+/// The queued episode waiting to start once the countdown runs out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UpNext {
+    pub episode: QueuedEpisode,
+    pub remaining: u32,
+}
+
+/// Starts a queued episode the same way clicking it would.
+pub fn play_queued(episode: &QueuedEpisode, client: &PinepodsClient, user_id: i32, audio_dispatch: &Dispatch<UIState>) {
+    on_play_click(
+        episode.EpisodeURL.clone(),
+        episode.EpisodeTitle.clone(),
+        episode.PodcastName.clone(),
+        episode.EpisodeArtwork.clone(),
+        episode.EpisodeDuration,
+        episode.EpisodeID,
+        episode.ListenDuration,
+        client.clone(),
+        user_id,
+        audio_dispatch.clone(),
+        audio_dispatch.get(),
+        None,
+        None,
+    )
+    .emit(MouseEvent::new("click").unwrap());
+}
+
+/// Wraps up the episode that just ended: marks it completed, takes it out of the queue
+/// if the user wants that, and lines up the next queued episode.
+pub fn finish_episode(audio_dispatch: &Dispatch<UIState>, client: &PinepodsClient, user_id: Option<i32>, mark_completed: bool) {
+    let state = audio_dispatch.get();
+    let (Some(user_id), Some(episode_id)) = (user_id, state.currently_playing.as_ref().map(|playing| playing.episode_id)) else { return };
+    let settings = state.playback_settings.queue_playback;
+    let in_db = state.episode_in_db.unwrap_or(false);
+    audio_dispatch.reduce_mut(|state| state.audio_playing = Some(false));
+
+    let client = client.clone();
+    let audio_dispatch = audio_dispatch.clone();
+    spawn_local(async move {
+        if in_db && mark_completed {
+            let request = MarkCompletedRequest { episode_id, user_id };
+            if let Err(e) = client.mark_episode_completed(&request).await {
+                defer_if_offline(&client, &e, Mutation::MarkCompleted(request));
+            }
+        }
+        let Ok(queue) = client.get_queued_episodes(&user_id).await else { return };
+        if settings.remove_finished && queue.iter().any(|episode| episode.EpisodeID == episode_id) {
+            let request = QueuePodcastRequest { episode_id, user_id };
+            let _ = client.remove_queued_episode(&request).await;
+        }
+        if !settings.auto_play_next {
+            return;
+        }
+        if let Some(next) = next_in_queue(&queue, episode_id) {
+            let up_next = UpNext { episode: next.clone(), remaining: COUNTDOWN_SECS };
+            audio_dispatch.reduce_mut(move |state| {
+                // Something else may have started while the queue loaded
+                if state.currently_playing.as_ref().map(|playing| playing.episode_id) == Some(episode_id) {
+                    state.up_next = Some(up_next);
+                }
+            });
+        }
+    });
+}
+
+/// Counts the up next card down, and starts its episode when it reaches zero.
+#[hook]
+pub fn use_up_next() {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (_audio_state, audio_dispatch) = use_store::<UIState>();
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+
+    use_effect_with((client, user_id), move |(client, user_id)| {
+        let client = client.clone();
+        let user_id = *user_id;
+        let interval = Interval::new(1000, move || {
+            let Some(up_next) = audio_dispatch.get().up_next.clone() else { return };
+            if up_next.remaining > 1 {
+                audio_dispatch.reduce_mut(|state| {
+                    if let Some(up_next) = state.up_next.as_mut() {
+                        up_next.remaining -= 1;
+                    }
+                });
+            } else if let Some(user_id) = user_id {
+                play_queued(&up_next.episode, &client, user_id, &audio_dispatch);
+            }
+        });
+        move || drop(interval)
+    });
+}
+
+/// Shows the episode that's about to start, with a way to start it now or stay put.
+#[function_component(UpNextCard)]
+pub fn up_next_card() -> Html {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (audio_state, audio_dispatch) = use_store::<UIState>();
+    let Some(up_next) = audio_state.up_next.clone() else { return html! {} };
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+
+    let play_now = {
+        let audio_dispatch = audio_dispatch.clone();
+        let episode = up_next.episode.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(user_id) = user_id {
+                play_queued(&episode, &client, user_id, &audio_dispatch);
+            }
+        })
+    };
+    let cancel = Callback::from(move |_: MouseEvent| audio_dispatch.reduce_mut(|state| state.up_next = None));
+
+    html! {
+        <div class="up-next-card">
+            <img src={up_next.episode.EpisodeArtwork.clone()} alt="" />
+            <div class="up-next-details">
+                <span class="up-next-countdown">{ format!("Up next in {}s", up_next.remaining) }</span>
+                <span class="up-next-title">{ &up_next.episode.EpisodeTitle }</span>
+                <span class="up-next-podcast">{ &up_next.episode.PodcastName }</span>
+            </div>
+            <button onclick={play_now} title="Play now" class="audio-top-button selector-button rounded-full w-10 h-10 flex items-center justify-center">
+                <span class="material-icons">{"play_arrow"}</span>
+            </button>
+            <button onclick={cancel} title="Cancel" class="audio-top-button selector-button rounded-full w-10 h-10 flex items-center justify-center">
+                <span class="material-icons">{"close"}</span>
+            </button>
+        </div>
+    }
+}
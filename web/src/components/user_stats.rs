@@ -2,11 +2,10 @@ use yew::{function_component, Html, html};
 use yew::prelude::*;
 use super::app_drawer::App_drawer;
 use super::gen_components::Search_nav;
-use crate::requests::stat_reqs;
 use yewdux::prelude::*;
 use crate::components::context::{AppState, UIState, UserStatsStore};
 use crate::components::audio::AudioPlayer;
-use crate::components::gen_funcs::{format_date, format_time_mins};
+use crate::components::gen_funcs::{format_date, format_time, format_time_mins};
 // use crate::requests::login_requests::use_check_authentication;
 
 
@@ -24,18 +23,16 @@ pub fn user_stats() -> Html {
     {
         // let episodes = episodes.clone();
         // let error = error.clone();
-        let api_key = post_state.auth_details.as_ref().map(|ud| ud.api_key.clone());
+        let client = post_state.client();
         let user_id = post_state.user_details.as_ref().map(|ud| ud.UserID.clone());
-        let server_name = post_state.auth_details.as_ref().map(|ud| ud.server_name.clone());
 
-        let server_name_effect = server_name.clone();
 
         use_effect_with(
-            (api_key.clone(), user_id.clone(), server_name.clone()),
+            (client.clone(), user_id.clone()),
             move |_| {
                 // your async call here, using stat_dispatch to update stat_state
                 wasm_bindgen_futures::spawn_local(async move {
-                    if let Ok(fetched_stats) = stat_reqs::call_get_stats(server_name_effect.unwrap().clone(), api_key.flatten().clone(), &user_id.unwrap()).await {
+                    if let Ok(fetched_stats) = client.get_stats(&user_id.unwrap()).await {
                         stat_dispatch.reduce_mut(move |state| {
                             state.stats = Some(fetched_stats);
                         });
@@ -59,6 +56,7 @@ pub fn user_stats() -> Html {
                             if let Some(stats) = user_stats {
                                 let formatted_date = format_date(&stats.UserCreated);
                                 let time_formatted = format_time_mins(stats.TimeListened);
+                                let silence_saved = format_time(stats.SilenceTimeSaved as f64);
                                 html! {
                                     <>
                                         <div class="stats-card">
@@ -90,6 +88,13 @@ pub fn user_stats() -> Html {
                                             <p class="stats-label">{"Episodes Downloaded"}</p>
                                             <p class="stats-value">{ &stats.EpisodesDownloaded }</p>
                                         </div>
+
+                                        if stats.SilenceTimeSaved > 0 {
+                                            <div class="stats-card">
+                                                <p class="stats-label">{"Time Saved Skipping Silence"}</p>
+                                                <p class="stats-value">{ &silence_saved }</p>
+                                            </div>
+                                        }
                                         <div class="large-card col-span-1 md:col-span-3">
                                             <img src="static/assets/favicon.png" alt="Pinepods Logo" class="large-card-image"/>
                                             <p class="large-card-paragraph item_container-text">{"Thanks for using Pinepods! This app was born from a love for podcasts, of homelabs, and a desire to have a secure and central location to manage personal data. Feel free to reach out for questions and open an issue if you have ideas for new features. Pull Requests on this software are welcome and encouraged. If you feel that you've gotten use out of this software and are thankful for it's existence donations to my Buymeacoffee are welcome but never required. Lastly, this app will ALWAYS remain open source."}</p>
@@ -113,7 +118,7 @@ pub fn user_stats() -> Html {
             </div>
         {
             if let Some(audio_props) = &audio_state.currently_playing {
-                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
+                html! { <AudioPlayer src={audio_props.src.clone()} title={audio_props.title.clone()} podcast_name={audio_props.podcast_name.clone()} artwork_url={audio_props.artwork_url.clone()} duration={audio_props.duration.clone()} episode_id={audio_props.episode_id.clone()} duration_sec={audio_props.duration_sec.clone()} start_pos_sec={audio_props.start_pos_sec.clone()} /> }
             } else {
                 html! {}
             }
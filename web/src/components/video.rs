@@ -0,0 +1,188 @@
+//! The picture of video episodes. The player's `<video>` element lives outside the
+//! page; the surface borrows it while it's mounted and parks it in a hidden holder
+//! afterwards, since taking a playing element out of the document pauses it.
+use gloo_events::EventListener;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{window, Document, Element, HtmlMediaElement, HtmlVideoElement};
+use yew::prelude::*;
+use yewdux::prelude::*;
+use crate::components::context::UIState;
+use crate::requests::search_pods::MediaKind;
+
+const PARKING_ID: &str = "video-parking";
+
+// web-sys only exposes picture-in-picture behind `web_sys_unstable_apis`
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends = Document)]
+    type PictureInPictureDocument;
+
+    #[wasm_bindgen(method, getter = pictureInPictureEnabled)]
+    fn picture_in_picture_enabled(this: &PictureInPictureDocument) -> Option<bool>;
+
+    #[wasm_bindgen(method, getter = pictureInPictureElement)]
+    fn picture_in_picture_element(this: &PictureInPictureDocument) -> Option<Element>;
+
+    #[wasm_bindgen(method, catch, js_name = exitPictureInPicture)]
+    fn exit_picture_in_picture(this: &PictureInPictureDocument) -> Result<js_sys::Promise, JsValue>;
+
+    #[wasm_bindgen(extends = HtmlVideoElement)]
+    type PictureInPictureVideo;
+
+    #[wasm_bindgen(method, catch, js_name = requestPictureInPicture)]
+    fn request_picture_in_picture(this: &PictureInPictureVideo) -> Result<js_sys::Promise, JsValue>;
+}
+
+fn document() -> Option<Document> {
+    window().and_then(|w| w.document())
+}
+
+/// Moves the player into a hidden holder, keeping it in the document so it plays on.
+fn park(video: &HtmlVideoElement) {
+    let Some(document) = document() else { return };
+    let parking = document.get_element_by_id(PARKING_ID).or_else(|| {
+        let parking = document.create_element("div").ok()?;
+        parking.set_id(PARKING_ID);
+        parking.set_attribute("hidden", "").ok()?;
+        document.body()?.append_child(&parking).ok()?;
+        Some(parking)
+    });
+    if let Some(parking) = parking {
+        let _ = parking.append_child(video);
+    }
+}
+
+/// Whether the loaded media has a picture, or `None` until its metadata is in.
+fn picture_of(video: &HtmlVideoElement) -> Option<bool> {
+    (video.ready_state() >= HtmlMediaElement::HAVE_METADATA).then(|| video.video_width() > 0)
+}
+
+#[derive(Properties, PartialEq)]
+pub struct VideoSurfaceProps {
+    /// Shown instead when the episode has no picture.
+    #[prop_or_default]
+    pub children: Html,
+}
+
+/// The playing episode's video, with fullscreen and picture-in-picture, or the children
+/// for audio.
+#[function_component(VideoSurface)]
+pub fn video_surface(props: &VideoSurfaceProps) -> Html {
+    let (audio_state, audio_dispatch) = use_store::<UIState>();
+    let holder_ref = use_node_ref();
+    let surface_ref = use_node_ref();
+    let video = audio_state.audio_element.clone();
+    let picture = use_state_eq(|| video.as_ref().and_then(picture_of));
+    let fullscreen = use_state_eq(|| false);
+
+    {
+        let holder_ref = holder_ref.clone();
+        let picture = picture.clone();
+        use_effect_with(video.clone(), move |video| {
+            let mut listeners = Vec::new();
+            if let (Some(video), Some(holder)) = (video.clone(), holder_ref.cast::<Element>()) {
+                let _ = holder.append_child(&video);
+                let check = {
+                    let video = video.clone();
+                    move || picture.set(picture_of(&video))
+                };
+                check();
+                for event in ["loadedmetadata", "resize", "emptied"] {
+                    let check = check.clone();
+                    listeners.push(EventListener::new(&video, event, move |_| check()));
+                }
+            }
+            let video = video.clone();
+            move || {
+                drop(listeners);
+                if let Some(video) = video {
+                    park(&video);
+                }
+            }
+        });
+    }
+
+    {
+        let fullscreen = fullscreen.clone();
+        let surface_ref = surface_ref.clone();
+        use_effect_with((), move |_| {
+            let listener = document().map(|document| {
+                EventListener::new(&document.clone(), "fullscreenchange", move |_| {
+                    let surface = surface_ref.cast::<Element>();
+                    fullscreen.set(surface.is_some() && document.fullscreen_element() == surface);
+                })
+            });
+            move || drop(listener)
+        });
+    }
+
+    // Before the metadata is in, the feed's word is all there is
+    let kind = audio_state.currently_playing.as_ref().map(|playing| playing.media_kind).unwrap_or_default();
+    let shown = picture.unwrap_or(kind == MediaKind::Video);
+    let picture_in_picture = document()
+        .and_then(|document| document.unchecked_into::<PictureInPictureDocument>().picture_in_picture_enabled())
+        .unwrap_or(false);
+
+    let toggle_playback = {
+        let audio_dispatch = audio_dispatch.clone();
+        Callback::from(move |_: MouseEvent| audio_dispatch.reduce_mut(UIState::toggle_playback))
+    };
+    let toggle_fullscreen = {
+        let surface_ref = surface_ref.clone();
+        let audio_dispatch = audio_dispatch.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.stop_propagation();
+            let Some(document) = document() else { return };
+            if document.fullscreen_element().is_some() {
+                document.exit_fullscreen();
+            } else if let Some(surface) = surface_ref.cast::<Element>() {
+                if let Err(e) = surface.request_fullscreen() {
+                    audio_dispatch.reduce_mut(|state| state.error_message = Some(format!("Couldn't go fullscreen: {:?}", e)));
+                }
+            }
+        })
+    };
+    let toggle_picture_in_picture = {
+        let video = video.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.stop_propagation();
+            let (Some(video), Some(document)) = (video.clone(), document()) else { return };
+            let document = document.unchecked_into::<PictureInPictureDocument>();
+            let toggled = if document.picture_in_picture_element().is_some() {
+                document.exit_picture_in_picture()
+            } else {
+                video.unchecked_into::<PictureInPictureVideo>().request_picture_in_picture()
+            };
+            let audio_dispatch = audio_dispatch.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Err(e) = async { JsFuture::from(toggled?).await }.await {
+                    audio_dispatch.reduce_mut(|state| state.error_message = Some(format!("Couldn't open picture-in-picture: {:?}", e)));
+                }
+            });
+        })
+    };
+
+    html! {
+        <>
+        <div ref={surface_ref} class="video-surface" hidden={!shown} onclick={toggle_playback} ondblclick={toggle_fullscreen.clone()}>
+            // The element is put in here by hand, so nothing else may be rendered inside
+            <div ref={holder_ref} class="video-holder"></div>
+            <div class="video-controls">
+                if picture_in_picture {
+                    <button onclick={toggle_picture_in_picture} title="Picture in picture">
+                        <span class="material-icons">{"picture_in_picture_alt"}</span>
+                    </button>
+                }
+                <button onclick={toggle_fullscreen} title={if *fullscreen { "Exit fullscreen" } else { "Fullscreen" }}>
+                    <span class="material-icons">{ if *fullscreen { "fullscreen_exit" } else { "fullscreen" } }</span>
+                </button>
+            </div>
+        </div>
+        if !shown {
+            { props.children.clone() }
+        }
+        </>
+    }
+}
@@ -8,6 +8,7 @@ use components::routes::Route;
 // use components::login::LogOut;
 use components::downloads::Downloads;
 use components::history::PodHistory;
+use components::bookmarks::Bookmarks;
 use components::queue::Queue;
 use components::saved::Saved;
 use components::search::Search;
@@ -16,9 +17,13 @@ use components::user_stats::UserStats;
 use components::home::Home;
 use components::search_new::SearchNew;
 use components::podcast_layout::PodLayout;
-use components::episodes_layout::EpisodeLayout;
+use components::episodes_layout::{EpisodeLayout, PodcastLink};
 use components::podcasts::Podcasts;
 use components::episode::Episode;
+use components::player_session::use_player_session;
+use requests::outbox::use_outbox_replay;
+use requests::login_requests::use_server_handshake;
+use requests::setting_reqs::use_playback_settings;
 
 #[cfg(feature = "server_build")]
 use {
@@ -64,6 +69,7 @@ fn switch(route: Route) -> Html {
         Route::Saved => html! { <Saved /> },
         Route::Settings => html! { <Settings /> },
         Route::PodHistory => html! { <PodHistory /> },
+        Route::Bookmarks => html! { <Bookmarks /> },
         Route::Downloads => html! { <Downloads /> },
         Route::Search => html! { <Search on_search={Callback::from(move |_| {})} /> },
         Route::UserStats => html! { <UserStats /> },
@@ -73,6 +79,8 @@ fn switch(route: Route) -> Html {
         Route::EpisodeLayout => html! { <EpisodeLayout /> },
         Route::Podcasts => html! { <Podcasts /> },
         Route::Episode => html! { <Episode /> },
+        Route::EpisodeLink { id } => html! { <Episode episode_id={id} /> },
+        Route::PodcastLink { id } => html! { <PodcastLink podcast_id={id} /> },
 
     }
 }
@@ -82,6 +90,10 @@ fn switch(route: Route) -> Html {
 fn main_component() -> Html {
     // console::log_1(&format!("Initial User Context: {:?}", (*user_context).clone()).into());
     // console::log_1(&format!("Initial Auth Context: {:?}", (*user_auth_context).clone()).into());
+    use_outbox_replay();
+    use_server_handshake();
+    use_playback_settings();
+    use_player_session();
 
     html! {
         <BrowserRouter>
@@ -0,0 +1,138 @@
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use crate::components::gen_funcs::format_time;
+use crate::requests::client::PinepodsClient;
+
+/// A moment the user marked in an episode, with the note they left on it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub bookmark_id: i32,
+    pub episode_id: i32,
+    /// Seconds into the episode.
+    pub time: i32,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    pub episode_title: String,
+    pub podcast_name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct BookmarksResponse {
+    bookmarks: Vec<Bookmark>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AddBookmarkRequest {
+    pub user_id: i32,
+    pub episode_id: i32,
+    pub time: i32,
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct AddBookmarkResponse {
+    bookmark_id: i32,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DeleteBookmarkRequest {
+    pub user_id: i32,
+    pub bookmark_id: i32,
+}
+
+impl PinepodsClient {
+    /// The bookmarks in one episode, in playback order, or every bookmark the user has
+    /// when `episode_id` is `None`.
+    pub async fn get_bookmarks(&self, user_id: i32, episode_id: Option<i32>) -> Result<Vec<Bookmark>, Error> {
+        let path = match episode_id {
+            Some(episode_id) => format!("/api/data/bookmarks?user_id={}&episode_id={}", user_id, episode_id),
+            None => format!("/api/data/bookmarks?user_id={}", user_id),
+        };
+        let response: BookmarksResponse = self.get(path).fetch("Error fetching bookmarks").await?;
+        Ok(response.bookmarks)
+    }
+
+    /// Adds a bookmark and returns its id.
+    pub async fn add_bookmark(&self, request: &AddBookmarkRequest) -> Result<i32, Error> {
+        let response: AddBookmarkResponse = self
+            .post("/api/data/add_bookmark")
+            .json(request)?
+            .fetch("Error adding bookmark")
+            .await?;
+        Ok(response.bookmark_id)
+    }
+
+    pub async fn delete_bookmark(&self, request: &DeleteBookmarkRequest) -> Result<(), Error> {
+        self.post("/api/data/delete_bookmark")
+            .json(request)?
+            .send("Error deleting bookmark")
+            .await?;
+        Ok(())
+    }
+}
+
+/// The bookmarks as a Markdown document, a section per episode. Each time links back to
+/// the moment in the episode, through `link_base` (the app's address).
+pub fn bookmarks_to_markdown(bookmarks: &[Bookmark], link_base: &str) -> String {
+    let mut markdown = String::from("# My Bookmarks\n");
+    let mut episode = None;
+    for bookmark in bookmarks {
+        if episode != Some(bookmark.episode_id) {
+            episode = Some(bookmark.episode_id);
+            markdown.push_str(&format!("\n## {}: {}\n\n", bookmark.podcast_name, bookmark.episode_title));
+        }
+        let time = format!(
+            "[{}]({}/episode/{}?t={})",
+            format_time(bookmark.time as f64),
+            link_base.trim_end_matches('/'),
+            bookmark.episode_id,
+            bookmark.time
+        );
+        match bookmark.note.as_deref().map(str::trim).filter(|note| !note.is_empty()) {
+            // Line breaks in a note would end the list item
+            Some(note) => markdown.push_str(&format!("- {} {}\n", time, note.split_whitespace().collect::<Vec<_>>().join(" "))),
+            None => markdown.push_str(&format!("- {}\n", time)),
+        }
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::stub_server::{fixture, StubServer};
+    use pollster::block_on;
+
+    #[test]
+    fn fetches_the_bookmarks_of_one_episode() {
+        let server = StubServer::start();
+        server.route("GET", "/api/data/bookmarks?user_id=2&episode_id=311", 200, fixture("bookmarks.json"));
+        let client = server.client(Some("secret"));
+
+        let bookmarks = block_on(client.get_bookmarks(2, Some(311))).unwrap();
+
+        assert_eq!(bookmarks.len(), 3);
+        assert_eq!(bookmarks[0].time, 125);
+        assert_eq!(bookmarks[1].note, None);
+        assert_eq!(server.requests()[0].path, "/api/data/bookmarks?user_id=2&episode_id=311");
+    }
+
+    #[test]
+    fn exports_bookmarks_as_markdown_by_episode() {
+        let response: BookmarksResponse = serde_json::from_str(&fixture("bookmarks.json")).unwrap();
+
+        let markdown = bookmarks_to_markdown(&response.bookmarks, "https://pods.example.com/");
+
+        assert_eq!(
+            markdown,
+            "# My Bookmarks\n\
+             \n## Hard Fork: The AI Episode\n\n\
+             - [00:02:05](https://pods.example.com/episode/311?t=125) Great quote about time\n\
+             - [01:01:01](https://pods.example.com/episode/311?t=3661)\n\
+             \n## Radiolab: Colors\n\n\
+             - [00:00:42](https://pods.example.com/episode/298?t=42) Why is the sky blue?\n"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::requests::client::PinepodsClient;
+
+/// A library list that views render from cache while they fetch it again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CacheKey {
+    RecentEpisodes(i32),
+    Podcasts(i32),
+    Queue(i32),
+    Saved(i32),
+    History(i32),
+    Downloads(i32),
+}
+
+impl CacheKey {
+    /// Every list of a user, for changes like removing a podcast that touch all of them.
+    pub fn all(user_id: i32) -> [CacheKey; 6] {
+        [
+            CacheKey::RecentEpisodes(user_id),
+            CacheKey::Podcasts(user_id),
+            CacheKey::Queue(user_id),
+            CacheKey::Saved(user_id),
+            CacheKey::History(user_id),
+            CacheKey::Downloads(user_id),
+        ]
+    }
+}
+
+thread_local! {
+    static CACHE: RefCell<HashMap<(String, CacheKey), Rc<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+impl PinepodsClient {
+    /// The last response this client's server gave for `key`, if it is still valid.
+    pub fn cached<T: Clone + 'static>(&self, key: CacheKey) -> Option<T> {
+        CACHE.with(|cache| {
+            cache
+                .borrow()
+                .get(&(self.server_name().to_string(), key))
+                .and_then(|value| value.downcast_ref::<T>())
+                .cloned()
+        })
+    }
+
+    pub(crate) fn store_cached<T: 'static>(&self, key: CacheKey, value: T) {
+        CACHE.with(|cache| {
+            cache
+                .borrow_mut()
+                .insert((self.server_name().to_string(), key), Rc::new(value));
+        });
+    }
+
+    /// Drops entries a mutation made outdated, so the next view waits for fresh data.
+    pub(crate) fn invalidate(&self, keys: &[CacheKey]) {
+        CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            for key in keys {
+                cache.remove(&(self.server_name().to_string(), *key));
+            }
+        });
+    }
+}
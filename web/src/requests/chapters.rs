@@ -0,0 +1,220 @@
+use anyhow::Error;
+use rss::Item;
+use serde::{Deserialize, Serialize};
+use crate::requests::client::PinepodsClient;
+
+/// One chapter of an episode. Chapters from a `podcast:chapters` JSON file keep all
+/// their fields; Podlove Simple Chapters embedded in the feed fill in what they have.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Chapter {
+    pub start_time: f64,
+    #[serde(default)]
+    pub end_time: Option<f64>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub img: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// `false` marks a chapter, like an ad break, that shouldn't be listed.
+    #[serde(default, skip_serializing)]
+    toc: Option<bool>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChaptersFile {
+    chapters: Vec<Chapter>,
+}
+
+/// Keeps the listed chapters, in playback order.
+fn listed(mut chapters: Vec<Chapter>) -> Vec<Chapter> {
+    chapters.retain(|chapter| chapter.toc != Some(false) && chapter.start_time.is_finite());
+    chapters.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    chapters
+}
+
+/// The `podcast:chapters` link of a feed item, if it has one.
+pub(crate) fn item_chapters_url(item: &Item) -> Option<String> {
+    item.extensions()
+        .get("podcast")?
+        .get("chapters")?
+        .iter()
+        .find_map(|ext| ext.attrs().get("url").cloned())
+}
+
+/// Podlove Simple Chapters (`psc:chapters`) embedded in a feed item.
+pub(crate) fn item_embedded_chapters(item: &Item) -> Vec<Chapter> {
+    let chapters = item
+        .extensions()
+        .get("psc")
+        .and_then(|exts| exts.get("chapters"))
+        .into_iter()
+        .flatten()
+        .flat_map(|ext| ext.children().get("chapter").into_iter().flatten())
+        .filter_map(|ext| {
+            let attrs = ext.attrs();
+            Some(Chapter {
+                start_time: parse_npt(attrs.get("start")?)?,
+                end_time: None,
+                title: attrs.get("title").cloned(),
+                img: attrs.get("image").cloned(),
+                url: attrs.get("href").cloned(),
+                toc: None,
+            })
+        })
+        .collect();
+    listed(chapters)
+}
+
+/// Parses a Normal Play Time like "01:02:03.500", "02:03" or "123.5" into seconds.
+pub(crate) fn parse_npt(time: &str) -> Option<f64> {
+    time.trim()
+        .split(':')
+        .try_fold(0.0, |total, part| part.parse::<f64>().ok().map(|value| total * 60.0 + value))
+}
+
+/// The chapters of the episode playing from `src`, kept with it so they can be
+/// reused rather than fetched again whenever the player is redrawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpisodeChapters {
+    pub src: String,
+    pub chapters: Vec<Chapter>,
+}
+
+impl EpisodeChapters {
+    /// Index of the chapter playing at `time`.
+    pub fn current(&self, time: f64) -> Option<usize> {
+        self.chapters.iter().rposition(|chapter| chapter.start_time <= time)
+    }
+
+    /// Where "previous chapter" goes from `time`: the start of the current chapter, or
+    /// of the one before it when the current one has only just begun.
+    pub fn previous_start(&self, time: f64) -> Option<f64> {
+        let current = self.current(time)?;
+        let start = self.chapters[current].start_time;
+        if time - start > 3.0 || current == 0 {
+            Some(start)
+        } else {
+            Some(self.chapters[current - 1].start_time)
+        }
+    }
+
+    pub fn next_start(&self, time: f64) -> Option<f64> {
+        self.chapters
+            .iter()
+            .find(|chapter| chapter.start_time > time)
+            .map(|chapter| chapter.start_time)
+    }
+}
+
+impl PinepodsClient {
+    /// Fetches a JSON chapters file. It goes through the server because chapter files
+    /// are rarely served with CORS headers.
+    pub async fn get_chapters(&self, chapters_url: &str) -> Result<Vec<Chapter>, Error> {
+        let response_text = self
+            .get(format!("/api/data/fetch_podcast_feed?podcast_feed={}", urlencoding::encode(chapters_url)))
+            .text("Failed to fetch chapters")
+            .await?;
+        let file: ChaptersFile = serde_json::from_str(&response_text)?;
+        Ok(listed(file.chapters))
+    }
+
+    /// Looks up the chapters of the episode at `episode_url` in the feed at `feed_url`.
+    /// A chapters file is preferred over embedded chapters since it can carry images.
+    pub async fn get_episode_chapters(&self, feed_url: &str, episode_url: &str) -> Result<Vec<Chapter>, Error> {
+        let feed = self.parse_podcast_url(feed_url).await?;
+        let Some(episode) = feed
+            .episodes
+            .into_iter()
+            .find(|episode| episode.enclosure_url.as_deref() == Some(episode_url))
+        else {
+            return Ok(Vec::new());
+        };
+        if let Some(chapters_url) = episode.chapters_url.as_deref() {
+            match self.get_chapters(chapters_url).await {
+                Ok(chapters) if !chapters.is_empty() => return Ok(chapters),
+                Ok(_) => {}
+                Err(e) if episode.chapters.is_empty() => return Err(e),
+                Err(_) => {}
+            }
+        }
+        Ok(episode.chapters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::stub_server::{fixture, StubServer};
+    use pollster::block_on;
+
+    const FEED_URL: &str = "https://pods.example.com/feed.xml";
+
+    fn chapter_server() -> StubServer {
+        let server = StubServer::start();
+        server
+            .route(
+                "GET",
+                &format!("/api/data/fetch_podcast_feed?podcast_feed={}", urlencoding::encode(FEED_URL)),
+                200,
+                fixture("feed_with_chapters.xml"),
+            )
+            .route(
+                "GET",
+                &format!("/api/data/fetch_podcast_feed?podcast_feed={}", urlencoding::encode("https://pods.example.com/linked.json")),
+                200,
+                fixture("chapters.json"),
+            );
+        server
+    }
+
+    fn titles(chapters: &[Chapter]) -> Vec<&str> {
+        chapters.iter().filter_map(|chapter| chapter.title.as_deref()).collect()
+    }
+
+    #[test]
+    fn feed_items_keep_chapter_links_and_embedded_chapters() {
+        let server = chapter_server();
+        let feed = block_on(server.client(Some("key")).parse_podcast_url(FEED_URL)).unwrap();
+
+        let linked = &feed.episodes[0];
+        assert_eq!(linked.chapters_url.as_deref(), Some("https://pods.example.com/linked.json"));
+
+        let embedded = &feed.episodes[1];
+        assert_eq!(embedded.chapters_url, None);
+        assert_eq!(titles(&embedded.chapters), ["Intro", "Second", "Last"]);
+        assert_eq!(embedded.chapters[1].start_time, 90.5);
+        assert_eq!(embedded.chapters[1].img.as_deref(), Some("https://pods.example.com/second.jpg"));
+        assert_eq!(embedded.chapters[2].start_time, 3723.0);
+
+        assert!(feed.episodes[2].chapters.is_empty());
+    }
+
+    #[test]
+    fn chapter_file_is_preferred_and_hidden_chapters_dropped() {
+        let server = chapter_server();
+        let client = server.client(Some("key"));
+
+        let chapters = block_on(client.get_episode_chapters(FEED_URL, "https://pods.example.com/linked.mp3")).unwrap();
+        assert_eq!(titles(&chapters), ["Welcome", "Interview", "Wrap Up"]);
+        assert_eq!(chapters[2].end_time, Some(180.0));
+
+        let plain = block_on(client.get_episode_chapters(FEED_URL, "https://pods.example.com/plain.mp3")).unwrap();
+        assert!(plain.is_empty());
+    }
+
+    #[test]
+    fn chapter_navigation_follows_playback_position() {
+        let server = chapter_server();
+        let client = server.client(Some("key"));
+        let chapters = block_on(client.get_episode_chapters(FEED_URL, "https://pods.example.com/embedded.mp3")).unwrap();
+        let episode = EpisodeChapters { src: "https://pods.example.com/embedded.mp3".to_string(), chapters };
+
+        assert_eq!(episode.current(100.0), Some(1));
+        assert_eq!(episode.next_start(100.0), Some(3723.0));
+        assert_eq!(episode.previous_start(100.0), Some(90.5));
+        assert_eq!(episode.previous_start(92.0), Some(0.0));
+        assert_eq!(episode.next_start(4000.0), None);
+    }
+}
@@ -0,0 +1,403 @@
+use anyhow::Error;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::rc::Rc;
+use crate::requests::http::{AbortHandle, DefaultBackend, HttpBackend, HttpRequest, Method};
+use crate::requests::login_requests::LoginServerRequest;
+
+/// Handle to a single Pinepods server.
+///
+/// The client owns the server URL and API key, so every endpoint gets the same
+/// `Api-Key` header, JSON encoding and status handling. Endpoints are methods in
+/// `impl PinepodsClient` blocks that live next to their models in the other
+/// `requests` modules.
+#[derive(Clone)]
+pub struct PinepodsClient {
+    server_name: String,
+    api_key: Option<String>,
+    backend: Rc<dyn HttpBackend>,
+    abort: Option<AbortHandle>,
+}
+
+impl PinepodsClient {
+    pub fn new(server_name: impl Into<String>, api_key: Option<String>) -> Self {
+        PinepodsClient::with_backend(server_name, api_key, Rc::new(DefaultBackend::default()))
+    }
+
+    /// A client that sends its requests through `backend` instead of the platform default.
+    pub fn with_backend(server_name: impl Into<String>, api_key: Option<String>, backend: Rc<dyn HttpBackend>) -> Self {
+        PinepodsClient {
+            server_name: server_name.into(),
+            api_key,
+            backend,
+            abort: None,
+        }
+    }
+
+    /// The same client, but `abort` cancels its requests. Calls that were cancelled
+    /// fail with `ApiError::Aborted`, so a late response never reaches the caller.
+    pub fn with_abort(&self, abort: AbortHandle) -> Self {
+        PinepodsClient {
+            abort: Some(abort),
+            ..self.clone()
+        }
+    }
+
+    pub fn server_name(&self) -> &str {
+        &self.server_name
+    }
+
+    pub fn api_key(&self) -> Result<&str, Error> {
+        self.api_key.as_deref().ok_or_else(|| Error::msg("API key is missing"))
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    pub(crate) fn get(&self, path: impl Into<String>) -> ApiRequest<'_> {
+        ApiRequest::new(self, Method::Get, path.into())
+    }
+
+    pub(crate) fn post(&self, path: impl Into<String>) -> ApiRequest<'_> {
+        ApiRequest::new(self, Method::Post, path.into())
+    }
+
+    pub(crate) fn put(&self, path: impl Into<String>) -> ApiRequest<'_> {
+        ApiRequest::new(self, Method::Put, path.into())
+    }
+
+    pub(crate) fn delete(&self, path: impl Into<String>) -> ApiRequest<'_> {
+        ApiRequest::new(self, Method::Delete, path.into())
+    }
+}
+
+impl Default for PinepodsClient {
+    fn default() -> Self {
+        PinepodsClient::new(String::new(), None)
+    }
+}
+
+// The backend and abort handle only decide how requests travel, so two clients for the same server and
+// key are the same client. Components rely on this for `use_effect_with` deps.
+impl PartialEq for PinepodsClient {
+    fn eq(&self, other: &Self) -> bool {
+        self.server_name == other.server_name && self.api_key == other.api_key
+    }
+}
+
+// Clients end up in logged state, so the key itself never is
+impl fmt::Debug for PinepodsClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PinepodsClient")
+            .field("server_name", &self.server_name)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl From<&LoginServerRequest> for PinepodsClient {
+    fn from(auth: &LoginServerRequest) -> Self {
+        PinepodsClient::new(auth.server_name.clone(), auth.api_key.clone())
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorDetail {
+    detail: String,
+}
+
+/// Why a call against the server failed.
+///
+/// Endpoint methods still return `anyhow::Error`; components that need to react to
+/// the kind of failure can get this back with `e.downcast_ref::<ApiError>()`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApiError {
+    /// 401, the API key is missing, revoked or expired.
+    Unauthorized(String),
+    /// 403, the key is valid but the user may not do this.
+    Forbidden(String),
+    NotFound(String),
+    /// Any other non-success status, with its code.
+    Server(u16, String),
+    /// The request never got a response.
+    Network(String),
+    /// The response body was not what the endpoint promised.
+    Decode(String),
+    /// The client's `AbortHandle` cancelled the request.
+    Aborted,
+}
+
+impl ApiError {
+    fn from_status(status: u16, body: String) -> Self {
+        match status {
+            401 => ApiError::Unauthorized(body),
+            403 => ApiError::Forbidden(body),
+            404 => ApiError::NotFound(body),
+            _ => ApiError::Server(status, body),
+        }
+    }
+
+    /// The response body, or the underlying error text for network and decode failures.
+    pub fn body(&self) -> &str {
+        match self {
+            ApiError::Unauthorized(body)
+            | ApiError::Forbidden(body)
+            | ApiError::NotFound(body)
+            | ApiError::Server(_, body)
+            | ApiError::Network(body)
+            | ApiError::Decode(body) => body,
+            ApiError::Aborted => "",
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            ApiError::Unauthorized(_) => "Unauthorized".to_string(),
+            ApiError::Forbidden(_) => "Forbidden".to_string(),
+            ApiError::NotFound(_) => "Not Found".to_string(),
+            ApiError::Server(status, _) => format!("Server Error {}", status),
+            ApiError::Network(_) => "Network Request Error".to_string(),
+            ApiError::Decode(_) => "Response Parsing Error".to_string(),
+            ApiError::Aborted => "Request Aborted".to_string(),
+        };
+        if self.body().is_empty() {
+            write!(f, "{}", kind)
+        } else {
+            write!(f, "{} - {}", kind, self.body())
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// Wraps `err` so it prints with the endpoint's context but still downcasts to `ApiError`.
+fn api_error(context: &str, err: ApiError) -> Error {
+    let message = format!("{}: {}", context, err);
+    Error::new(err).context(message)
+}
+
+/// A single call against the client's server, built by `get`/`post`/`put`/`delete`.
+pub(crate) struct ApiRequest<'a> {
+    client: &'a PinepodsClient,
+    method: Method,
+    path: String,
+    headers: Vec<(&'static str, String)>,
+    body: Option<String>,
+    authorized: bool,
+}
+
+impl<'a> ApiRequest<'a> {
+    fn new(client: &'a PinepodsClient, method: Method, path: String) -> Self {
+        ApiRequest {
+            client,
+            method,
+            path,
+            headers: Vec::new(),
+            body: None,
+            authorized: true,
+        }
+    }
+
+    /// Encodes `body` as the JSON request body.
+    pub(crate) fn json<B: Serialize + ?Sized>(mut self, body: &B) -> Result<Self, Error> {
+        let encoded = serde_json::to_string(body)
+            .map_err(|e| Error::msg(format!("Serialization Error: {}", e)))?;
+        self.body = Some(encoded);
+        Ok(self)
+    }
+
+    pub(crate) fn header(mut self, key: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((key, value.into()));
+        self
+    }
+
+    /// Skips the `Api-Key` header, for the endpoints that are used before login.
+    pub(crate) fn public(mut self) -> Self {
+        self.authorized = false;
+        self
+    }
+
+    /// Sends the request and returns the body of a successful response as text.
+    pub(crate) async fn text(self, context: &str) -> Result<String, Error> {
+        let mut headers: Vec<(String, String)> = Vec::new();
+        if self.authorized {
+            headers.push(("Api-Key".to_string(), self.client.api_key()?.to_string()));
+        }
+        for (key, value) in self.headers {
+            headers.push((key.to_string(), value));
+        }
+        if self.body.is_some() {
+            headers.push(("Content-Type".to_string(), "application/json".to_string()));
+        }
+        let request = HttpRequest {
+            method: self.method,
+            url: format!("{}{}", self.client.server_name, self.path),
+            headers,
+            body: self.body,
+            abort: self.client.abort.clone(),
+        };
+
+        let result = self.client.backend.send(request).await;
+        // Checked after the response too, as it may have arrived just before the abort
+        if self.client.abort.as_ref().map_or(false, AbortHandle::is_aborted) {
+            return Err(api_error(context, ApiError::Aborted));
+        }
+        let response = result.map_err(|e| api_error(context, ApiError::Network(e)))?;
+        if response.ok() {
+            return Ok(response.body);
+        }
+        let detail = serde_json::from_str::<ErrorDetail>(&response.body)
+            .map(|err| err.detail)
+            .unwrap_or(response.body);
+        let err = ApiError::from_status(response.status, detail);
+        // Public endpoints answer 401 for bad credentials, which is not a lost session.
+        #[cfg(target_arch = "wasm32")]
+        if self.authorized && matches!(err, ApiError::Unauthorized(_)) {
+            crate::requests::login_requests::handle_unauthorized();
+        }
+        Err(api_error(context, err))
+    }
+
+    /// Sends the request and decodes the JSON body of a successful response.
+    pub(crate) async fn fetch<T: DeserializeOwned>(self, context: &str) -> Result<T, Error> {
+        let body = self.text(context).await?;
+        serde_json::from_str(&body)
+            .map_err(|e| api_error(context, ApiError::Decode(e.to_string())))
+    }
+
+    /// Sends the request and only checks that the server accepted it.
+    pub(crate) async fn send(self, context: &str) -> Result<(), Error> {
+        self.text(context).await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::stub_server::StubServer;
+    use pollster::block_on;
+
+    fn api_error_of(err: &Error) -> &ApiError {
+        err.downcast_ref::<ApiError>().expect("error should carry an ApiError")
+    }
+
+    #[test]
+    fn sends_api_key_and_json_body() {
+        let server = StubServer::start();
+        server.route("POST", "/api/data/echo", 200, r#"{"data":"ok"}"#);
+        let client = server.client(Some("secret"));
+
+        let body = block_on(async {
+            client
+                .post("/api/data/echo")
+                .json(&serde_json::json!({ "episode_id": 7 }))?
+                .text("Failed")
+                .await
+        })
+        .unwrap();
+
+        assert_eq!(body, r#"{"data":"ok"}"#);
+        let request = &server.requests()[0];
+        assert_eq!(request.header("Api-Key"), Some("secret"));
+        assert_eq!(request.header("Content-Type"), Some("application/json"));
+        assert_eq!(request.body, r#"{"episode_id":7}"#);
+    }
+
+    #[test]
+    fn public_requests_skip_the_api_key() {
+        let server = StubServer::start();
+        server.route("GET", "/api/pinepods_check", 200, "{}");
+        let client = server.client(None);
+
+        block_on(client.get("/api/pinepods_check").public().send("Failed")).unwrap();
+
+        assert_eq!(server.requests()[0].header("Api-Key"), None);
+    }
+
+    #[test]
+    fn authorized_requests_need_a_key() {
+        let server = StubServer::start();
+        let client = server.client(None);
+
+        assert!(block_on(client.get("/api/data/verify_key").send("Failed")).is_err());
+        assert!(server.requests().is_empty());
+    }
+
+    #[test]
+    fn error_statuses_map_to_api_errors() {
+        let server = StubServer::start();
+        server
+            .route("GET", "/unauthorized", 401, r#"{"detail":"Your API key is either invalid or does not have correct permission"}"#)
+            .route("GET", "/forbidden", 403, r#"{"detail":"Forbidden"}"#)
+            .route("GET", "/broken", 500, "Internal Server Error");
+        let client = server.client(Some("secret"));
+
+        let err = block_on(client.get("/unauthorized").send("Failed")).unwrap_err();
+        assert_eq!(
+            api_error_of(&err),
+            &ApiError::Unauthorized("Your API key is either invalid or does not have correct permission".to_string())
+        );
+        let err = block_on(client.get("/forbidden").send("Failed")).unwrap_err();
+        assert_eq!(api_error_of(&err), &ApiError::Forbidden("Forbidden".to_string()));
+        let err = block_on(client.get("/missing").send("Failed")).unwrap_err();
+        assert_eq!(api_error_of(&err), &ApiError::NotFound("Not Found".to_string()));
+        let err = block_on(client.get("/broken").send("Failed")).unwrap_err();
+        assert_eq!(api_error_of(&err), &ApiError::Server(500, "Internal Server Error".to_string()));
+        assert!(err.to_string().starts_with("Failed: "));
+    }
+
+    #[test]
+    fn unreachable_server_is_a_network_error() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let client = PinepodsClient::new(format!("http://{}", addr), Some("secret".to_string()));
+
+        let err = block_on(client.get("/api/data/verify_key").send("Failed")).unwrap_err();
+
+        assert!(matches!(api_error_of(&err), ApiError::Network(_)));
+    }
+
+    #[test]
+    fn aborted_requests_are_not_sent() {
+        let server = StubServer::start();
+        server.route("GET", "/api/data/verify_key", 200, r#"{"status":"success"}"#);
+        let abort = AbortHandle::new();
+        let client = server.client(Some("secret")).with_abort(abort.clone());
+
+        block_on(client.get("/api/data/verify_key").send("Failed")).unwrap();
+        abort.abort();
+        let err = block_on(client.get("/api/data/verify_key").send("Failed")).unwrap_err();
+
+        assert_eq!(api_error_of(&err), &ApiError::Aborted);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn debug_output_hides_the_api_key() {
+        let client = PinepodsClient::new("http://localhost".to_string(), Some("secret".to_string()));
+
+        let debug = format!("{:?}", client);
+
+        assert!(!debug.contains("secret"));
+        assert!(debug.contains("<redacted>"));
+    }
+
+    #[test]
+    fn unexpected_json_is_a_decode_error() {
+        #[derive(Deserialize, Debug)]
+        struct Expected {
+            #[allow(dead_code)]
+            status: String,
+        }
+        let server = StubServer::start();
+        server.route("GET", "/api/data/verify_key", 200, r#"{"state":"success"}"#);
+        let client = server.client(Some("secret"));
+
+        let err = block_on(client.get("/api/data/verify_key").fetch::<Expected>("Failed")).unwrap_err();
+
+        assert!(matches!(api_error_of(&err), ApiError::Decode(_)));
+    }
+}
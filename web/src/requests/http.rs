@@ -0,0 +1,172 @@
+use futures_util::future::LocalBoxFuture;
+use std::cell::Cell;
+use std::rc::Rc;
+use yew::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Delete => "DELETE",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub abort: Option<AbortHandle>,
+}
+
+/// Cancels every request sent with it, for views whose responses would be stale
+/// once the user has moved on. Clones share the same state.
+#[derive(Clone, Debug, Default)]
+pub struct AbortHandle {
+    aborted: Rc<Cell<bool>>,
+    #[cfg(target_arch = "wasm32")]
+    controller: Option<web_sys::AbortController>,
+}
+
+impl AbortHandle {
+    pub fn new() -> Self {
+        AbortHandle {
+            aborted: Rc::new(Cell::new(false)),
+            #[cfg(target_arch = "wasm32")]
+            controller: web_sys::AbortController::new().ok(),
+        }
+    }
+
+    pub fn abort(&self) {
+        self.aborted.set(true);
+        #[cfg(target_arch = "wasm32")]
+        if let Some(controller) = self.controller.as_ref() {
+            controller.abort();
+        }
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.aborted.get()
+    }
+}
+
+impl PartialEq for AbortHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.aborted, &other.aborted)
+    }
+}
+
+/// An `AbortHandle` that is aborted when the calling component unmounts.
+#[hook]
+pub fn use_abort_handle() -> AbortHandle {
+    let handle = (*use_memo((), |_| AbortHandle::new())).clone();
+    {
+        let handle = handle.clone();
+        use_effect_with((), move |_| move || handle.abort());
+    }
+    handle
+}
+
+/// Any response the server sent back, successful or not.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn ok(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Sends requests for a `PinepodsClient`. `Err` means no response arrived at all.
+///
+/// The browser build uses `GlooBackend`; native builds, including `cargo test`,
+/// use `NativeBackend`.
+pub trait HttpBackend {
+    fn send(&self, request: HttpRequest) -> LocalBoxFuture<'static, Result<HttpResponse, String>>;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub type DefaultBackend = GlooBackend;
+#[cfg(not(target_arch = "wasm32"))]
+pub type DefaultBackend = NativeBackend;
+
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GlooBackend;
+
+#[cfg(target_arch = "wasm32")]
+impl HttpBackend for GlooBackend {
+    fn send(&self, request: HttpRequest) -> LocalBoxFuture<'static, Result<HttpResponse, String>> {
+        use gloo_net::http::{Method as GlooMethod, RequestBuilder};
+
+        Box::pin(async move {
+            let method = GlooMethod::from_bytes(request.method.as_str().as_bytes())
+                .map_err(|e| format!("Request Building Error: {}", e))?;
+            let signal = request.abort.as_ref().and_then(|abort| abort.controller.as_ref()).map(|c| c.signal());
+            let mut builder = RequestBuilder::new(&request.url)
+                .method(method)
+                .abort_signal(signal.as_ref());
+            for (key, value) in &request.headers {
+                builder = builder.header(key, value);
+            }
+            let built = match request.body {
+                Some(body) => builder.body(body),
+                None => builder.build(),
+            }
+            .map_err(|e| format!("Request Building Error: {}", e))?;
+
+            let response = built.send().await.map_err(|e| e.to_string())?;
+            Ok(HttpResponse {
+                status: response.status(),
+                body: response.text().await.unwrap_or_default(),
+            })
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeBackend;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl HttpBackend for NativeBackend {
+    /// Blocks the calling thread for the round trip; there is no event loop to yield
+    /// to outside the browser.
+    fn send(&self, request: HttpRequest) -> LocalBoxFuture<'static, Result<HttpResponse, String>> {
+        Box::pin(async move {
+            if request.abort.as_ref().map_or(false, AbortHandle::is_aborted) {
+                return Err("The request was aborted".to_string());
+            }
+            let mut call = ureq::request(request.method.as_str(), &request.url);
+            for (key, value) in &request.headers {
+                call = call.set(key, value);
+            }
+            let result = match request.body {
+                Some(body) => call.send_string(&body),
+                None => call.call(),
+            };
+            let response = match result {
+                Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+                Err(e) => return Err(e.to_string()),
+            };
+            let status = response.status();
+            let body = response.into_string().map_err(|e| e.to_string())?;
+            Ok(HttpResponse { status, body })
+        })
+    }
+}
@@ -1,13 +1,14 @@
-use gloo_net::http::Request;
 use serde::{Deserialize, Serialize};
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD;
 
+use yew::prelude::*;
 use yew_router::history::{BrowserHistory, History};
-use yewdux::Dispatch;
+use yewdux::prelude::*;
 // Add imports for your context modules
-use crate::components::context::{AppState};
-use anyhow::{Error, Context};
+use crate::components::context::{AppState, UIState};
+use anyhow::Error;
+use crate::requests::client::PinepodsClient;
 
 #[derive(Serialize)]
 pub struct LoginRequest {
@@ -31,24 +32,90 @@ pub struct LoginResponse {
     retrieved_key: String,
 }
 
+/// Oldest server version this client is written against.
+pub const MIN_SERVER_VERSION: &str = "0.6.0";
+
 #[derive(Deserialize)]
 pub struct PinepodsCheckResponse {
     pinepods_instance: Option<bool>,
+    // Servers older than 0.6.0 report neither of these
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub capabilities: Option<Vec<String>>,
+}
+
+/// Optional server features the UI checks for before offering them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Capability {
+    Gpodder,
+    Nextcloud,
+    Stream,
+    Pagination,
+    PlaybackSettings,
+    PodcastPlayback,
+    AudioProcessing,
+    ContinuousPlayback,
+    PositionSync,
+    Bookmarks,
+    Completion,
+}
+
+impl Capability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Capability::Gpodder => "gpodder",
+            Capability::Nextcloud => "nextcloud",
+            Capability::Stream => "stream",
+            Capability::Pagination => "pagination",
+            Capability::PlaybackSettings => "playback_settings",
+            Capability::PodcastPlayback => "podcast_playback",
+            Capability::AudioProcessing => "audio_processing",
+            Capability::ContinuousPlayback => "continuous_playback",
+            Capability::PositionSync => "position_sync",
+            Capability::Bookmarks => "bookmarks",
+            Capability::Completion => "completion",
+        }
+    }
+
+    /// Whether servers from before the capability list already had this feature.
+    fn legacy(&self) -> bool {
+        !matches!(
+            self,
+            Capability::Pagination
+                | Capability::PlaybackSettings
+                | Capability::PodcastPlayback
+                | Capability::AudioProcessing
+                | Capability::ContinuousPlayback
+                | Capability::PositionSync
+                | Capability::Bookmarks
+                | Capability::Completion
+        )
+    }
 }
 
-pub async fn verify_pinepods_instance(server_name: &str) -> Result<PinepodsCheckResponse, anyhow::Error> {
-    let url = format!("{}/api/pinepods_check", server_name);
-    let response = Request::get(&url).send().await?;
+/// Parses "x.y.z", ignoring a leading "v" and any pre-release suffix.
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let core = version.trim().trim_start_matches('v').split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
 
-    if response.ok() {
-        let check_data: PinepodsCheckResponse = response.json().await?;
+impl PinepodsClient {
+    pub async fn verify_pinepods_instance(&self) -> Result<PinepodsCheckResponse, Error> {
+        let check_data: PinepodsCheckResponse = self
+            .get("/api/pinepods_check")
+            .public()
+            .fetch("Failed to verify Pinepods instance")
+            .await?;
         if check_data.pinepods_instance.unwrap_or(false) {
             Ok(check_data)
         } else {
-            Err(anyhow::Error::msg("Pinepods instance not found"))
+            Err(Error::msg("Pinepods instance not found"))
         }
-    } else {
-        Err(anyhow::Error::msg("Failed to verify Pinepods instance"))
     }
 }
 
@@ -58,19 +125,11 @@ pub struct KeyVerification {
     pub status: String
 }
 
-pub async fn call_verify_key(server_name: &str, api_key: &str) -> Result<crate::requests::login_requests::KeyVerification, anyhow::Error> {
-    let url = format!("{}/api/data/verify_key", server_name);
-
-    let response = Request::get(&url)
-        .header("Api-Key", api_key)
-        .send()
-        .await?;
-
-    if response.ok() {
-        let key_verify: crate::requests::login_requests::KeyVerification = response.json().await?;
-        Ok(key_verify)
-    } else {
-        Err(anyhow::Error::msg("Failed to get user data"))
+impl PinepodsClient {
+    pub async fn verify_key(&self) -> Result<KeyVerification, Error> {
+        self.get("/api/data/verify_key")
+            .fetch("Failed to verify API key")
+            .await
     }
 }
 #[derive(Deserialize, Debug)]
@@ -104,19 +163,11 @@ pub struct GetUserIdResponse {
     pub retrieved_id: Option<i32>,
 }
 
-pub async fn call_get_user_id(server_name: &str, api_key: &str) -> Result<GetUserIdResponse, anyhow::Error> {
-    let url = format!("{}/api/data/get_user", server_name);
-
-    let response = Request::get(&url)
-        .header("Api-Key", api_key)
-        .send()
-        .await?;
-
-    if response.ok() {
-        let user_id_data: GetUserIdResponse = response.json().await?;
-        Ok(user_id_data)
-    } else {
-        Err(anyhow::Error::msg("Failed to get user ID"))
+impl PinepodsClient {
+    pub async fn get_user_id(&self) -> Result<GetUserIdResponse, Error> {
+        self.get("/api/data/get_user")
+            .fetch("Failed to get user ID")
+            .await
     }
 }
 
@@ -132,21 +183,11 @@ pub struct GetUserDetails {
     pub Salt: Option<String>
 }
 
-pub async fn call_get_user_details(server_name: &str, api_key: &str, user_id: &i32) -> Result<crate::requests::login_requests::GetUserDetails, anyhow::Error> {
-    let url = format!("{}/api/data/user_details_id/{}", server_name, user_id);
-
-    let response = Request::get(&url)
-        .header("Api-Key", api_key)
-        .send()
-        .await?;
-
-    if response.ok() {
-        let body_str = response.text().await?;
-
-        let user_data: crate::requests::login_requests::GetUserDetails = serde_json::from_str(&body_str)?;
-        Ok(user_data)
-    } else {
-        Err(anyhow::Error::msg("Failed to get user information"))
+impl PinepodsClient {
+    pub async fn get_user_details(&self, user_id: &i32) -> Result<GetUserDetails, Error> {
+        self.get(format!("/api/data/user_details_id/{}", user_id))
+            .fetch("Failed to get user information")
+            .await
     }
 }
 
@@ -158,87 +199,147 @@ pub struct GetApiDetails {
     pub proxy_host: Option<String>,
     pub proxy_port: Option<String>,
     pub proxy_protocol: Option<String>,
-    pub reverse_proxy: Option<String>
+    pub reverse_proxy: Option<String>,
+    // Filled in from /api/pinepods_check, not the config endpoint
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub capabilities: Option<Vec<String>>,
+}
+
+impl GetApiDetails {
+    pub fn apply_check(&mut self, check: &PinepodsCheckResponse) {
+        self.version = check.version.clone();
+        self.capabilities = check.capabilities.clone();
+    }
+
+    pub fn supports(&self, capability: Capability) -> bool {
+        match &self.capabilities {
+            Some(capabilities) => capabilities.iter().any(|c| c == capability.as_str()),
+            None => capability.legacy(),
+        }
+    }
+
+    /// Explains why this client may not work fully with the server, if it may not.
+    pub fn compatibility_warning(&self) -> Option<String> {
+        let min = parse_version(MIN_SERVER_VERSION)?;
+        let Some(version) = self.version.as_deref() else {
+            return Some(format!(
+                "This Pinepods server is older than version {}. Some features may not work until it is updated.",
+                MIN_SERVER_VERSION
+            ));
+        };
+        match parse_version(version) {
+            Some(parsed) if parsed.0 > min.0 => Some(format!(
+                "This Pinepods server runs version {}, which is newer than this app supports. Some features may not work until the app is updated.",
+                version
+            )),
+            Some(parsed) if parsed >= min => None,
+            _ => Some(format!(
+                "This Pinepods server runs version {}, but this app needs {} or newer. Some features may not work until the server is updated.",
+                version, MIN_SERVER_VERSION
+            )),
+        }
+    }
 }
 
-pub async fn call_get_api_config(server_name: &str, api_key: &str) -> Result<crate::requests::login_requests::GetApiDetails, anyhow::Error> {
-    let url = format!("{}/api/data/config", server_name);
+impl PinepodsClient {
+    pub async fn get_api_config(&self) -> Result<GetApiDetails, Error> {
+        self.get("/api/data/config")
+            .fetch("Failed to get server information")
+            .await
+    }
 
-    let response = Request::get(&url)
-        .header("Api-Key", api_key)
-        .send()
-        .await?;
+    /// Exchanges username and password for an API key, then loads everything the
+    /// app keeps about the logged in user and the server.
+    pub async fn login_new_server(&self, username: String, password: String) -> Result<(GetUserDetails, LoginServerRequest, GetApiDetails), Error> {
+        let credentials = STANDARD.encode(format!("{}:{}", username, password).as_bytes());
+        let auth_header = format!("Basic {}", credentials);
 
-    if response.ok() {
-        let server_data: GetApiDetails = response.json().await?;
-        Ok(server_data)
-    } else {
-        Err(anyhow::Error::msg("Failed to get user information"))
+        // Step 1: Verify Server
+        let check_data = self.verify_pinepods_instance().await?;
+        if !check_data.pinepods_instance.unwrap_or(false) {
+            return Err(Error::msg("Pinepods instance not found at specified server"));
+        }
+
+        // Step 2: Get API key
+        let api_key = self
+            .get("/api/data/get_key")
+            .public()
+            .header("Authorization", auth_header)
+            .fetch::<LoginResponse>("Failed to authenticate user. Incorrect credentials?")
+            .await?
+            .retrieved_key;
+        let client = PinepodsClient::new(self.server_name(), Some(api_key.clone()));
+
+        // Step 3: Verify the API key
+        let verify_response = client.verify_key().await?;
+        if verify_response.status != "success" {
+            return Err(Error::msg("API key verification failed"));
+        }
+
+        // Step 4: Get user ID
+        let user_id_response = client.get_user_id().await?;
+        if user_id_response.status != "success" {
+            return Err(Error::msg("Failed to get user ID"));
+        }
+        let user_id = user_id_response.retrieved_id.ok_or_else(|| Error::msg("Failed to get user ID"))?;
+
+        let login_request = LoginServerRequest {
+            server_name: self.server_name().to_string(),
+            username,
+            password,
+            api_key: Some(api_key),
+        };
+
+        // Step 5: Get user details
+        let user_details = client.get_user_details(&user_id).await?;
+        if user_details.Username.is_none() {
+            return Err(Error::msg("Failed to get user details"));
+        }
+
+        // Step 6: Get server details
+        let mut server_details = client.get_api_config().await?;
+        if server_details.api_url.is_none() {
+            return Err(Error::msg("Failed to get server details"));
+        }
+        server_details.apply_check(&check_data);
+
+        Ok((user_details, login_request, server_details))
     }
 }
 
-pub async fn login_new_server(server_name: String, username: String, password: String) -> Result<(GetUserDetails, LoginServerRequest, GetApiDetails), anyhow::Error> {
-    let credentials = STANDARD.encode(format!("{}:{}", username, password).as_bytes());
-    let auth_header = format!("Basic {}", credentials);
-    let url = format!("{}/api/data/get_key", server_name);
-
-    // Step 1: Verify Server
-    match verify_pinepods_instance(&server_name).await {
-        Ok(check_data) => {
-            if !check_data.pinepods_instance.unwrap_or(false) {
-                return Err(anyhow::Error::msg("Pinepods instance not found at specified server"));
-            }
-            // Step 2: Get API key
-            let response = Request::get(&url)
-                .header("Authorization", &auth_header)
-                .send()
-                .await?;
-
-            if !response.ok() {
-                return Err(anyhow::Error::msg("Failed to authenticate user. Incorrect credentials?"));
-            }
-
-            let api_key = response.json::<LoginResponse>().await?.retrieved_key;
-
-            // Step 2: Verify the API key
-            let verify_response = call_verify_key(&server_name, &api_key).await?;
-            if verify_response.status != "success" {
-                return Err(anyhow::Error::msg("API key verification failed"));
-            }
-
-            // Step 3: Get user ID
-            let user_id_response = call_get_user_id(&server_name, &api_key).await?;
-            if user_id_response.status != "success" {
-                return Err(anyhow::Error::msg("Failed to get user ID"));
-            }
-
-            let login_request = LoginServerRequest {
-                server_name: server_name.clone(),
-                username: username.clone(),
-                password: password.clone(),
-                api_key: Some(api_key.clone()), // or None, depending on the context
-            };
-
-
-            // Step 4: Get user details
-            let user_details = call_get_user_details(&server_name, &api_key, &user_id_response.retrieved_id.unwrap()).await?;
-            if user_details.Username.is_none() {
-                return Err(anyhow::Error::msg("Failed to get user details"));
-            }
-
-            // Step 5: Get server details
-            let server_details = call_get_api_config(&server_name, &api_key).await?;
-            if server_details.api_url.is_none() {
-                return Err(anyhow::Error::msg("Failed to get server details"));
-            }
-
-            Ok((user_details, login_request, server_details))
-        },
-        Err(e) => {
-            // Directly propagate the error from verify_pinepods_instance
-            return Err(e);
+/// Refreshes the server's version and capabilities whenever the app connects to a
+/// server, so sessions restored from storage or a saved profile pick up upgrades,
+/// and warns when the server is too old or too new for this client.
+#[hook]
+pub fn use_server_handshake() {
+    let (state, dispatch) = use_store::<AppState>();
+    let (_ui_state, ui_dispatch) = use_store::<UIState>();
+    let client = state.client();
+    use_effect_with(client, move |client| {
+        if !client.server_name().is_empty() && dispatch.get().server_details.is_some() {
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let Ok(check) = client.verify_pinepods_instance().await else { return };
+                dispatch.reduce_mut(|state| {
+                    if let Some(details) = state.server_details.as_mut() {
+                        details.apply_check(&check);
+                    }
+                    state.store_app_state();
+                });
+                let warning = dispatch
+                    .get()
+                    .server_details
+                    .as_ref()
+                    .and_then(|details| details.compatibility_warning());
+                if let Some(warning) = warning {
+                    ui_dispatch.reduce_mut(|ui_state| ui_state.error_message = Some(warning));
+                }
+            });
         }
-    }
+        || ()
+    });
 }
 
 pub(crate) fn use_check_authentication(_dispatch: Dispatch<AppState>, current_route: &str) {
@@ -258,6 +359,30 @@ pub(crate) fn use_check_authentication(_dispatch: Dispatch<AppState>, current_ro
     }
 }
 
+/// Called when the server rejects the stored API key. Drops the auth details and
+/// sends the user back to login, remembering where they were so the login flow
+/// can return there the same way it does for `use_check_authentication`.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn handle_unauthorized() {
+    use crate::components::routes::Route;
+    use yew_router::Routable;
+
+    Dispatch::<AppState>::global().reduce_mut(|state| state.auth_details = None);
+
+    let window = web_sys::window().expect("no global `window` exists");
+    let session_storage = window.session_storage().unwrap().unwrap();
+    session_storage.set_item("isAuthenticated", "false").unwrap();
+
+    let history = BrowserHistory::new();
+    let location = history.location();
+    let login_path = Route::Login.to_path();
+    if location.path() != login_path {
+        let current_route = format!("{}{}", location.path(), location.query_str());
+        session_storage.set_item("requested_route", &current_route).unwrap();
+        history.push(login_path);
+    }
+}
+
 
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
@@ -273,24 +398,14 @@ pub struct AddUserResponse {
     detail: String,
 }
 
-pub async fn call_add_login_user(server_name: String, add_user: &Option<AddUserRequest>) -> Result<bool, Error> {
-    let server = server_name.clone();
-    let url = format!("{}/api/data/add_login_user", server);
-    let add_user_req = add_user.as_ref().unwrap();
-
-    // Serialize `add_user` into JSON
-    let json_body = serde_json::to_string(&add_user_req)?;
-
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .body(json_body)?
-        .send()
-        .await?;
-
-    if response.ok() {
+impl PinepodsClient {
+    pub async fn add_login_user(&self, add_user: &AddUserRequest) -> Result<bool, Error> {
+        self.post("/api/data/add_login_user")
+            .public()
+            .json(add_user)?
+            .send("Error adding user")
+            .await?;
         Ok(true)
-    } else {
-        Err(Error::msg(format!("Error adding user: {}", response.status_text())))
     }
 }
 
@@ -300,20 +415,13 @@ struct FirstLoginResponse {
     FirstLogin: bool,
 }
 
-pub async fn call_first_login_done(server_name: String, api_key: String, user_id: &i32) -> Result<bool, Error> {
-    let url = format!("{}/api/data/first_login_done/{}", server_name, user_id);
-
-    let response = Request::get(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", &api_key)
-        .send()
-        .await?;
-
-    if response.ok() {
-        let response_body = response.json::<FirstLoginResponse>().await?;
-        Ok(response_body.FirstLogin) // Use the struct field to get the boolean value
-    } else {
-        Err(Error::msg(format!("Error checking first login status: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn first_login_done(&self, user_id: &i32) -> Result<bool, Error> {
+        let response: FirstLoginResponse = self
+            .get(format!("/api/data/first_login_done/{}", user_id))
+            .fetch("Error checking first login status")
+            .await?;
+        Ok(response.FirstLogin)
     }
 }
 
@@ -331,33 +439,12 @@ pub struct SetupTimeZoneInfoResponse {
     pub success: bool,
 }
 
-pub async fn call_setup_timezone_info(
-    server_name: String,
-    api_key: String,
-    time_zone_info: TimeZoneInfo,
-) -> Result<SetupTimeZoneInfoResponse, anyhow::Error> {
-    let url = format!("{}/api/data/setup_time_info", server_name);
-    let request_body = serde_json::to_string(&time_zone_info)
-        .context("Serialization Error")?;
-
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key.as_str())
-        .body(request_body)
-        .context("Request Building Error")?
-        .send()
-        .await
-        .context("Network Request Error")?;
-
-    if response.ok() {
-        response.json::<SetupTimeZoneInfoResponse>()
+impl PinepodsClient {
+    pub async fn setup_timezone_info(&self, time_zone_info: &TimeZoneInfo) -> Result<SetupTimeZoneInfoResponse, Error> {
+        self.post("/api/data/setup_time_info")
+            .json(time_zone_info)?
+            .fetch("Error setting up time info")
             .await
-            .context("Response Parsing Error")
-    } else {
-        Err(anyhow::anyhow!(
-            "Error setting up time info. Server Response: {}",
-            response.status_text()
-        ))
     }
 }
 
@@ -368,29 +455,11 @@ pub struct TimeInfoResponse {
     pub date_format: String,
 }
 
-pub async fn call_get_time_info(
-    server_name: String,
-    api_key: String,
-    user_id: &i32,
-) -> Result<TimeInfoResponse, anyhow::Error> {
-    let url = format!("{}/api/data/get_time_info?user_id={}", server_name, user_id);
-
-    let response = Request::get(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", &api_key)
-        .send()
-        .await
-        .context("Network Request Error")?;
-
-    if response.ok() {
-        response.json::<TimeInfoResponse>()
+impl PinepodsClient {
+    pub async fn get_time_info(&self, user_id: &i32) -> Result<TimeInfoResponse, Error> {
+        self.get(format!("/api/data/get_time_info?user_id={}", user_id))
+            .fetch("Error fetching time info")
             .await
-            .context("Response Parsing Error")
-    } else {
-        Err(anyhow::anyhow!(
-            "Error fetching time info. Server Response: {}",
-            response.status_text()
-        ))
     }
 }
 
@@ -400,24 +469,11 @@ pub struct CheckMfaEnabledResponse {
     pub(crate) mfa_enabled: bool,
 }
 
-pub async fn call_check_mfa_enabled(server_name: String, api_key: String, user_id: &i32) -> Result<CheckMfaEnabledResponse, Error> {
-    let url = format!("{}/api/data/check_mfa_enabled/{}", server_name, user_id);
-    
-    let response = Request::get(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key.as_str())
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network Request Error: {}", e)))?;
-
-    if response.ok() {
-        response.json::<CheckMfaEnabledResponse>()
+impl PinepodsClient {
+    pub async fn check_mfa_enabled(&self, user_id: &i32) -> Result<CheckMfaEnabledResponse, Error> {
+        self.get(format!("/api/data/check_mfa_enabled/{}", user_id))
+            .fetch("Error checking MFA enabled status")
             .await
-            .map_err(|e| Error::msg(format!("Response Parsing Error: {}", e)))
-    } else {
-        let status_text = response.status_text();
-        let error_text = response.text().await.unwrap_or_else(|_| String::from("Failed to read error message"));
-        Err(Error::msg(format!("Error checking MFA enabled status: {} - {}", status_text, error_text)))
     }
 }
 
@@ -432,23 +488,12 @@ pub struct VerifyMFAResponse {
     pub(crate) verified: bool,
 }
 
-pub async fn call_verify_mfa(server_name: &String, api_key: &String, user_id: i32, mfa_code: String) -> Result<VerifyMFAResponse, Error> {
-    let url = format!("{}/api/data/verify_mfa", server_name);
-    let body = VerifyMFABody { user_id, mfa_code };
-    let request_body = serde_json::to_string(&body)?;
-
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key)
-        .body(&request_body)?
-        .send()
-        .await?;
-
-    if response.ok() {
-        let response_body = response.json::<VerifyMFAResponse>().await?;
-        Ok(response_body)
-    } else {
-        Err(anyhow::Error::msg(format!("Error verifying MFA: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn verify_mfa(&self, user_id: i32, mfa_code: String) -> Result<VerifyMFAResponse, Error> {
+        self.post("/api/data/verify_mfa")
+            .json(&VerifyMFABody { user_id, mfa_code })?
+            .fetch("Error verifying MFA")
+            .await
     }
 }
 
@@ -457,19 +502,14 @@ pub struct SelfServiceStatusResponse {
     status: bool,
 }
 
-pub async fn call_self_service_login_status(server_name: String) -> Result<bool, Error> {
-    let url = format!("{}/api/data/self_service_status", server_name);
-
-    let response = Request::get(&url)
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        let status_response: SelfServiceStatusResponse = response.json().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))?;
-        Ok(status_response.status)
-    } else {
-        Err(Error::msg(format!("Error fetching self service status: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn self_service_login_status(&self) -> Result<bool, Error> {
+        let response: SelfServiceStatusResponse = self
+            .get("/api/data/self_service_status")
+            .public()
+            .fetch("Error fetching self service status")
+            .await?;
+        Ok(response.status)
     }
 }
 
@@ -490,34 +530,16 @@ pub struct ErrorResponse {
 }
 
 
-pub async fn call_reset_password_create_code(server_name: String, create_code: &ResetCodePayload) -> Result<bool, Error> {
-    let url = format!("{}/api/data/reset_password_create_code", server_name);
-
-    let json_body = serde_json::to_string(&create_code)?;
-
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .body(json_body)?
-        .send()
-        .await?;
-
-    if response.ok() {
-        let response_body = response.json::<ResetCodeResponse>().await?;
-        Ok(response_body.code_created)
-    } else {
-        let error_response: Result<ErrorResponse, _> = response.json().await;
-        match error_response {
-            Ok(err) => {
-                Err(Error::msg(err.detail))
-            },
-            Err(_) => {
-                // If parsing the detailed error fails, fall back to the status text
-                let status_text = response.status_text();
-                Err(Error::msg(format!("Error creating reset code: {}", status_text)))
-            }
-        }
+impl PinepodsClient {
+    pub async fn reset_password_create_code(&self, create_code: &ResetCodePayload) -> Result<bool, Error> {
+        let response: ResetCodeResponse = self
+            .post("/api/data/reset_password_create_code")
+            .public()
+            .json(create_code)?
+            .fetch("Error creating reset code")
+            .await?;
+        Ok(response.code_created)
     }
-
 }
 
 #[derive(Serialize)]
@@ -532,22 +554,96 @@ pub struct ForgotResetPasswordResponse {
     pub message: String,
 }
 
-pub async fn call_verify_and_reset_password(server_name: String, verify_and_reset: &ResetForgotPasswordPayload) -> Result<ForgotResetPasswordResponse, Error> {
-    let url = format!("{}/api/data/verify_and_reset_password", server_name);
-
-    let json_body = serde_json::to_string(&verify_and_reset)?;
+impl PinepodsClient {
+    pub async fn verify_and_reset_password(&self, verify_and_reset: &ResetForgotPasswordPayload) -> Result<ForgotResetPasswordResponse, Error> {
+        self.post("/api/data/verify_and_reset_password")
+            .public()
+            .json(verify_and_reset)?
+            .fetch("Error resetting password")
+            .await
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::client::ApiError;
+    use crate::requests::stub_server::{fixture, StubServer};
+    use pollster::block_on;
+
+    const KEY: &str = "K9l5r3Qx2tVb8mYw1zPq";
+
+    fn login_server() -> StubServer {
+        let server = StubServer::start();
+        server
+            .route("GET", "/api/pinepods_check", 200, fixture("pinepods_check.json"))
+            .route("GET", "/api/data/get_key", 200, fixture("get_key.json"))
+            .route("GET", "/api/data/verify_key", 200, fixture("verify_key.json"))
+            .route("GET", "/api/data/get_user", 200, fixture("get_user.json"))
+            .route("GET", "/api/data/user_details_id/2", 200, fixture("user_details_id.json"))
+            .route("GET", "/api/data/config", 200, fixture("config.json"));
+        server
+    }
 
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .body(json_body)?
-        .send()
-        .await?;
+    #[test]
+    fn login_new_server_collects_user_and_server_details() {
+        let server = login_server();
+        let client = server.client(None);
+
+        let (user, login, config) =
+            block_on(client.login_new_server("pinepods".to_string(), "hunter2".to_string())).unwrap();
+
+        assert_eq!(user.UserID, 2);
+        assert_eq!(user.Username.as_deref(), Some("pinepods"));
+        assert_eq!(login.api_key.as_deref(), Some(KEY));
+        assert_eq!(login.server_name, server.url());
+        assert_eq!(config.proxy_port.as_deref(), Some("8040"));
+        assert_eq!(config.version.as_deref(), Some("0.6.0"));
+        assert!(config.supports(Capability::Pagination));
+        assert_eq!(config.compatibility_warning(), None);
+
+        let requests = server.requests();
+        let get_key = requests.iter().find(|r| r.path == "/api/data/get_key").unwrap();
+        assert_eq!(get_key.header("Authorization"), Some("Basic cGluZXBvZHM6aHVudGVyMg=="));
+        assert_eq!(get_key.header("Api-Key"), None);
+        let details = requests.iter().find(|r| r.path == "/api/data/user_details_id/2").unwrap();
+        assert_eq!(details.header("Api-Key"), Some(KEY));
+    }
 
-    if response.ok() {
-        let response_body = response.json::<ForgotResetPasswordResponse>().await?;
-        Ok(response_body)
-    } else {
-        Err(Error::msg(format!("Error creating reset code: {}", response.status_text())))
+    #[test]
+    fn login_new_server_stops_on_bad_credentials() {
+        let server = StubServer::start();
+        server
+            .route("GET", "/api/pinepods_check", 200, fixture("pinepods_check.json"))
+            .route("GET", "/api/data/get_key", 401, r#"{"detail":"Invalid credentials"}"#);
+        let client = server.client(None);
+
+        let err = block_on(client.login_new_server("pinepods".to_string(), "wrong".to_string())).unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<ApiError>(),
+            Some(&ApiError::Unauthorized("Invalid credentials".to_string()))
+        );
+        assert!(!server.requests().iter().any(|r| r.path == "/api/data/verify_key"));
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn legacy_server_keeps_legacy_features_and_warns() {
+        let server = StubServer::start();
+        server.route("GET", "/api/pinepods_check", 200, r#"{"status_code": 200, "pinepods_instance": true}"#);
+        let check = block_on(server.client(None).verify_pinepods_instance()).unwrap();
+
+        let mut details: GetApiDetails = serde_json::from_str(&fixture("config.json")).unwrap();
+        details.apply_check(&check);
+
+        assert!(details.supports(Capability::Nextcloud));
+        assert!(!details.supports(Capability::Pagination));
+        assert!(details.compatibility_warning().is_some());
+
+        details.version = Some("0.5.9".to_string());
+        assert!(details.compatibility_warning().unwrap().contains("0.5.9"));
+        details.version = Some("v0.7.1-beta".to_string());
+        assert_eq!(details.compatibility_warning(), None);
+        details.version = Some("1.0.0".to_string());
+        assert!(details.compatibility_warning().unwrap().contains("newer"));
+    }
+}
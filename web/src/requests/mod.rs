@@ -1,7 +1,16 @@
+pub(crate) mod cache;
+pub(crate) mod bookmarks;
+pub(crate) mod chapters;
+pub(crate) mod client;
+pub(crate) mod http;
 pub(crate) mod login_requests;
 pub(crate) mod models;
+pub(crate) mod outbox;
 pub(crate) mod pod_req;
 pub(crate) mod search_pods;
 
 pub(crate) mod stat_reqs;
-pub(crate) mod setting_reqs;
\ No newline at end of file
+pub(crate) mod transcripts;
+pub(crate) mod setting_reqs;
+#[cfg(test)]
+pub(crate) mod stub_server;
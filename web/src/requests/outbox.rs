@@ -0,0 +1,338 @@
+use std::cell::Cell;
+use anyhow::Error;
+use gloo_events::EventListener;
+use gloo_timers::callback::Interval;
+use serde::{Deserialize, Serialize};
+use web_sys::window;
+use yew::prelude::*;
+use yewdux::prelude::*;
+use crate::components::context::AppState;
+use crate::requests::client::{ApiError, PinepodsClient};
+use crate::requests::pod_req::{HistoryAddRequest, MarkCompletedRequest, QueuePodcastRequest, RecordListenDurationRequest, SavePodcastRequest};
+use crate::requests::stat_reqs::SilenceTimeSavedRequest;
+
+const OUTBOX_KEY: &str = "mutationOutbox";
+/// Milliseconds between retries while changes are waiting. The browser only reports
+/// losing the network, not a server that's down or restarting behind its proxy.
+const RETRY_EVERY_MS: u32 = 30_000;
+
+/// A change made while the server was unreachable, kept until it can be replayed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", content = "request")]
+pub enum Mutation {
+    QueueEpisode(QueuePodcastRequest),
+    SaveEpisode(SavePodcastRequest),
+    AddHistory(HistoryAddRequest),
+    RecordListenDuration(RecordListenDurationRequest),
+    AddSilenceTimeSaved(SilenceTimeSavedRequest),
+    MarkCompleted(MarkCompletedRequest),
+    MarkUncompleted(MarkCompletedRequest),
+}
+
+impl Mutation {
+    fn user_id(&self) -> i32 {
+        match self {
+            Mutation::QueueEpisode(request) => request.user_id,
+            Mutation::SaveEpisode(request) => request.user_id,
+            Mutation::AddHistory(request) => request.user_id,
+            Mutation::RecordListenDuration(request) => request.user_id,
+            Mutation::AddSilenceTimeSaved(request) => request.user_id,
+            Mutation::MarkCompleted(request) | Mutation::MarkUncompleted(request) => request.user_id,
+        }
+    }
+
+    async fn send(&self, client: &PinepodsClient) -> Result<(), Error> {
+        match self {
+            Mutation::QueueEpisode(request) => client.queue_episode(request).await.map(|_| ()),
+            Mutation::SaveEpisode(request) => client.save_episode(request).await.map(|_| ()),
+            Mutation::AddHistory(request) => client.add_history(request).await,
+            Mutation::RecordListenDuration(request) => client.record_listen_duration(request).await.map(|_| ()),
+            Mutation::AddSilenceTimeSaved(request) => client.add_silence_time_saved(request).await,
+            Mutation::MarkCompleted(request) => client.mark_episode_completed(request).await,
+            Mutation::MarkUncompleted(request) => client.mark_episode_uncompleted(request).await,
+        }
+    }
+
+    /// A newer listen duration, or played mark, for the same episode makes the older one
+    /// pointless.
+    fn replaces(&self, other: &Mutation) -> bool {
+        match (self, other) {
+            (Mutation::RecordListenDuration(new), Mutation::RecordListenDuration(old)) => {
+                new.episode_id == old.episode_id && new.user_id == old.user_id
+            }
+            (
+                Mutation::MarkCompleted(new) | Mutation::MarkUncompleted(new),
+                Mutation::MarkCompleted(old) | Mutation::MarkUncompleted(old),
+            ) => new == old,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct OutboxEntry {
+    server_name: String,
+    /// Another user's key would be refused the change, so only its own user replays it.
+    user_id: i32,
+    mutation: Mutation,
+}
+
+impl OutboxEntry {
+    fn belongs_to(&self, server_name: &str, user_id: i32) -> bool {
+        self.server_name == server_name && self.user_id == user_id
+    }
+}
+
+fn load() -> Vec<OutboxEntry> {
+    read_stored()
+        .and_then(|stored| serde_json::from_str(&stored).ok())
+        .unwrap_or_default()
+}
+
+fn store(entries: &[OutboxEntry]) {
+    write_stored((!entries.is_empty()).then(|| serde_json::to_string(entries).unwrap_or_default()));
+    #[cfg(target_arch = "wasm32")]
+    {
+        let pending = entries.len();
+        Dispatch::<crate::components::context::OutboxState>::global().reduce_mut(move |state| state.pending = pending);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_stored() -> Option<String> {
+    window()
+        .and_then(|w| w.local_storage().ok().flatten())
+        .and_then(|storage| storage.get_item(OUTBOX_KEY).ok().flatten())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_stored(stored: Option<String>) {
+    if let Some(storage) = window().and_then(|w| w.local_storage().ok().flatten()) {
+        let _ = match stored {
+            Some(stored) => storage.set_item(OUTBOX_KEY, &stored),
+            None => storage.remove_item(OUTBOX_KEY),
+        };
+    }
+}
+
+// Native builds, including `cargo test`, have no localStorage
+#[cfg(not(target_arch = "wasm32"))]
+thread_local! {
+    static STORED: std::cell::RefCell<Option<String>> = std::cell::RefCell::new(None);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_stored() -> Option<String> {
+    STORED.with(|stored| stored.borrow().clone())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_stored(stored: Option<String>) {
+    STORED.with(|slot| *slot.borrow_mut() = stored);
+}
+
+/// Whether `err` means the server couldn't be reached, or couldn't answer for now,
+/// like a proxy's 502 or 503 while the backend restarts. Any other 5xx is the
+/// server failing the change itself, and would fail the same way on every retry.
+fn is_unreachable(err: &Error) -> bool {
+    match err.downcast_ref::<ApiError>() {
+        Some(ApiError::Network(_)) => true,
+        Some(ApiError::Server(status, _)) => matches!(status, 502..=504),
+        _ => false,
+    }
+}
+
+/// Keeps `mutation` for replay if `err` means the server could not be reached.
+/// Returns whether it was kept, so callers can tell the user it is not lost.
+pub fn defer_if_offline(client: &PinepodsClient, err: &Error, mutation: Mutation) -> bool {
+    if !is_unreachable(err) {
+        return false;
+    }
+    let user_id = mutation.user_id();
+    let mut entries = load();
+    entries.retain(|entry| !entry.belongs_to(&client.server_name(), user_id) || !mutation.replaces(&entry.mutation));
+    entries.push(OutboxEntry {
+        server_name: client.server_name().to_string(),
+        user_id,
+        mutation,
+    });
+    store(&entries);
+    true
+}
+
+thread_local! {
+    static REPLAYING: Cell<bool> = Cell::new(false);
+}
+
+fn has_pending(client: &PinepodsClient, user_id: i32) -> bool {
+    load().iter().any(|entry| entry.belongs_to(&client.server_name(), user_id))
+}
+
+/// Sends the stored mutations `user_id` made on `client`'s server in the order they
+/// were made. Stops at the first one that still can't reach the server, or whose API
+/// key was rejected so it can go out after the next login. Mutations the server
+/// rejects for any other reason are dropped, since retrying them would fail the same way.
+pub async fn replay(client: &PinepodsClient, user_id: i32) {
+    if !client.is_authenticated() || REPLAYING.with(|flag| flag.replace(true)) {
+        return;
+    }
+    // Storage is re-read around every send because new changes can be deferred meanwhile.
+    while let Some(entry) = load().into_iter().find(|entry| entry.belongs_to(&client.server_name(), user_id)) {
+        match entry.mutation.send(client).await {
+            Ok(_) => {}
+            Err(e) if is_unreachable(&e) => break,
+            Err(e) if matches!(e.downcast_ref::<ApiError>(), Some(ApiError::Unauthorized(_))) => break,
+            Err(e) => log::error!("Dropping queued change {:?}: {}", entry.mutation, e),
+        }
+        let mut entries = load();
+        if let Some(index) = entries.iter().position(|stored| *stored == entry) {
+            entries.remove(index);
+        }
+        store(&entries);
+    }
+    REPLAYING.with(|flag| flag.set(false));
+}
+
+/// Replays the logged-in user's outbox after login, whenever the browser comes back
+/// online or the app regains focus, and every so often while changes are waiting.
+#[hook]
+pub fn use_outbox_replay() {
+    let (state, _dispatch) = use_store::<AppState>();
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+    use_effect_with((client, user_id), |(client, user_id)| {
+        store(&load());
+        let mut listeners = Vec::new();
+        let mut retry = None;
+        if let Some(user_id) = *user_id {
+            let try_replay = {
+                let client = client.clone();
+                move || {
+                    if has_pending(&client, user_id) && window().map_or(false, |w| w.navigator().on_line()) {
+                        let client = client.clone();
+                        wasm_bindgen_futures::spawn_local(async move { replay(&client, user_id).await });
+                    }
+                }
+            };
+            if let Some(w) = window() {
+                for event in ["online", "focus"] {
+                    let try_replay = try_replay.clone();
+                    listeners.push(EventListener::new(&w, event, move |_| try_replay()));
+                }
+            }
+            try_replay();
+            retry = Some(Interval::new(RETRY_EVERY_MS, try_replay));
+        }
+        move || {
+            drop(listeners);
+            drop(retry);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::stub_server::StubServer;
+    use pollster::block_on;
+
+    fn offline() -> Error {
+        Error::new(ApiError::Network("Connection refused".to_string()))
+    }
+
+    fn listened(episode_id: i32, user_id: i32, listen_duration: f64) -> Mutation {
+        Mutation::RecordListenDuration(RecordListenDurationRequest {
+            episode_id,
+            user_id,
+            listen_duration,
+            observed_at: None,
+        })
+    }
+
+    fn queued(episode_id: i32, user_id: i32) -> Mutation {
+        Mutation::QueueEpisode(QueuePodcastRequest { episode_id, user_id })
+    }
+
+    fn stored() -> Vec<Mutation> {
+        load().into_iter().map(|entry| entry.mutation).collect()
+    }
+
+    #[test]
+    fn newer_positions_and_played_marks_replace_older_ones() {
+        let completed = Mutation::MarkCompleted(MarkCompletedRequest { episode_id: 7, user_id: 1 });
+        let uncompleted = Mutation::MarkUncompleted(MarkCompletedRequest { episode_id: 7, user_id: 1 });
+
+        assert!(listened(7, 1, 90.0).replaces(&listened(7, 1, 30.0)));
+        assert!(!listened(7, 1, 90.0).replaces(&listened(8, 1, 30.0)));
+        assert!(!listened(7, 1, 90.0).replaces(&listened(7, 2, 30.0)));
+        assert!(uncompleted.replaces(&completed));
+        assert!(!queued(7, 1).replaces(&queued(7, 1)));
+    }
+
+    #[test]
+    fn only_unreachable_servers_defer_changes() {
+        let client = PinepodsClient::new("http://pinepods.test".to_string(), Some("secret".to_string()));
+
+        assert!(!defer_if_offline(&client, &Error::new(ApiError::Server(500, "Internal Server Error".to_string())), queued(7, 1)));
+        assert!(!defer_if_offline(&client, &Error::new(ApiError::NotFound("Not Found".to_string())), queued(7, 1)));
+        assert!(defer_if_offline(&client, &Error::new(ApiError::Server(503, "Service Unavailable".to_string())), queued(7, 1)));
+        assert!(defer_if_offline(&client, &offline(), queued(8, 1)));
+
+        assert_eq!(stored(), vec![queued(7, 1), queued(8, 1)]);
+    }
+
+    #[test]
+    fn a_newer_change_only_replaces_its_own_accounts_entries() {
+        let client = PinepodsClient::new("http://pinepods.test".to_string(), Some("secret".to_string()));
+        let other_server = PinepodsClient::new("http://other.test".to_string(), Some("secret".to_string()));
+
+        defer_if_offline(&client, &offline(), listened(7, 1, 30.0));
+        defer_if_offline(&client, &offline(), listened(7, 2, 45.0));
+        defer_if_offline(&other_server, &offline(), listened(7, 1, 60.0));
+        defer_if_offline(&client, &offline(), listened(7, 1, 90.0));
+
+        assert_eq!(stored(), vec![listened(7, 2, 45.0), listened(7, 1, 60.0), listened(7, 1, 90.0)]);
+    }
+
+    #[test]
+    fn replay_sends_the_users_changes_and_drops_rejected_ones() {
+        let server = StubServer::start();
+        server
+            .route("POST", "/api/data/queue_pod", 200, r#"{"data":"Episode queued successfully"}"#)
+            .route("POST", "/api/data/mark_episode_completed", 400, r#"{"detail":"Episode not found"}"#);
+        let client = server.client(Some("secret"));
+        defer_if_offline(&client, &offline(), queued(7, 1));
+        defer_if_offline(&client, &offline(), queued(8, 2));
+        defer_if_offline(&client, &offline(), Mutation::MarkCompleted(MarkCompletedRequest { episode_id: 9, user_id: 1 }));
+
+        block_on(replay(&client, 1));
+
+        let paths: Vec<String> = server.requests().into_iter().map(|request| request.path).collect();
+        assert_eq!(paths, vec!["/api/data/queue_pod", "/api/data/mark_episode_completed"]);
+        assert_eq!(stored(), vec![queued(8, 2)]);
+    }
+
+    #[test]
+    fn replay_keeps_changes_a_rejected_key_or_a_down_server_could_not_send() {
+        let server = StubServer::start();
+        server.route("POST", "/api/data/queue_pod", 401, r#"{"detail":"Your API key is either invalid or does not have correct permission"}"#);
+        let client = server.client(Some("revoked"));
+        defer_if_offline(&client, &offline(), queued(7, 1));
+        defer_if_offline(&client, &offline(), queued(8, 1));
+
+        block_on(replay(&client, 1));
+
+        assert_eq!(server.requests().len(), 1);
+        assert_eq!(stored(), vec![queued(7, 1), queued(8, 1)]);
+
+        let down = StubServer::start();
+        down.route("POST", "/api/data/queue_pod", 503, "Service Unavailable");
+        let client = down.client(Some("secret"));
+        defer_if_offline(&client, &offline(), queued(7, 1));
+
+        block_on(replay(&client, 1));
+
+        assert_eq!(down.requests().len(), 1);
+        assert_eq!(stored(), vec![queued(7, 1), queued(8, 1), queued(7, 1)]);
+    }
+}
@@ -1,7 +1,21 @@
+use std::cell::Cell;
 use std::collections::HashMap;
-use anyhow::{Context, Error};
-use gloo_net::http::Request;
+use std::future::Future;
+use std::rc::Rc;
+use anyhow::Error;
+use web_sys::MouseEvent;
+use yew::{hook, use_mut_ref, use_state, Callback};
+use yewdux::prelude::use_store;
+use crate::components::context::AppState;
+use crate::components::gen_components::use_infinite_scroll;
+use crate::requests::cache::CacheKey;
+use crate::requests::client::PinepodsClient;
+use crate::requests::login_requests::Capability;
+use crate::requests::search_pods::PodcastFeedResult;
+use data_encoding::HEXLOWER;
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
 
 fn bool_from_int<'de, D>(deserializer: D) -> Result<bool, D::Error>
     where
@@ -11,6 +25,222 @@ fn bool_from_int<'de, D>(deserializer: D) -> Result<bool, D::Error>
     Ok(value != 0)
 }
 
+/// Whether the user finished an episode. MySQL sends the flag as 0/1, PostgreSQL as a
+/// bool, and both send null for episodes never started.
+pub(crate) fn completed_flag<'de, D>(deserializer: D) -> Result<bool, D::Error>
+    where
+        D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Int(i64),
+    }
+    Ok(match Option::<Flag>::deserialize(deserializer)? {
+        Some(Flag::Bool(value)) => value,
+        Some(Flag::Int(value)) => value != 0,
+        None => false,
+    })
+}
+
+/// A slice of one of the long episode lists, sent as `offset`/`limit` query parameters.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Page {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Page {
+    pub const SIZE: usize = 50;
+
+    pub fn first() -> Self {
+        Page { offset: 0, limit: Page::SIZE }
+    }
+
+    /// The page after this one, or `None` when `received` rows show this was the last.
+    /// Servers without pagination ignore the limit and send everything at once, which
+    /// is also the last page.
+    pub fn after(self, received: usize) -> Option<Page> {
+        (received == self.limit).then(|| Page {
+            offset: self.offset + self.limit,
+            limit: self.limit,
+        })
+    }
+
+    /// Whether a list holding `loaded` rows, with `next` still to fetch, has only its first
+    /// page. With the next page in flight, `next` has been taken.
+    pub fn first_only(loaded: usize, next: Option<Page>) -> bool {
+        loaded <= Page::SIZE && next == Page::first().after(loaded)
+    }
+
+    pub(crate) fn query(&self) -> String {
+        format!("offset={}&limit={}", self.offset, self.limit)
+    }
+}
+
+/// A list that loads a page at a time as it's scrolled.
+pub trait PagedList {
+    /// What the request for one page returns.
+    type More;
+
+    fn next_page_mut(&mut self) -> &mut Option<Page>;
+
+    /// Adds `more`, fetched for `page`, and sets the page after it.
+    fn append(&mut self, page: Page, more: Self::More);
+}
+
+impl PagedList for RecentEps {
+    type More = Vec<Episode>;
+
+    fn next_page_mut(&mut self) -> &mut Option<Page> {
+        &mut self.next_page
+    }
+
+    fn append(&mut self, page: Page, more: Vec<Episode>) {
+        self.next_page = page.after(more.len());
+        self.episodes.get_or_insert_with(Vec::new).extend(more);
+    }
+}
+
+impl PagedList for SavedEpisodesResponse {
+    type More = Vec<SavedEpisode>;
+
+    fn next_page_mut(&mut self) -> &mut Option<Page> {
+        &mut self.next_page
+    }
+
+    fn append(&mut self, page: Page, more: Vec<SavedEpisode>) {
+        self.next_page = page.after(more.len());
+        self.episodes.extend(more);
+    }
+}
+
+impl PagedList for HistoryDataResponse {
+    type More = Vec<HistoryEpisode>;
+
+    fn next_page_mut(&mut self) -> &mut Option<Page> {
+        &mut self.next_page
+    }
+
+    fn append(&mut self, page: Page, more: Vec<HistoryEpisode>) {
+        self.next_page = page.after(more.len());
+        self.data.extend(more);
+    }
+}
+
+impl PagedList for PodcastFeedResult {
+    type More = PodcastFeedResult;
+
+    fn next_page_mut(&mut self) -> &mut Option<Page> {
+        &mut self.next_page
+    }
+
+    fn append(&mut self, _page: Page, more: PodcastFeedResult) {
+        self.next_page = more.next_page;
+        self.episodes.extend(more.episodes);
+    }
+}
+
+/// How the next page of a paged list is doing, for `load_more_indicator`.
+pub struct MorePages {
+    pub loading: bool,
+    pub error: Option<String>,
+    pub retry: Callback<MouseEvent>,
+}
+
+/// Loads the next page of the list `list` finds in the store whenever the view is
+/// scrolled near its end. `next_page` is that list's next page as rendered, and
+/// `fetch_page` requests one page, or gives `None` while there's no user to request it
+/// for. A page that failed waits for `retry` instead of going out again on every scroll.
+#[hook]
+pub fn use_paged_list<L, G, F, Fut>(next_page: Option<Page>, list: G, fetch_page: F) -> MorePages
+where
+    L: PagedList + 'static,
+    G: Fn(&mut AppState) -> Option<&mut L> + 'static,
+    F: Fn(Page) -> Option<Fut> + 'static,
+    Fut: Future<Output = Result<L::More, Error>> + 'static,
+{
+    let (state, dispatch) = use_store::<AppState>();
+    let loading = use_state(|| false);
+    let error = use_state(|| None::<String>);
+    // The store changes before the view re-renders, so this keeps a second scroll event
+    // from taking the same page
+    let in_flight = use_mut_ref(|| false);
+    // Servers without pagination send the whole list as its first page
+    let paged = state
+        .server_details
+        .as_ref()
+        .map_or(false, |details| details.supports(Capability::Pagination));
+
+    let load_more = {
+        let list = Rc::new(list);
+        let loading = loading.clone();
+        let error = error.clone();
+        Callback::from(move |_: ()| {
+            let Some(page) = next_page else { return };
+            let Some(request) = fetch_page(page) else { return };
+            if in_flight.replace(true) {
+                return;
+            }
+            // Taking the page also tells the list's refresh that more is on its way
+            dispatch.reduce_mut({
+                let list = list.clone();
+                move |state| {
+                    if let Some(list) = list(state) {
+                        *list.next_page_mut() = None;
+                    }
+                }
+            });
+            loading.set(true);
+            let dispatch = dispatch.clone();
+            let list = list.clone();
+            let loading = loading.clone();
+            let error = error.clone();
+            let in_flight = in_flight.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = request.await;
+                dispatch.reduce_mut(move |state| {
+                    if let Some(list) = list(state) {
+                        match result {
+                            Ok(more) => list.append(page, more),
+                            Err(e) => {
+                                // Give the page back for the retry
+                                *list.next_page_mut() = Some(page);
+                                error.set(Some(e.to_string()));
+                            }
+                        }
+                    }
+                });
+                loading.set(false);
+                *in_flight.borrow_mut() = false;
+            });
+        })
+    };
+    {
+        let load_more = load_more.clone();
+        let failed = error.is_some();
+        use_infinite_scroll(Callback::from(move |_| {
+            if paged && !failed {
+                load_more.emit(());
+            }
+        }));
+    }
+    let retry = {
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            error.set(None);
+            load_more.emit(());
+        })
+    };
+
+    MorePages {
+        loading: *loading,
+        error: (*error).clone(),
+        retry,
+    }
+}
+
 #[derive(Deserialize, Debug, PartialEq, Clone)]
 #[allow(non_snake_case)]
 pub struct Episode {
@@ -22,38 +252,31 @@ pub struct Episode {
     pub EpisodeURL: String,
     pub EpisodeDuration: i32,
     pub ListenDuration: Option<i32>,
+    #[serde(default, deserialize_with = "completed_flag")]
+    pub Completed: bool,
     pub EpisodeID: i32,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Clone)]
 pub struct RecentEps {
     pub episodes: Option<Vec<Episode>>,
-}
-
-pub async fn call_get_recent_eps(server_name: &String, api_key: &Option<String>, user_id: &i32) -> Result<Vec<Episode>, anyhow::Error> {
-    let url = format!("{}/api/data/return_episodes/{}", server_name, user_id);
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    let response = Request::get(&url)
-        .header("Api-Key", api_key_ref)
-        .send()
-        .await?;
-    if !response.ok() {
-        return Err(anyhow::Error::msg(format!("Failed to fetch episodes: {}", response.status_text())));
-    }
-    
-    // First, capture the response text for diagnostic purposes
-    let response_text = response.text().await.unwrap_or_else(|_| "Failed to get response text".to_string());
-    // Try to deserialize the response text
-    match serde_json::from_str::<RecentEps>(&response_text) {
-        Ok(response_body) => {
-            Ok(response_body.episodes.unwrap_or_else(Vec::new))
-        }
-        Err(_e) => {
-            Err(anyhow::Error::msg("Failed to deserialize response"))
+    /// Next page to load when the list is scrolled to the end.
+    #[serde(default)]
+    pub next_page: Option<Page>,
+}
+
+impl PinepodsClient {
+    /// Only the first page is cached, since that is what a view shows when it opens.
+    pub async fn get_recent_eps(&self, user_id: &i32, page: Page) -> Result<Vec<Episode>, Error> {
+        let response: RecentEps = self
+            .get(format!("/api/data/return_episodes/{}?{}", user_id, page.query()))
+            .fetch("Failed to fetch episodes")
+            .await?;
+        let episodes = response.episodes.unwrap_or_default();
+        if page.offset == 0 {
+            self.store_cached(CacheKey::RecentEpisodes(*user_id), episodes.clone());
         }
+        Ok(episodes)
     }
 }
 
@@ -79,25 +302,15 @@ struct PodcastStatusResponse {
     // Include other fields if your response contains more data
 }
 
-pub async fn call_add_podcast(server_name: &str, api_key: &Option<String>, _user_id: i32, added_podcast: &PodcastValues) -> Result<bool, Error> {
-    let url = format!("{}/api/data/add_podcast", server_name);
-    let api_key_ref = api_key.as_deref().ok_or_else(|| Error::msg("API key is missing"))?;
-
-    // Serialize `added_podcast` into JSON
-    let json_body = serde_json::to_string(added_podcast)?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(json_body)?
-        .send()
-        .await?;
-
-    if response.ok() {
-        let response_body = response.json::<PodcastStatusResponse>().await?;
-        Ok(response_body.success)
-    } else {
-        Err(Error::msg(format!("Error adding podcast: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn add_podcast(&self, added_podcast: &PodcastValues) -> Result<bool, Error> {
+        let response: PodcastStatusResponse = self
+            .post("/api/data/add_podcast")
+            .json(added_podcast)?
+            .fetch("Error adding podcast")
+            .await?;
+        self.invalidate(&[CacheKey::Podcasts(added_podcast.user_id), CacheKey::RecentEpisodes(added_podcast.user_id)]);
+        Ok(response.success)
     }
 }
 
@@ -107,34 +320,15 @@ pub struct RemovePodcastValues {
     pub user_id: i32
 }
 
-pub async fn call_remove_podcasts(server_name: &String, api_key: &Option<String>, remove_podcast: &RemovePodcastValues) -> Result<bool, Error> {
-    let url = format!("{}/api/data/remove_podcast_id", server_name);
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    // Serialize `added_podcast` into JSON
-    let json_body = serde_json::to_string(remove_podcast)?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(json_body)?
-        .send()
-        .await?;
-
-    let response_text = response.text().await.unwrap_or_else(|_| "Failed to get response text".to_string());
-
-
-    if response.ok() {
-        match serde_json::from_str::<PodcastStatusResponse>(&response_text) {
-            Ok(parsed_response) => Ok(parsed_response.success),
-            Err(_parse_error) => {
-                Err(anyhow::Error::msg("Failed to parse response"))
-            }
-        }
-    } else {
-        Err(anyhow::Error::msg(format!("Error removing podcast: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn remove_podcasts(&self, remove_podcast: &RemovePodcastValues) -> Result<bool, Error> {
+        let response: PodcastStatusResponse = self
+            .post("/api/data/remove_podcast_id")
+            .json(remove_podcast)?
+            .fetch("Error removing podcast")
+            .await?;
+        self.invalidate(&CacheKey::all(remove_podcast.user_id));
+        Ok(response.success)
     }
 }
 
@@ -145,34 +339,15 @@ pub struct RemovePodcastValuesName {
     pub podcast_url: String,
 }
 
-pub async fn call_remove_podcasts_name(server_name: &String, api_key: &Option<String>, remove_podcast: &RemovePodcastValuesName) -> Result<bool, Error> {
-    let url = format!("{}/api/data/remove_podcast", server_name);
-
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    // Serialize `added_podcast` into JSON
-    let json_body = serde_json::to_string(remove_podcast)?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(json_body)?
-        .send()
-        .await?;
-
-    let response_text = response.text().await.unwrap_or_else(|_| "Failed to get response text".to_string());
-
-    if response.ok() {
-        match serde_json::from_str::<PodcastStatusResponse>(&response_text) {
-            Ok(parsed_response) => Ok(parsed_response.success),
-            Err(_parse_error) => {
-                Err(anyhow::Error::msg("Failed to parse response"))
-            }
-        }
-    } else {
-        Err(anyhow::Error::msg(format!("Error removing podcast: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn remove_podcasts_name(&self, remove_podcast: &RemovePodcastValuesName) -> Result<bool, Error> {
+        let response: PodcastStatusResponse = self
+            .post("/api/data/remove_podcast")
+            .json(remove_podcast)?
+            .fetch("Error removing podcast")
+            .await?;
+        self.invalidate(&CacheKey::all(remove_podcast.user_id));
+        Ok(response.success)
     }
 }
 
@@ -199,62 +374,15 @@ pub struct Podcast {
 }
 
 
-pub async fn call_get_podcasts(server_name: &String, api_key: &Option<String>, user_id: &i32) -> Result<Vec<Podcast>, anyhow::Error> {
-    let url = format!("{}/api/data/return_pods/{}", server_name, user_id);
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    let response = Request::get(&url)
-        .header("Api-Key", api_key_ref)
-        .send()
-        .await?;
-    if !response.ok() {
-        return Err(anyhow::Error::msg(format!("Failed to fetch podcasts: {}", response.status_text())));
-    }
-
-    
-    // First, capture the response text for diagnostic purposes
-    let response_text = response.text().await.unwrap_or_else(|_| "Failed to get response text".to_string());
-    // Try to deserialize the response text
-    match serde_json::from_str::<PodcastResponse>(&response_text) {
-        Ok(response_body) => {
-            Ok(response_body.pods.unwrap_or_else(Vec::new))
-        }
-        Err(_e) => {
-            Err(anyhow::Error::msg("Failed to deserialize response"))
-        }
-    }
-}
-
-#[derive(Deserialize, Debug)]
-pub struct TimeInfoResponse {
-    pub timezone: String,
-    pub hour_pref: i32,
-}
-#[allow(dead_code)]
-pub async fn call_get_time_info(
-    server: &str,
-    key: String,
-    user_id: i32,
-) -> Result<TimeInfoResponse, anyhow::Error> {
-    let endpoint = format!("{}/api/data/get_time_info?user_id={}", server, user_id);
-
-    let resp = Request::get(&endpoint)
-        .header("Api-Key", key.as_str())
-        .send()
-        .await
-        .context("Network Request Error")?;
-
-    if resp.ok() {
-        resp.json::<TimeInfoResponse>()
-            .await
-            .context("Response Parsing Error")
-    } else {
-        Err(anyhow::anyhow!(
-            "Error fetching time info. Server Response: {}",
-            resp.status_text()
-        ))
+impl PinepodsClient {
+    pub async fn get_podcasts(&self, user_id: &i32) -> Result<Vec<Podcast>, Error> {
+        let response: PodcastResponse = self
+            .get(format!("/api/data/return_pods/{}", user_id))
+            .fetch("Failed to fetch podcasts")
+            .await?;
+        let pods = response.pods.unwrap_or_default();
+        self.store_cached(CacheKey::Podcasts(*user_id), pods.clone());
+        Ok(pods)
     }
 }
 
@@ -264,35 +392,21 @@ pub struct CheckPodcastResponse {
     pub exists: bool,
 }
 
-pub async fn call_check_podcast(
-    server: &str,
-    api_key: &str,
-    user_id: i32,
-    podcast_name: &str,
-    podcast_url: &str,
-) -> Result<CheckPodcastResponse, Error> {
-    let encoded_name = utf8_percent_encode(podcast_name, NON_ALPHANUMERIC).to_string();
-    let encoded_url = utf8_percent_encode(podcast_url, NON_ALPHANUMERIC).to_string();
-    let endpoint = format!(
-        "{}/api/data/check_podcast?user_id={}&podcast_name={}&podcast_url={}", 
-        server, user_id, encoded_name, encoded_url
-    );
-
-    let resp = Request::get(&endpoint)
-        .header("Api-Key", api_key)
-        .send()
-        .await
-        .context("Network Request Error")?;
-
-    if resp.ok() {
-        resp.json::<CheckPodcastResponse>()
-            .await
-            .context("Response Parsing Error")
-    } else {
-        Err(anyhow::anyhow!(
-            "Error checking podcast. Server Response: {}",
-            resp.status_text()
+impl PinepodsClient {
+    pub async fn check_podcast(
+        &self,
+        user_id: i32,
+        podcast_name: &str,
+        podcast_url: &str,
+    ) -> Result<CheckPodcastResponse, Error> {
+        let encoded_name = utf8_percent_encode(podcast_name, NON_ALPHANUMERIC).to_string();
+        let encoded_url = utf8_percent_encode(podcast_url, NON_ALPHANUMERIC).to_string();
+        self.get(format!(
+            "/api/data/check_podcast?user_id={}&podcast_name={}&podcast_url={}",
+            user_id, encoded_name, encoded_url
         ))
+        .fetch("Error checking podcast")
+        .await
     }
 }
 
@@ -302,34 +416,23 @@ pub struct EpisodeInDbResponse {
 }
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 
-pub async fn call_check_episode_in_db(
-    server: &str,
-    api_key: &str,
-    user_id: i32,
-    episode_title: &str,
-    episode_url: &str,
-) -> Result<bool, anyhow::Error> {
-    let encoded_title = utf8_percent_encode(episode_title, NON_ALPHANUMERIC).to_string();
-    let encoded_url = utf8_percent_encode(episode_url, NON_ALPHANUMERIC).to_string();
-    let endpoint = format!("{}/api/data/check_episode_in_db/{}?episode_title={}&episode_url={}", 
-        server, user_id, encoded_title, encoded_url);
-
-    let resp = Request::get(&endpoint)
-        .header("Api-Key", api_key)
-        .send()
-        .await
-        .context("Network Request Error")?;
-
-    if resp.ok() {
-        let episode_in_db_response = resp.json::<EpisodeInDbResponse>()
-            .await
-            .context("Response Parsing Error")?;
-        Ok(episode_in_db_response.episode_in_db)
-    } else {
-        Err(anyhow::anyhow!(
-            "Error checking episode in db. Server Response: {}",
-            resp.status_text()
-        ))
+impl PinepodsClient {
+    pub async fn check_episode_in_db(
+        &self,
+        user_id: i32,
+        episode_title: &str,
+        episode_url: &str,
+    ) -> Result<bool, Error> {
+        let encoded_title = utf8_percent_encode(episode_title, NON_ALPHANUMERIC).to_string();
+        let encoded_url = utf8_percent_encode(episode_url, NON_ALPHANUMERIC).to_string();
+        let response: EpisodeInDbResponse = self
+            .get(format!(
+                "/api/data/check_episode_in_db/{}?episode_title={}&episode_url={}",
+                user_id, encoded_title, encoded_url
+            ))
+            .fetch("Error checking episode in db")
+            .await?;
+        Ok(response.episode_in_db)
     }
 }
 
@@ -337,7 +440,7 @@ pub async fn call_check_episode_in_db(
 
 // Queue calls
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct QueuePodcastRequest {
     pub episode_id: i32,
     pub user_id: i32,
@@ -349,64 +452,25 @@ struct QueueResponse {
     data: String,
 }
 
-pub async fn call_queue_episode(
-    server_name: &String, 
-    api_key: &Option<String>, 
-    request_data: &QueuePodcastRequest
-) -> Result<String, Error> {
-    let url = format!("{}/api/data/queue_pod", server_name);
-
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    let request_body = serde_json::to_string(request_data).map_err(|e| anyhow::Error::msg(format!("Serialization Error: {}", e)))?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(request_body)?
-        .send()
-        .await?;
-
-    if response.ok() {
-        // Deserialize the JSON response into QueueResponse
-        let response_body: QueueResponse = response.json().await.map_err(|e| anyhow::Error::new(e))?;
-        // Extract and return the data string
-        Ok(response_body.data)
-    } else {
-        let error_text = response.text().await.unwrap_or_else(|_| String::from("Failed to read error message"));
-        Err(anyhow::Error::msg(format!("Failed to queue episode: {} - {}", response.status_text(), error_text)))
+impl PinepodsClient {
+    pub async fn queue_episode(&self, request_data: &QueuePodcastRequest) -> Result<String, Error> {
+        let response: QueueResponse = self
+            .post("/api/data/queue_pod")
+            .json(request_data)?
+            .fetch("Failed to queue episode")
+            .await?;
+        self.invalidate(&[CacheKey::Queue(request_data.user_id)]);
+        Ok(response.data)
     }
-}
 
-
-
-pub async fn call_remove_queued_episode(
-    server_name: &String, 
-    api_key: &Option<String>, 
-    request_data: &QueuePodcastRequest
-) -> Result<String, Error> {
-    let url = format!("{}/api/data/remove_queued_pod", server_name);
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    let request_body = serde_json::to_string(request_data).map_err(|e| anyhow::Error::msg(format!("Serialization Error: {}", e)))?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(request_body)?
-        .send()
-        .await?;
-
-    if response.ok() {
-        // Read the success message from the response body as text
-        let success_message = response.text().await.unwrap_or_else(|_| String::from("Episode queued successfully"));
-        Ok(success_message)
-    } else {
-        // Read the error response body as text to include in the error
-        let error_text = response.text().await.unwrap_or_else(|_| String::from("Failed to read error message"));
-        Err(anyhow::Error::msg(format!("Failed to queue episode: {} - {}", response.status_text(), error_text)))
+    pub async fn remove_queued_episode(&self, request_data: &QueuePodcastRequest) -> Result<String, Error> {
+        let message = self
+            .post("/api/data/remove_queued_pod")
+            .json(request_data)?
+            .text("Failed to remove queued episode")
+            .await?;
+        self.invalidate(&[CacheKey::Queue(request_data.user_id)]);
+        Ok(message)
     }
 }
 
@@ -429,38 +493,37 @@ pub struct QueuedEpisode {
     pub EpisodeDuration: i32,
     pub QueueDate: String,
     pub ListenDuration: Option<i32>,
+    #[serde(default, deserialize_with = "completed_flag")]
+    pub Completed: bool,
     pub EpisodeID: i32,
 }
 
+/// The episode to play after `current`: the one behind it in queue order, or the
+/// front of the queue when `current` isn't queued.
+pub fn next_in_queue(episodes: &[QueuedEpisode], current: i32) -> Option<&QueuedEpisode> {
+    let mut ordered: Vec<&QueuedEpisode> = episodes.iter().collect();
+    // Episodes without a position go after the numbered ones
+    ordered.sort_by_key(|episode| episode.QueuePosition.unwrap_or(i32::MAX));
+    match ordered.iter().position(|episode| episode.EpisodeID == current) {
+        Some(index) => ordered.get(index + 1).copied(),
+        None => ordered.first().copied(),
+    }
+}
+
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 pub struct DataResponse {
     pub data: Vec<QueuedEpisode>,
 }
 
-pub async fn call_get_queued_episodes(
-    server_name: &str, 
-    api_key: &Option<String>, 
-    user_id: &i32
-) -> Result<Vec<QueuedEpisode>, anyhow::Error> {
-    // Append the user_id as a query parameter
-    let url = format!("{}/api/data/get_queued_episodes?user_id={}", server_name, user_id);
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    let response = Request::get(&url)
-        .header("Api-Key", api_key_ref)
-        .send()
-        .await?;
-
-    if !response.ok() {
-        return Err(anyhow::Error::msg(format!("Failed to fetch queued episodes: {}", response.status_text())));
+impl PinepodsClient {
+    pub async fn get_queued_episodes(&self, user_id: &i32) -> Result<Vec<QueuedEpisode>, Error> {
+        let response: DataResponse = self
+            .get(format!("/api/data/get_queued_episodes?user_id={}", user_id))
+            .fetch("Failed to fetch queued episodes")
+            .await?;
+        self.store_cached(CacheKey::Queue(*user_id), response.data.clone());
+        Ok(response.data)
     }
-    let response_text = response.text().await?;
-
-    
-    let response_data: DataResponse = serde_json::from_str(&response_text)?;
-    Ok(response_data.data)
 }
 
 // Save episode calls
@@ -469,6 +532,8 @@ pub async fn call_get_queued_episodes(
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 pub struct SavedEpisodesResponse {
     pub episodes: Vec<SavedEpisode>,
+    #[serde(default)]
+    pub next_page: Option<Page>,
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -482,43 +547,33 @@ pub struct SavedEpisode {
     pub EpisodeURL: String,
     pub EpisodeDuration: i32,
     pub ListenDuration: Option<i32>,
+    #[serde(default, deserialize_with = "completed_flag")]
+    pub Completed: bool,
     pub EpisodeID: i32,
     pub WebsiteURL: String,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 pub struct SavedDataResponse {
-    pub saved_episodes: Vec<SavedEpisode>,
-}
-
-pub async fn call_get_saved_episodes(
-    server_name: &str, 
-    api_key: &Option<String>, 
-    user_id: &i32
-) -> Result<Vec<SavedEpisode>, anyhow::Error> {
-    // Append the user_id as a query parameter
-    let url = format!("{}/api/data/saved_episode_list/{}", server_name, user_id);
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    let response = Request::get(&url)
-        .header("Api-Key", api_key_ref)
-        .send()
-        .await?;
-
-    if !response.ok() {
-        return Err(anyhow::Error::msg(format!("Failed to fetch saved episodes: {}", response.status_text())));
+    // The server sends null instead of an empty list
+    pub saved_episodes: Option<Vec<SavedEpisode>>,
+}
+
+impl PinepodsClient {
+    pub async fn get_saved_episodes(&self, user_id: &i32, page: Page) -> Result<Vec<SavedEpisode>, Error> {
+        let response: SavedDataResponse = self
+            .get(format!("/api/data/saved_episode_list/{}?{}", user_id, page.query()))
+            .fetch("Failed to fetch saved episodes")
+            .await?;
+        let saved_episodes = response.saved_episodes.unwrap_or_default();
+        if page.offset == 0 {
+            self.store_cached(CacheKey::Saved(*user_id), saved_episodes.clone());
+        }
+        Ok(saved_episodes)
     }
-
-    let response_text = response.text().await?;
-
-    
-    let response_data: SavedDataResponse = serde_json::from_str(&response_text)?;
-    Ok(response_data.saved_episodes)
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SavePodcastRequest {
     pub episode_id: i32,
     pub user_id: i32,
@@ -529,64 +584,25 @@ struct SaveResponse {
     detail: String,
 }
 
-pub async fn call_save_episode(
-    server_name: &String, 
-    api_key: &Option<String>, 
-    request_data: &SavePodcastRequest
-) -> Result<String, Error> {
-    let url = format!("{}/api/data/save_episode", server_name);
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    let request_body = serde_json::to_string(request_data).map_err(|e| anyhow::Error::msg(format!("Serialization Error: {}", e)))?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(request_body)?
-        .send()
-        .await?;
-
-    if response.ok() {
-        // Deserialize the JSON response into SaveResponse
-        let response_body: SaveResponse = response.json().await.map_err(|e| anyhow::Error::new(e))?;
-        // Extract and return the detail string
-        Ok(response_body.detail)
-    } else {
-        // Read the error response body as text to include in the error
-        let error_text = response.text().await.unwrap_or_else(|_| String::from("Failed to read error message"));
-        Err(anyhow::Error::msg(format!("Failed to save episode: {} - {}", response.status_text(), error_text)))
+impl PinepodsClient {
+    pub async fn save_episode(&self, request_data: &SavePodcastRequest) -> Result<String, Error> {
+        let response: SaveResponse = self
+            .post("/api/data/save_episode")
+            .json(request_data)?
+            .fetch("Failed to save episode")
+            .await?;
+        self.invalidate(&[CacheKey::Saved(request_data.user_id)]);
+        Ok(response.detail)
     }
-}
-
-pub async fn call_remove_saved_episode(
-    server_name: &String, 
-    api_key: &Option<String>, 
-    request_data: &SavePodcastRequest
-) -> Result<String, Error> {
-    let url = format!("{}/api/data/remove_saved_episode", server_name);
 
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    let request_body = serde_json::to_string(request_data).map_err(|e| anyhow::Error::msg(format!("Serialization Error: {}", e)))?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(request_body)?
-        .send()
-        .await?;
-
-    if response.ok() {
-        // Read the success message from the response body as text
-        let success_message = response.text().await.unwrap_or_else(|_| String::from("Episode saved successfully"));
-        Ok(success_message)
-    } else {
-        // Read the error response body as text to include in the error
-        let error_text = response.text().await.unwrap_or_else(|_| String::from("Failed to read error message"));
-        Err(anyhow::Error::msg(format!("Failed to save episode: {} - {}", response.status_text(), error_text)))
+    pub async fn remove_saved_episode(&self, request_data: &SavePodcastRequest) -> Result<String, Error> {
+        let message = self
+            .post("/api/data/remove_saved_episode")
+            .json(request_data)?
+            .text("Failed to remove saved episode")
+            .await?;
+        self.invalidate(&[CacheKey::Saved(request_data.user_id)]);
+        Ok(message)
     }
 }
 
@@ -608,73 +624,47 @@ pub struct HistoryEpisode {
     pub EpisodeURL: String,
     pub EpisodeDuration: i32,
     pub ListenDuration: Option<i32>,
+    #[serde(default, deserialize_with = "completed_flag")]
+    pub Completed: bool,
     pub EpisodeID: i32,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 pub struct HistoryDataResponse {
     pub data: Vec<HistoryEpisode>,
+    #[serde(default)]
+    pub next_page: Option<Page>,
 }
 
-pub async fn call_get_user_history(
-    server_name: &str, 
-    api_key: &Option<String>, 
-    user_id: &i32
-) -> Result<Vec<HistoryEpisode>, anyhow::Error> {
-    // Append the user_id as a query parameter
-    let url = format!("{}/api/data/user_history/{}", server_name, user_id);
-
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    let response = Request::get(&url)
-        .header("Api-Key", api_key_ref)
-        .send()
-        .await?;
-
-    if !response.ok() {
-        return Err(anyhow::Error::msg(format!("Failed to fetch history: {}", response.status_text())));
+impl PinepodsClient {
+    pub async fn get_user_history(&self, user_id: &i32, page: Page) -> Result<Vec<HistoryEpisode>, Error> {
+        let response: HistoryDataResponse = self
+            .get(format!("/api/data/user_history/{}?{}", user_id, page.query()))
+            .fetch("Failed to fetch history")
+            .await?;
+        if page.offset == 0 {
+            self.store_cached(CacheKey::History(*user_id), response.data.clone());
+        }
+        Ok(response.data)
     }
-
-    let response_text = response.text().await?;
-
-    
-    let response_data: HistoryDataResponse = serde_json::from_str(&response_text)?;
-    Ok(response_data.data)
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct HistoryAddRequest {
     pub episode_id: i32,
     pub episode_pos: f32,
     pub user_id: i32,
 }
 
-pub async fn call_add_history(
-    server_name: &String, 
-    api_key: String, 
-    request_data: &HistoryAddRequest
-) -> Result<(), Error> {
-    let url = format!("{}/api/data/record_podcast_history", server_name);
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_str();
-
-    let request_body = serde_json::to_string(request_data).map_err(|e| anyhow::Error::msg(format!("Serialization Error: {}", e)))?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(request_body)?
-        .send()
-        .await?;
-
-    if !response.ok() {
-        return Err(anyhow::Error::msg(format!("Failed to record history: {}", response.status_text())));
+impl PinepodsClient {
+    pub async fn add_history(&self, request_data: &HistoryAddRequest) -> Result<(), Error> {
+        self.post("/api/data/record_podcast_history")
+            .json(request_data)?
+            .send("Failed to record history")
+            .await?;
+        self.invalidate(&[CacheKey::History(request_data.user_id)]);
+        Ok(())
     }
-
-    Ok(())
 }
 
 // Download calls
@@ -695,6 +685,8 @@ pub struct EpisodeDownload {
     pub EpisodeURL: String,
     pub EpisodeDuration: i32,
     pub ListenDuration: Option<i32>,
+    #[serde(default, deserialize_with = "completed_flag")]
+    pub Completed: bool,
     pub EpisodeID: i32,
     pub DownloadedLocation: String,
     pub PodcastID: i32,
@@ -706,33 +698,65 @@ pub struct DownloadDataResponse {
     pub episodes: Vec<EpisodeDownload>,
 }
 
-pub async fn call_get_episode_downloads(
-    server_name: &str,
-    api_key: &Option<String>,
-    user_id: &i32
-) -> Result<Vec<EpisodeDownload>, anyhow::Error> {
-    // Append the user_id as a query parameter
-    let url = format!("{}/api/data/download_episode_list?user_id={}", server_name, user_id);
-
-
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
+/// How long a relay URL works for, at least an hour less than this.
+const RELAY_LINK_SECS: i64 = 12 * 3600;
+/// How long a beacon's signature works for. The browser sends it right away.
+const BEACON_LINK_SECS: i64 = 300;
+
+impl PinepodsClient {
+    /// URL the audio element can stream a server-side download from. The key goes in
+    /// the query string because media elements can't send custom headers.
+    pub fn stream_url(&self, episode_id: i32, user_id: i32) -> String {
+        format!(
+            "{}/api/data/stream/{}?api_key={}&user_id={}",
+            self.server_name(),
+            episode_id,
+            self.api_key().unwrap_or_default(),
+            user_id
+        )
+    }
 
-    let response = Request::get(&url)
-        .header("Api-Key", api_key_ref)
-        .send()
-        .await?;
+    /// Whether `src` streams one of this server's downloads rather than the podcast's host.
+    pub fn is_stream_url(&self, src: &str) -> bool {
+        src.starts_with(&format!("{}/api/data/stream/", self.server_name()))
+    }
 
-    if !response.ok() {
-        return Err(anyhow::Error::msg(format!("Failed to episode downloads: {}", response.status_text())));
+    /// URL the audio element can fetch `src` through the server's relay from, at `now` in
+    /// Unix seconds. Instead of the key, which would end up in logs and `Referer`
+    /// headers, it carries an HMAC of `src` and an expiry made with the key, and a
+    /// fingerprint the server finds the key by.
+    pub fn relay_url(&self, src: &str, now: i64) -> String {
+        // Signing per hour keeps an episode on the same URL, and the browser's cache, for the hour
+        let expires = now - now.rem_euclid(3600) + RELAY_LINK_SECS;
+        let (fingerprint, token) = self.sign(&format!("{}\n{}", src, expires));
+        format!(
+            "{}/api/data/relay_audio?url={}&expires={}&key={}&token={}",
+            self.server_name(),
+            utf8_percent_encode(src, NON_ALPHANUMERIC),
+            expires,
+            fingerprint,
+            token
+        )
     }
 
-    let response_text = response.text().await?;
+    /// The fingerprint the server finds the key by, and an HMAC of `message` made with the
+    /// key, for requests that can't send the key in a header.
+    fn sign(&self, message: &str) -> (String, String) {
+        let key = self.api_key().unwrap_or_default();
+        let fingerprint = HEXLOWER.encode(&Sha256::digest(key.as_bytes()));
+        let mut signature = Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC takes keys of any length");
+        signature.update(message.as_bytes());
+        (fingerprint[..16].to_string(), HEXLOWER.encode(&signature.finalize().into_bytes()))
+    }
 
-    
-    let response_data: DownloadDataResponse = serde_json::from_str(&response_text)?;
-    Ok(response_data.episodes)
+    pub async fn get_episode_downloads(&self, user_id: &i32) -> Result<Vec<EpisodeDownload>, Error> {
+        let response: DownloadDataResponse = self
+            .get(format!("/api/data/download_episode_list?user_id={}", user_id))
+            .fetch("Failed to fetch episode downloads")
+            .await?;
+        self.store_cached(CacheKey::Downloads(*user_id), response.episodes.clone());
+        Ok(response.episodes)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -746,64 +770,25 @@ struct DownloadResponse {
     detail: String,
 }
 
-pub async fn call_download_episode (
-    server_name: &String, 
-    api_key: &Option<String>, 
-    request_data: &DownloadEpisodeRequest
-) -> Result<String, Error> {
-    let url = format!("{}/api/data/download_podcast", server_name);
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    let request_body = serde_json::to_string(request_data).map_err(|e| anyhow::Error::msg(format!("Serialization Error: {}", e)))?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(request_body)?
-        .send()
-        .await?;
-
-        if response.ok() {
-            // Deserialize the JSON response into DownloadResponse
-            let response_body: DownloadResponse = response.json().await.map_err(|e| anyhow::Error::new(e))?;
-            // Extract and return the detail string
-            Ok(response_body.detail)
-    } else {
-        // Read the error response body as text to include in the error
-        let error_text = response.text().await.unwrap_or_else(|_| String::from("Failed to read error message"));
-        Err(anyhow::Error::msg(format!("Failed to download episode: {} - {}", response.status_text(), error_text)))
+impl PinepodsClient {
+    pub async fn download_episode(&self, request_data: &DownloadEpisodeRequest) -> Result<String, Error> {
+        let response: DownloadResponse = self
+            .post("/api/data/download_podcast")
+            .json(request_data)?
+            .fetch("Failed to download episode")
+            .await?;
+        self.invalidate(&[CacheKey::Downloads(request_data.user_id)]);
+        Ok(response.detail)
     }
-}
 
-pub async fn call_remove_downloaded_episode (
-    server_name: &String, 
-    api_key: &Option<String>, 
-    request_data: &DownloadEpisodeRequest
-) -> Result<String, Error> {
-    let url = format!("{}/api/data/delete_episode", server_name);
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    let request_body = serde_json::to_string(request_data).map_err(|e| anyhow::Error::msg(format!("Serialization Error: {}", e)))?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(request_body)?
-        .send()
-        .await?;
-
-    if response.ok() {
-        // Read the success message from the response body as text
-        let success_message = response.text().await.unwrap_or_else(|_| String::from("Episode downloaded successfully"));
-        Ok(success_message)
-    } else {
-        // Read the error response body as text to include in the error
-        let error_text = response.text().await.unwrap_or_else(|_| String::from("Failed to read error message"));
-        Err(anyhow::Error::msg(format!("Failed to download episode: {} - {}", response.status_text(), error_text)))
+    pub async fn remove_downloaded_episode(&self, request_data: &DownloadEpisodeRequest) -> Result<String, Error> {
+        let message = self
+            .post("/api/data/delete_episode")
+            .json(request_data)?
+            .text("Failed to remove downloaded episode")
+            .await?;
+        self.invalidate(&[CacheKey::Downloads(request_data.user_id)]);
+        Ok(message)
     }
 }
 
@@ -821,7 +806,11 @@ pub struct EpisodeInfo {
     pub EpisodeURL: String,
     pub EpisodeDuration: i32,
     pub ListenDuration: Option<i32>,
+    #[serde(default, deserialize_with = "completed_flag")]
+    pub Completed: bool,
     pub EpisodeID: i32,
+    #[serde(default)]
+    pub FeedURL: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -835,125 +824,150 @@ pub struct EpisodeMetadataResponse {
     pub episode: EpisodeInfo,
 }
 
-pub async fn call_get_episode_metadata(
-    server_name: &str, 
-    api_key: Option<String>, 
-    episode_request: &EpisodeRequest,
-) -> Result<EpisodeInfo, anyhow::Error> {
-    let url = format!("{}/api/data/get_episode_metadata", server_name);
-
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-    
-    let request_body = serde_json::to_string(episode_request).map_err(|e| anyhow::Error::msg(format!("Serialization Error: {}", e)))?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(request_body)?
-        .send()
-        .await?;
-
-    if !response.ok() {
-        return Err(anyhow::Error::msg(format!("Failed to episode downloads: {}", response.status_text())));
+impl PinepodsClient {
+    pub async fn get_episode_metadata(&self, episode_request: &EpisodeRequest) -> Result<EpisodeInfo, Error> {
+        let response: EpisodeMetadataResponse = self
+            .post("/api/data/get_episode_metadata")
+            .json(episode_request)?
+            .fetch("Failed to fetch episode metadata")
+            .await?;
+        Ok(response.episode)
     }
+}
 
-    let response_text = response.text().await?;
-
-    let response_data: EpisodeMetadataResponse = serde_json::from_str(&response_text)
-        .map_err(|e| anyhow::Error::msg(format!("Deserialization Error: {}", e)))?;
+thread_local! {
+    // How far the server's clock is ahead of this one, in milliseconds, as of the last save
+    static SERVER_CLOCK_OFFSET: Cell<i64> = const { Cell::new(0) };
+}
 
-    Ok(response_data.episode)
+/// The time on the server's clock, in milliseconds, as near as this device can tell.
+pub fn server_now() -> i64 {
+    chrono::Utc::now().timestamp_millis() + SERVER_CLOCK_OFFSET.with(Cell::get)
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct RecordListenDurationRequest {
     pub episode_id: i32,
     pub user_id: i32,
     pub listen_duration: f64, // Assuming float is appropriate here; adjust the type if necessary
+    /// When the player was at this position, on the server's clock. The server keeps
+    /// whichever position was seen last, however late it arrives.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observed_at: Option<i64>,
 }
 
-#[derive(Deserialize, Debug)]
-pub struct RecordListenDurationResponse {
-    pub detail: String, // Assuming a simple status response; adjust according to actual API response
+impl RecordListenDurationRequest {
+    /// The position the player is at now.
+    pub fn new(episode_id: i32, user_id: i32, listen_duration: f64) -> Self {
+        RecordListenDurationRequest { episode_id, user_id, listen_duration, observed_at: Some(server_now()) }
+    }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MarkCompletedRequest {
+    pub episode_id: i32,
+    pub user_id: i32,
+}
 
-pub async fn call_record_listen_duration(
-    server_name: &str,
-    api_key: &str,
-    request_data: RecordListenDurationRequest,
-) -> Result<RecordListenDurationResponse, Error> {
-    let url = format!("{}/api/data/record_listen_duration", server_name);
-    let request_body = serde_json::to_string(&request_data)
-        .map_err(|e| Error::msg(format!("Serialization Error: {}", e)))?;
-
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key)
-        .body(&request_body)
-        .map_err(|e| Error::msg(format!("Request Building Error: {}", e)))?
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network Request Error: {}", e)))?;
+#[derive(Deserialize, Debug)]
+struct MarkOlderCompletedResponse {
+    marked: Vec<i32>,
+}
 
-    if response.ok() {
-        response.json::<RecordListenDurationResponse>()
-            .await
-            .map_err(|e| Error::msg(format!("Response Parsing Error: {}", e)))
-    } else {
-        Err(Error::msg(format!(
-            "Error recording listen duration. Server Response: {}",
-            response.status_text()
-        )))
+#[derive(Deserialize, Debug)]
+pub struct RecordListenDurationResponse {
+    pub detail: String, // Assuming a simple status response; adjust according to actual API response
+    #[serde(default)]
+    pub server_time: Option<i64>,
+}
+
+
+impl PinepodsClient {
+    pub async fn record_listen_duration(
+        &self,
+        request_data: &RecordListenDurationRequest,
+    ) -> Result<RecordListenDurationResponse, Error> {
+        let response: RecordListenDurationResponse = self
+            .post("/api/data/record_listen_duration")
+            .json(request_data)?
+            .fetch("Error recording listen duration")
+            .await?;
+        if let Some(server_time) = response.server_time {
+            let offset = server_time - chrono::Utc::now().timestamp_millis();
+            SERVER_CLOCK_OFFSET.with(|cell| cell.set(offset));
+        }
+        Ok(response)
     }
-}
 
+    /// Hands the position to the browser to deliver, so it still arrives if the page is
+    /// closing. Returns false if the browser wouldn't take it.
+    pub fn beacon_listen_duration(&self, request_data: &RecordListenDurationRequest) -> bool {
+        let (Some(window), Ok(_), Ok(body)) = (web_sys::window(), self.api_key(), serde_json::to_string(request_data)) else {
+            return false;
+        };
+        let url = self.beacon_url(&body, chrono::Utc::now().timestamp());
+        window.navigator().send_beacon_with_opt_str(&url, Some(&body)).unwrap_or(false)
+    }
 
-pub async fn call_increment_listen_time(
-    server_name: &str,
-    api_key: &str,
-    user_id: i32, // Assuming user_id is an integer based on your endpoint definition
-) -> Result<String, Error> {
-    let url = format!("{}/api/data/increment_listen_time/{}", server_name, user_id);
+    /// Where a beacon carrying `body` goes, at `now` in Unix seconds. Beacons can't carry
+    /// headers, so like a relay link it's signed with the key instead of carrying it.
+    pub(crate) fn beacon_url(&self, body: &str, now: i64) -> String {
+        let expires = now + BEACON_LINK_SECS;
+        let (fingerprint, token) = self.sign(&format!("{}\n{}", body, expires));
+        format!(
+            "{}/api/data/record_listen_duration_beacon?expires={}&key={}&token={}",
+            self.server_name(),
+            expires,
+            fingerprint,
+            token
+        )
+    }
 
-    let response = Request::put(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key)
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network Request Error: {}", e)))?;
+    /// Records that the episode was played to the end.
+    pub async fn mark_episode_completed(&self, request_data: &MarkCompletedRequest) -> Result<(), Error> {
+        self.post("/api/data/mark_episode_completed")
+            .json(request_data)?
+            .send("Error marking episode completed")
+            .await?;
+        // Every list shows whether its episodes are played
+        self.invalidate(&CacheKey::all(request_data.user_id));
+        Ok(())
+    }
 
-    if response.ok() {
-        Ok("Listen time incremented.".to_string()) // Assuming a simple success message for now
-    } else {
-        Err(Error::msg(format!(
-            "Error incrementing listen time. Server Response: {}",
-            response.status_text()
-        )))
+    /// Marks the episode unplayed again, which also starts it over.
+    pub async fn mark_episode_uncompleted(&self, request_data: &MarkCompletedRequest) -> Result<(), Error> {
+        self.post("/api/data/mark_episode_uncompleted")
+            .json(request_data)?
+            .send("Error marking episode uncompleted")
+            .await?;
+        self.invalidate(&CacheKey::all(request_data.user_id));
+        Ok(())
     }
-}
 
-pub async fn call_increment_played(
-    server_name: &str,
-    api_key: &str,
-    user_id: i32, // Assuming user_id is an integer based on your endpoint definition
-) -> Result<String, Error> {
-    let url = format!("{}/api/data/increment_played/{}", server_name, user_id);
+    /// Marks every episode of the podcast published before this one played, returning
+    /// the ones that weren't already.
+    pub async fn mark_older_episodes_completed(&self, request_data: &MarkCompletedRequest) -> Result<Vec<i32>, Error> {
+        let response: MarkOlderCompletedResponse = self
+            .post("/api/data/mark_older_episodes_completed")
+            .json(request_data)?
+            .fetch("Error marking older episodes completed")
+            .await?;
+        self.invalidate(&CacheKey::all(request_data.user_id));
+        Ok(response.marked)
+    }
 
-    let response = Request::put(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key)
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network Request Error: {}", e)))?;
+    pub async fn increment_listen_time(&self, user_id: i32) -> Result<String, Error> {
+        self.put(format!("/api/data/increment_listen_time/{}", user_id))
+            .send("Error incrementing listen time")
+            .await?;
+        Ok("Listen time incremented.".to_string())
+    }
 
-    if response.ok() {
-        Ok("Played count incremented.".to_string()) // Assuming a simple success message for now
-    } else {
-        Err(Error::msg(format!(
-            "Error incrementing played count. Server Response: {}",
-            response.status_text()
-        )))
+    pub async fn increment_played(&self, user_id: i32) -> Result<String, Error> {
+        self.put(format!("/api/data/increment_played/{}", user_id))
+            .send("Error incrementing played count")
+            .await?;
+        Ok("Played count incremented.".to_string())
     }
 }
 
@@ -962,34 +976,94 @@ pub struct PodcastIdResponse {
     pub episodes: i32,
 }
 
-pub async fn call_get_podcast_id(
-    server_name: &str,
-    api_key: &Option<String>,
-    user_id: &i32,
-    podcast_feed: &str,
-    podcast_title: &str,
-) -> Result<i32, anyhow::Error> {
-    // Append the user_id, podcast_feed, and podcast_title as query parameters
-    let encoded_feed = utf8_percent_encode(podcast_feed, NON_ALPHANUMERIC).to_string();
-    let encoded_title = utf8_percent_encode(podcast_title, NON_ALPHANUMERIC).to_string();
-    let url = format!("{}/api/data/get_podcast_id?user_id={}&podcast_feed={}&podcast_title={}", server_name, user_id, encoded_feed, encoded_title);
+impl PinepodsClient {
+    pub async fn get_podcast_id(
+        &self,
+        user_id: &i32,
+        podcast_feed: &str,
+        podcast_title: &str,
+    ) -> Result<i32, Error> {
+        let encoded_feed = utf8_percent_encode(podcast_feed, NON_ALPHANUMERIC).to_string();
+        let encoded_title = utf8_percent_encode(podcast_title, NON_ALPHANUMERIC).to_string();
+        let response: PodcastIdResponse = self
+            .get(format!(
+                "/api/data/get_podcast_id?user_id={}&podcast_feed={}&podcast_title={}",
+                user_id, encoded_feed, encoded_title
+            ))
+            .fetch("Failed to get podcast id")
+            .await?;
+        Ok(response.episodes)
+    }
+}
 
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
+/// Playback defaults a user has set for one of their podcasts, applied whenever one
+/// of its episodes starts.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PodcastPlaybackSettings {
+    pub playback_speed: f64,
+    /// Seconds at the start of each episode to jump over.
+    pub skip_intro: u32,
+    /// Seconds at the end of each episode to cut, moving on early.
+    pub skip_outro: u32,
+    // Audio processing overrides; None follows the user's own setting
+    #[serde(default)]
+    pub voice_boost: Option<bool>,
+    #[serde(default)]
+    pub normalize_loudness: Option<bool>,
+    #[serde(default)]
+    pub skip_silence: Option<bool>,
+}
+
+impl Default for PodcastPlaybackSettings {
+    fn default() -> Self {
+        PodcastPlaybackSettings {
+            playback_speed: 1.0,
+            skip_intro: 0,
+            skip_outro: 0,
+            voice_boost: None,
+            normalize_loudness: None,
+            skip_silence: None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SetPodcastPlaybackRequest {
+    pub user_id: i32,
+    pub podcast_id: i32,
+    #[serde(flatten)]
+    pub settings: PodcastPlaybackSettings,
+}
 
-    let response = Request::get(&url)
-        .header("Api-Key", api_key_ref)
-        .send()
-        .await?;
+#[derive(Deserialize, Debug)]
+struct SetPodcastPlaybackResponse {
+    #[allow(dead_code)]
+    message: String,
+}
 
-    if !response.ok() {
-        return Err(anyhow::Error::msg(format!("Failed to get podcast id: {}", response.status_text())));
+impl PinepodsClient {
+    pub async fn get_podcast_playback_settings(&self, user_id: i32, podcast_id: i32) -> Result<PodcastPlaybackSettings, Error> {
+        self.get(format!("/api/data/podcast_playback_settings?user_id={}&podcast_id={}", user_id, podcast_id))
+            .fetch("Error getting podcast playback settings")
+            .await
     }
 
-    let response_text = response.text().await?;
+    /// The settings of the podcast an episode belongs to. Episodes that aren't in the
+    /// database get the defaults.
+    pub async fn get_episode_playback_settings(&self, user_id: i32, episode_id: i32) -> Result<PodcastPlaybackSettings, Error> {
+        self.get(format!("/api/data/podcast_playback_settings?user_id={}&episode_id={}", user_id, episode_id))
+            .fetch("Error getting podcast playback settings")
+            .await
+    }
 
-    let response_data: PodcastIdResponse = serde_json::from_str(&response_text)?;
-    Ok(response_data.episodes)
+    pub async fn set_podcast_playback_settings(&self, request: &SetPodcastPlaybackRequest) -> Result<(), Error> {
+        let _response: SetPodcastPlaybackResponse = self
+            .put("/api/data/podcast_playback_settings")
+            .json(request)?
+            .fetch("Error updating podcast playback settings")
+            .await?;
+        Ok(())
+    }
 }
 
 fn explicit_from_int<'de, D>(deserializer: D) -> Result<bool, D::Error>
@@ -1030,28 +1104,281 @@ struct PodcastDetailsResponse {
     details: PodcastDetails,
 }
 
-pub async fn call_get_podcast_details(
-    server_name: &str,
-    api_key: &str,
-    user_id: i32,
-    podcast_id: &i32,
-) -> Result<PodcastDetails, Error> {
-    let url = format!("{}/api/data/get_podcast_details?user_id={}&podcast_id={}", server_name, user_id, podcast_id);
+impl PinepodsClient {
+    pub async fn get_podcast_details(&self, user_id: i32, podcast_id: &i32) -> Result<PodcastDetails, Error> {
+        let response: PodcastDetailsResponse = self
+            .get(format!("/api/data/get_podcast_details?user_id={}&podcast_id={}", user_id, podcast_id))
+            .fetch("Error retrieving podcast details")
+            .await?;
+        Ok(response.details)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::stub_server::{fixture, StubServer};
+    use pollster::block_on;
+
+    #[test]
+    fn decodes_recent_episodes() {
+        let server = StubServer::start();
+        server.route("GET", "/api/data/return_episodes/2?offset=0&limit=50", 200, fixture("return_episodes.json"));
+        let client = server.client(Some("secret"));
+
+        let episodes = block_on(client.get_recent_eps(&2, Page::first())).unwrap();
+
+        assert_eq!(episodes.len(), 2);
+        assert_eq!(episodes[0].EpisodeID, 311);
+        assert_eq!(episodes[0].ListenDuration, Some(1260));
+        assert_eq!(episodes[1].ListenDuration, None);
+        assert!(!episodes[0].Completed && !episodes[1].Completed);
+        assert_eq!(client.cached::<Vec<Episode>>(CacheKey::RecentEpisodes(2)), Some(episodes));
+    }
 
-    let response = Request::get(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key)
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network request error: {}", e)))?;
-
-    if response.ok() {
-        let response_data: PodcastDetailsResponse = response.json().await.map_err(|e| Error::msg(format!("Failed to parse response: {}", e)))?;
-        Ok(response_data.details)
-    } else {
-        Err(Error::msg(format!(
-            "Error retrieving podcast details. Server response: {}",
-            response.status_text()
-        )))
-    }
-}
\ No newline at end of file
+    #[test]
+    fn played_flags_come_as_ints_bools_or_null() {
+        let episode = |completed: &str| {
+            let json = fixture("return_episodes.json").replace(r#""Completed": 0"#, &format!(r#""Completed": {}"#, completed));
+            let response: RecentEps = serde_json::from_str(&json).unwrap();
+            response.episodes.unwrap()[0].Completed
+        };
+
+        assert!(episode("1"));
+        assert!(episode("true"));
+        assert!(!episode("false"));
+        assert!(!episode("null"));
+    }
+
+    #[test]
+    fn marking_older_episodes_played_invalidates_every_list() {
+        let server = StubServer::start();
+        server
+            .route("GET", "/api/data/return_episodes/2?offset=0&limit=50", 200, fixture("return_episodes.json"))
+            .route("POST", "/api/data/mark_older_episodes_completed", 200, r#"{"detail":"Older episodes marked completed.","marked":[298,275]}"#);
+        let client = server.client(Some("secret"));
+        block_on(client.get_recent_eps(&2, Page::first())).unwrap();
+
+        let request = MarkCompletedRequest { episode_id: 311, user_id: 2 };
+        let marked = block_on(client.mark_older_episodes_completed(&request)).unwrap();
+
+        assert_eq!(marked, [298, 275]);
+        assert_eq!(client.cached::<Vec<Episode>>(CacheKey::RecentEpisodes(2)), None);
+        assert_eq!(server.requests()[1].body, r#"{"episode_id":311,"user_id":2}"#);
+    }
+
+    #[test]
+    fn later_pages_are_requested_but_not_cached() {
+        let server = StubServer::start();
+        server.route("GET", "/api/data/return_episodes/2?offset=50&limit=50", 200, fixture("return_episodes.json"));
+        let client = server.client(Some("secret"));
+        let page = Page::first().after(Page::SIZE).unwrap();
+
+        let episodes = block_on(client.get_recent_eps(&2, page)).unwrap();
+
+        assert_eq!(episodes.len(), 2);
+        assert_eq!(page.after(episodes.len()), None);
+        assert_eq!(client.cached::<Vec<Episode>>(CacheKey::RecentEpisodes(2)), None);
+    }
+
+    #[test]
+    fn only_a_lone_first_page_is_refreshed_in_place() {
+        let second = Page::first().after(Page::SIZE);
+
+        assert!(Page::first_only(12, None));
+        assert!(Page::first_only(Page::SIZE, second));
+        // The second page is on its way
+        assert!(!Page::first_only(Page::SIZE, None));
+        assert!(!Page::first_only(Page::SIZE + 7, None));
+        assert!(!Page::first_only(2 * Page::SIZE, second.and_then(|page| page.after(Page::SIZE))));
+    }
+
+    #[test]
+    fn appended_pages_extend_the_list_until_a_short_one() {
+        let fetched: RecentEps = serde_json::from_str(&fixture("return_episodes.json")).unwrap();
+        let episodes = fetched.episodes.unwrap();
+        let first = Page { offset: 0, limit: episodes.len() };
+        let mut list = RecentEps { episodes: None, next_page: None };
+
+        list.append(first, episodes.clone());
+        assert_eq!(list.next_page, first.after(episodes.len()));
+        list.append(list.next_page.unwrap(), episodes[..1].to_vec());
+
+        assert_eq!(list.episodes.map_or(0, |loaded| loaded.len()), episodes.len() + 1);
+        assert_eq!(list.next_page, None);
+    }
+
+    #[test]
+    fn decodes_podcasts_with_integer_explicit_flag() {
+        let server = StubServer::start();
+        server.route("GET", "/api/data/return_pods/2", 200, fixture("return_pods.json"));
+        let client = server.client(Some("secret"));
+
+        let pods = block_on(client.get_podcasts(&2)).unwrap();
+
+        assert_eq!(pods.len(), 2);
+        assert!(pods[0].Explicit);
+        assert!(!pods[1].Explicit);
+        assert_eq!(pods[1].ArtworkURL, None);
+    }
+
+    #[test]
+    fn missing_podcast_list_is_empty() {
+        let server = StubServer::start();
+        server.route("GET", "/api/data/return_pods/2", 200, fixture("return_pods_empty.json"));
+        let client = server.client(Some("secret"));
+
+        assert!(block_on(client.get_podcasts(&2)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn decodes_queue_history_and_downloads() {
+        let server = StubServer::start();
+        server
+            .route("GET", "/api/data/get_queued_episodes?user_id=2", 200, fixture("get_queued_episodes.json"))
+            .route("GET", "/api/data/user_history/2?offset=0&limit=50", 200, fixture("user_history.json"))
+            .route("GET", "/api/data/download_episode_list?user_id=2", 200, fixture("download_episode_list.json"));
+        let client = server.client(Some("secret"));
+
+        let queue = block_on(client.get_queued_episodes(&2)).unwrap();
+        assert_eq!(queue[0].QueuePosition, Some(1));
+        let history = block_on(client.get_user_history(&2, Page::first())).unwrap();
+        assert_eq!(history[0].EpisodeID, 311);
+        let downloads = block_on(client.get_episode_downloads(&2)).unwrap();
+        assert_eq!(downloads[0].PodcastID, 14);
+        assert!(downloads[0].DownloadedLocation.ends_with(".mp3"));
+    }
+
+    #[test]
+    fn queueing_invalidates_the_cached_queue() {
+        let server = StubServer::start();
+        server
+            .route("GET", "/api/data/get_queued_episodes?user_id=2", 200, fixture("get_queued_episodes.json"))
+            .route("POST", "/api/data/queue_pod", 200, r#"{"data":"Podcast queued successfully"}"#);
+        let client = server.client(Some("secret"));
+
+        block_on(client.get_queued_episodes(&2)).unwrap();
+        assert!(client.cached::<Vec<QueuedEpisode>>(CacheKey::Queue(2)).is_some());
+
+        let request = QueuePodcastRequest { episode_id: 298, user_id: 2 };
+        let message = block_on(client.queue_episode(&request)).unwrap();
+
+        assert_eq!(message, "Podcast queued successfully");
+        assert!(client.cached::<Vec<QueuedEpisode>>(CacheKey::Queue(2)).is_none());
+        let queued = &server.requests()[1];
+        assert_eq!(queued.method, "POST");
+        assert_eq!(queued.body, r#"{"episode_id":298,"user_id":2}"#);
+    }
+
+    #[test]
+    fn next_in_queue_follows_queue_positions() {
+        let queued = |id: i32, position: i32| QueuedEpisode {
+            EpisodeTitle: format!("Episode {}", id),
+            PodcastName: "Radiolab".to_string(),
+            EpisodePubDate: "2024-04-11T08:00:00".to_string(),
+            EpisodeDescription: String::new(),
+            EpisodeArtwork: String::new(),
+            EpisodeURL: format!("https://feeds.example.com/episodes/{}.mp3", id),
+            QueuePosition: Some(position),
+            EpisodeDuration: 1800,
+            QueueDate: "2024-05-01T12:00:00".to_string(),
+            ListenDuration: None,
+            Completed: false,
+            EpisodeID: id,
+        };
+        // Out of order, with a gap where an episode was removed
+        let queue = vec![queued(30, 4), queued(10, 1), queued(20, 2)];
+
+        assert_eq!(next_in_queue(&queue, 10).map(|ep| ep.EpisodeID), Some(20));
+        assert_eq!(next_in_queue(&queue, 20).map(|ep| ep.EpisodeID), Some(30));
+        assert_eq!(next_in_queue(&queue, 30), None);
+        assert_eq!(next_in_queue(&queue, 99).map(|ep| ep.EpisodeID), Some(10));
+    }
+
+    #[test]
+    fn listen_durations_are_stamped_on_the_servers_clock() {
+        let server = StubServer::start();
+        let server_time = chrono::Utc::now().timestamp_millis() + 60_000;
+        let response = format!(r#"{{"detail":"Listen duration recorded.","recorded":true,"server_time":{}}}"#, server_time);
+        server.route("POST", "/api/data/record_listen_duration", 200, response);
+        let client = server.client(Some("secret"));
+
+        let request = RecordListenDurationRequest::new(311, 2, 754.5);
+        let response = block_on(client.record_listen_duration(&request)).unwrap();
+
+        assert_eq!(response.detail, "Listen duration recorded.");
+        let sent: RecordListenDurationRequest = serde_json::from_str(&server.requests()[0].body).unwrap();
+        assert_eq!(sent, request);
+        // Later positions are stamped a minute ahead, like the server's clock
+        let ahead = RecordListenDurationRequest::new(311, 2, 760.0).observed_at.unwrap() - chrono::Utc::now().timestamp_millis();
+        assert!((59_000..=61_000).contains(&ahead), "{}", ahead);
+    }
+
+    #[test]
+    fn relay_urls_are_signed_instead_of_carrying_the_key() {
+        let client = PinepodsClient::new("https://pods.example.com", Some("secret".to_string()));
+        let src = "https://feeds.example.com/episodes/298.mp3";
+
+        let url = client.relay_url(src, 1_760_000_000);
+
+        assert_eq!(
+            url,
+            "https://pods.example.com/api/data/relay_audio?url=https%3A%2F%2Ffeeds%2Eexample%2Ecom%2Fepisodes%2F298%2Emp3\
+             &expires=1760040000&key=2bb80d537b1da3e3\
+             &token=03f46d3ab0ca86dbb867b6f877c36e6c466c0d185f79d722553af79d57da2cc0"
+        );
+        assert!(!url.contains("secret"));
+        // Later in the same hour the URL stays the same
+        assert_eq!(client.relay_url(src, 1_760_000_399), url);
+    }
+
+    #[test]
+    fn beacons_are_signed_instead_of_carrying_the_key() {
+        let client = PinepodsClient::new("https://pods.example.com", Some("secret".to_string()));
+        let body = r#"{"episode_id":311,"user_id":2,"listen_duration":754.5}"#;
+
+        let url = client.beacon_url(body, 1_760_000_000);
+
+        assert_eq!(
+            url,
+            "https://pods.example.com/api/data/record_listen_duration_beacon?expires=1760000300&key=2bb80d537b1da3e3\
+             &token=46db3ad6e3c74b46515812c8c49be0178b62c05957573d3d4c63d3d30a361aa3"
+        );
+        assert!(!url.contains("secret"));
+    }
+
+    #[test]
+    fn downloads_are_streamed_from_the_server() {
+        let server = StubServer::start();
+        let client = server.client(Some("secret"));
+
+        assert!(client.is_stream_url(&client.stream_url(298, 2)));
+        assert!(!client.is_stream_url("https://feeds.example.com/episodes/298.mp3"));
+    }
+
+    #[test]
+    fn episode_playback_settings_come_from_its_podcast() {
+        let server = StubServer::start();
+        server
+            .route(
+                "GET",
+                "/api/data/podcast_playback_settings?user_id=2&episode_id=298",
+                200,
+                r#"{"playback_speed": 1.8, "skip_intro": 45, "skip_outro": 0}"#,
+            )
+            .route("PUT", "/api/data/podcast_playback_settings", 200, r#"{"message": "Podcast playback settings updated successfully"}"#);
+        let client = server.client(Some("secret"));
+
+        let settings = block_on(client.get_episode_playback_settings(2, 298)).unwrap();
+        assert_eq!(
+            settings,
+            PodcastPlaybackSettings { playback_speed: 1.8, skip_intro: 45, ..PodcastPlaybackSettings::default() }
+        );
+
+        let request = SetPodcastPlaybackRequest { user_id: 2, podcast_id: 14, settings };
+        block_on(client.set_podcast_playback_settings(&request)).unwrap();
+        assert_eq!(
+            server.requests()[1].body,
+            r#"{"user_id":2,"podcast_id":14,"playback_speed":1.8,"skip_intro":45,"skip_outro":0,"voice_boost":null,"normalize_loudness":null,"skip_silence":null}"#
+        );
+    }
+}
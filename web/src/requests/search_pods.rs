@@ -4,8 +4,11 @@ use serde::{Deserialize, Serialize, Deserializer};
 use serde::de::{self, Visitor};
 use std::fmt;
 use anyhow::Error;
+use crate::requests::client::PinepodsClient;
+use crate::requests::pod_req::{completed_flag, Page};
+use crate::requests::chapters::{item_chapters_url, item_embedded_chapters, Chapter};
+use crate::requests::transcripts::{item_transcripts, TranscriptLink};
 use rss::Channel;
-use wasm_bindgen::JsValue;
 use chrono::{DateTime, Utc, TimeZone};
 use yew::Properties;
 
@@ -14,6 +17,35 @@ pub struct RecentEps {
     pub episodes: Vec<Episode>,
 }
 
+/// What an episode's enclosure holds, which decides how the player shows it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MediaKind {
+    #[default]
+    Audio,
+    Video,
+    /// An HLS playlist, of audio or video. Browsers play these natively or not at all.
+    Hls,
+}
+
+impl MediaKind {
+    /// The kind of the media at `url`: from the feed's MIME type when there is one, and
+    /// from the extension otherwise. The server doesn't keep enclosure types, so
+    /// episodes from the database only have the extension to go on.
+    pub fn of(mime_type: Option<&str>, url: &str) -> MediaKind {
+        let mime_type = mime_type.unwrap_or_default().trim().to_ascii_lowercase();
+        let path = url.split(['?', '#']).next().unwrap_or_default().to_ascii_lowercase();
+        if mime_type.contains("mpegurl") || path.ends_with(".m3u8") {
+            MediaKind::Hls
+        } else if mime_type.starts_with("video/") {
+            MediaKind::Video
+        } else if mime_type.is_empty() && [".mp4", ".m4v", ".mov", ".webm"].iter().any(|ext| path.ends_with(ext)) {
+            MediaKind::Video
+        } else {
+            MediaKind::Audio
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, PartialEq, Clone, Serialize)]
 pub struct PodcastSearchResult {
     pub status: Option<String>, // for PodcastIndex
@@ -223,6 +255,9 @@ pub struct Episode {
     #[serde(rename = "EpisodeURL")]
     pub enclosure_url: Option<String>,
     pub enclosure_length: Option<String>,
+    // Only known for episodes parsed straight from the feed
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub enclosure_type: Option<String>,
     #[serde(rename = "EpisodeArtwork")]
     pub artwork: Option<String>,
     pub content: Option<String>,
@@ -234,6 +269,22 @@ pub struct Episode {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "EpisodeID")]
     pub episode_id: Option<i32>,
+    // Only known for episodes parsed straight from the feed
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub chapters_url: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub chapters: Vec<Chapter>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub transcripts: Vec<TranscriptLink>,
+    // Only known for episodes in the database
+    #[serde(rename = "Completed", default, deserialize_with = "completed_flag")]
+    pub completed: bool,
+}
+
+impl Episode {
+    pub fn media_kind(&self) -> MediaKind {
+        MediaKind::of(self.enclosure_type.as_deref(), self.enclosure_url.as_deref().unwrap_or_default())
+    }
 }
 
 
@@ -241,6 +292,11 @@ pub struct Episode {
 pub struct PodcastFeedResult {
     // ... other fields ...
     pub(crate) episodes: Vec<Episode>,
+    /// Set when the episodes come from the server a page at a time, for loading the rest.
+    #[serde(default)]
+    pub(crate) podcast_id: Option<i32>,
+    #[serde(default)]
+    pub(crate) next_page: Option<Page>,
 }
 
 pub async fn call_get_podcast_info(podcast_value: &String, search_api_url: &Option<String>, search_index: &str) -> Result<PodcastSearchResult, anyhow::Error> {
@@ -285,51 +341,28 @@ pub struct PodcastEpisodesResponse {
     pub episodes: Vec<Episode>,
 }
 
-pub async fn call_get_podcast_episodes(
-    server_name: &str,
-    api_key: &Option<String>,
-    user_id: &i32,
-    podcast_id: &i32,
-) -> Result<PodcastFeedResult, anyhow::Error> {
-    let url = format!("{}/api/data/podcast_episodes?user_id={}&podcast_id={}", server_name, user_id, podcast_id);
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    let response = Request::get(&url)
-        .header("Api-Key", api_key_ref)
-        .send()
-        .await?;
-
-    if !response.ok() {
-        return Err(anyhow::Error::msg(format!("Failed to get podcast episodes: {}", response.status_text())));
-    }
-
-    let response_text = response.text().await?;
-    web_sys::console::log_1(&JsValue::from_str(&response_text));
-
-    let response_data: PodcastEpisodesResponse = serde_json::from_str(&response_text)?;
-
-    let episodes = response_data.episodes.into_iter().map(|mut episode| {
-        episode.guid = episode.guid.or_else(|| episode.episode_id.map(|id| id.to_string()));
-        episode
-    }).collect::<Vec<_>>();
+impl PinepodsClient {
+    pub async fn get_podcast_episodes(&self, user_id: &i32, podcast_id: &i32, page: Page) -> Result<PodcastFeedResult, Error> {
+        let response: PodcastEpisodesResponse = self
+            .get(format!("/api/data/podcast_episodes?user_id={}&podcast_id={}&{}", user_id, podcast_id, page.query()))
+            .fetch("Failed to get podcast episodes")
+            .await?;
 
-    Ok(PodcastFeedResult { episodes })
-}
-
-pub async fn call_parse_podcast_url(server_name: String, api_key: &Option<String>, podcast_url: &str) -> Result<PodcastFeedResult, Error> {
-    let encoded_podcast_url = urlencoding::encode(podcast_url);
-    let endpoint = format!("{}/api/data/fetch_podcast_feed?podcast_feed={}", server_name, encoded_podcast_url);
-    
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
+        let episodes = response.episodes.into_iter().map(|mut episode| {
+            episode.guid = episode.guid.or_else(|| episode.episode_id.map(|id| id.to_string()));
+            episode
+        }).collect::<Vec<_>>();
+        let next_page = page.after(episodes.len());
 
-    let request = Request::get(&endpoint)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key_ref)
-        .send()
-        .await?;
+        Ok(PodcastFeedResult { episodes, podcast_id: Some(*podcast_id), next_page })
+    }
 
-    if request.ok() {
-        let response_text = request.text().await?;
+    pub async fn parse_podcast_url(&self, podcast_url: &str) -> Result<PodcastFeedResult, Error> {
+        let encoded_podcast_url = urlencoding::encode(podcast_url);
+        let response_text = self
+            .get(format!("/api/data/fetch_podcast_feed?podcast_feed={}", encoded_podcast_url))
+            .text("Failed to fetch podcast feed")
+            .await?;
         let channel = Channel::read_from(response_text.as_bytes())?;
 
         let podcast_artwork_url = channel.image().map(|img| img.url().to_string())
@@ -342,6 +375,7 @@ pub async fn call_parse_podcast_url(server_name: String, api_key: &Option<String
                 content: item.content().map(|c| c.to_string()),
                 enclosure_url: item.enclosure().map(|enclosure| enclosure.url().to_string()),
                 enclosure_length: item.enclosure().map(|e| e.length().to_string()),
+                enclosure_type: item.enclosure().map(|e| e.mime_type().to_string()).filter(|mime_type| !mime_type.is_empty()),
                 pub_date: item.pub_date().map(|p| p.to_string()),
                 authors: item.author().map(|a| vec![a.to_string()]).unwrap_or_default(),
                 links: item.link().map(|l| vec![l.to_string()]).unwrap_or_default(),
@@ -350,12 +384,14 @@ pub async fn call_parse_podcast_url(server_name: String, api_key: &Option<String
                 guid: item.guid().map(|g| g.value().to_string()),
                 duration: item.itunes_ext().and_then(|ext| ext.duration()).map(|d| d.to_string()),
                 episode_id: None,
+                chapters_url: item_chapters_url(item),
+                chapters: item_embedded_chapters(item),
+                transcripts: item_transcripts(item),
+                completed: false,
             }
         }).collect();
 
-        Ok(PodcastFeedResult { episodes })
-    } else {
-        Err(anyhow::Error::msg(format!("Failed to fetch podcast feed: HTTP {}", request.status())))
+        Ok(PodcastFeedResult { episodes, podcast_id: None, next_page: None })
     }
 }
 
@@ -441,35 +477,46 @@ pub struct SearchEpisode {
     pub EpisodeDuration: i32,
     // Existing fields
     pub ListenDuration: Option<i32>,
+    #[serde(default, deserialize_with = "completed_flag")]
+    pub Completed: bool,
 }
 
-pub async fn call_search_database (
-    server_name: &String, 
-    api_key: &Option<String>, 
-    request_data: &SearchRequest
-) -> Result<Vec<SearchEpisode>, Error> {
-    let url = format!("{}/api/data/search_data", server_name);
-
-    // Convert Option<String> to Option<&str>
-    let api_key_ref = api_key.as_deref().ok_or_else(|| anyhow::Error::msg("API key is missing"))?;
-
-    let request_body = serde_json::to_string(request_data).map_err(|e| anyhow::Error::msg(format!("Serialization Error: {}", e)))?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(request_body)?
-        .send()
-        .await?;
-
-        if !response.ok() {
-            return Err(anyhow::Error::msg(format!("Failed to search database: {}", response.status_text())));
-        }
-    // Deserialize the response body into a SearchResponse
-        let search_response: SearchResponse = response.json().await?;
-
-        // Extract the vector of episodes from the SearchResponse
-        let results = search_response.data;
+impl PinepodsClient {
+    pub async fn search_database(&self, request_data: &SearchRequest) -> Result<Vec<SearchEpisode>, Error> {
+        let response: SearchResponse = self
+            .post("/api/data/search_data")
+            .json(request_data)?
+            .fetch("Failed to search database")
+            .await?;
+        Ok(response.data)
+    }
+}
 
-        Ok(results)
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::stub_server::{fixture, StubServer};
+    use pollster::block_on;
+
+    #[test]
+    fn feed_episodes_know_whether_they_are_video() {
+        let feed_url = "https://pods.example.com/video.xml";
+        let server = StubServer::start();
+        server.route(
+            "GET",
+            &format!("/api/data/fetch_podcast_feed?podcast_feed={}", urlencoding::encode(feed_url)),
+            200,
+            fixture("video_feed.xml"),
+        );
+        let client = server.client(Some("key"));
+
+        let feed = block_on(client.parse_podcast_url(feed_url)).unwrap();
+        let kinds: Vec<MediaKind> = feed.episodes.iter().map(Episode::media_kind).collect();
+
+        assert_eq!(feed.episodes[0].enclosure_type.as_deref(), Some("video/mp4"));
+        assert_eq!(kinds, [MediaKind::Video, MediaKind::Hls, MediaKind::Audio, MediaKind::Video]);
+        // Episodes from the database have no MIME type, only their URL
+        assert_eq!(MediaKind::of(None, "https://cdn.example.com/show/42.M4V?token=abc"), MediaKind::Video);
+        assert_eq!(MediaKind::of(None, "https://cdn.example.com/show/42.mp3"), MediaKind::Audio);
+    }
+}
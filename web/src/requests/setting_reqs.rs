@@ -1,27 +1,24 @@
 use anyhow::Error;
-use gloo_net::http::Request;
+use crate::components::context::{AppState, UIState};
+use crate::requests::client::PinepodsClient;
+use crate::requests::login_requests::Capability;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use yew::prelude::*;
+use yewdux::prelude::*;
 
 #[derive(Deserialize, Debug, PartialEq, Clone)]
 pub struct GetThemeResponse {
     theme: String,
 }
-pub async fn call_get_theme(server_name: String, api_key: String, user_id: &i32) -> Result<String, anyhow::Error> {
-    let url = format!("{}/api/data/get_theme/{}", server_name, user_id);
-    let api_key_ref = api_key.as_str();
-
-    let response = Request::get(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .send()
-        .await?;
-
-    if response.ok() {
-        let response_body = response.json::<GetThemeResponse>().await?;
-        Ok(response_body.theme)
-    } else {
-        Err(Error::msg(format!("Error getting theme. Is the server reachable? Server Response: {}", response.status_text())))
+
+impl PinepodsClient {
+    pub async fn get_theme(&self, user_id: &i32) -> Result<String, Error> {
+        let response: GetThemeResponse = self
+            .get(format!("/api/data/get_theme/{}", user_id))
+            .fetch("Error getting theme")
+            .await?;
+        Ok(response.theme)
     }
 }
 
@@ -35,29 +32,312 @@ pub struct SetThemeResponse {
     message: String,
 }
 
-pub async fn call_set_theme(server_name: &Option<String>, api_key: &Option<String>, set_theme: &SetThemeRequest) -> Result<bool, Error> {
-    let server = server_name.clone().unwrap();
-    let url = format!("{}/api/data/user/set_theme", server);
-    let api_key_ref = api_key.as_deref().ok_or_else(|| Error::msg("API key is missing"))?;
+impl PinepodsClient {
+    pub async fn set_theme(&self, set_theme: &SetThemeRequest) -> Result<bool, Error> {
+        let response: SetThemeResponse = self
+            .put("/api/data/user/set_theme")
+            .json(set_theme)?
+            .fetch("Error updating theme")
+            .await?;
+        Ok(response.message == "Success")
+    }
+}
+
+/// Player actions that can be bound to keys.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    PlayPause,
+    SkipBack,
+    SkipForward,
+    SpeedUp,
+    SlowDown,
+    NextEpisode,
+    Mute,
+    ShowHelp,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 8] = [
+        ShortcutAction::PlayPause,
+        ShortcutAction::SkipBack,
+        ShortcutAction::SkipForward,
+        ShortcutAction::SpeedUp,
+        ShortcutAction::SlowDown,
+        ShortcutAction::NextEpisode,
+        ShortcutAction::Mute,
+        ShortcutAction::ShowHelp,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ShortcutAction::PlayPause => "Play / pause",
+            ShortcutAction::SkipBack => "Skip back",
+            ShortcutAction::SkipForward => "Skip forward",
+            ShortcutAction::SpeedUp => "Speed up",
+            ShortcutAction::SlowDown => "Slow down",
+            ShortcutAction::NextEpisode => "Next in queue",
+            ShortcutAction::Mute => "Mute / unmute",
+            ShortcutAction::ShowHelp => "Show shortcuts",
+        }
+    }
+}
+
+/// A key, as reported by `KeyboardEvent.key`, bound to a player action.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct KeyBinding {
+    pub action: ShortcutAction,
+    pub key: String,
+}
+
+/// Letters are matched regardless of case, so Shift or Caps Lock don't break a binding.
+pub fn normalize_key(key: &str) -> String {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => c.to_lowercase().collect(),
+        _ => key.to_string(),
+    }
+}
+
+/// How a bound key is shown to the user.
+pub fn key_label(key: &str) -> String {
+    match key {
+        " " => "Space".to_string(),
+        "ArrowLeft" => "\u{2190}".to_string(),
+        "ArrowRight" => "\u{2192}".to_string(),
+        "ArrowUp" => "\u{2191}".to_string(),
+        "ArrowDown" => "\u{2193}".to_string(),
+        _ => key.to_uppercase(),
+    }
+}
+
+fn default_key_bindings() -> Vec<KeyBinding> {
+    [
+        (ShortcutAction::PlayPause, " "),
+        (ShortcutAction::PlayPause, "k"),
+        (ShortcutAction::SkipBack, "j"),
+        (ShortcutAction::SkipBack, "ArrowLeft"),
+        (ShortcutAction::SkipForward, "l"),
+        (ShortcutAction::SkipForward, "ArrowRight"),
+        (ShortcutAction::SpeedUp, "+"),
+        (ShortcutAction::SlowDown, "-"),
+        (ShortcutAction::NextEpisode, "n"),
+        (ShortcutAction::Mute, "m"),
+        (ShortcutAction::ShowHelp, "?"),
+    ]
+    .into_iter()
+    .map(|(action, key)| KeyBinding { action, key: key.to_string() })
+    .collect()
+}
 
-    // Serialize `added_podcast` into JSON
-    let json_body = serde_json::to_string(set_theme)?;
+/// Processing applied to the audio on its way to the speakers. Podcasts can override
+/// each of these for their own episodes.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone, Copy)]
+#[serde(default)]
+pub struct AudioEffects {
+    /// Compresses and raises quiet voices.
+    pub voice_boost: bool,
+    /// Evens out episodes recorded too quiet or too loud.
+    pub normalize_loudness: bool,
+    /// Speeds through the pauses between speech.
+    pub skip_silence: bool,
+}
 
-    let response = Request::put(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(json_body)?
-        .send()
-        .await?;
+impl AudioEffects {
+    pub fn any(&self) -> bool {
+        self.voice_boost || self.normalize_loudness || self.skip_silence
+    }
+}
+
+/// What happens when an episode finishes.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(default)]
+pub struct QueuePlayback {
+    /// Start the next episode in the queue after a short countdown.
+    pub auto_play_next: bool,
+    /// Take the finished episode out of the queue.
+    pub remove_finished: bool,
+}
 
-    if response.ok() {
-        let response_body = response.json::<SetThemeResponse>().await?;
-        Ok(response_body.message == "Success")
-    } else {
-        Err(Error::msg(format!("Error updating theme: {}", response.status_text())))
+impl Default for QueuePlayback {
+    fn default() -> Self {
+        QueuePlayback { auto_play_next: true, remove_finished: true }
     }
 }
 
+/// When an episode counts as played.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(default)]
+pub struct CompletionThreshold {
+    /// Played once this share of the episode has gone by, in percent.
+    pub completion_percent: u32,
+    /// Or once no more than this many seconds are left, for the credits and outros.
+    pub completion_remaining: u32,
+}
+
+impl Default for CompletionThreshold {
+    fn default() -> Self {
+        CompletionThreshold { completion_percent: 95, completion_remaining: 60 }
+    }
+}
+
+impl CompletionThreshold {
+    /// Whether `position` is far enough into an episode `duration` long for it to count
+    /// as played. The time left only counts past half way, so a short episode isn't
+    /// played as soon as it starts.
+    pub fn reached(&self, position: f64, duration: f64) -> bool {
+        if duration <= 0.0 {
+            return false;
+        }
+        position >= duration * self.completion_percent as f64 / 100.0
+            || (position >= duration / 2.0 && duration - position <= self.completion_remaining as f64)
+    }
+}
+
+/// Skip intervals, keyboard shortcuts, audio processing, queue behaviour and when
+/// episodes count as played, saved with the user's settings.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PlaybackSettings {
+    pub skip_back: u32,
+    pub skip_forward: u32,
+    pub key_bindings: Vec<KeyBinding>,
+    pub audio_effects: AudioEffects,
+    pub queue_playback: QueuePlayback,
+    pub completion: CompletionThreshold,
+}
+
+impl Default for PlaybackSettings {
+    fn default() -> Self {
+        PlaybackSettings {
+            skip_back: 15,
+            skip_forward: 15,
+            key_bindings: default_key_bindings(),
+            audio_effects: AudioEffects::default(),
+            queue_playback: QueuePlayback::default(),
+            completion: CompletionThreshold::default(),
+        }
+    }
+}
+
+impl PlaybackSettings {
+    pub fn action_for(&self, key: &str) -> Option<ShortcutAction> {
+        let key = normalize_key(key);
+        self.key_bindings
+            .iter()
+            .find(|binding| binding.key == key)
+            .map(|binding| binding.action)
+    }
+
+    pub fn keys_for(&self, action: ShortcutAction) -> Vec<&str> {
+        self.key_bindings
+            .iter()
+            .filter(|binding| binding.action == action)
+            .map(|binding| binding.key.as_str())
+            .collect()
+    }
+
+    /// Binds `key` to `action`, taking it away from whatever it was bound to before.
+    pub fn bind(&mut self, action: ShortcutAction, key: &str) {
+        let key = normalize_key(key);
+        self.key_bindings.retain(|binding| binding.key != key);
+        self.key_bindings.push(KeyBinding { action, key });
+    }
+
+    pub fn unbind(&mut self, key: &str) {
+        self.key_bindings.retain(|binding| binding.key != key);
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+struct GetPlaybackSettingsResponse {
+    skip_back: u32,
+    skip_forward: u32,
+    // Null until the user changes a shortcut
+    key_bindings: Option<Vec<KeyBinding>>,
+    // Missing from servers without audio processing
+    #[serde(flatten)]
+    audio_effects: AudioEffects,
+    // Missing from servers without continuous playback
+    #[serde(flatten)]
+    queue_playback: QueuePlayback,
+    // Missing from servers without played episodes
+    #[serde(flatten)]
+    completion: CompletionThreshold,
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct SetPlaybackSettingsRequest {
+    pub(crate) user_id: i32,
+    pub(crate) skip_back: u32,
+    pub(crate) skip_forward: u32,
+    pub(crate) key_bindings: Vec<KeyBinding>,
+    #[serde(flatten)]
+    pub(crate) audio_effects: AudioEffects,
+    #[serde(flatten)]
+    pub(crate) queue_playback: QueuePlayback,
+    #[serde(flatten)]
+    pub(crate) completion: CompletionThreshold,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+struct SetPlaybackSettingsResponse {
+    message: String,
+}
+
+impl PinepodsClient {
+    pub async fn get_playback_settings(&self, user_id: &i32) -> Result<PlaybackSettings, Error> {
+        let response: GetPlaybackSettingsResponse = self
+            .get(format!("/api/data/get_playback_settings/{}", user_id))
+            .fetch("Error getting playback settings")
+            .await?;
+        Ok(PlaybackSettings {
+            skip_back: response.skip_back,
+            skip_forward: response.skip_forward,
+            key_bindings: response.key_bindings.unwrap_or_else(default_key_bindings),
+            audio_effects: response.audio_effects,
+            queue_playback: response.queue_playback,
+            completion: response.completion,
+        })
+    }
+
+    pub async fn set_playback_settings(&self, request: &SetPlaybackSettingsRequest) -> Result<(), Error> {
+        let _response: SetPlaybackSettingsResponse = self
+            .put("/api/data/user/set_playback_settings")
+            .json(request)?
+            .fetch("Error updating playback settings")
+            .await?;
+        Ok(())
+    }
+}
+
+/// Loads the user's skip intervals and shortcuts once they're logged in. Servers
+/// without playback settings leave the defaults in place.
+#[hook]
+pub fn use_playback_settings() {
+    let (state, _dispatch) = use_store::<AppState>();
+    let (_ui_state, ui_dispatch) = use_store::<UIState>();
+    let client = state.client();
+    let user_id = state.user_details.as_ref().map(|ud| ud.UserID);
+    let supported = state
+        .server_details
+        .as_ref()
+        .map_or(false, |details| details.supports(Capability::PlaybackSettings));
+    use_effect_with((client, user_id, supported), move |(client, user_id, supported)| {
+        match (*user_id, *supported) {
+            (Some(user_id), true) => {
+                let client = client.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Ok(settings) = client.get_playback_settings(&user_id).await {
+                        ui_dispatch.reduce_mut(|ui_state| ui_state.playback_settings = settings);
+                    }
+                });
+            }
+            _ => ui_dispatch.reduce_mut(|ui_state| ui_state.playback_settings = PlaybackSettings::default()),
+        }
+        || ()
+    });
+}
+
 // Admin Only API Calls
 
 #[derive(Deserialize, Debug, PartialEq, Clone)]
@@ -74,22 +354,11 @@ pub struct SettingsUser {
     pub IsAdmin: i32,
 }
 
-pub async fn call_get_user_info(server_name: String, api_key: String) -> Result<Vec<SettingsUser>, anyhow::Error> {
-    let url = format!("{}/api/data/get_user_info", server_name);
-    let api_key_ref = api_key.as_str();
-
-    let response = Request::get(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .send()
-        .await?;
-
-    if response.ok() {
-        let response_text = response.text().await?;
-        let users: Vec<SettingsUser> = serde_json::from_str(&response_text)?;
-        Ok(users)
-    } else {
-        Err(Error::msg(format!("Error getting user info. Is the server reachable? Server Response: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn get_user_info(&self) -> Result<Vec<SettingsUser>, Error> {
+        self.get("/api/data/get_user_info")
+            .fetch("Error getting user info")
+            .await
     }
 }
 
@@ -107,27 +376,14 @@ pub struct AddUserResponse {
 }
 
 
-pub async fn call_add_user(server_name: String, api_key: String, add_user: &AddSettingsUserRequest) -> Result<bool, Error> {
-    let server = server_name.clone();
-    let url = format!("{}/api/data/add_user", server);
-
-    // let add_user_req = add_user.as_ref().unwrap();
-
-    // Serialize `add_user` into JSON
-    let json_body = serde_json::to_string(&add_user)?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", &api_key)
-        .header("Content-Type", "application/json")
-        .body(json_body)?
-        .send()
-        .await?;
-
-    if response.ok() {
-        let response_body = response.json::<AddUserResponse>().await?;
-        Ok(response_body.detail == "Success")
-    } else {
-        Err(Error::msg(format!("Error adding user: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn add_user(&self, add_user: &AddSettingsUserRequest) -> Result<bool, Error> {
+        let response: AddUserResponse = self
+            .post("/api/data/add_user")
+            .json(add_user)?
+            .fetch("Error adding user")
+            .await?;
+        Ok(response.detail == "Success")
     }
 }
 
@@ -136,95 +392,41 @@ pub struct DetailResponse {
     detail: String,
 }
 
-pub async fn call_set_fullname(server_name: String, api_key: String, user_id: i32, new_name: String) -> Result<DetailResponse, Error> {
-    let url = format!("{}/api/data/set_fullname/{}?new_name={}", server_name, user_id, new_name);
-    let response = Request::put(&url)
-        .header("Api-Key", &api_key)
-        .header("Content-Type", "application/json")
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        response.json::<DetailResponse>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error setting fullname: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn set_fullname(&self, user_id: i32, new_name: String) -> Result<DetailResponse, Error> {
+        let encoded_name = urlencoding::encode(&new_name);
+        self.put(format!("/api/data/set_fullname/{}?new_name={}", user_id, encoded_name))
+            .fetch("Error setting fullname")
+            .await
     }
-}
-
-pub async fn call_set_password(server_name: String, api_key: String, user_id: i32, hash_pw: String) -> Result<DetailResponse, Error> {
-    let url = format!("{}/api/data/set_password/{}", server_name, user_id);
-    let body = serde_json::json!({ "hash_pw": hash_pw });
-
-    let response = Request::put(&url)
-        .header("Api-Key", &api_key)
-        .header("Content-Type", "application/json")
-        .body(&body.to_string())?
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
 
-    if response.ok() {
-        response.json::<DetailResponse>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error setting password: {}", response.status_text())))
+    pub async fn set_password(&self, user_id: i32, hash_pw: String) -> Result<DetailResponse, Error> {
+        self.put(format!("/api/data/set_password/{}", user_id))
+            .json(&serde_json::json!({ "hash_pw": hash_pw }))?
+            .fetch("Error setting password")
+            .await
     }
-}
-
-pub async fn call_set_email(server_name: String, api_key: String, user_id: i32, new_email: String) -> Result<DetailResponse, Error> {
-    let url = format!("{}/api/data/user/set_email", server_name);
-    let body = serde_json::json!({ "user_id": user_id, "new_email": new_email });
-
-    let response = Request::put(&url)
-        .header("Api-Key", &api_key)
-        .header("Content-Type", "application/json")
-        .body(&body.to_string())?
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
 
-    if response.ok() {
-        response.json::<DetailResponse>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error setting email: {}", response.status_text())))
+    pub async fn set_email(&self, user_id: i32, new_email: String) -> Result<DetailResponse, Error> {
+        self.put("/api/data/user/set_email")
+            .json(&serde_json::json!({ "user_id": user_id, "new_email": new_email }))?
+            .fetch("Error setting email")
+            .await
     }
-}
-
-pub async fn call_set_username(server_name: String, api_key: String, user_id: i32, new_username: String) -> Result<DetailResponse, Error> {
-    let url = format!("{}/api/data/user/set_username", server_name);
-    let body = serde_json::json!({ "user_id": user_id, "new_username": new_username });
 
-    let response = Request::put(&url)
-        .header("Api-Key", &api_key)
-        .header("Content-Type", "application/json")
-        .body(&body.to_string())?
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        response.json::<DetailResponse>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error setting username: {}", response.status_text())))
+    pub async fn set_username(&self, user_id: i32, new_username: String) -> Result<DetailResponse, Error> {
+        self.put("/api/data/user/set_username")
+            .json(&serde_json::json!({ "user_id": user_id, "new_username": new_username }))?
+            .fetch("Error setting username")
+            .await
     }
-}
-#[allow(dead_code)]
-pub async fn call_set_isadmin(server_name: String, api_key: String, user_id: i32, isadmin: bool) -> Result<DetailResponse, Error> {
-    let url = format!("{}/api/data/user/set_isadmin", server_name);
-    let body = serde_json::json!({ "user_id": user_id, "isadmin": isadmin });
-
-    let response = Request::put(&url)
-        .header("Api-Key", &api_key)
-        .header("Content-Type", "application/json")
-        .body(&body.to_string())?
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        response.json::<DetailResponse>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error setting admin status: {}", response.status_text())))
+
+    #[allow(dead_code)]
+    pub async fn set_isadmin(&self, user_id: i32, isadmin: bool) -> Result<DetailResponse, Error> {
+        self.put("/api/data/user/set_isadmin")
+            .json(&serde_json::json!({ "user_id": user_id, "isadmin": isadmin }))?
+            .fetch("Error setting admin status")
+            .await
     }
 }
 
@@ -234,20 +436,12 @@ pub struct FinalAdminResponse {
 }
 
 
-#[allow(dead_code)]
-pub async fn call_check_admin(server_name: String, api_key: String, user_id: i32) -> Result<FinalAdminResponse, Error> {
-    let url = format!("{}/api/data/user/final_admin/{}", server_name, user_id);
-
-    let response = Request::get(&url)
-        .header("Api-Key", &api_key)
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        response.json::<FinalAdminResponse>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error getting admin status: {}", response.status_text())))
+impl PinepodsClient {
+    #[allow(dead_code)]
+    pub async fn check_admin(&self, user_id: i32) -> Result<FinalAdminResponse, Error> {
+        self.get(format!("/api/data/user/final_admin/{}", user_id))
+            .fetch("Error getting admin status")
+            .await
     }
 }
 
@@ -270,54 +464,23 @@ pub struct SuccessResponse {
     success: bool,
 }
 
-pub async fn call_enable_disable_guest(server_name: String, api_key: String) -> Result<SuccessResponse, Error> {
-    let url = format!("{}/api/data/enable_disable_guest", server_name);
-
-    let response = Request::post(&url)
-        .header("Api-Key", &api_key)
-        .header("Content-Type", "application/json")
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        response.json::<SuccessResponse>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error enabling/disabling guest access: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn enable_disable_guest(&self) -> Result<SuccessResponse, Error> {
+        self.post("/api/data/enable_disable_guest")
+            .fetch("Error enabling/disabling guest access")
+            .await
     }
-}
-
-pub async fn call_enable_disable_downloads(server_name: String, api_key: String) -> Result<SuccessResponse, Error> {
-    let url = format!("{}/api/data/enable_disable_downloads", server_name);
 
-    let response = Request::post(&url)
-        .header("Api-Key", &api_key)
-        .header("Content-Type", "application/json")
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        response.json::<SuccessResponse>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error enabling/disabling downloads: {}", response.status_text())))
+    pub async fn enable_disable_downloads(&self) -> Result<SuccessResponse, Error> {
+        self.post("/api/data/enable_disable_downloads")
+            .fetch("Error enabling/disabling downloads")
+            .await
     }
-}
-
-pub async fn call_enable_disable_self_service(server_name: String, api_key: String) -> Result<SuccessResponse, Error> {
-    let url = format!("{}/api/data/enable_disable_self_service", server_name);
-
-    let response = Request::post(&url)
-        .header("Api-Key", &api_key)
-        .header("Content-Type", "application/json")
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
 
-    if response.ok() {
-        response.json::<SuccessResponse>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error enabling/disabling self service: {}", response.status_text())))
+    pub async fn enable_disable_self_service(&self) -> Result<SuccessResponse, Error> {
+        self.post("/api/data/enable_disable_self_service")
+            .fetch("Error enabling/disabling self service")
+            .await
     }
 }
 
@@ -326,19 +489,11 @@ pub struct GuestStatusResponse {
     pub guest_status: bool,
 }
 
-pub async fn call_guest_status(server_name: String, api_key: String) -> Result<bool, Error> {
-    let url = format!("{}/api/data/guest_status", server_name);
-
-    let response = Request::get(&url)
-        .header("Api-Key", &api_key)
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        response.json::<bool>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error fetching guest status: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn guest_status(&self) -> Result<bool, Error> {
+        self.get("/api/data/guest_status")
+            .fetch("Error fetching guest status")
+            .await
     }
 }
 
@@ -347,19 +502,11 @@ pub struct DownloadStatusResponse {
     download_status: bool,
 }
 
-pub async fn call_download_status(server_name: String, api_key: String) -> Result<bool, Error> {
-    let url = format!("{}/api/data/download_status", server_name);
-
-    let response = Request::get(&url)
-        .header("Api-Key", &api_key)
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        response.json::<bool>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error fetching download status: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn download_status(&self) -> Result<bool, Error> {
+        self.get("/api/data/download_status")
+            .fetch("Error fetching download status")
+            .await
     }
 }
 
@@ -368,20 +515,13 @@ pub struct SelfServiceStatusResponse {
     status: bool,
 }
 
-pub async fn call_self_service_status(server_name: String, api_key: String) -> Result<bool, Error> {
-    let url = format!("{}/api/data/self_service_status", server_name);
-
-    let response = Request::get(&url)
-        .header("Api-Key", &api_key)
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        let status_response: SelfServiceStatusResponse = response.json().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))?;
-        Ok(status_response.status)
-    } else {
-        Err(Error::msg(format!("Error fetching self service status: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn self_service_status(&self) -> Result<bool, Error> {
+        let response: SelfServiceStatusResponse = self
+            .get("/api/data/self_service_status")
+            .fetch("Error fetching self service status")
+            .await?;
+        Ok(response.status)
     }
 }
 
@@ -402,28 +542,12 @@ pub struct EmailSettings {
     pub(crate) email_password: String,
 }
 
-pub async fn call_save_email_settings(
-    server_name: String,
-    api_key: String,
-    email_settings: EmailSettings,
-) -> Result<DetailResponse, Error> {
-    let url = format!("{}/api/data/save_email_settings", server_name);
-    let body = EmailSettingsRequest {
-        email_settings,
-    };
-
-    let response = Request::post(&url)
-        .header("Api-Key", &api_key)
-        .header("Content-Type", "application/json")
-        .body(serde_json::to_string(&body)?)?
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        response.json::<DetailResponse>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error saving email settings: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn save_email_settings(&self, email_settings: EmailSettings) -> Result<DetailResponse, Error> {
+        self.post("/api/data/save_email_settings")
+            .json(&EmailSettingsRequest { email_settings })?
+            .fetch("Error saving email settings")
+            .await
     }
 }
 
@@ -447,26 +571,12 @@ pub struct EmailSendResponse {
     email_status: String,
 }
 
-pub async fn call_send_test_email(
-    server_name: String,
-    api_key: String,
-    email_settings: TestEmailSettings,
-) -> Result<EmailSendResponse, Error> {
-    let url = format!("{}/api/data/send_test_email", server_name);
-    let body = serde_json::to_string(&email_settings)?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", &api_key)
-        .header("Content-Type", "application/json")
-        .body(&body)?
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        response.json::<EmailSendResponse>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error sending email: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn send_test_email(&self, email_settings: &TestEmailSettings) -> Result<EmailSendResponse, Error> {
+        self.post("/api/data/send_test_email")
+            .json(email_settings)?
+            .fetch("Error sending email")
+            .await
     }
 }
 
@@ -478,26 +588,12 @@ pub struct SendEmailSettings {
     pub(crate) message: String,
 }
 
-pub async fn call_send_email(
-    server_name: String,
-    api_key: String,
-    email_settings: SendEmailSettings,
-) -> Result<EmailSendResponse, Error> {
-    let url = format!("{}/api/data/send_email", server_name);
-    let body = email_settings;
-
-    let response = Request::post(&url)
-        .header("Api-Key", &api_key)
-        .header("Content-Type", "application/json")
-        .body(serde_json::to_string(&body)?)?
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        response.json::<EmailSendResponse>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error sending email: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn send_email(&self, email_settings: &SendEmailSettings) -> Result<EmailSendResponse, Error> {
+        self.post("/api/data/send_email")
+            .json(email_settings)?
+            .fetch("Error sending email")
+            .await
     }
 }
 
@@ -515,25 +611,11 @@ pub struct EmailSettingsResponse {
     pub(crate) Password: String,
 }
 
-pub async fn call_get_email_settings(
-    server_name: String,
-    api_key: String,
-) -> Result<EmailSettingsResponse, Error> {
-    let url = format!("{}/api/data/get_email_settings", server_name);
-
-    let response = Request::get(&url)
-        .header("Api-Key", &api_key)
-        .header("Content-Type", "application/json")
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        let response_text = response.text().await.map_err(|e| Error::msg(format!("Error getting response text: {}", e)))?;
-        println!("Response text: {}", response_text);
-        serde_json::from_str::<EmailSettingsResponse>(&response_text).map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error retrieving email settings: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn get_email_settings(&self) -> Result<EmailSettingsResponse, Error> {
+        self.get("/api/data/get_email_settings")
+            .fetch("Error retrieving email settings")
+            .await
     }
 }
 
@@ -554,23 +636,11 @@ pub struct APIInfoResponse {
     pub(crate) api_info: Vec<APIInfo>,
 }
 
-pub async fn call_get_api_info(
-    server_name: String,
-    user_id: i32,
-    api_key: String,
-) -> Result<APIInfoResponse, Error> {
-    let url = format!("{}/api/data/get_api_info/{}", server_name, user_id);
-
-    let response = Request::get(&url)
-        .header("Api-Key", &api_key)
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    if response.ok() {
-        response.json::<APIInfoResponse>().await.map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error retrieving API info: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn get_api_info(&self, user_id: i32) -> Result<APIInfoResponse, Error> {
+        self.get(format!("/api/data/get_api_info/{}", user_id))
+            .fetch("Error retrieving API info")
+            .await
     }
 }
 
@@ -579,26 +649,12 @@ pub struct CreateAPIKeyResponse {
     pub api_key: String,
 }
 
-pub async fn call_create_api_key(
-    server_name: &str,
-    user_id: i32,
-    api_key: &str,
-) -> Result<CreateAPIKeyResponse, anyhow::Error> {
-    let url = format!("{}/api/data/create_api_key", server_name);
-    let request_body = serde_json::json!({ "user_id": user_id });
-
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key)
-        .body(serde_json::to_string(&request_body)?)?
-        .send()
-        .await
-        .map_err(anyhow::Error::msg)?;
-
-    if response.ok() {
-        response.json::<CreateAPIKeyResponse>().await.map_err(anyhow::Error::msg)
-    } else {
-        Err(anyhow::Error::msg("Error creating API key"))
+impl PinepodsClient {
+    pub async fn create_api_key(&self, user_id: i32) -> Result<CreateAPIKeyResponse, Error> {
+        self.post("/api/data/create_api_key")
+            .json(&serde_json::json!({ "user_id": user_id }))?
+            .fetch("Error creating API key")
+            .await
     }
 }
 
@@ -613,28 +669,12 @@ pub struct DeleteAPIRequest {
     pub(crate) user_id: String,
 }
 
-pub async fn call_delete_api_key(
-    server_name: &str,
-    request_body: DeleteAPIRequest,
-    api_key: &str,
-) -> Result<DeleteAPIKeyResponse, anyhow::Error> {
-    let url = format!("{}/api/data/delete_api_key", server_name);
-    let body = request_body;
-
-    let response = Request::delete(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key)
-        .body(serde_json::to_string(&body)?)?
-        .send()
-        .await
-        .map_err(anyhow::Error::msg)?;
-
-    if response.ok() {
-        response.json::<DeleteAPIKeyResponse>().await.map_err(anyhow::Error::msg)
-    } else {
-        // If the response is not ok(), read the response body to extract the error message
-        let error_message = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        Err(anyhow::Error::msg(format!("Error deleting API key: {}", error_message)))
+impl PinepodsClient {
+    pub async fn delete_api_key(&self, request_body: &DeleteAPIRequest) -> Result<DeleteAPIKeyResponse, Error> {
+        self.delete("/api/data/delete_api_key")
+            .json(request_body)?
+            .fetch("Error deleting API key")
+            .await
     }
 }
 
@@ -644,51 +684,20 @@ pub struct BackupUserRequest {
     pub user_id: i32,
 }
 
-pub async fn call_backup_user(
-    server_name: &str,
-    user_id: i32,
-    api_key: &str,
-) -> Result<String, anyhow::Error> { // Assuming the OPML content is returned as a plain string
-    let url = format!("{}/api/data/backup_user", server_name);
-    let request_body = BackupUserRequest { user_id };
-
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key)
-        .body(serde_json::to_string(&request_body)?)?
-        .send()
-        .await
-        .map_err(anyhow::Error::msg)?;
-
-    if response.ok() {
-        response.text().await.map_err(anyhow::Error::msg)
-    } else {
-        Err(anyhow::Error::msg("Error backing up user data"))
+impl PinepodsClient {
+    /// Returns the user's subscriptions as an OPML document.
+    pub async fn backup_user(&self, user_id: i32) -> Result<String, Error> {
+        self.post("/api/data/backup_user")
+            .json(&BackupUserRequest { user_id })?
+            .text("Error backing up user data")
+            .await
     }
-}
-
-pub async fn call_backup_server(
-    server_name: &str,
-    database_pass: &str,
-    api_key: &str,
-) -> Result<String, anyhow::Error> {
-    let url = format!("{}/api/data/backup_server", server_name);
-    let request_body = serde_json::json!({
-        "database_pass": database_pass
-    });
 
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key)
-        .body(serde_json::to_string(&request_body)?)?
-        .send()
-        .await
-        .map_err(anyhow::Error::msg)?;
-
-    if response.ok() {
-        response.text().await.map_err(anyhow::Error::msg)
-    } else {
-        Err(anyhow::Error::msg(format!("Error backing up server data: {}", response.status_text())))
+    pub async fn backup_server(&self, database_pass: &str) -> Result<String, Error> {
+        self.post("/api/data/backup_server")
+            .json(&serde_json::json!({ "database_pass": database_pass }))?
+            .text("Error backing up server data")
+            .await
     }
 }
 
@@ -698,32 +707,16 @@ struct RestoreServerRequest {
     server_restore_data: String,
 }
 
-pub async fn call_restore_server(
-    server_name: &str,
-    database_pass: &str,
-    server_restore_data: &str,
-    api_key: &str,
-) -> Result<String, Error> {
-    let url = format!("{}/api/data/restore_server", server_name);
-    let request_body = RestoreServerRequest {
-        database_pass: database_pass.to_string().clone(),
-        server_restore_data: server_restore_data.to_string().clone(),
-    };
-    log::info!("db: {:?}", database_pass.to_string().clone());
-    log::info!("restore: {:?}", server_restore_data.to_string().clone());
-
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key)
-        .body(serde_json::to_string(&request_body)?)?
-        .send()
-        .await
-        .map_err(Error::msg)?;
-
-    if response.ok() {
-        response.text().await.map_err(Error::msg)
-    } else {
-        Err(Error::msg(format!("Error restoring server data: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn restore_server(&self, database_pass: &str, server_restore_data: &str) -> Result<String, Error> {
+        let request_body = RestoreServerRequest {
+            database_pass: database_pass.to_string(),
+            server_restore_data: server_restore_data.to_string(),
+        };
+        self.post("/api/data/restore_server")
+            .json(&request_body)?
+            .text("Error restoring server data")
+            .await
     }
 }
 
@@ -734,21 +727,11 @@ pub struct GenerateMFAResponse {
 }
 
 // Then adjust the function to return this struct:
-pub async fn call_generate_mfa_secret(server_name: String, api_key: String, user_id: i32) -> Result<GenerateMFAResponse, anyhow::Error> {
-    let url = format!("{}/api/data/generate_mfa_secret/{}", server_name, user_id);
-    let api_key_ref = &api_key;
-
-    let response = Request::get(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .send()
-        .await?;
-
-    if response.ok() {
-        let response_body = response.json::<GenerateMFAResponse>().await?;
-        Ok(response_body)
-    } else {
-        Err(anyhow::Error::msg(format!("Error generating MFA secret. Server Response: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn generate_mfa_secret(&self, user_id: i32) -> Result<GenerateMFAResponse, Error> {
+        self.get(format!("/api/data/generate_mfa_secret/{}", user_id))
+            .fetch("Error generating MFA secret")
+            .await
     }
 }
 
@@ -763,30 +746,12 @@ pub struct VerifyTempMFAResponse {
     pub verified: bool,
 }
 
-pub async fn call_verify_temp_mfa(
-    server_name: &str,
-    api_key: &str,
-    user_id: i32,
-    mfa_code: String,
-) -> Result<VerifyTempMFAResponse, Error> {
-    let url = format!("{}/api/data/verify_temp_mfa", server_name);
-    let body = VerifyTempMFABody { user_id, mfa_code };
-    let request_body = serde_json::to_string(&body).map_err(Error::msg)?;
-
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key)
-        .body(request_body)?
-        .send()
-        .await
-        .map_err(Error::msg)?;
-
-    if response.ok() {
-        response.json::<VerifyTempMFAResponse>().await.map_err(Error::msg)
-    } else {
-        let status_text = response.status_text();
-        let error_text = response.text().await.unwrap_or_default();
-        Err(Error::msg(format!("Error verifying temp MFA: {} - {}", status_text, error_text)))
+impl PinepodsClient {
+    pub async fn verify_temp_mfa(&self, user_id: i32, mfa_code: String) -> Result<VerifyTempMFAResponse, Error> {
+        self.post("/api/data/verify_temp_mfa")
+            .json(&VerifyTempMFABody { user_id, mfa_code })?
+            .fetch("Error verifying temp MFA")
+            .await
     }
 }
 
@@ -795,21 +760,14 @@ pub async fn call_verify_temp_mfa(
 pub struct GetMFAResponse {
     mfa_enabled: bool,
 }
-pub async fn call_mfa_settings(server_name: String, api_key: String, user_id: i32) -> Result<bool, anyhow::Error> {
-    let url = format!("{}/api/data/check_mfa_enabled/{}", server_name, user_id);
-    let api_key_ref = api_key.as_str();
-
-    let response = Request::get(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .send()
-        .await?;
-
-    if response.ok() {
-        let response_body = response.json::<GetMFAResponse>().await?;
-        Ok(response_body.mfa_enabled)
-    } else {
-        Err(Error::msg(format!("Error getting MFA status. Is the server reachable? Server Response: {}", response.status_text())))
+
+impl PinepodsClient {
+    pub async fn mfa_settings(&self, user_id: i32) -> Result<bool, Error> {
+        let response: GetMFAResponse = self
+            .get(format!("/api/data/check_mfa_enabled/{}", user_id))
+            .fetch("Error getting MFA status")
+            .await?;
+        Ok(response.mfa_enabled)
     }
 }
 
@@ -823,25 +781,14 @@ pub struct SaveMFASecretRequest {
 pub struct SaveMFASecretResponse {
     pub(crate) status: String,
 }
-#[allow(dead_code)]
-pub async fn call_save_mfa_secret(server_name: &String, api_key: &String, user_id: i32, mfa_secret: String) -> Result<SaveMFASecretResponse, anyhow::Error> {
-    let url = format!("{}/api/data/save_mfa_secret", server_name);
-    let api_key_ref = api_key.as_str();
-    let body = SaveMFASecretRequest { user_id, mfa_secret };
-    let json_body = serde_json::to_string(&body)?;
-
-    let response = Request::post(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .body(json_body)?
-        .send()
-        .await?;
-
-    if response.ok() {
-        let response_body = response.json::<SaveMFASecretResponse>().await?;
-        Ok(response_body)
-    } else {
-        Err(Error::msg(format!("Error saving MFA secret. Is the server reachable? Server Response: {}", response.status_text())))
+
+impl PinepodsClient {
+    #[allow(dead_code)]
+    pub async fn save_mfa_secret(&self, user_id: i32, mfa_secret: String) -> Result<SaveMFASecretResponse, Error> {
+        self.post("/api/data/save_mfa_secret")
+            .json(&SaveMFASecretRequest { user_id, mfa_secret })?
+            .fetch("Error saving MFA secret")
+            .await
     }
 }
 
@@ -871,36 +818,16 @@ pub struct LoginInitiateRequest {
     nextcloud_url: String,
 }
 
-pub async fn initiate_nextcloud_login(nextcloud_url: &str, server_name: &str, api_key: &str, user_id: i32) -> Result<NextcloudInitiateResponse, Error> {
-    // Construct the URL with query parameters
-    let url = format!("{}/api/data/initiate_nextcloud_login", server_name);
-    let request_body = LoginInitiateRequest {
-        user_id,
-        nextcloud_url: nextcloud_url.to_string(),
-    };
-    let json_body = serde_json::to_string(&request_body)
-        .map_err(|e| Error::msg(format!("Failed to serialize request body: {}", e)))?;
-
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key)
-        .body(json_body)?
-        .send()
-        .await
-        .map_err(|e| Error::msg(format!("Network error: {}", e)))?;
-
-    // Get the response body as text
-    let response_body = response.text().await?;
-
-    // Log the response body for debugging
-    web_sys::console::log_1(&response_body.clone().into());
-
-    // Parse the JSON response into the NextcloudLoginResponse struct
-    if response.ok() {
-        serde_json::from_str::<NextcloudInitiateResponse>(&response_body)
-            .map_err(|e| Error::msg(format!("Error parsing JSON: {}", e)))
-    } else {
-        Err(Error::msg(format!("Error initiating Nextcloud login: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn initiate_nextcloud_login(&self, nextcloud_url: &str, user_id: i32) -> Result<NextcloudInitiateResponse, Error> {
+        let request_body = LoginInitiateRequest {
+            user_id,
+            nextcloud_url: nextcloud_url.to_string(),
+        };
+        self.post("/api/data/initiate_nextcloud_login")
+            .json(&request_body)?
+            .fetch("Error initiating Nextcloud login")
+            .await
     }
 }
 
@@ -918,27 +845,12 @@ pub struct NextcloudAuthResponse {
     // Define additional fields as needed
 }
 
-pub async fn call_add_nextcloud_server(
-    server_name: &String,
-    api_key: &String,
-    auth_request: NextcloudAuthRequest,
-) -> Result<NextcloudAuthResponse, anyhow::Error> {
-    let url = format!("{}/api/data/add_nextcloud_server", server_name);
-    let api_key_ref = api_key.as_str();
-    let request_body = serde_json::to_string(&auth_request)?;
-
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key_ref)
-        .body(request_body)?
-        .send()
-        .await?;
-
-    if response.ok() {
-        let response_body = response.json::<NextcloudAuthResponse>().await?;
-        Ok(response_body)
-    } else {
-        Err(Error::msg(format!("Error saving Nextcloud Server Info. Is the server reachable? Server Response: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn add_nextcloud_server(&self, auth_request: &NextcloudAuthRequest) -> Result<NextcloudAuthResponse, Error> {
+        self.post("/api/data/add_nextcloud_server")
+            .json(auth_request)?
+            .fetch("Error saving Nextcloud Server Info")
+            .await
     }
 }
 
@@ -948,25 +860,11 @@ pub struct NextcloudCheckResponse {
     // Define additional fields as needed
 }
 
-pub async fn call_check_nextcloud_server(
-    server_name: &String,
-    api_key: &String,
-    user_id: i32
-) -> Result<NextcloudCheckResponse, anyhow::Error> {
-    let url = format!("{}/api/data/check_gpodder_settings/{}", server_name, user_id);
-    let api_key_ref = api_key.as_str();
-
-    let response = Request::get(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key_ref)
-        .send()
-        .await?;
-
-    if response.ok() {
-        let response_body = response.json::<NextcloudCheckResponse>().await?;
-        Ok(response_body)
-    } else {
-        Err(Error::msg(format!("Error pulling Nextcloud Server Info. Is the server reachable? Server Response: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn check_nextcloud_server(&self, user_id: i32) -> Result<NextcloudCheckResponse, Error> {
+        self.get(format!("/api/data/check_gpodder_settings/{}", user_id))
+            .fetch("Error pulling Nextcloud Server Info")
+            .await
     }
 }
 
@@ -974,42 +872,20 @@ pub async fn call_check_nextcloud_server(
 pub struct NextcloudGetResponse {
     pub(crate) data: Vec<String>, // Assuming the response always wraps the gpodder_url and token in a list under "data"
 }
-pub async fn call_get_nextcloud_server(
-    server_name: &String,
-    api_key: &String,
-    user_id: i32,
-) -> Result<String, anyhow::Error> {
-    let url = format!("{}/api/data/get_gpodder_settings/{}", server_name, user_id);
-    let api_key_ref = api_key.as_str();
-
-    let response = Request::get(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key_ref)
-        .send()
-        .await?;
-
-    if response.ok() {
-        match response.json::<NextcloudGetResponse>().await {
-            Ok(response_body) => {
-                // Check if the data array is not empty and does not contain just empty strings
-                if !response_body.data.is_empty() && !response_body.data.iter().all(|s| s.trim().is_empty()) {
-                    let gpodder_url = response_body.data.get(0).cloned().unwrap_or_default(); 
-                    Ok(gpodder_url)
-                } else {
-                    // Instead of returning an error, return a specific message indicating no sync
-                    Ok(String::from("Not currently syncing with Nextcloud server"))
-                }
-            },
-            Err(e) => {
-                Err(anyhow::Error::new(e))
-            },
+
+impl PinepodsClient {
+    pub async fn get_nextcloud_server(&self, user_id: i32) -> Result<String, Error> {
+        let response: NextcloudGetResponse = self
+            .get(format!("/api/data/get_gpodder_settings/{}", user_id))
+            .fetch("Error pulling Nextcloud Server Info")
+            .await?;
+        // Check if the data array is not empty and does not contain just empty strings
+        if !response.data.is_empty() && !response.data.iter().all(|s| s.trim().is_empty()) {
+            Ok(response.data.first().cloned().unwrap_or_default())
+        } else {
+            // Instead of returning an error, return a specific message indicating no sync
+            Ok(String::from("Not currently syncing with Nextcloud server"))
         }
-    } else {
-        let error_text = response.text().await.unwrap_or_default();
-        Err(anyhow::Error::new(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("Error pulling Nextcloud Server Info. Is the server reachable? Server Response: {}, {}", response.status_text(), error_text),
-        )))
     }
 }
 
@@ -1019,25 +895,11 @@ pub struct AdminCheckResponse {
     pub is_admin: bool,
 }
 
-pub async fn call_user_admin_check(
-    server_name: &String,
-    api_key: &String,
-    user_id: i32
-) -> Result<AdminCheckResponse, anyhow::Error> {
-    let url = format!("{}/api/data/user_admin_check/{}", server_name, user_id);
-    let api_key_ref = api_key.as_str();
-
-    let response = Request::get(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key_ref)
-        .send()
-        .await?;
-
-    if response.ok() {
-        let response_body = response.json::<AdminCheckResponse>().await?;
-        Ok(response_body)
-    } else {
-        Err(anyhow::Error::msg(format!("Error checking admin status. Is the server reachable? Server Response: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn user_admin_check(&self, user_id: i32) -> Result<AdminCheckResponse, Error> {
+        self.get(format!("/api/data/user_admin_check/{}", user_id))
+            .fetch("Error checking admin status")
+            .await
     }
 }
 
@@ -1047,30 +909,85 @@ struct CustomFeedRequest {
     user_id: i32,
 }
 
-pub async fn call_add_custom_feed(
-    server_name: &str,
-    feed_url: &str,
-    user_id: &i32,
-    api_key: &str,
-) -> Result<String, Error> {
-    let url = format!("{}/api/data/add_custom_podcast", server_name);
-    let request_body = CustomFeedRequest {
-        feed_url: feed_url.to_string().clone(),
-        user_id: user_id.clone(),
-    };
-    log::info!("url: {:?}", feed_url.to_string().clone());
-
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .header("Api-Key", api_key)
-        .body(serde_json::to_string(&request_body)?)?
-        .send()
-        .await
-        .map_err(Error::msg)?;
-
-    if response.ok() {
-        response.text().await.map_err(Error::msg)
-    } else {
-        Err(Error::msg(format!("Error adding feed: {}", response.status_text())))
+impl PinepodsClient {
+    pub async fn add_custom_feed(&self, feed_url: &str, user_id: &i32) -> Result<String, Error> {
+        let request_body = CustomFeedRequest {
+            feed_url: feed_url.to_string(),
+            user_id: *user_id,
+        };
+        self.post("/api/data/add_custom_podcast")
+            .json(&request_body)?
+            .text("Error adding feed")
+            .await
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::stub_server::StubServer;
+    use pollster::block_on;
+
+    #[test]
+    fn playback_settings_fall_back_to_default_shortcuts_and_save_bindings() {
+        let server = StubServer::start();
+        server
+            .route(
+                "GET",
+                "/api/data/get_playback_settings/2",
+                200,
+                r#"{"skip_back": 10, "skip_forward": 30, "key_bindings": null}"#,
+            )
+            .route("PUT", "/api/data/user/set_playback_settings", 200, r#"{"message": "Playback settings updated successfully"}"#);
+        let client = server.client(Some("key"));
+
+        let mut settings = block_on(client.get_playback_settings(&2)).unwrap();
+        assert_eq!((settings.skip_back, settings.skip_forward), (10, 30));
+        assert_eq!(settings.audio_effects, AudioEffects::default());
+        assert!(settings.queue_playback.auto_play_next);
+        assert_eq!(settings.completion, CompletionThreshold::default());
+        assert_eq!(settings.action_for("K"), Some(ShortcutAction::PlayPause));
+        assert_eq!(settings.action_for("ArrowLeft"), Some(ShortcutAction::SkipBack));
+
+        settings.bind(ShortcutAction::NextEpisode, "K");
+        assert_eq!(settings.action_for("k"), Some(ShortcutAction::NextEpisode));
+        assert_eq!(settings.keys_for(ShortcutAction::PlayPause), [" "]);
+
+        let request = SetPlaybackSettingsRequest {
+            user_id: 2,
+            skip_back: settings.skip_back,
+            skip_forward: settings.skip_forward,
+            key_bindings: settings.key_bindings.clone(),
+            audio_effects: AudioEffects { skip_silence: true, ..settings.audio_effects },
+            queue_playback: QueuePlayback { remove_finished: false, ..settings.queue_playback },
+            completion: CompletionThreshold { completion_percent: 90, ..settings.completion },
+        };
+        block_on(client.set_playback_settings(&request)).unwrap();
+        let sent = server.requests().into_iter().find(|r| r.method == "PUT").unwrap();
+        let body: serde_json::Value = serde_json::from_str(&sent.body).unwrap();
+        assert_eq!(body["skip_forward"], 30);
+        assert_eq!(body["skip_silence"], true);
+        assert_eq!(body["remove_finished"], false);
+        assert_eq!(body["completion_percent"], 90);
+        assert_eq!(body["completion_remaining"], 60);
+        assert!(body["key_bindings"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!({"action": "next_episode", "key": "k"})));
+    }
+
+    #[test]
+    fn episodes_are_played_near_the_end() {
+        let threshold = CompletionThreshold::default();
+
+        // An hour: 95% comes before the last minute
+        assert!(!threshold.reached(3400.0, 3600.0));
+        assert!(threshold.reached(3420.0, 3600.0));
+        // Ten minutes: the last minute comes first
+        assert!(!threshold.reached(530.0, 600.0));
+        assert!(threshold.reached(540.0, 600.0));
+        // A minute: all of it is the last minute, so half of it has to be heard
+        assert!(!threshold.reached(20.0, 60.0));
+        assert!(threshold.reached(30.0, 60.0));
+        assert!(!threshold.reached(0.0, 0.0));
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,6 @@
 use anyhow::Error;
-use gloo_net::http::Request;
-use serde::Deserialize;
+use crate::requests::client::PinepodsClient;
+use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Debug, PartialEq, Clone)]
 #[allow(non_snake_case)]
@@ -11,31 +11,28 @@ pub struct UserStats {
     pub(crate) PodcastsAdded: i32,
     pub(crate) EpisodesSaved: i32,
     pub(crate) EpisodesDownloaded: i32,
+    // Seconds; servers without silence skipping don't report it
+    #[serde(default)]
+    pub(crate) SilenceTimeSaved: i32,
 }
-pub async fn call_get_stats(server_name: String, api_key: Option<String>, user_id: &i32) -> Result<UserStats, anyhow::Error> {
-    let url = format!("{}/api/data/get_stats?user_id={}", server_name, user_id);
-    let api_key_ref = api_key.as_deref().ok_or_else(|| Error::msg("API key is missing"))?;
 
-    let response = Request::get(&url)
-        .header("Api-Key", api_key_ref)
-        .header("Content-Type", "application/json")
-        .send()
-        .await?;
-    if !response.ok() {
-        return Err(anyhow::Error::msg(format!("Failed to get stats: {}", response.status_text())));
-    }
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct SilenceTimeSavedRequest {
+    pub user_id: i32,
+    pub seconds: u32,
+}
 
-    // First, capture the response text for diagnostic purposes
-    let response_text = response.text().await.unwrap_or_else(|_| "Failed to get response text".to_string());
+impl PinepodsClient {
+    pub async fn get_stats(&self, user_id: &i32) -> Result<UserStats, Error> {
+        self.get(format!("/api/data/get_stats?user_id={}", user_id))
+            .fetch("Failed to get stats")
+            .await
+    }
 
-    // Try to deserialize the response text
-    match serde_json::from_str::<UserStats>(&response_text) {
-        Ok(response_body) => {
-            Ok(response_body)
-        }
-        Err(_e) => {
-            Err(anyhow::Error::msg("Failed to deserialize response"))
-        }
+    pub async fn add_silence_time_saved(&self, request: &SilenceTimeSavedRequest) -> Result<(), Error> {
+        self.put("/api/data/add_silence_time_saved")
+            .json(request)?
+            .send("Error recording time saved")
+            .await
     }
 }
-
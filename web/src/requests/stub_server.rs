@@ -0,0 +1,142 @@
+//! A throwaway HTTP server for the request tests. It answers each route with a
+//! canned response, usually a fixture from `tests/fixtures`, and records what the
+//! client sent.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use crate::requests::client::PinepodsClient;
+
+/// Body of `tests/fixtures/<name>`, a response captured from a Pinepods server.
+pub(crate) fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("missing fixture {}: {}", path, e))
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Clone)]
+struct Route {
+    method: &'static str,
+    path: String,
+    status: u16,
+    body: String,
+}
+
+pub(crate) struct StubServer {
+    url: String,
+    routes: Arc<Mutex<Vec<Route>>>,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl StubServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind stub server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let routes = Arc::new(Mutex::new(Vec::new()));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let thread_routes = routes.clone();
+        let thread_requests = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                handle(stream, &thread_routes, &thread_requests);
+            }
+        });
+
+        StubServer { url, routes, requests }
+    }
+
+    /// Answers `method path` (path including any query string) with `status` and `body`.
+    pub fn route(&self, method: &'static str, path: &str, status: u16, body: impl Into<String>) -> &Self {
+        self.routes.lock().unwrap().push(Route {
+            method,
+            path: path.to_string(),
+            status,
+            body: body.into(),
+        });
+        self
+    }
+
+    pub fn client(&self, api_key: Option<&str>) -> PinepodsClient {
+        PinepodsClient::new(self.url.clone(), api_key.map(str::to_string))
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn handle(stream: TcpStream, routes: &Mutex<Vec<Route>>, requests: &Mutex<Vec<RecordedRequest>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.trim_end().split_once(':') {
+            headers.push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    let length = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    let _ = reader.read_exact(&mut body);
+
+    let route = routes
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|route| route.method == method && route.path == path)
+        .cloned();
+    requests.lock().unwrap().push(RecordedRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    });
+
+    let (status, body) = route
+        .map(|route| (route.status, route.body))
+        .unwrap_or((404, r#"{"detail":"Not Found"}"#.to_string()));
+    let response = format!(
+        "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let mut stream = stream;
+    let _ = stream.write_all(response.as_bytes());
+}
@@ -0,0 +1,322 @@
+use anyhow::Error;
+use htmlentity::entity::{decode, ICodedDataTrait};
+use regex::Regex;
+use rss::Item;
+use serde::{Deserialize, Serialize};
+use crate::requests::chapters::parse_npt;
+use crate::requests::client::PinepodsClient;
+
+/// A `podcast:transcript` link of a feed item.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TranscriptLink {
+    pub url: String,
+    pub mime_type: String,
+    pub language: Option<String>,
+}
+
+/// The transcript formats feeds publish, best first: the timed formats come before
+/// HTML, which often has no timings at all.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TranscriptFormat {
+    Vtt,
+    Srt,
+    Json,
+    Html,
+}
+
+impl TranscriptFormat {
+    /// Works out the format from the link's type, falling back to the file extension
+    /// since feeds don't always get the type right.
+    pub fn of(link: &TranscriptLink) -> Option<Self> {
+        let mime_type = link.mime_type.to_ascii_lowercase();
+        let path = link.url.split(['?', '#']).next().unwrap_or_default().to_ascii_lowercase();
+        if mime_type.contains("vtt") || path.ends_with(".vtt") {
+            Some(TranscriptFormat::Vtt)
+        } else if mime_type.contains("srt") || mime_type.contains("subrip") || path.ends_with(".srt") {
+            Some(TranscriptFormat::Srt)
+        } else if mime_type.contains("json") || path.ends_with(".json") {
+            Some(TranscriptFormat::Json)
+        } else if mime_type.contains("html") || path.ends_with(".html") || path.ends_with(".htm") {
+            Some(TranscriptFormat::Html)
+        } else {
+            None
+        }
+    }
+}
+
+/// One line of a transcript, whatever format it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub start: f64,
+    pub end: f64,
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcript {
+    pub cues: Vec<Cue>,
+    /// `false` when the source had no timings, so cues can't follow playback.
+    pub timed: bool,
+}
+
+impl Transcript {
+    /// Index of the cue being spoken at `time`. Between cues, the last one stays current.
+    pub fn current(&self, time: f64) -> Option<usize> {
+        if !self.timed {
+            return None;
+        }
+        self.cues.iter().rposition(|cue| cue.start <= time)
+    }
+
+    /// Indexes of the cues containing `query`, ignoring case.
+    pub fn search(&self, query: &str) -> Vec<usize> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return (0..self.cues.len()).collect();
+        }
+        self.cues
+            .iter()
+            .enumerate()
+            .filter(|(_, cue)| {
+                cue.text.to_lowercase().contains(&query)
+                    || cue.speaker.as_ref().map_or(false, |speaker| speaker.to_lowercase().contains(&query))
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+/// The `podcast:transcript` links of a feed item.
+pub(crate) fn item_transcripts(item: &Item) -> Vec<TranscriptLink> {
+    item.extensions()
+        .get("podcast")
+        .and_then(|exts| exts.get("transcript"))
+        .into_iter()
+        .flatten()
+        .filter_map(|ext| {
+            let attrs = ext.attrs();
+            Some(TranscriptLink {
+                url: attrs.get("url")?.clone(),
+                mime_type: attrs.get("type").cloned().unwrap_or_default(),
+                language: attrs.get("language").cloned(),
+            })
+        })
+        .collect()
+}
+
+/// The link worth loading out of `links`, if any is in a format we read.
+pub fn best_transcript(links: &[TranscriptLink]) -> Option<(&TranscriptLink, TranscriptFormat)> {
+    links
+        .iter()
+        .filter_map(|link| TranscriptFormat::of(link).map(|format| (link, format)))
+        .min_by_key(|(_, format)| *format)
+}
+
+fn decode_entities(text: &str) -> String {
+    decode(text.as_bytes()).to_string().unwrap_or_else(|_| text.to_string())
+}
+
+fn strip_tags(text: &str) -> String {
+    let tags = Regex::new(r"<[^>]*>").unwrap();
+    tags.replace_all(text, "").to_string()
+}
+
+/// Collapses the whitespace of text that may have been wrapped across lines.
+fn clean_text(text: &str) -> String {
+    decode_entities(&strip_tags(text)).split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Parses SRT and WebVTT, which differ only in their header, the decimal separator
+/// and WebVTT's `<v Speaker>` voice tags.
+fn parse_subtitles(body: &str) -> Transcript {
+    let voice = Regex::new(r"^<v(?:\.[^ >]*)?\s+([^>]+)>").unwrap();
+    let body = body.replace("\r\n", "\n");
+    let cues = body
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut lines = block.lines().skip_while(|line| !line.contains("-->"));
+            let (start, end) = lines.next()?.split_once("-->")?;
+            let start = parse_npt(&start.trim().replace(',', "."))?;
+            let end = parse_npt(&end.split_whitespace().next()?.replace(',', "."))?;
+            let raw = lines.collect::<Vec<_>>().join(" ");
+            let speaker = voice.captures(raw.trim_start()).map(|caps| caps[1].trim().to_string());
+            let text = clean_text(&raw);
+            (!text.is_empty()).then_some(Cue { start, end, speaker, text })
+        })
+        .collect();
+    Transcript { cues, timed: true }
+}
+
+#[derive(Deserialize)]
+struct JsonTranscript {
+    segments: Vec<JsonSegment>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonSegment {
+    start_time: f64,
+    end_time: f64,
+    #[serde(default)]
+    speaker: Option<String>,
+    body: String,
+}
+
+fn parse_json(body: &str) -> Result<Transcript, Error> {
+    let transcript: JsonTranscript = serde_json::from_str(body)?;
+    let cues = transcript
+        .segments
+        .into_iter()
+        .map(|segment| Cue {
+            start: segment.start_time,
+            end: segment.end_time,
+            speaker: segment.speaker,
+            text: segment.body.trim().to_string(),
+        })
+        .filter(|cue| !cue.text.is_empty())
+        .collect();
+    Ok(Transcript { cues, timed: true })
+}
+
+/// Parses the HTML layout the podcast namespace recommends: a `<cite>` speaker and
+/// `<time>` start before each `<p>` of text. Times are optional.
+fn parse_html(body: &str) -> Transcript {
+    let elements = Regex::new(r"(?is)<(cite|time|p)\b[^>]*>(.*?)</(?:cite|time|p)\s*>").unwrap();
+    let mut cues: Vec<Cue> = Vec::new();
+    let mut speaker = None;
+    let mut start = None;
+    let mut timed = true;
+    for caps in elements.captures_iter(body) {
+        let content = clean_text(&caps[2]);
+        match caps[1].to_ascii_lowercase().as_str() {
+            "cite" => speaker = Some(content.trim_end_matches(':').trim().to_string()),
+            "time" => start = parse_npt(&content),
+            _ if content.is_empty() => {}
+            _ => {
+                timed &= start.is_some();
+                let cue_start = start.take().unwrap_or(0.0);
+                if let Some(previous) = cues.last_mut() {
+                    previous.end = cue_start;
+                }
+                cues.push(Cue { start: cue_start, end: cue_start, speaker: speaker.clone(), text: content });
+            }
+        }
+    }
+    if cues.is_empty() {
+        let text = clean_text(body);
+        if !text.is_empty() {
+            cues.push(Cue { start: 0.0, end: 0.0, speaker: None, text });
+        }
+        timed = false;
+    }
+    Transcript { cues, timed }
+}
+
+pub fn parse_transcript(format: TranscriptFormat, body: &str) -> Result<Transcript, Error> {
+    match format {
+        TranscriptFormat::Vtt | TranscriptFormat::Srt => Ok(parse_subtitles(body)),
+        TranscriptFormat::Json => parse_json(body),
+        TranscriptFormat::Html => Ok(parse_html(body)),
+    }
+}
+
+impl PinepodsClient {
+    /// Fetches and parses a transcript. Like chapter files, it goes through the server
+    /// because transcript hosts rarely send CORS headers.
+    pub async fn get_transcript(&self, link: &TranscriptLink, format: TranscriptFormat) -> Result<Transcript, Error> {
+        let response_text = self
+            .get(format!("/api/data/fetch_podcast_feed?podcast_feed={}", urlencoding::encode(&link.url)))
+            .text("Failed to fetch transcript")
+            .await?;
+        parse_transcript(format, &response_text)
+    }
+
+    /// Loads the transcript of the episode at `episode_url` in the feed at `feed_url`,
+    /// or `None` when the feed doesn't link one we can read.
+    pub async fn get_episode_transcript(&self, feed_url: &str, episode_url: &str) -> Result<Option<Transcript>, Error> {
+        let feed = self.parse_podcast_url(feed_url).await?;
+        let links = feed
+            .episodes
+            .into_iter()
+            .find(|episode| episode.enclosure_url.as_deref() == Some(episode_url))
+            .map(|episode| episode.transcripts)
+            .unwrap_or_default();
+        match best_transcript(&links) {
+            Some((link, format)) => self.get_transcript(link, format).await.map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::stub_server::{fixture, StubServer};
+    use pollster::block_on;
+
+    fn texts(transcript: &Transcript) -> Vec<&str> {
+        transcript.cues.iter().map(|cue| cue.text.as_str()).collect()
+    }
+
+    #[test]
+    fn episode_transcript_prefers_timed_format() {
+        let feed_url = "https://pods.example.com/feed.xml";
+        let server = StubServer::start();
+        server
+            .route(
+                "GET",
+                &format!("/api/data/fetch_podcast_feed?podcast_feed={}", urlencoding::encode(feed_url)),
+                200,
+                fixture("feed_with_chapters.xml"),
+            )
+            .route(
+                "GET",
+                &format!("/api/data/fetch_podcast_feed?podcast_feed={}", urlencoding::encode("https://pods.example.com/linked.vtt")),
+                200,
+                fixture("transcript.vtt"),
+            );
+        let client = server.client(Some("key"));
+
+        let transcript = block_on(client.get_episode_transcript(feed_url, "https://pods.example.com/linked.mp3"))
+            .unwrap()
+            .unwrap();
+        assert!(transcript.timed);
+        assert_eq!(texts(&transcript), ["Welcome back to Pine Radio.", "Today we're talking about & with conifers."]);
+        assert_eq!(transcript.cues[1].speaker.as_deref(), Some("Bob"));
+        assert_eq!((transcript.cues[1].start, transcript.cues[1].end), (4.5, 9.0));
+        assert_eq!(transcript.current(4.2), Some(0));
+        assert_eq!(transcript.current(5.0), Some(1));
+        assert_eq!(transcript.search("CONIFER"), [1]);
+
+        let none = block_on(client.get_episode_transcript(feed_url, "https://pods.example.com/plain.mp3")).unwrap();
+        assert_eq!(none, None);
+    }
+
+    #[test]
+    fn srt_json_and_html_share_the_cue_model() {
+        let srt = "1\r\n00:00:01,000 --> 00:00:02,500\r\nFirst line\r\n\r\n2\r\n00:01:00,000 --> 00:01:03,000\r\nSecond\r\nline\r\n";
+        let srt = parse_transcript(TranscriptFormat::Srt, srt).unwrap();
+        assert_eq!(texts(&srt), ["First line", "Second line"]);
+        assert_eq!(srt.cues[1].start, 60.0);
+
+        let json = r#"{"version": "1.0.0", "segments": [
+            {"speaker": "Alice", "startTime": 0.5, "endTime": 2, "body": "Hello"},
+            {"startTime": 2, "endTime": 3.25, "body": "there"}]}"#;
+        let json = parse_transcript(TranscriptFormat::Json, json).unwrap();
+        assert_eq!(texts(&json), ["Hello", "there"]);
+        assert_eq!(json.cues[0].speaker.as_deref(), Some("Alice"));
+        assert_eq!(json.cues[1].end, 3.25);
+
+        let html = "<cite>Alice:</cite><time>0:00</time><p>Hi &amp; welcome</p>\n<cite>Bob:</cite><time>1:05</time><p>Thanks <b>Alice</b></p>";
+        let html = parse_transcript(TranscriptFormat::Html, html).unwrap();
+        assert!(html.timed);
+        assert_eq!(texts(&html), ["Hi & welcome", "Thanks Alice"]);
+        assert_eq!(html.cues[0].end, 65.0);
+        assert_eq!(html.cues[1].speaker.as_deref(), Some("Bob"));
+
+        let untimed = parse_transcript(TranscriptFormat::Html, "<p>Just words</p>").unwrap();
+        assert!(!untimed.timed);
+        assert_eq!(untimed.current(10.0), None);
+    }
+}